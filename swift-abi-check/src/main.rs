@@ -0,0 +1,191 @@
+//! Compares two builds of a Swift library and reports the changes that
+//! break bindings compiled against the older one, such as removed symbols or
+//! reordered enum cases.
+//!
+//! Each change is printed as a JSON object on its own line, and the exit
+//! status is 1 if any of them is an error, so that CI can block a dependency
+//! bump that would corrupt memory through existing bindings. For a library
+//! built with library evolution, `--resilient` reports changes to the stored
+//! properties of its types as warnings, except for the `@frozen` types named
+//! by `--frozen`, since the metadata doesn't record which types are frozen.
+
+#![deny(missing_docs)]
+
+use std::{
+    env,
+    io::{self, Write},
+    path::{Path, PathBuf},
+    process,
+};
+use swift_reflection::{
+    abi::{Abi, Change, Resilience, Severity},
+    json::Json,
+    Image,
+};
+
+const USAGE: &str = "\
+Reports ABI-breaking changes between two builds of a Swift library.
+
+Each change is printed as a line of JSON with a severity of \"error\" or
+\"warning\". The exit status is 1 if there are any errors, and 2 if either
+library can't be read.
+
+Changes to stored properties are errors unless the library is built with
+library evolution and the type isn't @frozen.
+
+USAGE:
+    swift-abi-check [OPTIONS] <OLD> <NEW>
+
+OPTIONS:
+        --resilient        Report changes to the stored properties of types
+                           as warnings, for libraries built with library
+                           evolution
+        --frozen <TYPE>    Report changes to the stored properties of the
+                           @frozen type TYPE, such as Foo.Point, as errors
+                           with --resilient
+    -h, --help             Print this message
+";
+
+fn fail(message: &str) -> ! {
+    eprint!("error: {}\n\n{}", message, USAGE);
+    process::exit(2);
+}
+
+fn parse_args() -> (PathBuf, PathBuf, Resilience) {
+    let mut paths = Vec::new();
+    let mut resilient = false;
+    let mut frozen = Vec::new();
+    let mut args = env::args_os().skip(1);
+    while let Some(arg) = args.next() {
+        match arg.to_str() {
+            Some("-h") | Some("--help") => {
+                print!("{}", USAGE);
+                process::exit(0);
+            }
+            Some("--resilient") => resilient = true,
+            Some("--frozen") => {
+                match args.next().map(|name| name.into_string()) {
+                    Some(Ok(name)) => frozen.push(name),
+                    Some(Err(_)) => fail("invalid type name for '--frozen'"),
+                    None => fail("missing value for '--frozen'"),
+                }
+            }
+            Some(flag) if flag.starts_with("--frozen=") => {
+                frozen.push(flag["--frozen=".len()..].to_owned())
+            }
+            Some(flag) if flag.starts_with('-') => {
+                fail(&format!("unknown argument '{}'", flag))
+            }
+            _ => paths.push(PathBuf::from(arg)),
+        }
+    }
+    if !frozen.is_empty() && !resilient {
+        fail("'--frozen' requires '--resilient'");
+    }
+
+    let mut resilience = if resilient {
+        Resilience::library_evolution()
+    } else {
+        Resilience::new()
+    };
+    for name in frozen {
+        resilience = resilience.frozen(name);
+    }
+
+    let mut paths = paths.into_iter();
+    match (paths.next(), paths.next(), paths.next()) {
+        (Some(old), Some(new), None) => (old, new, resilience),
+        _ => fail("expected an old and a new library"),
+    }
+}
+
+fn read_abi(path: &Path) -> Abi {
+    let image = Image::open(path).and_then(|image| {
+        let abi = Abi::read(&image.map()?);
+        Ok(abi)
+    });
+    match image {
+        Ok(abi) => abi,
+        Err(error) => {
+            eprintln!("error: {}: {}", path.display(), error);
+            process::exit(2);
+        }
+    }
+}
+
+/// Returns `items` as a JSON array of strings.
+fn strings<T: ToString>(items: &[T]) -> Json {
+    items
+        .iter()
+        .map(|item| Json::from(item.to_string()))
+        .collect()
+}
+
+/// Returns `change` as a JSON object.
+fn to_json(change: &Change, resilience: &Resilience) -> Json {
+    let mut fields = vec![
+        ("kind", change.kind().into()),
+        ("severity", change.severity(resilience).name().into()),
+        ("message", change.to_string().into()),
+    ];
+
+    match change {
+        Change::SymbolRemoved { symbol } => {
+            fields.push(("symbol", symbol.as_str().into()));
+        }
+        Change::TypeRemoved { name }
+        | Change::TypeKindChanged { name }
+        | Change::VTableRemoved { name } => {
+            fields.push(("type", name.as_str().into()));
+        }
+        Change::FieldsChanged { name, old, new }
+        | Change::CasesChanged { name, old, new } => {
+            fields.push(("type", name.as_str().into()));
+            fields.push(("old", strings(old)));
+            fields.push(("new", strings(new)));
+        }
+        Change::VTableChanged {
+            name,
+            old_offset,
+            new_offset,
+            old,
+            new,
+        } => {
+            fields.push(("type", name.as_str().into()));
+            fields.push(("old_offset", u64::from(*old_offset).into()));
+            fields.push(("new_offset", u64::from(*new_offset).into()));
+            fields.push(("old", strings(old)));
+            fields.push(("new", strings(new)));
+        }
+        Change::ConformanceRemoved {
+            type_name,
+            protocol,
+        } => {
+            fields.push(("type", type_name.as_str().into()));
+            fields.push(("protocol", protocol.as_str().into()));
+        }
+    }
+    Json::Object(fields)
+}
+
+fn main() {
+    let (old, new, resilience) = parse_args();
+    let changes = read_abi(&old).changes(&read_abi(&new));
+
+    let stdout = io::stdout();
+    let mut stdout = stdout.lock();
+    for change in &changes {
+        let json = to_json(change, &resilience);
+        if let Err(error) = writeln!(stdout, "{}", json) {
+            if error.kind() != io::ErrorKind::BrokenPipe {
+                eprintln!("error: {}", error);
+            }
+            break;
+        }
+    }
+
+    let is_error = |c: &Change| c.severity(&resilience) == Severity::Error;
+    if changes.iter().any(is_error) {
+        process::exit(1);
+    }
+}
@@ -0,0 +1,326 @@
+//! Replaces the mangled Swift and Rust symbols in text read from stdin with
+//! their demangled forms, similar to `swift demangle` and `rustfilt`.
+//!
+//! This is useful for filtering the output of tools such as `perf`, crash
+//! logs and linker errors. Tokens that look mangled but fail to demangle are
+//! left untouched by default.
+
+#![deny(missing_docs)]
+
+use std::{
+    env,
+    io::{self, BufRead, Write},
+    panic::{self, AssertUnwindSafe},
+    process, str,
+};
+use swift_rt::sym::{self, DemangleOptions};
+
+const USAGE: &str = "\
+Demangles Swift and Rust symbols in text read from stdin.
+
+USAGE:
+    swift-demangle-filter [OPTIONS]
+
+OPTIONS:
+    -s, --simplified        Render symbols in a compact form, such as `foo(x:)`
+        --no-rust           Leave Rust symbols untouched
+        --unknown <MODE>    How to handle Swift symbols that fail to demangle:
+                              keep     leave them untouched (default)
+                              warn     leave them untouched and report why
+                                       they failed to stderr
+                              partial  replace them with the part that was
+                                       demangled before the failure, if any
+    -h, --help              Print this message
+";
+
+/// How Swift symbols that fail to demangle are handled.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+enum Unknown {
+    Keep,
+    Warn,
+    Partial,
+}
+
+struct Filter {
+    swift: DemangleOptions,
+    rust: bool,
+    simplified: bool,
+    unknown: Unknown,
+}
+
+impl Filter {
+    fn new() -> Self {
+        Self {
+            swift: DemangleOptions::new(),
+            rust: true,
+            simplified: false,
+            unknown: Unknown::Keep,
+        }
+    }
+
+    fn from_args() -> Self {
+        match Self::parse_args(env::args().skip(1)) {
+            Ok(Some(filter)) => filter,
+            Ok(None) => {
+                print!("{}", USAGE);
+                process::exit(0);
+            }
+            Err(error) => {
+                eprint!("error: {}\n\n{}", error, USAGE);
+                process::exit(2);
+            }
+        }
+    }
+
+    /// Parses command-line arguments, returning `None` if help was requested.
+    fn parse_args(
+        args: impl IntoIterator<Item = String>,
+    ) -> Result<Option<Self>, String> {
+        let mut filter = Self::new();
+        let mut args = args.into_iter();
+        while let Some(arg) = args.next() {
+            let (name, value) = match arg.find('=') {
+                Some(i) if arg.starts_with("--") => {
+                    (&arg[..i], Some(arg[(i + 1)..].to_owned()))
+                }
+                _ => (arg.as_str(), None),
+            };
+            match name {
+                "-s" | "--simplified" => {
+                    filter.swift = DemangleOptions::simplified();
+                    filter.simplified = true;
+                }
+                "--no-rust" => filter.rust = false,
+                "--unknown" => {
+                    let mode =
+                        value.or_else(|| args.next()).ok_or_else(|| {
+                            "missing value for '--unknown'".to_owned()
+                        })?;
+                    filter.unknown = match mode.as_str() {
+                        "keep" => Unknown::Keep,
+                        "warn" => Unknown::Warn,
+                        "partial" => Unknown::Partial,
+                        _ => {
+                            return Err(format!(
+                                "unknown mode '{}' for '--unknown'",
+                                mode
+                            ))
+                        }
+                    };
+                }
+                "-h" | "--help" => return Ok(None),
+                _ => return Err(format!("unknown argument '{}'", arg)),
+            }
+        }
+        Ok(Some(filter))
+    }
+
+    /// Appends `line` to `out`, replacing every mangled symbol with its
+    /// demangled form.
+    fn filter_line(&self, line: &[u8], out: &mut Vec<u8>) {
+        let mut pos = 0;
+        while pos < line.len() {
+            let start = pos;
+            if !is_symbol_char(line[pos]) {
+                while pos < line.len() && !is_symbol_char(line[pos]) {
+                    pos += 1;
+                }
+                out.extend_from_slice(&line[start..pos]);
+                continue;
+            }
+
+            while pos < line.len() && is_symbol_char(line[pos]) {
+                pos += 1;
+            }
+            let token = &line[start..pos];
+
+            // Trailing periods are more likely punctuation than a suffix.
+            let mut end = token.len();
+            while end > 0 && token[end - 1] == b'.' {
+                end -= 1;
+            }
+            self.filter_token(&token[..end], out);
+            out.extend_from_slice(&token[end..]);
+        }
+    }
+
+    /// Appends the demangled form of `token` to `out`, or `token` itself if
+    /// it isn't a mangled symbol.
+    fn filter_token(&self, token: &[u8], out: &mut Vec<u8>) {
+        if sym::is_mangled(token) {
+            self.filter_swift_token(token, out);
+            return;
+        }
+
+        if self.rust {
+            let rust = str::from_utf8(token)
+                .ok()
+                .and_then(|token| rustc_demangle::try_demangle(token).ok());
+            if let Some(rust) = rust {
+                // Writing to a `Vec` never fails.
+                let _ = if self.simplified {
+                    write!(out, "{:#}", rust)
+                } else {
+                    write!(out, "{}", rust)
+                };
+                return;
+            }
+        }
+
+        out.extend_from_slice(token);
+    }
+
+    fn filter_swift_token(&self, token: &[u8], out: &mut Vec<u8>) {
+        let len = out.len();
+        // A bug in the demangler must not end the whole stream, so a panic
+        // is treated like any other failure to demangle.
+        let result = panic::catch_unwind(AssertUnwindSafe(|| {
+            self.swift.demangle_into(token, out).map(|_| ())
+        }));
+        let error = match result {
+            Ok(Ok(())) => return,
+            Ok(Err(error)) => Some(error),
+            Err(_) => None,
+        };
+        out.truncate(len);
+
+        match self.unknown {
+            Unknown::Keep => {}
+            Unknown::Warn => {
+                let token = String::from_utf8_lossy(token);
+                match &error {
+                    Some(error) => eprintln!("warning: {}: {}", token, error),
+                    None => eprintln!("warning: {}: demangler panicked", token),
+                }
+            }
+            Unknown::Partial => {
+                let partial = error.as_ref().map_or("", |e| e.partial());
+                if !partial.is_empty() {
+                    out.extend_from_slice(partial.as_bytes());
+                    return;
+                }
+            }
+        }
+        out.extend_from_slice(token);
+    }
+}
+
+/// Returns whether `b` may be part of a Swift or Rust symbol.
+#[inline]
+fn is_symbol_char(b: u8) -> bool {
+    b.is_ascii_alphanumeric() || b == b'_' || b == b'$' || b == b'.'
+}
+
+fn run(filter: &Filter) -> io::Result<()> {
+    let stdin = io::stdin();
+    let stdout = io::stdout();
+    let mut input = io::BufReader::new(stdin.lock());
+    let mut output = io::BufWriter::new(stdout.lock());
+
+    // Both buffers are reused across lines to avoid reallocating.
+    let mut line = Vec::new();
+    let mut out = Vec::new();
+
+    loop {
+        line.clear();
+        if input.read_until(b'\n', &mut line)? == 0 {
+            break;
+        }
+
+        out.clear();
+        filter.filter_line(&line, &mut out);
+        output.write_all(&out)?;
+
+        // Flush before waiting for more input so that the output of streams
+        // such as `tail -f` isn't held back.
+        if input.buffer().is_empty() {
+            output.flush()?;
+        }
+    }
+    output.flush()
+}
+
+fn main() {
+    let filter = Filter::from_args();
+    // Panics while demangling are caught and reported per token, if at all.
+    panic::set_hook(Box::new(|_| {}));
+    match run(&filter) {
+        Ok(()) => {}
+        Err(error) if error.kind() == io::ErrorKind::BrokenPipe => {}
+        Err(error) => {
+            eprintln!("error: {}", error);
+            process::exit(1);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn filter(args: &[&str], line: &str) -> String {
+        let args = args.iter().map(|&arg| arg.to_owned());
+        let filter = Filter::parse_args(args).unwrap().unwrap();
+        let mut out = Vec::new();
+        filter.filter_line(line.as_bytes(), &mut out);
+        String::from_utf8(out).unwrap()
+    }
+
+    #[test]
+    fn swift_symbols() {
+        assert_eq!(
+            filter(&[], "at $s4main3fooyyF+0x10 in main"),
+            "at main.foo() -> ()+0x10 in main",
+        );
+        assert_eq!(
+            filter(&["--simplified"], "call $s4main3foo1xSSSi_tF."),
+            "call foo(x:).",
+        );
+    }
+
+    #[test]
+    fn rust_symbols() {
+        let line = "_ZN4core3fmt5write17h0123456789abcdefE";
+        assert_eq!(filter(&[], line), "core::fmt::write::h0123456789abcdef");
+        assert_eq!(filter(&["-s"], line), "core::fmt::write");
+        assert_eq!(filter(&["--no-rust"], line), line);
+    }
+
+    #[test]
+    fn unknown_tokens() {
+        let line = "$s4main3fooyyF! $s1D5IndexVy__GN hello";
+        assert_eq!(
+            filter(&[], line),
+            "main.foo() -> ()! $s1D5IndexVy__GN hello"
+        );
+        assert_eq!(filter(&["--unknown", "keep"], line), filter(&[], line));
+        assert_eq!(filter(&["--unknown=warn"], line), filter(&[], line));
+        assert_eq!(
+            filter(&["--unknown=partial"], "$s4main3fooyyFy"),
+            "main.foo() -> ()",
+        );
+        assert_eq!(
+            filter(&["--unknown=partial"], "$s1D5IndexVy__GN"),
+            "$s1D5IndexVy__GN",
+        );
+    }
+
+    #[test]
+    fn deeply_nested_tokens_pass_through() {
+        let mut token = String::from("$sSi");
+        token.push_str(&"Sg".repeat(10_000));
+        token.push('D');
+        assert_eq!(filter(&[], &token), token);
+    }
+
+    #[test]
+    fn invalid_args() {
+        let parse = |args: &[&str]| {
+            Filter::parse_args(args.iter().map(|&arg| arg.to_owned()))
+        };
+        assert!(parse(&["--help"]).unwrap().is_none());
+        assert!(parse(&["--unknown"]).is_err());
+        assert!(parse(&["--unknown=drop"]).is_err());
+        assert!(parse(&["--frobnicate"]).is_err());
+    }
+}
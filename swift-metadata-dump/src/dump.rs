@@ -0,0 +1,557 @@
+//! Collecting the Swift metadata of an image into a dump that can be printed
+//! as text or JSON.
+
+use std::fmt;
+use swift_reflection::{
+    json::Json, GenericRequirement, Image, MappedImage, Reference,
+};
+use swift_rt::{
+    field::{self, Field, ReferenceStorage},
+    sym,
+};
+use swift_sys::metadata::context::{
+    ContextDescriptor, ContextDescriptorKind, GenericRequirementDescriptor,
+    GenericRequirementKind,
+};
+
+/// The Swift metadata of an image.
+#[derive(Debug, Default)]
+pub struct Dump {
+    /// The nominal types declared by the image.
+    pub types: Vec<TypeDump>,
+    /// The protocols declared by the image.
+    pub protocols: Vec<ProtocolDump>,
+    /// Conformances of types that aren't declared by the image, such as
+    /// those added by extensions of standard library types.
+    pub conformances: Vec<ConformanceDump>,
+}
+
+/// A struct, enum or class.
+#[derive(Debug)]
+pub struct TypeDump {
+    addr: u64,
+    kind: &'static str,
+    name: String,
+    generic_params: Vec<String>,
+    generic_requirements: Vec<String>,
+    superclass: Option<String>,
+    fields: Vec<FieldDump>,
+    conformances: Vec<ConformanceDump>,
+}
+
+/// A stored property or enum case.
+#[derive(Debug)]
+pub struct FieldDump {
+    name: String,
+    type_name: Option<String>,
+    is_var: bool,
+    is_indirect_case: bool,
+    storage: ReferenceStorage,
+}
+
+/// A protocol and its requirement signature.
+#[derive(Debug)]
+pub struct ProtocolDump {
+    name: String,
+    is_class_constrained: bool,
+    associated_types: Vec<String>,
+    requirements: Vec<String>,
+}
+
+/// A conformance of a type to a protocol.
+#[derive(Debug)]
+pub struct ConformanceDump {
+    type_name: String,
+    protocol: String,
+    is_retroactive: bool,
+    conditional_requirements: Vec<String>,
+    associated_types: Vec<(String, String)>,
+}
+
+impl Dump {
+    /// Reads the metadata of `image`, whose contents are laid out in
+    /// `mapped`.
+    pub fn read(image: &Image, mapped: &MappedImage) -> Self {
+        let reader = Reader { image, mapped };
+        let mut dump = Self::default();
+
+        for record in image.types() {
+            let addr = match record.target {
+                Some(Reference::Addr(addr)) => addr,
+                _ => continue,
+            };
+            if let Some(ty) = reader.type_dump(addr) {
+                dump.types.push(ty);
+            }
+        }
+
+        for record in image.protocols() {
+            let addr = match record.descriptor {
+                Some(Reference::Addr(addr)) => addr,
+                _ => continue,
+            };
+            if let Some(protocol) = reader.protocol_dump(addr) {
+                dump.protocols.push(protocol);
+            }
+        }
+
+        for conformance in image.conformance_descriptors() {
+            let type_addr = conformance.type_ref.and_then(Reference::addr);
+            let conformance = ConformanceDump {
+                type_name: conformance
+                    .type_ref
+                    .map_or_else(unknown, |ty| reader.reference_name(ty)),
+                protocol: conformance
+                    .protocol
+                    .map_or_else(unknown, |p| reader.reference_name(p)),
+                is_retroactive: conformance.flags.is_retroactive(),
+                conditional_requirements: conformance
+                    .conditional_requirements
+                    .iter()
+                    .filter_map(|r| reader.requirement(r, false))
+                    .collect(),
+                associated_types: Vec::new(),
+            };
+            match dump.types.iter_mut().find(|ty| Some(ty.addr) == type_addr) {
+                Some(ty) => ty.conformances.push(conformance),
+                None => dump.conformances.push(conformance),
+            }
+        }
+
+        // Safety: the section was emitted by the compiler, and `map` only
+        // succeeds for images laid out like the current target.
+        for descriptor in unsafe { mapped.associated_type_descriptors() } {
+            let type_name = descriptor
+                .conforming_type_name()
+                .map_or_else(unknown, |name| reader.mapped_type_name(name));
+            let protocol = descriptor
+                .protocol_type_name()
+                .map_or_else(unknown, |name| reader.mapped_type_name(name));
+            let associated_types = descriptor.records().iter().map(|record| {
+                let ty = record
+                    .substituted_type_name()
+                    .map_or_else(unknown, |name| reader.mapped_type_name(name));
+                (record.name().to_string_lossy().into_owned(), ty)
+            });
+
+            let conformance = dump
+                .types
+                .iter_mut()
+                .flat_map(|ty| ty.conformances.iter_mut())
+                .chain(dump.conformances.iter_mut())
+                .find(|c| c.type_name == type_name && c.protocol == protocol);
+            match conformance {
+                Some(conformance) => {
+                    conformance.associated_types.extend(associated_types)
+                }
+                None => dump.conformances.push(ConformanceDump {
+                    type_name,
+                    protocol,
+                    is_retroactive: false,
+                    conditional_requirements: Vec::new(),
+                    associated_types: associated_types.collect(),
+                }),
+            }
+        }
+
+        dump
+    }
+
+    /// Returns `self` as a JSON value.
+    pub fn to_json(&self) -> Json {
+        Json::Object(vec![
+            ("types", self.types.iter().map(TypeDump::to_json).collect()),
+            (
+                "protocols",
+                self.protocols.iter().map(ProtocolDump::to_json).collect(),
+            ),
+            (
+                "conformances",
+                self.conformances
+                    .iter()
+                    .map(ConformanceDump::to_json)
+                    .collect(),
+            ),
+        ])
+    }
+}
+
+impl TypeDump {
+    fn to_json(&self) -> Json {
+        Json::Object(vec![
+            ("kind", self.kind.into()),
+            ("name", self.name.as_str().into()),
+            ("generic_params", self.generic_params.clone().into()),
+            (
+                "generic_requirements",
+                self.generic_requirements.clone().into(),
+            ),
+            ("superclass", self.superclass.clone().into()),
+            (
+                "fields",
+                self.fields.iter().map(FieldDump::to_json).collect(),
+            ),
+            (
+                "conformances",
+                self.conformances
+                    .iter()
+                    .map(ConformanceDump::to_json)
+                    .collect(),
+            ),
+        ])
+    }
+}
+
+impl FieldDump {
+    fn to_json(&self) -> Json {
+        let storage = match self.storage {
+            ReferenceStorage::Strong => "strong",
+            ReferenceStorage::Weak => "weak",
+            ReferenceStorage::Unowned => "unowned",
+            ReferenceStorage::Unmanaged => "unmanaged",
+        };
+        Json::Object(vec![
+            ("name", self.name.as_str().into()),
+            ("type", self.type_name.clone().into()),
+            ("is_var", self.is_var.into()),
+            ("is_indirect_case", self.is_indirect_case.into()),
+            ("storage", storage.into()),
+        ])
+    }
+}
+
+impl ProtocolDump {
+    fn to_json(&self) -> Json {
+        Json::Object(vec![
+            ("name", self.name.as_str().into()),
+            ("is_class_constrained", self.is_class_constrained.into()),
+            ("associated_types", self.associated_types.clone().into()),
+            ("requirements", self.requirements.clone().into()),
+        ])
+    }
+}
+
+impl ConformanceDump {
+    fn to_json(&self) -> Json {
+        let associated_types = self.associated_types.iter().map(|(n, ty)| {
+            Json::Object(vec![
+                ("name", n.as_str().into()),
+                ("type", ty.as_str().into()),
+            ])
+        });
+        Json::Object(vec![
+            ("type", self.type_name.as_str().into()),
+            ("protocol", self.protocol.as_str().into()),
+            ("is_retroactive", self.is_retroactive.into()),
+            (
+                "conditional_requirements",
+                self.conditional_requirements.clone().into(),
+            ),
+            ("associated_types", associated_types.collect()),
+        ])
+    }
+
+    /// Writes the conformance on one line, followed by its associated types
+    /// indented by `indent`.
+    fn write_text(
+        &self,
+        f: &mut fmt::Formatter,
+        prefix: &str,
+        indent: &str,
+    ) -> fmt::Result {
+        write!(f, "{}{}", prefix, self.protocol)?;
+        write_where(f, &self.conditional_requirements)?;
+        if self.is_retroactive {
+            f.write_str(" (retroactive)")?;
+        }
+        writeln!(f)?;
+        for (name, ty) in &self.associated_types {
+            writeln!(f, "{}associatedtype {} = {}", indent, name, ty)?;
+        }
+        Ok(())
+    }
+}
+
+impl fmt::Display for Dump {
+    /// Writes the dump in a form resembling Swift declarations.
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        for ty in &self.types {
+            write!(f, "{} {}", ty.kind, ty.name)?;
+            if !ty.generic_params.is_empty() {
+                write!(f, "<{}", ty.generic_params.join(", "))?;
+                write_where(f, &ty.generic_requirements)?;
+                f.write_str(">")?;
+            }
+            if let Some(superclass) = &ty.superclass {
+                write!(f, ": {}", superclass)?;
+            }
+            writeln!(f)?;
+
+            for field in &ty.fields {
+                f.write_str("  ")?;
+                if ty.kind == "enum" {
+                    if field.is_indirect_case {
+                        f.write_str("indirect ")?;
+                    }
+                    write!(f, "case {}", field.name)?;
+                    if let Some(ty) = &field.type_name {
+                        write!(f, "({})", ty)?;
+                    }
+                } else {
+                    match field.storage {
+                        ReferenceStorage::Strong => {}
+                        ReferenceStorage::Weak => f.write_str("weak ")?,
+                        ReferenceStorage::Unowned => f.write_str("unowned ")?,
+                        ReferenceStorage::Unmanaged => {
+                            f.write_str("unowned(unsafe) ")?
+                        }
+                    }
+                    let keyword = if field.is_var { "var" } else { "let" };
+                    write!(f, "{} {}", keyword, field.name)?;
+                    if let Some(ty) = &field.type_name {
+                        write!(f, ": {}", ty)?;
+                    }
+                }
+                writeln!(f)?;
+            }
+
+            for conformance in &ty.conformances {
+                conformance.write_text(f, "  conforms to ", "    ")?;
+            }
+        }
+
+        for protocol in &self.protocols {
+            write!(f, "protocol {}", protocol.name)?;
+            if protocol.is_class_constrained {
+                f.write_str(": AnyObject")?;
+            }
+            write_where(f, &protocol.requirements)?;
+            writeln!(f)?;
+            for name in &protocol.associated_types {
+                writeln!(f, "  associatedtype {}", name)?;
+            }
+        }
+
+        for conformance in &self.conformances {
+            let prefix = format!("extension {}: ", conformance.type_name);
+            conformance.write_text(f, &prefix, "  ")?;
+        }
+        Ok(())
+    }
+}
+
+/// Writes ` where ` followed by `requirements`, or nothing if there are none.
+fn write_where(f: &mut fmt::Formatter, requirements: &[String]) -> fmt::Result {
+    if requirements.is_empty() {
+        Ok(())
+    } else {
+        write!(f, " where {}", requirements.join(", "))
+    }
+}
+
+/// The name used for references that couldn't be resolved.
+#[inline]
+fn unknown() -> String {
+    String::from("<unknown>")
+}
+
+/// Resolves the names of descriptors and types in an image.
+struct Reader<'a> {
+    image: &'a Image,
+    mapped: &'a MappedImage<'a>,
+}
+
+impl Reader<'_> {
+    /// Returns the context descriptor at `addr`.
+    #[inline]
+    fn descriptor(&self, addr: u64) -> Option<&ContextDescriptor> {
+        // Safety: only addresses that the compiler emitted as references to
+        // context descriptors are read.
+        unsafe { self.mapped.context_descriptor(addr) }
+    }
+
+    fn type_dump(&self, addr: u64) -> Option<TypeDump> {
+        let descriptor = self.descriptor(addr)?;
+        let ty = descriptor.as_type()?;
+        let kind = match descriptor.kind() {
+            ContextDescriptorKind::CLASS => "class",
+            ContextDescriptorKind::STRUCT => "struct",
+            ContextDescriptorKind::ENUM => "enum",
+            _ => "type",
+        };
+        let (generic_params, generic_requirements) =
+            self.generic_signature(descriptor);
+        let superclass = descriptor
+            .as_class()
+            .and_then(|class| class.superclass_type())
+            .map(|name| self.mapped_type_name(name));
+        let fields = field::fields(ty)
+            .map(|field| FieldDump {
+                name: field.name().into_owned(),
+                type_name: self.field_type_name(&field),
+                is_var: field.is_var(),
+                is_indirect_case: field.is_indirect_case(),
+                storage: field.storage(),
+            })
+            .collect();
+
+        Some(TypeDump {
+            addr,
+            kind,
+            name: self.mapped.context_name(descriptor),
+            generic_params,
+            generic_requirements,
+            superclass,
+            fields,
+            conformances: Vec::new(),
+        })
+    }
+
+    fn protocol_dump(&self, addr: u64) -> Option<ProtocolDump> {
+        let protocol = self.descriptor(addr)?.as_protocol()?;
+        let associated_types = protocol
+            .associated_type_names()
+            .to_string_lossy()
+            .split_whitespace()
+            .map(String::from)
+            .collect();
+        Some(ProtocolDump {
+            name: self.mapped.context_name(protocol),
+            is_class_constrained: protocol.is_class_constrained(),
+            associated_types,
+            requirements: self
+                .requirements(protocol.requirement_signature(), true),
+        })
+    }
+
+    /// Returns the generic parameters and requirements of `descriptor`,
+    /// including those of its parents.
+    fn generic_signature(
+        &self,
+        descriptor: &ContextDescriptor,
+    ) -> (Vec<String>, Vec<String>) {
+        let generic = match descriptor.generic_context() {
+            Some(generic) => generic,
+            None => return Default::default(),
+        };
+
+        // Parameters of parent contexts come first, so each generic parent
+        // marks where the parameters of the next depth start.
+        let mut starts = descriptor
+            .parents()
+            .filter_map(ContextDescriptor::generic_context)
+            .map(|parent| usize::from(parent.header().num_params))
+            .filter(|&start| start != 0)
+            .collect::<Vec<_>>();
+        starts.sort_unstable();
+        starts.dedup();
+
+        let params = (0..generic.params().len())
+            .map(|i| {
+                let depth = starts.iter().filter(|&&start| start <= i).count();
+                let start = depth.checked_sub(1).map_or(0, |d| starts[d]);
+                generic_param_name(depth, i - start)
+            })
+            .collect();
+        let requirements = self.requirements(generic.requirements(), false);
+        (params, requirements)
+    }
+
+    /// Formats the requirements of a generic context.
+    fn requirements(
+        &self,
+        requirements: &[GenericRequirementDescriptor],
+        is_protocol: bool,
+    ) -> Vec<String> {
+        requirements
+            .iter()
+            .filter_map(|requirement| {
+                let ptr = requirement as *const GenericRequirementDescriptor;
+                let addr = self.mapped.addr(ptr as *const u8)?;
+                let requirement = self.image.generic_requirement(addr)?;
+                self.requirement(&requirement, is_protocol)
+            })
+            .collect()
+    }
+
+    /// Formats `requirement`, or returns `None` if it's implied by others.
+    ///
+    /// The first generic parameter of a protocol is `Self`.
+    fn requirement(
+        &self,
+        requirement: &GenericRequirement,
+        is_protocol: bool,
+    ) -> Option<String> {
+        let mut param = self.type_name_at(requirement.param?);
+        if is_protocol && (param == "A" || param.starts_with("A.")) {
+            param.replace_range(..1, "Self");
+        }
+        let content = requirement.content;
+        let constraint = match requirement.flags.kind() {
+            GenericRequirementKind::PROTOCOL => {
+                format!(": {}", self.reference_name(content?))
+            }
+            GenericRequirementKind::SAME_TYPE => {
+                format!(" == {}", self.type_name_at(content?.addr()?))
+            }
+            GenericRequirementKind::BASE_CLASS => {
+                format!(": {}", self.type_name_at(content?.addr()?))
+            }
+            GenericRequirementKind::LAYOUT => String::from(": AnyObject"),
+            _ => return None,
+        };
+        Some(param + &constraint)
+    }
+
+    /// Returns the name of the descriptor that `reference` refers to.
+    #[inline]
+    fn reference_name(&self, reference: Reference) -> String {
+        // Safety: only references that the compiler emitted to context
+        // descriptors are named.
+        unsafe { self.mapped.reference_name(reference) }
+    }
+
+    /// Returns the name of the type whose field is `field`, without its
+    /// reference storage.
+    fn field_type_name(&self, field: &Field) -> Option<String> {
+        match field.type_name()? {
+            Ok(name) => Some(name),
+            Err(_) => field.mangled_type().map(|m| self.mapped_type_name(m)),
+        }
+    }
+
+    /// Returns the name of the type mangled as `mangled` within the mapping.
+    #[inline]
+    fn mapped_type_name(&self, mangled: &[u8]) -> String {
+        self.mapped.type_name(mangled)
+    }
+
+    /// Returns the name of the type mangled at `addr`.
+    #[inline]
+    fn type_name_at(&self, addr: u64) -> String {
+        self.mapped.type_name_at(addr).unwrap_or_else(unknown)
+    }
+}
+
+/// Returns the name of the generic parameter at `index` of `depth`, such as
+/// `B` or `A1`, as printed by the demangler.
+fn generic_param_name(depth: usize, index: usize) -> String {
+    fn mangled_index(n: usize) -> String {
+        match n {
+            0 => String::from("_"),
+            n => format!("{}_", n - 1),
+        }
+    }
+
+    let mangled = match (depth, index) {
+        (0, 0) => String::from("x"),
+        (0, index) => format!("q{}", mangled_index(index - 1)),
+        (depth, index) => {
+            format!("qd{}{}", mangled_index(depth - 1), mangled_index(index))
+        }
+    };
+    match sym::parse_type(mangled.as_bytes()) {
+        Ok(node) => node.to_string(),
+        Err(_) => mangled,
+    }
+}
@@ -0,0 +1,88 @@
+//! Prints the Swift metadata of a binary: every nominal type with its fields,
+//! generic signature, conformances and associated types, and every protocol.
+//!
+//! This is similar to `swift-reflection-dump` but doesn't need a Swift
+//! toolchain, since the metadata is read by `swift-reflection` without
+//! loading the binary.
+
+#![deny(missing_docs)]
+
+mod dump;
+
+use dump::Dump;
+use std::{env, io, path::PathBuf, process};
+use swift_reflection::Image;
+
+const USAGE: &str = "\
+Prints the Swift metadata of an ELF binary.
+
+USAGE:
+    swift-metadata-dump [OPTIONS] <FILE>
+
+OPTIONS:
+    -j, --json    Print the metadata as JSON
+    -h, --help    Print this message
+";
+
+struct Args {
+    path: PathBuf,
+    json: bool,
+}
+
+impl Args {
+    fn parse() -> Self {
+        let mut path = None;
+        let mut json = false;
+        for arg in env::args_os().skip(1) {
+            match arg.to_str() {
+                Some("-j") | Some("--json") => json = true,
+                Some("-h") | Some("--help") => {
+                    print!("{}", USAGE);
+                    process::exit(0);
+                }
+                Some(flag) if flag.starts_with('-') => {
+                    fail(&format!("unknown argument '{}'", flag))
+                }
+                _ if path.is_some() => fail("expected a single file"),
+                _ => path = Some(PathBuf::from(arg)),
+            }
+        }
+        match path {
+            Some(path) => Self { path, json },
+            None => fail("expected a file"),
+        }
+    }
+}
+
+fn fail(message: &str) -> ! {
+    eprint!("error: {}\n\n{}", message, USAGE);
+    process::exit(2);
+}
+
+fn run(args: &Args) -> Result<(), swift_reflection::Error> {
+    let image = Image::open(&args.path)?;
+    let mapped = image.map()?;
+    let dump = Dump::read(&image, &mapped);
+
+    let result = if args.json {
+        use io::Write;
+        writeln!(io::stdout(), "{:#}", dump.to_json())
+    } else {
+        use io::Write;
+        write!(io::stdout(), "{}", dump)
+    };
+    match result {
+        Err(error) if error.kind() != io::ErrorKind::BrokenPipe => {
+            Err(error.into())
+        }
+        _ => Ok(()),
+    }
+}
+
+fn main() {
+    let args = Args::parse();
+    if let Err(error) = run(&args) {
+        eprintln!("error: {}: {}", args.path.display(), error);
+        process::exit(1);
+    }
+}
@@ -0,0 +1,563 @@
+//! Comparing the ABI of two builds of a library, as relied upon by code that
+//! was compiled against the older one.
+//!
+//! Only changes that corrupt memory or fail to link through existing
+//! bindings are reported: removed symbols, types and conformances, and
+//! changed layouts of structs, classes, enums and vtables.
+//!
+//! Changes to stored properties are errors, since bindings access fields at
+//! fixed offsets. Reflection metadata doesn't record whether a type is
+//! `@frozen`, so a library built with library evolution is described by a
+//! [`Resilience`](struct.Resilience.html), under which changes to the fields
+//! of resilient types are reported as warnings instead.
+
+use crate::{
+    elf::{SectionType, Symbol},
+    MappedImage, Reference,
+};
+use std::{
+    collections::{BTreeMap, BTreeSet, HashMap},
+    fmt,
+};
+use swift_rt::{field, sym};
+use swift_sys::metadata::context::{
+    ContextDescriptorKind, MethodDescriptor, TypeContextDescriptor,
+};
+
+/// The parts of a library's ABI that bindings depend on.
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct Abi {
+    /// Exported Swift symbols.
+    symbols: BTreeSet<String>,
+    /// Nominal types by qualified name.
+    types: BTreeMap<String, TypeAbi>,
+    /// Conformances as `(type, protocol)` names.
+    conformances: BTreeSet<(String, String)>,
+}
+
+/// The layout of a nominal type.
+#[derive(Clone, Debug, PartialEq, Eq)]
+struct TypeAbi {
+    kind: ContextDescriptorKind,
+    /// Stored properties or enum cases, with their types.
+    fields: Vec<Field>,
+    /// The offset in words and the methods of a class's vtable.
+    vtable: Option<(u32, Vec<String>)>,
+}
+
+/// A stored property or enum case and its type, which is `None` for cases
+/// without a payload.
+#[derive(Clone, Debug, PartialEq, Eq, Hash)]
+pub struct Field {
+    /// The name of the property or case.
+    pub name: String,
+    /// The demangled type of the property or payload.
+    pub type_name: Option<String>,
+}
+
+impl fmt::Display for Field {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match &self.type_name {
+            Some(ty) => write!(f, "{}: {}", self.name, ty),
+            None => f.write_str(&self.name),
+        }
+    }
+}
+
+/// A change between two builds of a library that breaks bindings to the
+/// older build.
+#[derive(Clone, Debug, PartialEq, Eq, Hash)]
+pub enum Change {
+    /// An exported Swift symbol was removed.
+    SymbolRemoved {
+        /// The mangled symbol.
+        symbol: String,
+    },
+    /// A nominal type was removed.
+    TypeRemoved {
+        /// The qualified name of the type.
+        name: String,
+    },
+    /// A type changed between being a struct, enum or class.
+    TypeKindChanged {
+        /// The qualified name of the type.
+        name: String,
+    },
+    /// The stored properties of a struct or class were removed, reordered or
+    /// changed type, or new ones were added.
+    FieldsChanged {
+        /// The qualified name of the struct or class.
+        name: String,
+        /// The stored properties in the old build.
+        old: Vec<Field>,
+        /// The stored properties in the new build.
+        new: Vec<Field>,
+    },
+    /// The cases of an enum were removed, reordered or changed payload, or
+    /// new ones were added, which changes the tags of the enum's values.
+    CasesChanged {
+        /// The qualified name of the enum.
+        name: String,
+        /// The cases in the old build.
+        old: Vec<Field>,
+        /// The cases in the new build.
+        new: Vec<Field>,
+    },
+    /// The methods of a class's vtable were removed or reordered, or the
+    /// vtable moved within the class's metadata.
+    VTableChanged {
+        /// The qualified name of the class.
+        name: String,
+        /// The offset in words of the vtable in the old build.
+        old_offset: u32,
+        /// The offset in words of the vtable in the new build.
+        new_offset: u32,
+        /// The methods in the old build.
+        old: Vec<String>,
+        /// The methods in the new build.
+        new: Vec<String>,
+    },
+    /// A class no longer has a vtable, so its methods are no longer
+    /// dispatched through the offsets of the old build.
+    VTableRemoved {
+        /// The qualified name of the class.
+        name: String,
+    },
+    /// A conformance of a type to a protocol was removed.
+    ConformanceRemoved {
+        /// The qualified name of the conforming type.
+        type_name: String,
+        /// The qualified name of the protocol.
+        protocol: String,
+    },
+}
+
+impl Change {
+    /// Returns the name of the kind of change in `snake_case`, such as
+    /// `symbol_removed`.
+    pub fn kind(&self) -> &'static str {
+        match self {
+            Self::SymbolRemoved { .. } => "symbol_removed",
+            Self::TypeRemoved { .. } => "type_removed",
+            Self::TypeKindChanged { .. } => "type_kind_changed",
+            Self::FieldsChanged { .. } => "fields_changed",
+            Self::CasesChanged { .. } => "cases_changed",
+            Self::VTableChanged { .. } => "vtable_changed",
+            Self::VTableRemoved { .. } => "vtable_removed",
+            Self::ConformanceRemoved { .. } => "conformance_removed",
+        }
+    }
+
+    /// Returns how certain it is that the change breaks bindings, given
+    /// which types of the library are resilient.
+    pub fn severity(&self, resilience: &Resilience) -> Severity {
+        match self {
+            Self::FieldsChanged { name, .. }
+                if resilience.is_resilient(name) =>
+            {
+                Severity::Warning
+            }
+            _ => Severity::Error,
+        }
+    }
+}
+
+/// How certain it is that a [`Change`](enum.Change.html) breaks bindings.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash, PartialOrd, Ord)]
+pub enum Severity {
+    /// The change breaks bindings compiled against the older build.
+    Error,
+    /// The change breaks bindings only if they access the layout of a
+    /// resilient type directly.
+    Warning,
+}
+
+impl Severity {
+    /// Returns the name of the severity in `snake_case`, such as `error`.
+    pub fn name(self) -> &'static str {
+        match self {
+            Self::Error => "error",
+            Self::Warning => "warning",
+        }
+    }
+}
+
+/// Which types of a library are resilient, meaning that code outside the
+/// library accesses their stored properties through accessors rather than at
+/// fixed offsets.
+///
+/// By default no types are resilient, as in a library built without library
+/// evolution.
+///
+/// # Examples
+///
+/// ```
+/// use swift_reflection::abi::Resilience;
+///
+/// let resilience = Resilience::library_evolution().frozen("Foo.Point");
+/// assert!(resilience.is_resilient("Foo.Bar"));
+/// assert!(!resilience.is_resilient("Foo.Point"));
+/// assert!(!Resilience::new().is_resilient("Foo.Bar"));
+/// ```
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct Resilience {
+    library_evolution: bool,
+    frozen: BTreeSet<String>,
+}
+
+impl Resilience {
+    /// Returns the resilience of a library built without library evolution,
+    /// where no types are resilient.
+    #[inline]
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Returns the resilience of a library built with library evolution,
+    /// where every type is resilient unless it's `@frozen`.
+    #[inline]
+    pub fn library_evolution() -> Self {
+        Self {
+            library_evolution: true,
+            frozen: BTreeSet::new(),
+        }
+    }
+
+    /// Marks the type with the qualified name `name` as `@frozen`, so that
+    /// it isn't resilient.
+    #[inline]
+    pub fn frozen(mut self, name: impl Into<String>) -> Self {
+        self.frozen.insert(name.into());
+        self
+    }
+
+    /// Returns whether the type with the qualified name `name` is resilient.
+    #[inline]
+    pub fn is_resilient(&self, name: &str) -> bool {
+        self.library_evolution && !self.frozen.contains(name)
+    }
+}
+
+impl fmt::Display for Change {
+    /// Writes a one-line description of the change.
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            Self::SymbolRemoved { symbol } => {
+                match sym::demangle(symbol.as_bytes()) {
+                    Ok(name) => {
+                        write!(f, "removed symbol {} ({})", symbol, name)
+                    }
+                    Err(_) => write!(f, "removed symbol {}", symbol),
+                }
+            }
+            Self::TypeRemoved { name } => write!(f, "removed type {}", name),
+            Self::TypeKindChanged { name } => {
+                write!(f, "changed kind of type {}", name)
+            }
+            Self::FieldsChanged { name, .. } => {
+                write!(f, "changed stored properties of {}", name)
+            }
+            Self::CasesChanged { name, .. } => {
+                write!(f, "changed cases of {}", name)
+            }
+            Self::VTableChanged { name, .. } => {
+                write!(f, "changed vtable of {}", name)
+            }
+            Self::VTableRemoved { name } => {
+                write!(f, "removed vtable of {}", name)
+            }
+            Self::ConformanceRemoved {
+                type_name,
+                protocol,
+            } => write!(f, "removed conformance {}: {}", type_name, protocol),
+        }
+    }
+}
+
+impl Abi {
+    /// Reads the ABI of the image laid out in `mapped`.
+    pub fn read(mapped: &MappedImage) -> Self {
+        let image = mapped.image();
+        let elf = image.elf();
+
+        let symbols = elf
+            .sections()
+            .iter()
+            .filter(|section| {
+                section.kind == SectionType::SYMTAB
+                    || section.kind == SectionType::DYNSYM
+            })
+            .flat_map(|table| elf.symbols(table))
+            .filter(|symbol| symbol.is_defined() && !symbol.name.is_empty())
+            .collect::<Vec<_>>();
+        let names = symbols
+            .iter()
+            .map(|symbol| (symbol.value, symbol.name.as_str()))
+            .collect::<HashMap<_, _>>();
+
+        let mut abi = Self {
+            symbols: elf
+                .dynamic_symbols()
+                .into_iter()
+                .filter(Symbol::is_exported)
+                .filter(|symbol| sym::is_mangled(symbol.name.as_bytes()))
+                .map(|symbol| symbol.name)
+                .collect(),
+            ..Self::default()
+        };
+
+        for record in image.types() {
+            let addr = match record.target {
+                Some(Reference::Addr(addr)) => addr,
+                _ => continue,
+            };
+            // Safety: records of `swift5_types` refer to type descriptors.
+            let descriptor = unsafe { mapped.context_descriptor(addr) };
+            if let Some(ty) = descriptor.and_then(|d| d.as_type()) {
+                let name = mapped.context_name(ty);
+                abi.types.insert(name, type_abi(mapped, ty, &names));
+            }
+        }
+
+        for conformance in image.conformance_descriptors() {
+            if let (Some(ty), Some(protocol)) =
+                (conformance.type_ref, conformance.protocol)
+            {
+                // Safety: conformances refer to the descriptors of the type
+                // and protocol.
+                let pair = unsafe {
+                    (mapped.reference_name(ty), mapped.reference_name(protocol))
+                };
+                abi.conformances.insert(pair);
+            }
+        }
+
+        abi
+    }
+
+    /// Returns the changes from `self` to `new` that break code compiled
+    /// against `self`, ordered by kind and then by name.
+    pub fn changes(&self, new: &Self) -> Vec<Change> {
+        let mut changes = self
+            .symbols
+            .difference(&new.symbols)
+            .map(|symbol| Change::SymbolRemoved {
+                symbol: symbol.clone(),
+            })
+            .collect::<Vec<_>>();
+
+        for (name, old) in &self.types {
+            let new = match new.types.get(name) {
+                Some(new) => new,
+                None => {
+                    changes.push(Change::TypeRemoved { name: name.clone() });
+                    continue;
+                }
+            };
+            if old.kind != new.kind {
+                changes.push(Change::TypeKindChanged { name: name.clone() });
+                continue;
+            }
+            if old.fields != new.fields {
+                let (name, fields) = (name.clone(), old.fields.clone());
+                let new_fields = new.fields.clone();
+                match old.kind {
+                    ContextDescriptorKind::STRUCT
+                    | ContextDescriptorKind::CLASS => {
+                        changes.push(Change::FieldsChanged {
+                            name,
+                            old: fields,
+                            new: new_fields,
+                        })
+                    }
+                    ContextDescriptorKind::ENUM => {
+                        changes.push(Change::CasesChanged {
+                            name,
+                            old: fields,
+                            new: new_fields,
+                        })
+                    }
+                    _ => {}
+                }
+            }
+            match (&old.vtable, &new.vtable) {
+                // Appended methods keep the offsets of existing ones.
+                (Some((old_offset, old)), Some((new_offset, new)))
+                    if old_offset != new_offset || !new.starts_with(old) =>
+                {
+                    changes.push(Change::VTableChanged {
+                        name: name.clone(),
+                        old_offset: *old_offset,
+                        new_offset: *new_offset,
+                        old: old.clone(),
+                        new: new.clone(),
+                    });
+                }
+                (Some(_), None) => {
+                    changes.push(Change::VTableRemoved { name: name.clone() });
+                }
+                _ => {}
+            }
+        }
+
+        changes.extend(self.conformances.difference(&new.conformances).map(
+            |(type_name, protocol)| Change::ConformanceRemoved {
+                type_name: type_name.clone(),
+                protocol: protocol.clone(),
+            },
+        ));
+
+        changes.sort_by_key(change_order);
+        changes
+    }
+}
+
+/// Returns the position of `change`'s kind in the order in which changes are
+/// reported.
+fn change_order(change: &Change) -> u8 {
+    match change {
+        Change::SymbolRemoved { .. } => 0,
+        Change::TypeRemoved { .. } => 1,
+        Change::TypeKindChanged { .. } => 2,
+        Change::FieldsChanged { .. } => 3,
+        Change::CasesChanged { .. } => 4,
+        Change::VTableChanged { .. } => 5,
+        Change::VTableRemoved { .. } => 6,
+        Change::ConformanceRemoved { .. } => 7,
+    }
+}
+
+fn type_abi(
+    mapped: &MappedImage,
+    ty: &TypeContextDescriptor,
+    names: &HashMap<u64, &str>,
+) -> TypeAbi {
+    let fields = field::fields(ty)
+        .map(|field| Field {
+            name: field.name().into_owned(),
+            type_name: match field.type_name() {
+                Some(Ok(name)) => Some(name),
+                _ => field.mangled_type().map(|m| mapped.type_name(m)),
+            },
+        })
+        .collect();
+
+    let vtable = ty.as_class().and_then(|class| {
+        let header = class.vtable_header()?;
+        let methods = class
+            .methods()
+            .iter()
+            .enumerate()
+            .map(|(i, method)| method_name(mapped, method, names, i))
+            .collect();
+        Some((header.vtable_offset, methods))
+    });
+
+    TypeAbi {
+        kind: ty.kind(),
+        fields,
+        vtable,
+    }
+}
+
+/// Returns the name of the method in slot `index` of a vtable, which is the
+/// demangled symbol of its descriptor or implementation if there is one.
+fn method_name(
+    mapped: &MappedImage,
+    method: &MethodDescriptor,
+    names: &HashMap<u64, &str>,
+    index: usize,
+) -> String {
+    let descriptor =
+        mapped.addr(method as *const MethodDescriptor as *const u8);
+    let implementation = mapped.addr(method.implementation.get() as *const u8);
+    let symbol = descriptor
+        .and_then(|addr| names.get(&addr))
+        .or_else(|| implementation.and_then(|addr| names.get(&addr)));
+    match symbol {
+        Some(symbol) => sym::demangle(symbol.as_bytes())
+            .unwrap_or_else(|_| (*symbol).to_owned()),
+        None => format!("<method #{} ({:?})>", index, method.flags.kind()),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn field(name: &str, type_name: &str) -> Field {
+        Field {
+            name: name.to_owned(),
+            type_name: Some(type_name.to_owned()),
+        }
+    }
+
+    fn abi(
+        kind: ContextDescriptorKind,
+        fields: Vec<Field>,
+        vtable: Option<(u32, Vec<String>)>,
+    ) -> Abi {
+        let mut abi = Abi::default();
+        let ty = TypeAbi {
+            kind,
+            fields,
+            vtable,
+        };
+        abi.types.insert("main.Foo".to_owned(), ty);
+        abi
+    }
+
+    #[test]
+    fn struct_fields_are_errors_unless_resilient() {
+        let old = abi(
+            ContextDescriptorKind::STRUCT,
+            vec![field("x", "Swift.Int")],
+            None,
+        );
+        let new = abi(
+            ContextDescriptorKind::STRUCT,
+            vec![field("x", "Swift.Int8")],
+            None,
+        );
+        let changes = old.changes(&new);
+        assert_eq!(changes.len(), 1);
+        assert_eq!(changes[0].kind(), "fields_changed");
+
+        let change = &changes[0];
+        let resilient = Resilience::library_evolution();
+        assert_eq!(change.severity(&Resilience::new()), Severity::Error);
+        assert_eq!(change.severity(&resilient), Severity::Warning);
+        let frozen = resilient.frozen("main.Foo");
+        assert_eq!(change.severity(&frozen), Severity::Error);
+    }
+
+    #[test]
+    fn class_fields_are_compared() {
+        let vtable = Some((10, vec!["main.Foo.bar() -> ()".to_owned()]));
+        let old = abi(
+            ContextDescriptorKind::CLASS,
+            vec![field("x", "Swift.Int")],
+            vtable.clone(),
+        );
+        let new = abi(ContextDescriptorKind::CLASS, Vec::new(), vtable);
+        let changes = old.changes(&new);
+        assert_eq!(changes.len(), 1);
+        assert_eq!(changes[0].kind(), "fields_changed");
+        assert_eq!(changes[0].severity(&Resilience::new()), Severity::Error);
+    }
+
+    #[test]
+    fn removed_vtable_is_an_error() {
+        let vtable = Some((10, vec!["main.Foo.bar() -> ()".to_owned()]));
+        let old = abi(ContextDescriptorKind::CLASS, Vec::new(), vtable);
+        let new = abi(ContextDescriptorKind::CLASS, Vec::new(), None);
+        assert_eq!(
+            old.changes(&new),
+            [Change::VTableRemoved {
+                name: "main.Foo".to_owned(),
+            }],
+        );
+        let resilient = Resilience::library_evolution();
+        assert_eq!(old.changes(&new)[0].severity(&resilient), Severity::Error);
+    }
+}
@@ -0,0 +1,728 @@
+//! A minimal reader for ELF files.
+//!
+//! Only what's needed to find Swift metadata is parsed: the file header and
+//! section headers. Both 32- and 64-bit files of either byte order are
+//! supported.
+
+// Based on:
+// https://refspecs.linuxfoundation.org/elf/gabi4+/ch4.eheader.html
+// https://refspecs.linuxfoundation.org/elf/gabi4+/ch4.sheader.html
+
+use crate::Error;
+use std::{convert::TryFrom, ops::Range};
+
+const MAGIC: &[u8; 4] = b"\x7FELF";
+
+const CLASS_32: u8 = 1;
+const CLASS_64: u8 = 2;
+
+const DATA_LITTLE: u8 = 1;
+const DATA_BIG: u8 = 2;
+
+/// Indicates that the real section count or string table index is stored in
+/// the first section header.
+const SHN_XINDEX: u16 = 0xFFFF;
+
+/// The byte order of an ELF file.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub enum Endian {
+    /// Least significant byte first.
+    Little,
+    /// Most significant byte first.
+    Big,
+}
+
+impl Endian {
+    /// Reads the `u16` at the start of `bytes`.
+    #[inline]
+    pub fn read_u16(self, bytes: &[u8]) -> Option<u16> {
+        let bytes = <[u8; 2]>::try_from(bytes.get(..2)?).ok()?;
+        Some(match self {
+            Self::Little => u16::from_le_bytes(bytes),
+            Self::Big => u16::from_be_bytes(bytes),
+        })
+    }
+
+    /// Reads the `u32` at the start of `bytes`.
+    #[inline]
+    pub fn read_u32(self, bytes: &[u8]) -> Option<u32> {
+        let bytes = <[u8; 4]>::try_from(bytes.get(..4)?).ok()?;
+        Some(match self {
+            Self::Little => u32::from_le_bytes(bytes),
+            Self::Big => u32::from_be_bytes(bytes),
+        })
+    }
+
+    /// Reads the `u64` at the start of `bytes`.
+    #[inline]
+    pub fn read_u64(self, bytes: &[u8]) -> Option<u64> {
+        let bytes = <[u8; 8]>::try_from(bytes.get(..8)?).ok()?;
+        Some(match self {
+            Self::Little => u64::from_le_bytes(bytes),
+            Self::Big => u64::from_be_bytes(bytes),
+        })
+    }
+}
+
+/// The type of an ELF file.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+#[repr(transparent)]
+pub struct FileType(pub u16);
+
+impl FileType {
+    /// A relocatable object file (`ET_REL`).
+    pub const RELOCATABLE: Self = Self(1);
+    /// An executable (`ET_EXEC`).
+    pub const EXECUTABLE: Self = Self(2);
+    /// A shared object or position-independent executable (`ET_DYN`).
+    pub const SHARED: Self = Self(3);
+    /// A core dump (`ET_CORE`).
+    pub const CORE: Self = Self(4);
+}
+
+/// The type of a section's contents.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+#[repr(transparent)]
+pub struct SectionType(pub u32);
+
+impl SectionType {
+    /// An unused section header (`SHT_NULL`).
+    pub const NULL: Self = Self(0);
+    /// Program-defined contents (`SHT_PROGBITS`).
+    pub const PROGBITS: Self = Self(1);
+    /// A symbol table for link editing (`SHT_SYMTAB`).
+    pub const SYMTAB: Self = Self(2);
+    /// A string table (`SHT_STRTAB`).
+    pub const STRTAB: Self = Self(3);
+    /// Relocations with explicit addends (`SHT_RELA`).
+    pub const RELA: Self = Self(4);
+    /// Dynamic linking information (`SHT_DYNAMIC`).
+    pub const DYNAMIC: Self = Self(6);
+    /// Contents that occupy no space in the file (`SHT_NOBITS`).
+    pub const NOBITS: Self = Self(8);
+    /// Relocations without explicit addends (`SHT_REL`).
+    pub const REL: Self = Self(9);
+    /// A symbol table for dynamic linking (`SHT_DYNSYM`).
+    pub const DYNSYM: Self = Self(11);
+}
+
+/// A section header.
+#[derive(Clone, Debug, PartialEq, Eq, Hash)]
+pub struct Section {
+    /// The name of the section, such as `.text`.
+    pub name: String,
+    /// The type of the section's contents.
+    pub kind: SectionType,
+    /// The `SHF_*` attribute flags.
+    pub flags: u64,
+    /// The virtual address of the section once loaded, or 0 if it isn't
+    /// loaded.
+    pub addr: u64,
+    /// The offset of the section's contents within the file.
+    pub offset: u64,
+    /// The size of the section in bytes.
+    pub size: u64,
+    /// The index of an associated section, such as the string table of a
+    /// symbol table.
+    pub link: u32,
+    /// Extra information whose meaning depends on the section type.
+    pub info: u32,
+    /// The size of each entry for sections that hold a table.
+    pub entry_size: u64,
+}
+
+impl Section {
+    /// Returns whether the section occupies space in the file.
+    #[inline]
+    pub fn has_data(&self) -> bool {
+        self.kind != SectionType::NOBITS && self.kind != SectionType::NULL
+    }
+
+    /// Returns the range of virtual addresses occupied by the section.
+    #[inline]
+    pub fn addr_range(&self) -> Range<u64> {
+        self.addr..self.addr.saturating_add(self.size)
+    }
+}
+
+/// A parsed ELF file, which owns its contents.
+#[derive(Clone, Debug)]
+pub struct Elf {
+    data: Vec<u8>,
+    endian: Endian,
+    is_64: bool,
+    file_type: FileType,
+    machine: u16,
+    sections: Vec<Section>,
+}
+
+impl Elf {
+    /// Parses the ELF file contained in `data`.
+    pub fn parse(data: Vec<u8>) -> Result<Self, Error> {
+        let ident = data.get(..16).ok_or(Error::NotElf)?;
+        if &ident[..4] != MAGIC {
+            return Err(Error::NotElf);
+        }
+        let is_64 = match ident[4] {
+            CLASS_32 => false,
+            CLASS_64 => true,
+            _ => return Err(Error::Unsupported),
+        };
+        let endian = match ident[5] {
+            DATA_LITTLE => Endian::Little,
+            DATA_BIG => Endian::Big,
+            _ => return Err(Error::Unsupported),
+        };
+
+        let mut elf = Self {
+            data,
+            endian,
+            is_64,
+            file_type: FileType(0),
+            machine: 0,
+            sections: Vec::new(),
+        };
+        let header = elf.data.get(16..).ok_or(Error::Malformed)?;
+        elf.file_type =
+            FileType(endian.read_u16(header).ok_or(Error::Malformed)?);
+        elf.machine = endian.read_u16(&header[2..]).ok_or(Error::Malformed)?;
+        elf.sections = elf.parse_sections().ok_or(Error::Malformed)?;
+        Ok(elf)
+    }
+
+    fn parse_sections(&self) -> Option<Vec<Section>> {
+        let endian = self.endian;
+        let header = &self.data;
+        let (shoff, shentsize, shnum, mut shstrndx) = if self.is_64 {
+            (
+                endian.read_u64(header.get(40..)?)?,
+                endian.read_u16(header.get(58..)?)?,
+                endian.read_u16(header.get(60..)?)?,
+                endian.read_u16(header.get(62..)?)?,
+            )
+        } else {
+            (
+                u64::from(endian.read_u32(header.get(32..)?)?),
+                endian.read_u16(header.get(46..)?)?,
+                endian.read_u16(header.get(48..)?)?,
+                endian.read_u16(header.get(50..)?)?,
+            )
+        };
+        if shoff == 0 {
+            return Some(Vec::new());
+        }
+
+        let shoff = usize::try_from(shoff).ok()?;
+        let shentsize = usize::from(shentsize);
+        let min_entsize = if self.is_64 { 64 } else { 40 };
+        if shentsize < min_entsize {
+            return None;
+        }
+
+        let (_, first) = self.parse_section_header(self.data.get(shoff..)?)?;
+        let count = if shnum == 0 {
+            // Too many sections for the file header; the count is stored in
+            // the first section's size instead.
+            usize::try_from(first.size).ok()?
+        } else {
+            usize::from(shnum)
+        };
+        if shstrndx == SHN_XINDEX {
+            shstrndx = u16::try_from(first.link).ok()?;
+        }
+
+        let table_len = count.checked_mul(shentsize)?;
+        let table = self.data.get(shoff..shoff.checked_add(table_len)?)?;
+        let headers = table
+            .chunks_exact(shentsize)
+            .map(|header| self.parse_section_header(header))
+            .collect::<Option<Vec<_>>>()?;
+
+        // Names are resolved once every header has been read, since they're
+        // stored in one of the sections.
+        let names = match headers.get(usize::from(shstrndx)) {
+            Some((_, names)) => self.section_data(names),
+            None => &[],
+        };
+        let sections = headers.into_iter().map(|(name, mut section)| {
+            let name = usize::try_from(name)
+                .ok()
+                .and_then(|start| c_str(names.get(start..)?));
+            if let Some(name) = name {
+                section.name = String::from_utf8_lossy(name).into_owned();
+            }
+            section
+        });
+        Some(sections.collect())
+    }
+
+    /// Parses a section header, returning the offset of its name within the
+    /// section name table.
+    fn parse_section_header(&self, header: &[u8]) -> Option<(u32, Section)> {
+        let endian = self.endian;
+        let name = endian.read_u32(header)?;
+        let kind = SectionType(endian.read_u32(header.get(4..)?)?);
+        let section = if self.is_64 {
+            Section {
+                name: String::new(),
+                kind,
+                flags: endian.read_u64(header.get(8..)?)?,
+                addr: endian.read_u64(header.get(16..)?)?,
+                offset: endian.read_u64(header.get(24..)?)?,
+                size: endian.read_u64(header.get(32..)?)?,
+                link: endian.read_u32(header.get(40..)?)?,
+                info: endian.read_u32(header.get(44..)?)?,
+                entry_size: endian.read_u64(header.get(56..)?)?,
+            }
+        } else {
+            let read = |offset: usize| -> Option<u64> {
+                endian.read_u32(header.get(offset..)?).map(u64::from)
+            };
+            Section {
+                name: String::new(),
+                kind,
+                flags: read(8)?,
+                addr: read(12)?,
+                offset: read(16)?,
+                size: read(20)?,
+                link: endian.read_u32(header.get(24..)?)?,
+                info: endian.read_u32(header.get(28..)?)?,
+                entry_size: read(36)?,
+            }
+        };
+        Some((name, section))
+    }
+
+    /// Returns the contents of the file.
+    #[inline]
+    pub fn data(&self) -> &[u8] {
+        &self.data
+    }
+
+    /// Returns the byte order of the file.
+    #[inline]
+    pub fn endian(&self) -> Endian {
+        self.endian
+    }
+
+    /// Returns whether the file uses 64-bit addresses.
+    #[inline]
+    pub fn is_64(&self) -> bool {
+        self.is_64
+    }
+
+    /// Returns the size of a pointer in bytes.
+    #[inline]
+    pub fn pointer_size(&self) -> usize {
+        if self.is_64 {
+            8
+        } else {
+            4
+        }
+    }
+
+    /// Returns the type of the file.
+    #[inline]
+    pub fn file_type(&self) -> FileType {
+        self.file_type
+    }
+
+    /// Returns the `EM_*` target architecture of the file.
+    #[inline]
+    pub fn machine(&self) -> u16 {
+        self.machine
+    }
+
+    /// Returns the section headers of the file.
+    #[inline]
+    pub fn sections(&self) -> &[Section] {
+        &self.sections
+    }
+
+    /// Returns the first section named `name`.
+    pub fn section(&self, name: &str) -> Option<&Section> {
+        self.sections.iter().find(|section| section.name == name)
+    }
+
+    /// Returns the contents of `section`, which are empty if it occupies no
+    /// space in the file or if its bounds are invalid.
+    pub fn section_data(&self, section: &Section) -> &[u8] {
+        if !section.has_data() {
+            return &[];
+        }
+        let range = usize::try_from(section.offset).ok().and_then(|start| {
+            let end = start.checked_add(usize::try_from(section.size).ok()?)?;
+            self.data.get(start..end)
+        });
+        range.unwrap_or(&[])
+    }
+
+    /// Returns the symbols of the symbol table `table`, such as `.dynsym`.
+    ///
+    /// Returns an empty list if `table` isn't a symbol table.
+    pub fn symbols(&self, table: &Section) -> Vec<Symbol> {
+        if table.kind != SectionType::SYMTAB
+            && table.kind != SectionType::DYNSYM
+        {
+            return Vec::new();
+        }
+        let names = self.sections.get(table.link as usize);
+        let names = names.map_or(&[][..], |names| self.section_data(names));
+
+        let entry_size = if self.is_64 { 24 } else { 16 };
+        let entries = self.section_data(table).chunks_exact(entry_size);
+        entries
+            .filter_map(|entry| self.parse_symbol(entry, names))
+            .collect()
+    }
+
+    /// Returns the symbols of the dynamic symbol table, which are those
+    /// exported or imported by a shared object.
+    pub fn dynamic_symbols(&self) -> Vec<Symbol> {
+        self.sections
+            .iter()
+            .find(|section| section.kind == SectionType::DYNSYM)
+            .map_or_else(Vec::new, |table| self.symbols(table))
+    }
+
+    fn parse_symbol(&self, entry: &[u8], names: &[u8]) -> Option<Symbol> {
+        let endian = self.endian;
+        let name = endian.read_u32(entry)?;
+        let (value, size, info, other, section_index) = if self.is_64 {
+            (
+                endian.read_u64(entry.get(8..)?)?,
+                endian.read_u64(entry.get(16..)?)?,
+                *entry.get(4)?,
+                *entry.get(5)?,
+                endian.read_u16(entry.get(6..)?)?,
+            )
+        } else {
+            (
+                u64::from(endian.read_u32(entry.get(4..)?)?),
+                u64::from(endian.read_u32(entry.get(8..)?)?),
+                *entry.get(12)?,
+                *entry.get(13)?,
+                endian.read_u16(entry.get(14..)?)?,
+            )
+        };
+        let name = usize::try_from(name)
+            .ok()
+            .and_then(|start| c_str(names.get(start..)?))
+            .unwrap_or_default();
+        Some(Symbol {
+            name: String::from_utf8_lossy(name).into_owned(),
+            value,
+            size,
+            info,
+            other,
+            section_index,
+        })
+    }
+
+    /// Returns the relocations of `section`, or an empty list if it isn't a
+    /// `SHT_REL` or `SHT_RELA` section.
+    ///
+    /// The `symbol` of each relocation indexes into the symbol table that
+    /// `section` links to.
+    pub fn relocations(&self, section: &Section) -> Vec<Relocation> {
+        let has_addend = match section.kind {
+            SectionType::RELA => true,
+            SectionType::REL => false,
+            _ => return Vec::new(),
+        };
+        let word = self.pointer_size();
+        let entry_size = if has_addend { word * 3 } else { word * 2 };
+
+        let endian = self.endian;
+        let parse = |entry: &[u8]| -> Option<Relocation> {
+            let (offset, kind, symbol) = if self.is_64 {
+                let info = endian.read_u64(entry.get(8..)?)?;
+                (endian.read_u64(entry)?, info as u32, (info >> 32) as u32)
+            } else {
+                let info = endian.read_u32(entry.get(4..)?)?;
+                (u64::from(endian.read_u32(entry)?), info & 0xFF, info >> 8)
+            };
+            let addend = if !has_addend {
+                None
+            } else if self.is_64 {
+                Some(endian.read_u64(entry.get(16..)?)? as i64)
+            } else {
+                Some(i64::from(endian.read_u32(entry.get(8..)?)? as i32))
+            };
+            Some(Relocation {
+                offset,
+                kind,
+                symbol,
+                addend,
+            })
+        };
+        self.section_data(section)
+            .chunks_exact(entry_size)
+            .filter_map(parse)
+            .collect()
+    }
+}
+
+/// An entry of a symbol table.
+#[derive(Clone, Debug, PartialEq, Eq, Hash)]
+pub struct Symbol {
+    /// The name of the symbol, which may be mangled.
+    pub name: String,
+    /// The value of the symbol, which is usually its virtual address.
+    pub value: u64,
+    /// The size of the object that the symbol refers to, or 0 if unknown.
+    pub size: u64,
+    /// The binding and type of the symbol.
+    pub info: u8,
+    /// The visibility of the symbol in the low 2 bits.
+    pub other: u8,
+    /// The index of the section that the symbol is defined in, or 0 if it's
+    /// undefined.
+    pub section_index: u16,
+}
+
+impl Symbol {
+    /// A symbol not visible outside of its object file (`STB_LOCAL`).
+    pub const BIND_LOCAL: u8 = 0;
+    /// A symbol visible to every object file (`STB_GLOBAL`).
+    pub const BIND_GLOBAL: u8 = 1;
+    /// A global symbol with lower precedence (`STB_WEAK`).
+    pub const BIND_WEAK: u8 = 2;
+
+    /// A data object, such as a variable or metadata record (`STT_OBJECT`).
+    pub const TYPE_OBJECT: u8 = 1;
+    /// A function or other executable code (`STT_FUNC`).
+    pub const TYPE_FUNC: u8 = 2;
+
+    /// A symbol whose visibility is given by its binding (`STV_DEFAULT`).
+    pub const VISIBILITY_DEFAULT: u8 = 0;
+    /// A symbol that is visible to other files but always resolves to this
+    /// file's definition (`STV_PROTECTED`).
+    pub const VISIBILITY_PROTECTED: u8 = 3;
+
+    /// Returns the `STB_*` binding of the symbol.
+    #[inline]
+    pub fn binding(&self) -> u8 {
+        self.info >> 4
+    }
+
+    /// Returns the `STT_*` type of the symbol.
+    #[inline]
+    pub fn kind(&self) -> u8 {
+        self.info & 0xF
+    }
+
+    /// Returns the `STV_*` visibility of the symbol.
+    #[inline]
+    pub fn visibility(&self) -> u8 {
+        self.other & 0x3
+    }
+
+    /// Returns whether the symbol is defined in this file rather than
+    /// imported from another.
+    #[inline]
+    pub fn is_defined(&self) -> bool {
+        self.section_index != 0
+    }
+
+    /// Returns whether the symbol is defined in this file and visible to
+    /// others, such as a function exported by a shared object.
+    #[inline]
+    pub fn is_exported(&self) -> bool {
+        let binding = self.binding();
+        self.is_defined()
+            && (binding == Self::BIND_GLOBAL || binding == Self::BIND_WEAK)
+            && (self.visibility() == Self::VISIBILITY_DEFAULT
+                || self.visibility() == Self::VISIBILITY_PROTECTED)
+    }
+}
+
+/// An entry of a relocation section.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub struct Relocation {
+    /// The location to apply the relocation at. This is a virtual address in
+    /// linked files and an offset within the relocated section in object
+    /// files.
+    pub offset: u64,
+    /// The machine-specific type of relocation.
+    pub kind: u32,
+    /// The index of the symbol the relocation refers to, or 0 if none.
+    pub symbol: u32,
+    /// The explicit addend of `SHT_RELA` relocations. `SHT_REL` relocations
+    /// store the addend at `offset` instead.
+    pub addend: Option<i64>,
+}
+
+/// Returns the bytes of the nul-terminated string at the start of `bytes`.
+#[inline]
+pub(crate) fn c_str(bytes: &[u8]) -> Option<&[u8]> {
+    let len = bytes.iter().position(|&b| b == 0)?;
+    Some(&bytes[..len])
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{
+        fixture::{ElfBuilder, FixtureSymbol},
+        Image,
+    };
+
+    const FORMATS: [(bool, Endian); 4] = [
+        (false, Endian::Little),
+        (false, Endian::Big),
+        (true, Endian::Little),
+        (true, Endian::Big),
+    ];
+
+    fn section_names(elf: &Elf) -> Vec<&str> {
+        elf.sections().iter().map(|s| s.name.as_str()).collect()
+    }
+
+    #[test]
+    fn parses_each_class_and_byte_order() {
+        for &(is_64, endian) in &FORMATS {
+            let mut builder = ElfBuilder::new(is_64, endian);
+            builder.machine(62);
+            builder.section(".text", SectionType::PROGBITS, 0x1000, vec![1; 4]);
+            builder.section(".bss", SectionType::NOBITS, 0x2000, Vec::new());
+
+            let elf = Elf::parse(builder.build()).unwrap();
+            assert_eq!(elf.endian(), endian);
+            assert_eq!(elf.is_64(), is_64);
+            assert_eq!(elf.pointer_size(), if is_64 { 8 } else { 4 });
+            assert_eq!(elf.file_type(), FileType::SHARED);
+            assert_eq!(elf.machine(), 62);
+            assert_eq!(section_names(&elf), ["", ".text", ".bss", ".shstrtab"]);
+
+            let text = elf.section(".text").unwrap();
+            assert_eq!(text.kind, SectionType::PROGBITS);
+            assert_eq!(text.addr_range(), 0x1000..0x1004);
+            assert_eq!(elf.section_data(text), [1; 4]);
+            assert!(elf.section_data(elf.section(".bss").unwrap()).is_empty());
+        }
+    }
+
+    #[test]
+    fn reads_extended_section_numbering() {
+        for &(is_64, endian) in &FORMATS {
+            let mut builder = ElfBuilder::new(is_64, endian);
+            builder.extended_numbering();
+            builder.section(".text", SectionType::PROGBITS, 0x1000, vec![1; 4]);
+
+            let elf = Elf::parse(builder.build()).unwrap();
+            assert_eq!(section_names(&elf), ["", ".text", ".shstrtab"]);
+            assert_eq!(elf.sections()[0].size, 3);
+            assert_eq!(elf.sections()[0].link, 2);
+        }
+    }
+
+    #[test]
+    fn rejects_other_formats() {
+        assert!(matches!(Elf::parse(Vec::new()), Err(Error::NotElf)));
+        assert!(matches!(
+            Elf::parse(b"\x7FELF".to_vec()),
+            Err(Error::NotElf)
+        ));
+
+        let elf = ElfBuilder::new(true, Endian::Little).build();
+        let mut bad_magic = elf.clone();
+        bad_magic[3] = b'G';
+        assert!(matches!(Elf::parse(bad_magic), Err(Error::NotElf)));
+
+        let mut bad_class = elf.clone();
+        bad_class[4] = 3;
+        assert!(matches!(Elf::parse(bad_class), Err(Error::Unsupported)));
+
+        let mut bad_data = elf;
+        bad_data[5] = 0;
+        assert!(matches!(Elf::parse(bad_data), Err(Error::Unsupported)));
+    }
+
+    #[test]
+    fn truncated_headers_are_malformed() {
+        for &(is_64, endian) in &FORMATS {
+            let mut builder = ElfBuilder::new(is_64, endian);
+            builder.section(".text", SectionType::PROGBITS, 0x1000, vec![1; 4]);
+            let elf = builder.build();
+
+            // Past the identification bytes, but before the section header
+            // fields.
+            for &len in &[17, 30, if is_64 { 63 } else { 51 }] {
+                let header = elf[..len].to_vec();
+                assert!(matches!(Elf::parse(header), Err(Error::Malformed)));
+            }
+
+            let table = elf[..elf.len() - 1].to_vec();
+            assert!(matches!(Elf::parse(table), Err(Error::Malformed)));
+        }
+    }
+
+    #[test]
+    fn dynamic_symbols_come_from_dynsym() {
+        let global = Symbol::BIND_GLOBAL << 4 | Symbol::TYPE_FUNC;
+        let symbol = |name, info, other, section_index| FixtureSymbol {
+            name,
+            value: 0x1000,
+            info,
+            other,
+            section_index,
+        };
+        for &(is_64, endian) in &FORMATS {
+            let mut builder = ElfBuilder::new(is_64, endian);
+            let text = builder.section(
+                ".text",
+                SectionType::PROGBITS,
+                0x1000,
+                vec![0; 16],
+            ) as u16;
+            builder.symbols(
+                ".symtab",
+                SectionType::SYMTAB,
+                &[symbol("static_only", global, 0, text)],
+            );
+            builder.symbols(
+                ".dynsym",
+                SectionType::DYNSYM,
+                &[
+                    symbol("exported", global, 0, text),
+                    symbol("weak", Symbol::BIND_WEAK << 4, 3, text),
+                    symbol("local", Symbol::TYPE_FUNC, 0, text),
+                    symbol("hidden", global, 2, text),
+                    symbol("imported", global, 0, 0),
+                ],
+            );
+
+            let elf = Elf::parse(builder.build()).unwrap();
+            let symbols = elf.dynamic_symbols();
+            let names = symbols.iter().map(|s| s.name.as_str());
+            assert_eq!(
+                names.collect::<Vec<_>>(),
+                ["", "exported", "weak", "local", "hidden", "imported"],
+            );
+            assert_eq!(symbols[1].value, 0x1000);
+            assert_eq!(symbols[1].kind(), Symbol::TYPE_FUNC);
+            assert_eq!(symbols[2].visibility(), Symbol::VISIBILITY_PROTECTED);
+
+            let exported = symbols.iter().filter(|s| s.is_exported());
+            let names = exported.map(|s| s.name.as_str());
+            assert_eq!(names.collect::<Vec<_>>(), ["exported", "weak"]);
+            assert!(!symbols[5].is_defined());
+        }
+    }
+
+    #[test]
+    fn images_reject_relocatable_objects() {
+        let mut builder = ElfBuilder::new(true, Endian::Little);
+        builder.file_type(FileType::RELOCATABLE);
+        builder.section("swift5_types", SectionType::PROGBITS, 0, vec![0; 4]);
+        let elf = builder.build();
+
+        let parsed = Elf::parse(elf.clone()).unwrap();
+        assert_eq!(parsed.file_type(), FileType::RELOCATABLE);
+        assert!(matches!(Image::parse(elf), Err(Error::Unsupported)));
+
+        builder.file_type(FileType::SHARED);
+        assert!(Image::parse(builder.build()).is_ok());
+    }
+}
@@ -0,0 +1,311 @@
+//! Builds ELF files in memory for unit tests.
+
+use crate::elf::{Endian, FileType, SectionType};
+
+/// Appends integers in a fixed byte order.
+#[derive(Clone, Debug)]
+pub(crate) struct Bytes {
+    pub(crate) endian: Endian,
+    pub(crate) data: Vec<u8>,
+}
+
+impl Bytes {
+    pub(crate) fn new(endian: Endian) -> Self {
+        Self {
+            endian,
+            data: Vec::new(),
+        }
+    }
+
+    pub(crate) fn u8(&mut self, value: u8) -> &mut Self {
+        self.data.push(value);
+        self
+    }
+
+    pub(crate) fn u16(&mut self, value: u16) -> &mut Self {
+        self.data.extend_from_slice(&match self.endian {
+            Endian::Little => value.to_le_bytes(),
+            Endian::Big => value.to_be_bytes(),
+        });
+        self
+    }
+
+    pub(crate) fn u32(&mut self, value: u32) -> &mut Self {
+        self.data.extend_from_slice(&match self.endian {
+            Endian::Little => value.to_le_bytes(),
+            Endian::Big => value.to_be_bytes(),
+        });
+        self
+    }
+
+    pub(crate) fn u64(&mut self, value: u64) -> &mut Self {
+        self.data.extend_from_slice(&match self.endian {
+            Endian::Little => value.to_le_bytes(),
+            Endian::Big => value.to_be_bytes(),
+        });
+        self
+    }
+
+    /// Appends a relative pointer to `target`, an offset from the start of
+    /// the data, with `bits` set in its low bits.
+    pub(crate) fn rel(&mut self, target: usize, bits: u32) -> &mut Self {
+        let offset = target as i64 - self.data.len() as i64;
+        self.u32(offset as u32 | bits)
+    }
+
+    /// Appends a 4- or 8-byte word.
+    pub(crate) fn word(&mut self, is_64: bool, value: u64) -> &mut Self {
+        if is_64 {
+            self.u64(value)
+        } else {
+            self.u32(value as u32)
+        }
+    }
+
+    /// Pads with zeros up to a multiple of `align`.
+    #[allow(clippy::manual_is_multiple_of)]
+    pub(crate) fn align(&mut self, align: usize) -> &mut Self {
+        while self.data.len() % align != 0 {
+            self.data.push(0);
+        }
+        self
+    }
+}
+
+/// A symbol to write into a symbol table.
+#[derive(Clone, Copy, Debug)]
+pub(crate) struct FixtureSymbol {
+    pub(crate) name: &'static str,
+    pub(crate) value: u64,
+    pub(crate) info: u8,
+    pub(crate) other: u8,
+    pub(crate) section_index: u16,
+}
+
+#[derive(Clone, Debug)]
+struct FixtureSection {
+    name: &'static str,
+    kind: SectionType,
+    addr: u64,
+    data: Vec<u8>,
+    link: u32,
+    entry_size: u64,
+}
+
+/// Lays out an ELF file with a section header table and no program headers.
+///
+/// Section 0 is the null section and the last section is `.shstrtab`, so the
+/// first added section has index 1.
+#[derive(Clone, Debug)]
+pub(crate) struct ElfBuilder {
+    is_64: bool,
+    endian: Endian,
+    file_type: FileType,
+    machine: u16,
+    extended_numbering: bool,
+    sections: Vec<FixtureSection>,
+}
+
+impl ElfBuilder {
+    pub(crate) fn new(is_64: bool, endian: Endian) -> Self {
+        Self {
+            is_64,
+            endian,
+            file_type: FileType::SHARED,
+            machine: 0,
+            extended_numbering: false,
+            sections: Vec::new(),
+        }
+    }
+
+    pub(crate) fn file_type(&mut self, file_type: FileType) -> &mut Self {
+        self.file_type = file_type;
+        self
+    }
+
+    pub(crate) fn machine(&mut self, machine: u16) -> &mut Self {
+        self.machine = machine;
+        self
+    }
+
+    /// Stores the section count and name table index in the first section
+    /// header, as if there were too many sections for the file header.
+    pub(crate) fn extended_numbering(&mut self) -> &mut Self {
+        self.extended_numbering = true;
+        self
+    }
+
+    /// Adds a section, returning its index.
+    pub(crate) fn section(
+        &mut self,
+        name: &'static str,
+        kind: SectionType,
+        addr: u64,
+        data: Vec<u8>,
+    ) -> u32 {
+        self.sections.push(FixtureSection {
+            name,
+            kind,
+            addr,
+            data,
+            link: 0,
+            entry_size: 0,
+        });
+        self.sections.len() as u32
+    }
+
+    /// Adds a symbol table of type `kind` and its string table, returning
+    /// the index of the symbol table.
+    ///
+    /// The null symbol is written first, as linkers do.
+    pub(crate) fn symbols(
+        &mut self,
+        name: &'static str,
+        kind: SectionType,
+        symbols: &[FixtureSymbol],
+    ) -> u32 {
+        let mut names = vec![0];
+        let mut table = Bytes::new(self.endian);
+        let null = FixtureSymbol {
+            name: "",
+            value: 0,
+            info: 0,
+            other: 0,
+            section_index: 0,
+        };
+        for symbol in Some(&null).into_iter().chain(symbols) {
+            let name = if symbol.name.is_empty() {
+                0
+            } else {
+                let start = names.len() as u32;
+                names.extend_from_slice(symbol.name.as_bytes());
+                names.push(0);
+                start
+            };
+            if self.is_64 {
+                table
+                    .u32(name)
+                    .u8(symbol.info)
+                    .u8(symbol.other)
+                    .u16(symbol.section_index)
+                    .u64(symbol.value)
+                    .u64(0);
+            } else {
+                table
+                    .u32(name)
+                    .u32(symbol.value as u32)
+                    .u32(0)
+                    .u8(symbol.info)
+                    .u8(symbol.other)
+                    .u16(symbol.section_index);
+            }
+        }
+
+        let strings = self.section(".strtab", SectionType::STRTAB, 0, names);
+        let index = self.section(name, kind, 0, table.data);
+        let section = &mut self.sections[index as usize - 1];
+        section.link = strings;
+        section.entry_size = if self.is_64 { 24 } else { 16 };
+        index
+    }
+
+    fn header_size(&self) -> usize {
+        if self.is_64 {
+            64
+        } else {
+            52
+        }
+    }
+
+    pub(crate) fn build(&self) -> Vec<u8> {
+        let is_64 = self.is_64;
+        let mut names = vec![0];
+        let mut name_offsets = Vec::new();
+        for name in self
+            .sections
+            .iter()
+            .map(|s| s.name)
+            .chain(Some(".shstrtab"))
+        {
+            name_offsets.push(names.len() as u32);
+            names.extend_from_slice(name.as_bytes());
+            names.push(0);
+        }
+        let shstrtab = FixtureSection {
+            name: ".shstrtab",
+            kind: SectionType::STRTAB,
+            addr: 0,
+            data: names,
+            link: 0,
+            entry_size: 0,
+        };
+        let sections = self.sections.iter().chain(Some(&shstrtab));
+        let count = self.sections.len() + 2;
+        let shstrndx = count - 1;
+
+        let mut body = Bytes::new(self.endian);
+        body.data.resize(self.header_size(), 0);
+        let mut offsets = Vec::new();
+        for section in sections.clone() {
+            body.align(8);
+            offsets.push(body.data.len() as u64);
+            body.data.extend_from_slice(&section.data);
+        }
+        body.align(8);
+        let shoff = body.data.len() as u64;
+
+        // The null section, which holds the real counts if they don't fit.
+        let (null_size, null_link) = if self.extended_numbering {
+            (count as u64, shstrndx as u32)
+        } else {
+            (0, 0)
+        };
+        let mut headers =
+            vec![(0, SectionType::NULL, 0, 0, null_size, null_link, 0)];
+        for ((section, offset), name) in sections.zip(offsets).zip(name_offsets)
+        {
+            headers.push((
+                name,
+                section.kind,
+                section.addr,
+                offset,
+                section.data.len() as u64,
+                section.link,
+                section.entry_size,
+            ));
+        }
+        for (name, kind, addr, offset, size, link, entry_size) in headers {
+            body.u32(name).u32(kind.0).word(is_64, 0).word(is_64, addr);
+            body.word(is_64, offset).word(is_64, size).u32(link).u32(0);
+            body.word(is_64, 1).word(is_64, entry_size);
+        }
+
+        let (shnum, shstrndx) = if self.extended_numbering {
+            (0, 0xFFFF)
+        } else {
+            (count as u16, shstrndx as u16)
+        };
+        let mut header = Bytes::new(self.endian);
+        header.data.extend_from_slice(b"\x7FELF");
+        header.u8(if is_64 { 2 } else { 1 });
+        header.u8(match self.endian {
+            Endian::Little => 1,
+            Endian::Big => 2,
+        });
+        header.u8(1).align(16);
+        header.u16(self.file_type.0).u16(self.machine).u32(1);
+        header
+            .word(is_64, 0)
+            .word(is_64, 0)
+            .word(is_64, shoff)
+            .u32(0);
+        header.u16(self.header_size() as u16);
+        header.u16(0).u16(0);
+        header
+            .u16(if is_64 { 64 } else { 40 })
+            .u16(shnum)
+            .u16(shstrndx);
+        body.data[..header.data.len()].copy_from_slice(&header.data);
+        body.data
+    }
+}
@@ -0,0 +1,421 @@
+//! Binaries and their Swift metadata sections.
+
+use crate::{
+    elf::{self, Elf, FileType, SectionType},
+    Error,
+};
+use std::{
+    collections::HashMap, convert::TryFrom, fs, ops::Range, path::Path, str,
+};
+
+/// A section of Swift metadata.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub enum SwiftSection {
+    /// Mangled type names referenced by other records (`swift5_typeref`).
+    TypeRef,
+    /// Strings referenced by other records, such as field names
+    /// (`swift5_reflstr`).
+    ReflStr,
+    /// Descriptors of the fields of nominal types (`swift5_fieldmd`).
+    FieldMd,
+    /// Descriptors of the associated types of conformances
+    /// (`swift5_assocty`).
+    AssocTy,
+    /// Descriptors of the layouts of builtin types (`swift5_builtin`).
+    Builtin,
+    /// Descriptors of the captures of closure contexts (`swift5_capture`).
+    Capture,
+    /// References to nominal type descriptors (`swift5_types`).
+    Types,
+    /// References to protocol descriptors (`swift5_protocols`).
+    Protocols,
+    /// References to protocol conformance descriptors
+    /// (`swift5_protocol_conformances`).
+    ProtocolConformances,
+}
+
+impl SwiftSection {
+    /// Every Swift metadata section.
+    pub const ALL: [Self; 9] = [
+        Self::TypeRef,
+        Self::ReflStr,
+        Self::FieldMd,
+        Self::AssocTy,
+        Self::Builtin,
+        Self::Capture,
+        Self::Types,
+        Self::Protocols,
+        Self::ProtocolConformances,
+    ];
+
+    /// Returns the name of the section in ELF files.
+    pub fn name(self) -> &'static str {
+        match self {
+            Self::TypeRef => "swift5_typeref",
+            Self::ReflStr => "swift5_reflstr",
+            Self::FieldMd => "swift5_fieldmd",
+            Self::AssocTy => "swift5_assocty",
+            Self::Builtin => "swift5_builtin",
+            Self::Capture => "swift5_capture",
+            Self::Types => "swift5_types",
+            Self::Protocols => "swift5_protocols",
+            Self::ProtocolConformances => "swift5_protocol_conformances",
+        }
+    }
+
+    /// Returns the section with the ELF section name `name`.
+    pub fn from_name(name: &str) -> Option<Self> {
+        Self::ALL
+            .iter()
+            .copied()
+            .find(|section| section.name() == name)
+    }
+
+    #[inline]
+    fn index(self) -> usize {
+        self as usize
+    }
+}
+
+/// What a pointer within an image refers to.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub enum Reference<'a> {
+    /// An address within the image.
+    Addr(u64),
+    /// A symbol imported from another image, such as the descriptor of a
+    /// protocol declared in another module.
+    Symbol(&'a str),
+}
+
+impl Reference<'_> {
+    /// Returns the address within the image, or `None` if it's imported.
+    #[inline]
+    pub fn addr(self) -> Option<u64> {
+        match self {
+            Self::Addr(addr) => Some(addr),
+            Self::Symbol(_) => None,
+        }
+    }
+}
+
+/// A pointer-sized slot that a dynamic relocation fills in.
+#[derive(Clone, Debug)]
+pub(crate) enum Slot {
+    Addr(u64),
+    Symbol(String),
+}
+
+/// A range of addresses backed by the file.
+#[derive(Clone, Debug)]
+pub(crate) struct Region {
+    pub(crate) addrs: Range<u64>,
+    pub(crate) offset: usize,
+}
+
+/// An executable or shared object containing Swift metadata.
+///
+/// Addresses are the virtual addresses of the file as if it were loaded at its
+/// preferred base, so relative pointers resolve the same way they do in
+/// memory. Relocatable object files aren't supported, since their references
+/// between sections are only resolved by applying their relocations.
+///
+/// # Examples
+///
+/// ```no_run
+/// use swift_reflection::Image;
+///
+/// let image = Image::open("libFoo.so")?;
+/// for record in image.types() {
+///     println!("{:?}", record);
+/// }
+/// # Ok::<(), swift_reflection::Error>(())
+/// ```
+#[derive(Clone, Debug)]
+pub struct Image {
+    elf: Elf,
+    regions: Vec<Region>,
+    swift_sections: [Option<usize>; SwiftSection::ALL.len()],
+    slots: HashMap<u64, Slot>,
+}
+
+impl Image {
+    /// Reads the binary at `path`.
+    pub fn open(path: impl AsRef<Path>) -> Result<Self, Error> {
+        Self::parse(fs::read(path)?)
+    }
+
+    /// Parses the binary contained in `data`.
+    #[inline]
+    pub fn parse(data: Vec<u8>) -> Result<Self, Error> {
+        Elf::parse(data).and_then(Self::from_elf)
+    }
+
+    /// Creates an image from a parsed ELF file.
+    ///
+    /// Returns [`Error::Unsupported`](enum.Error.html#variant.Unsupported) if
+    /// `elf` is a relocatable object file.
+    pub fn from_elf(elf: Elf) -> Result<Self, Error> {
+        if elf.file_type() == FileType::RELOCATABLE {
+            return Err(Error::Unsupported);
+        }
+
+        let mut regions = Vec::new();
+        let mut swift_sections = [None; SwiftSection::ALL.len()];
+        for (index, section) in elf.sections().iter().enumerate() {
+            if let Some(swift) = SwiftSection::from_name(&section.name) {
+                swift_sections[swift.index()] = Some(index);
+            }
+            if !section.has_data() || section.addr == 0 {
+                continue;
+            }
+            let offset = match usize::try_from(section.offset) {
+                Ok(offset) => offset,
+                Err(_) => continue,
+            };
+            regions.push(Region {
+                addrs: section.addr..section.addr.saturating_add(section.size),
+                offset,
+            });
+        }
+
+        let mut image = Self {
+            elf,
+            regions,
+            swift_sections,
+            slots: HashMap::new(),
+        };
+        image.slots = image.dynamic_slots();
+        Ok(image)
+    }
+
+    /// Returns the targets of the slots filled by dynamic relocations.
+    fn dynamic_slots(&self) -> HashMap<u64, Slot> {
+        let (relative, absolute) = match relocation_kinds(self.elf.machine()) {
+            Some(kinds) => kinds,
+            None => return HashMap::new(),
+        };
+
+        let mut slots = HashMap::new();
+        let sections = self.elf.sections();
+        for section in sections {
+            if section.kind != SectionType::REL
+                && section.kind != SectionType::RELA
+            {
+                continue;
+            }
+            let symbols = match sections.get(section.link as usize) {
+                Some(table) => self.elf.symbols(table),
+                None => Vec::new(),
+            };
+            for reloc in self.elf.relocations(section) {
+                let addend = match reloc.addend {
+                    Some(addend) => addend as u64,
+                    None => match self.read_pointer(reloc.offset) {
+                        Some(addend) => addend,
+                        None => continue,
+                    },
+                };
+                let slot = if reloc.kind == relative {
+                    Slot::Addr(addend)
+                } else if absolute.contains(&reloc.kind) {
+                    match symbols.get(reloc.symbol as usize) {
+                        Some(symbol) if symbol.is_defined() => {
+                            Slot::Addr(symbol.value.wrapping_add(addend))
+                        }
+                        Some(symbol) => Slot::Symbol(symbol.name.clone()),
+                        None => continue,
+                    }
+                } else {
+                    continue;
+                };
+                slots.insert(reloc.offset, slot);
+            }
+        }
+        slots
+    }
+
+    /// Returns the parsed ELF file.
+    #[inline]
+    pub fn elf(&self) -> &Elf {
+        &self.elf
+    }
+
+    /// Returns the ranges of addresses backed by the file.
+    #[inline]
+    pub(crate) fn regions(&self) -> &[Region] {
+        &self.regions
+    }
+
+    /// Returns the targets of the slots filled by dynamic relocations.
+    #[inline]
+    pub(crate) fn slots(&self) -> &HashMap<u64, Slot> {
+        &self.slots
+    }
+
+    /// Returns the header of the Swift metadata section `kind`, or `None` if
+    /// the image doesn't contain it.
+    #[inline]
+    pub fn section(&self, kind: SwiftSection) -> Option<&elf::Section> {
+        let index = self.swift_sections[kind.index()]?;
+        self.elf.sections().get(index)
+    }
+
+    /// Returns the contents of the Swift metadata section `kind`, which are
+    /// empty if the image doesn't contain it.
+    pub fn section_data(&self, kind: SwiftSection) -> &[u8] {
+        match self.section(kind) {
+            Some(section) => self.elf.section_data(section),
+            None => &[],
+        }
+    }
+
+    /// Returns the range of addresses occupied by the Swift metadata section
+    /// `kind`, which is empty if the image doesn't contain it.
+    pub fn section_addrs(&self, kind: SwiftSection) -> Range<u64> {
+        let section = match self.section(kind) {
+            Some(section) if section.has_data() => section,
+            _ => return 0..0,
+        };
+        section.addr..section.addr.saturating_add(section.size)
+    }
+
+    /// Returns the bytes from `addr` to the end of its containing section.
+    pub(crate) fn bytes_from(&self, addr: u64) -> Option<&[u8]> {
+        let region = self
+            .regions
+            .iter()
+            .find(|region| region.addrs.contains(&addr))?;
+        let start =
+            region.offset + usize::try_from(addr - region.addrs.start).ok()?;
+        let end = region.offset
+            + usize::try_from(region.addrs.end - region.addrs.start).ok()?;
+        self.elf.data().get(start..end)
+    }
+
+    /// Returns the `len` bytes at `addr`, or `None` if they aren't all within
+    /// one section of the file.
+    #[inline]
+    pub fn read(&self, addr: u64, len: usize) -> Option<&[u8]> {
+        self.bytes_from(addr)?.get(..len)
+    }
+
+    /// Reads the `u16` at `addr`.
+    #[inline]
+    pub fn read_u16(&self, addr: u64) -> Option<u16> {
+        self.elf.endian().read_u16(self.read(addr, 2)?)
+    }
+
+    /// Reads the `u32` at `addr`.
+    #[inline]
+    pub fn read_u32(&self, addr: u64) -> Option<u32> {
+        self.elf.endian().read_u32(self.read(addr, 4)?)
+    }
+
+    /// Reads the `i32` at `addr`, such as the offset of a relative pointer.
+    #[inline]
+    pub fn read_i32(&self, addr: u64) -> Option<i32> {
+        self.read_u32(addr).map(|n| n as i32)
+    }
+
+    /// Reads the pointer-sized value at `addr`, as stored in the file.
+    pub fn read_pointer(&self, addr: u64) -> Option<u64> {
+        let endian = self.elf.endian();
+        if self.elf.is_64() {
+            endian.read_u64(self.read(addr, 8)?)
+        } else {
+            endian.read_u32(self.read(addr, 4)?).map(u64::from)
+        }
+    }
+
+    /// Resolves the relative pointer at `addr`, returning `None` if it's null
+    /// or unreadable.
+    ///
+    /// Bits in `mask` are cleared from the offset before it's applied, which
+    /// accounts for pointers whose low bits hold other values.
+    pub fn relative(&self, addr: u64, mask: i32) -> Option<u64> {
+        let offset = self.read_i32(addr)? & !mask;
+        if offset == 0 {
+            None
+        } else {
+            Some(addr.wrapping_add(offset as i64 as u64))
+        }
+    }
+
+    /// Resolves the pointer stored at `slot`, such as the target of an
+    /// indirect relative pointer.
+    ///
+    /// Slots filled by the dynamic linker are resolved through the image's
+    /// relocations, which may refer to symbols imported from other images.
+    pub fn indirect(&self, slot: u64) -> Option<Reference<'_>> {
+        match self.slots.get(&slot) {
+            Some(Slot::Addr(addr)) => Some(Reference::Addr(*addr)),
+            Some(Slot::Symbol(name)) => Some(Reference::Symbol(name)),
+            None => match self.read_pointer(slot)? {
+                0 => None,
+                addr => Some(Reference::Addr(addr)),
+            },
+        }
+    }
+
+    /// Returns the bytes of the nul-terminated string at `addr`.
+    #[inline]
+    pub fn c_str(&self, addr: u64) -> Option<&[u8]> {
+        elf::c_str(self.bytes_from(addr)?)
+    }
+
+    /// Returns the UTF-8 string at `addr`, such as a field name in
+    /// `swift5_reflstr`.
+    #[inline]
+    pub fn string(&self, addr: u64) -> Option<&str> {
+        str::from_utf8(self.c_str(addr)?).ok()
+    }
+
+    /// Returns the mangled name at `addr`, such as a type name in
+    /// `swift5_typeref`.
+    ///
+    /// Unlike a C string, a mangled name may contain symbolic references: a
+    /// control character followed by a relative offset, which may itself
+    /// contain zero bytes.
+    pub fn mangled_name(&self, addr: u64) -> Option<&[u8]> {
+        let bytes = self.bytes_from(addr)?;
+        let len = mangled_name_len(bytes, self.elf.pointer_size())?;
+        Some(&bytes[..len])
+    }
+}
+
+/// Returns the length of the mangled name at the start of `bytes`.
+pub(crate) fn mangled_name_len(
+    bytes: &[u8],
+    pointer_size: usize,
+) -> Option<usize> {
+    let mut len = 0;
+    loop {
+        match *bytes.get(len)? {
+            0 => return Some(len),
+            0x01..=0x17 => len += 1 + 4,
+            0x18..=0x1F => len += 1 + pointer_size,
+            _ => len += 1,
+        }
+    }
+}
+
+/// Returns the `(relative, absolute)` types of the dynamic relocations that
+/// fill in pointers on `machine`.
+fn relocation_kinds(machine: u16) -> Option<(u32, &'static [u32])> {
+    const EM_386: u16 = 3;
+    const EM_ARM: u16 = 40;
+    const EM_X86_64: u16 = 62;
+    const EM_AARCH64: u16 = 183;
+
+    match machine {
+        // R_386_RELATIVE; R_386_GLOB_DAT, R_386_32
+        EM_386 => Some((8, &[6, 1])),
+        // R_ARM_RELATIVE; R_ARM_GLOB_DAT, R_ARM_ABS32
+        EM_ARM => Some((23, &[21, 2])),
+        // R_X86_64_RELATIVE; R_X86_64_GLOB_DAT, R_X86_64_64
+        EM_X86_64 => Some((8, &[6, 1])),
+        // R_AARCH64_RELATIVE; R_AARCH64_GLOB_DAT, R_AARCH64_ABS64
+        EM_AARCH64 => Some((1027, &[1025, 257])),
+        _ => None,
+    }
+}
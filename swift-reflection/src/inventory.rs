@@ -0,0 +1,306 @@
+//! The Swift symbols exported by an image, grouped by the module and type
+//! that declare them.
+
+use crate::{elf::Symbol, Image};
+use std::collections::BTreeMap;
+use swift_rt::sym::{self, SymbolInfo, SymbolKind};
+
+/// A Swift symbol exported by an image.
+#[derive(Clone, Debug, PartialEq, Eq, Hash)]
+pub struct ExportedSymbol {
+    /// The mangled name of the symbol.
+    pub symbol: String,
+    /// The demangled name of the symbol, such as `main.Foo.x.getter : Swift.Int`.
+    pub demangled: String,
+    /// The address of the symbol within the image.
+    pub addr: u64,
+    /// What the symbol refers to.
+    pub info: SymbolInfo,
+}
+
+impl ExportedSymbol {
+    /// Returns what the symbol refers to.
+    #[inline]
+    pub fn kind(&self) -> SymbolKind {
+        self.info.kind()
+    }
+
+    /// Returns the name of the member that the symbol refers to, such as a
+    /// method or property, or `None` if it refers to its type or module
+    /// itself.
+    #[inline]
+    pub fn member(&self) -> Option<&str> {
+        if is_type_entity(self.kind()) {
+            None
+        } else {
+            self.info.name()
+        }
+    }
+}
+
+/// The exported symbols of a nominal type or protocol.
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct TypeSymbols {
+    path: Vec<String>,
+    symbols: Vec<ExportedSymbol>,
+}
+
+impl TypeSymbols {
+    /// Returns the names of the type and the types it's nested in, outermost
+    /// first and excluding the module.
+    #[inline]
+    pub fn path(&self) -> &[String] {
+        &self.path
+    }
+
+    /// Returns the name of the type within its module, such as `Foo.Bar`.
+    #[inline]
+    pub fn name(&self) -> String {
+        self.path.join(".")
+    }
+
+    /// Returns the symbols of the type, such as its metadata accessor, and of
+    /// its members, in the order of their addresses.
+    #[inline]
+    pub fn symbols(&self) -> &[ExportedSymbol] {
+        &self.symbols
+    }
+
+    /// Returns the symbols of the type's members.
+    #[inline]
+    pub fn members(&self) -> impl Iterator<Item = &ExportedSymbol> {
+        self.symbols.iter().filter(|s| s.member().is_some())
+    }
+}
+
+/// The exported symbols of a module.
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct ModuleSymbols {
+    name: String,
+    symbols: Vec<ExportedSymbol>,
+    types: BTreeMap<Vec<String>, TypeSymbols>,
+}
+
+impl ModuleSymbols {
+    /// Returns the name of the module.
+    #[inline]
+    pub fn name(&self) -> &str {
+        &self.name
+    }
+
+    /// Returns the symbols of the module's global declarations, such as
+    /// functions and variables, and of the module itself.
+    #[inline]
+    pub fn symbols(&self) -> &[ExportedSymbol] {
+        &self.symbols
+    }
+
+    /// Returns the types of the module that have exported symbols, sorted by
+    /// name with nested types after their parents.
+    #[inline]
+    pub fn types(&self) -> impl Iterator<Item = &TypeSymbols> {
+        self.types.values()
+    }
+
+    /// Returns the symbols of the type named `name` within the module, such
+    /// as `Foo.Bar`.
+    pub fn get_type(&self, name: &str) -> Option<&TypeSymbols> {
+        let path: Vec<String> = name.split('.').map(String::from).collect();
+        self.types.get(&path)
+    }
+}
+
+/// The Swift symbols exported by an image, grouped by module and type.
+///
+/// Created by
+/// [`Image::swift_symbols`](../struct.Image.html#method.swift_symbols).
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct SymbolInventory {
+    modules: BTreeMap<String, ModuleSymbols>,
+    other: Vec<ExportedSymbol>,
+    unparsed: Vec<Symbol>,
+}
+
+impl SymbolInventory {
+    /// Groups `symbols` by module and type, keeping only exported Swift
+    /// symbols.
+    ///
+    /// Symbols that look mangled but fail to demangle are kept as they are in
+    /// [`unparsed`](#method.unparsed), so that none are silently dropped.
+    pub fn new<'a>(symbols: impl IntoIterator<Item = &'a Symbol>) -> Self {
+        let mut symbols: Vec<&Symbol> = symbols
+            .into_iter()
+            .filter(|symbol| symbol.is_exported())
+            .filter(|symbol| sym::is_mangled(symbol.name.as_bytes()))
+            .collect();
+        symbols.sort_by_key(|symbol| symbol.value);
+
+        let mut inventory = Self::default();
+        for symbol in symbols {
+            let node = match sym::parse(symbol.name.as_bytes()) {
+                Ok(node) => node,
+                Err(_) => {
+                    inventory.unparsed.push(symbol.clone());
+                    continue;
+                }
+            };
+            let info = SymbolInfo::of(&node);
+            let exported = ExportedSymbol {
+                symbol: symbol.name.clone(),
+                demangled: node.to_string(),
+                addr: symbol.value,
+                info,
+            };
+            inventory.insert(exported);
+        }
+        inventory
+    }
+
+    fn insert(&mut self, symbol: ExportedSymbol) {
+        let info = &symbol.info;
+        let (module, path) = match info.context().split_first() {
+            Some((module, types)) => {
+                let mut path = types.to_vec();
+                if is_type_entity(info.kind()) {
+                    path.extend(info.name().map(str::to_owned));
+                }
+                (module.clone(), path)
+            }
+            // Module descriptors are named by their module.
+            None => match (info.kind(), info.name()) {
+                (SymbolKind::ModuleDescriptor, Some(name)) => {
+                    (name.to_owned(), Vec::new())
+                }
+                _ => return self.other.push(symbol),
+            },
+        };
+
+        let module = self.modules.entry(module).or_insert_with_key(|name| {
+            ModuleSymbols {
+                name: name.clone(),
+                ..Default::default()
+            }
+        });
+        if path.is_empty() {
+            module.symbols.push(symbol);
+        } else {
+            module
+                .types
+                .entry(path)
+                .or_insert_with_key(|path| TypeSymbols {
+                    path: path.clone(),
+                    symbols: Vec::new(),
+                })
+                .symbols
+                .push(symbol);
+        }
+    }
+
+    /// Returns the modules that have exported symbols, sorted by name.
+    #[inline]
+    pub fn modules(&self) -> impl Iterator<Item = &ModuleSymbols> {
+        self.modules.values()
+    }
+
+    /// Returns the symbols of the module named `name`.
+    #[inline]
+    pub fn module(&self, name: &str) -> Option<&ModuleSymbols> {
+        self.modules.get(name)
+    }
+
+    /// Returns the symbols that aren't declared within a module, such as
+    /// thunks for function types.
+    #[inline]
+    pub fn other(&self) -> &[ExportedSymbol] {
+        &self.other
+    }
+
+    /// Returns the symbols that have a Swift mangling prefix but couldn't be
+    /// demangled, in the order of their addresses.
+    #[inline]
+    pub fn unparsed(&self) -> &[Symbol] {
+        &self.unparsed
+    }
+}
+
+impl Image {
+    /// Returns the Swift symbols exported through the image's dynamic symbol
+    /// table, grouped by the module and type that declare them.
+    ///
+    /// # Examples
+    ///
+    /// Listing the functions that can be called directly:
+    ///
+    /// ```no_run
+    /// use swift_reflection::Image;
+    /// use swift_rt::sym::SymbolKind;
+    ///
+    /// let image = Image::open("libFoo.so")?;
+    /// let inventory = image.swift_symbols();
+    /// for module in inventory.modules() {
+    ///     for ty in module.types() {
+    ///         for member in ty.members() {
+    ///             if member.kind() == SymbolKind::Function {
+    ///                 println!("{}", member.demangled);
+    ///             }
+    ///         }
+    ///     }
+    /// }
+    /// # Ok::<(), swift_reflection::Error>(())
+    /// ```
+    pub fn swift_symbols(&self) -> SymbolInventory {
+        SymbolInventory::new(&self.elf().dynamic_symbols())
+    }
+}
+
+/// Returns whether symbols of `kind` refer to a type, rather than to one of
+/// its members.
+fn is_type_entity(kind: SymbolKind) -> bool {
+    use SymbolKind::*;
+    matches!(
+        kind,
+        TypeMetadata
+            | TypeMetadataAccessor
+            | FullTypeMetadata
+            | Metaclass
+            | NominalTypeDescriptor
+            | ProtocolDescriptor
+            | ExtensionDescriptor
+            | ValueWitnessTable
+            | ValueWitness
+            | ProtocolWitnessTable
+            | ProtocolWitnessTableAccessor
+            | ProtocolConformanceDescriptor
+            | FieldDescriptor
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn exported(name: &str, value: u64) -> Symbol {
+        Symbol {
+            name: name.to_owned(),
+            value,
+            size: 0,
+            info: 0x12, // STB_GLOBAL, STT_FUNC
+            other: 0,
+            section_index: 1,
+        }
+    }
+
+    #[test]
+    fn keeps_unparsed_symbols() {
+        let symbols = [
+            exported("$s4main3FooVMn", 0x10),
+            exported("$s4main99Foo", 0x20),
+            exported("memcpy", 0x30),
+        ];
+        let inventory = SymbolInventory::new(&symbols);
+
+        let module = inventory.module("main").unwrap();
+        assert!(module.get_type("Foo").is_some());
+        assert_eq!(inventory.unparsed(), &symbols[1..2]);
+    }
+}
@@ -0,0 +1,175 @@
+//! A minimal JSON writer, for tools that print what they read from images.
+//!
+//! # Examples
+//!
+//! ```
+//! use swift_reflection::json::Json;
+//!
+//! let json = Json::Object(vec![("name", "Foo".into()), ("size", 8.into())]);
+//! assert_eq!(json.to_string(), r#"{"name": "Foo", "size": 8}"#);
+//! ```
+
+use std::{
+    fmt::{self, Write},
+    iter::FromIterator,
+};
+
+/// A JSON value whose objects keep the order of their keys.
+#[derive(Clone, Debug)]
+pub enum Json {
+    /// `null`.
+    Null,
+    /// `true` or `false`.
+    Bool(bool),
+    /// A non-negative integer.
+    Number(u64),
+    /// A string, which is escaped when written.
+    String(String),
+    /// An array of values.
+    Array(Vec<Json>),
+    /// An object, whose keys are written in order.
+    Object(Vec<(&'static str, Json)>),
+}
+
+impl From<bool> for Json {
+    #[inline]
+    fn from(b: bool) -> Self {
+        Self::Bool(b)
+    }
+}
+
+impl From<u64> for Json {
+    #[inline]
+    fn from(n: u64) -> Self {
+        Self::Number(n)
+    }
+}
+
+impl From<&str> for Json {
+    #[inline]
+    fn from(s: &str) -> Self {
+        Self::String(s.to_owned())
+    }
+}
+
+impl From<String> for Json {
+    #[inline]
+    fn from(s: String) -> Self {
+        Self::String(s)
+    }
+}
+
+impl<T: Into<Json>> From<Option<T>> for Json {
+    #[inline]
+    fn from(value: Option<T>) -> Self {
+        value.map_or(Self::Null, Into::into)
+    }
+}
+
+impl<T: Into<Json>> From<Vec<T>> for Json {
+    #[inline]
+    fn from(values: Vec<T>) -> Self {
+        Self::Array(values.into_iter().map(Into::into).collect())
+    }
+}
+
+impl FromIterator<Json> for Json {
+    #[inline]
+    fn from_iter<I: IntoIterator<Item = Json>>(iter: I) -> Self {
+        Self::Array(iter.into_iter().collect())
+    }
+}
+
+impl fmt::Display for Json {
+    /// Writes `self` on a single line, or indented by two spaces per level
+    /// with the alternate flag (`{:#}`).
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        let level = if f.alternate() { Some(0) } else { None };
+        self.write(f, level)
+    }
+}
+
+impl Json {
+    /// Writes `self` at the indentation `level`, or on a single line if it's
+    /// `None`.
+    fn write(
+        &self,
+        f: &mut fmt::Formatter,
+        level: Option<usize>,
+    ) -> fmt::Result {
+        let inner = level.map(|level| level + 1);
+        match self {
+            Self::Null => f.write_str("null"),
+            Self::Bool(b) => write!(f, "{}", b),
+            Self::Number(n) => write!(f, "{}", n),
+            Self::String(s) => write_string(f, s),
+            Self::Array(values) if values.is_empty() => f.write_str("[]"),
+            Self::Object(fields) if fields.is_empty() => f.write_str("{}"),
+            Self::Array(values) => {
+                f.write_char('[')?;
+                for (i, value) in values.iter().enumerate() {
+                    separate(f, i, inner)?;
+                    value.write(f, inner)?;
+                }
+                newline(f, level)?;
+                f.write_char(']')
+            }
+            Self::Object(fields) => {
+                f.write_char('{')?;
+                for (i, (key, value)) in fields.iter().enumerate() {
+                    separate(f, i, inner)?;
+                    write_string(f, key)?;
+                    f.write_str(": ")?;
+                    value.write(f, inner)?;
+                }
+                newline(f, level)?;
+                f.write_char('}')
+            }
+        }
+    }
+}
+
+/// Writes the separator before the `i`th element of an array or object.
+#[inline]
+fn separate(
+    f: &mut fmt::Formatter,
+    i: usize,
+    level: Option<usize>,
+) -> fmt::Result {
+    if i != 0 {
+        f.write_char(',')?;
+        if level.is_none() {
+            f.write_char(' ')?;
+        }
+    }
+    newline(f, level)
+}
+
+#[inline]
+fn newline(f: &mut fmt::Formatter, level: Option<usize>) -> fmt::Result {
+    if let Some(level) = level {
+        f.write_char('\n')?;
+        for _ in 0..level {
+            f.write_str("  ")?;
+        }
+    }
+    Ok(())
+}
+
+/// Writes `s` as a JSON string, escaping quotes, backslashes and control
+/// characters.
+fn write_string(f: &mut fmt::Formatter, s: &str) -> fmt::Result {
+    f.write_char('"')?;
+    for c in s.chars() {
+        match c {
+            '"' => f.write_str("\\\"")?,
+            '\\' => f.write_str("\\\\")?,
+            '\n' => f.write_str("\\n")?,
+            '\r' => f.write_str("\\r")?,
+            '\t' => f.write_str("\\t")?,
+            c if c < ' ' => write!(f, "\\u{:04x}", c as u32)?,
+            c => f.write_char(c)?,
+        }
+    }
+    f.write_char('"')
+}
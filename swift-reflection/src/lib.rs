@@ -0,0 +1,82 @@
+//! Reads the metadata that the Swift compiler emits into binaries, without
+//! loading them or linking to Swift's runtime.
+//!
+//! An [`Image`](struct.Image.html) holds the contents of an ELF executable or
+//! shared object, and locates its Swift metadata sections such as
+//! `swift5_types` and `swift5_fieldmd`. The [`remote`](remote/index.html)
+//! module reads the same metadata from the memory of another process, and the
+//! [`abi`](abi/index.html) module compares the metadata of two builds of a
+//! library. The [`inventory`](inventory/index.html) module groups the Swift
+//! symbols that an image exports by module and type, and the
+//! [`json`](json/index.html) module prints what tools read as JSON.
+//!
+//! This crate is under construction. Please contact
+//! [Nikolai Vazquez](https://twitter.com/NikolaiVazquez) if you would like to
+//! get involved.
+
+#![deny(missing_docs)]
+
+pub mod abi;
+pub mod elf;
+pub mod inventory;
+pub mod json;
+pub mod remote;
+
+#[cfg(test)]
+mod fixture;
+mod image;
+mod map;
+mod name;
+mod records;
+
+pub use image::{Image, Reference, SwiftSection};
+pub use map::MappedImage;
+pub use name::descriptor_symbol_name;
+pub use records::{
+    ConformanceDescriptor, ConformanceRecord, GenericRequirement,
+    ProtocolRecord, ResilientWitness, TypeRecord,
+};
+pub use swift_sys::metadata::conformance::TypeReferenceKind;
+
+use std::{error, fmt, io};
+
+/// An error returned when reading a binary fails.
+#[derive(Debug)]
+pub enum Error {
+    /// The binary couldn't be read.
+    Io(io::Error),
+    /// The binary isn't an ELF file.
+    NotElf,
+    /// The binary is an ELF file of an unsupported class, byte order or type,
+    /// such as a relocatable object file.
+    Unsupported,
+    /// The binary's headers are truncated or inconsistent.
+    Malformed,
+}
+
+impl fmt::Display for Error {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            Self::Io(error) => write!(f, "Failed to read binary: {}", error),
+            Self::NotElf => write!(f, "Binary is not an ELF file"),
+            Self::Unsupported => write!(f, "Binary has an unsupported format"),
+            Self::Malformed => write!(f, "Binary is malformed"),
+        }
+    }
+}
+
+impl error::Error for Error {
+    fn source(&self) -> Option<&(dyn error::Error + 'static)> {
+        match self {
+            Self::Io(error) => Some(error),
+            _ => None,
+        }
+    }
+}
+
+impl From<io::Error> for Error {
+    #[inline]
+    fn from(error: io::Error) -> Self {
+        Self::Io(error)
+    }
+}
@@ -0,0 +1,314 @@
+//! Laying out an image in memory so that its metadata can be accessed in
+//! place.
+
+use crate::{elf::Endian, image::Slot, Error, Image, SwiftSection};
+use std::{convert::TryFrom, mem, ops::Range};
+use swift_sys::metadata::{
+    conformance::ProtocolConformanceDescriptor,
+    context::ContextDescriptor,
+    reflection::{
+        AssociatedTypeDescriptor, BuiltinTypeDescriptor, CaptureDescriptor,
+    },
+};
+
+/// The largest number of bytes by which the mapping of an image may exceed
+/// its file, which allows for several segments aligned to 2 MiB pages.
+const MAX_PADDING: u64 = 16 << 20;
+
+/// The contents of an [`Image`](struct.Image.html) laid out at their
+/// addresses, as if it were loaded by the dynamic linker.
+///
+/// Swift metadata refers to other metadata by relative pointers, which are
+/// only meaningful at their original location. Mapping an image makes these
+/// pointers valid, so that types like
+/// [`ContextDescriptor`](../swift_sys/metadata/context/struct.ContextDescriptor.html)
+/// can be used on metadata that was never loaded.
+///
+/// Pointers filled in by dynamic relocations point into the mapping, or are
+/// null if they refer to a symbol imported from another image.
+///
+/// # Examples
+///
+/// ```no_run
+/// use swift_reflection::{Image, Reference};
+///
+/// let image = Image::open("libFoo.so")?;
+/// let mapped = image.map()?;
+/// for record in image.types() {
+///     if let Some(Reference::Addr(addr)) = record.target {
+///         let descriptor = unsafe { mapped.context_descriptor(addr) };
+///         if let Some(name) = descriptor.and_then(|d| d.name()) {
+///             println!("{:?}", name);
+///         }
+///     }
+/// }
+/// # Ok::<(), swift_reflection::Error>(())
+/// ```
+#[derive(Debug)]
+pub struct MappedImage<'a> {
+    image: &'a Image,
+    // Words ensure the alignment of the mapping.
+    memory: Vec<u64>,
+    addrs: Range<u64>,
+}
+
+impl Image {
+    /// Lays out the contents of `self` at their addresses.
+    ///
+    /// Fails with [`Error::Unsupported`](enum.Error.html#variant.Unsupported)
+    /// if the image's pointer size or byte order differs from that of the
+    /// current target, since its metadata couldn't be accessed in place, and
+    /// with [`Error::Malformed`](enum.Error.html#variant.Malformed) if its
+    /// sections span far more addresses than the file has bytes.
+    pub fn map(&self) -> Result<MappedImage<'_>, Error> {
+        let native = if cfg!(target_endian = "little") {
+            Endian::Little
+        } else {
+            Endian::Big
+        };
+        let elf = self.elf();
+        if elf.endian() != native
+            || elf.pointer_size() != mem::size_of::<usize>()
+        {
+            return Err(Error::Unsupported);
+        }
+
+        let word = mem::size_of::<u64>() as u64;
+        let start = match self.regions().iter().map(|r| r.addrs.start).min() {
+            Some(start) => start & !(word - 1),
+            None => 0,
+        };
+        let end = self.regions().iter().map(|r| r.addrs.end).max();
+        let end = end.unwrap_or(start).max(start);
+
+        // Sections only leave gaps between them for the alignment of their
+        // segments, so a span much larger than the file means the section
+        // headers are bogus, and allocating for it could exhaust memory.
+        let file_len = elf.data().len() as u64;
+        if end - start > file_len.saturating_add(MAX_PADDING) {
+            return Err(Error::Malformed);
+        }
+        let aligned_end = end.checked_add(word - 1).ok_or(Error::Malformed)?;
+        let words =
+            usize::try_from(((aligned_end & !(word - 1)) - start) / word)
+                .map_err(|_| Error::Malformed)?;
+
+        let mut mapped = MappedImage {
+            image: self,
+            memory: vec![0; words],
+            addrs: start..end,
+        };
+
+        for region in self.regions() {
+            let len = (region.addrs.end - region.addrs.start) as usize;
+            let bytes = region
+                .offset
+                .checked_add(len)
+                .and_then(|end| elf.data().get(region.offset..end))
+                .ok_or(Error::Malformed)?;
+            let offset = (region.addrs.start - start) as usize;
+            mapped.bytes_mut()[offset..offset + len].copy_from_slice(bytes);
+        }
+
+        for (&addr, slot) in self.slots() {
+            let target = match slot {
+                Slot::Addr(target) => mapped.ptr(*target) as usize,
+                Slot::Symbol(_) => 0,
+            };
+            let offset = match mapped.offset(addr) {
+                Some(offset) => offset,
+                None => continue,
+            };
+            let bytes = target.to_ne_bytes();
+            if let Some(slot) =
+                mapped.bytes_mut().get_mut(offset..offset + bytes.len())
+            {
+                slot.copy_from_slice(&bytes);
+            }
+        }
+
+        Ok(mapped)
+    }
+}
+
+impl<'a> MappedImage<'a> {
+    /// Returns the image that `self` is a mapping of.
+    #[inline]
+    pub fn image(&self) -> &'a Image {
+        self.image
+    }
+
+    /// Returns the contents of the mapping, starting at the lowest mapped
+    /// address.
+    #[inline]
+    pub fn bytes(&self) -> &[u8] {
+        let len = (self.addrs.end - self.addrs.start) as usize;
+        let ptr = self.memory.as_ptr() as *const u8;
+        unsafe { std::slice::from_raw_parts(ptr, len) }
+    }
+
+    #[inline]
+    fn bytes_mut(&mut self) -> &mut [u8] {
+        let len = (self.addrs.end - self.addrs.start) as usize;
+        let ptr = self.memory.as_mut_ptr() as *mut u8;
+        unsafe { std::slice::from_raw_parts_mut(ptr, len) }
+    }
+
+    /// Returns the range of mapped addresses.
+    #[inline]
+    pub fn addrs(&self) -> Range<u64> {
+        self.addrs.clone()
+    }
+
+    #[inline]
+    fn offset(&self, addr: u64) -> Option<usize> {
+        if self.addrs.contains(&addr) {
+            Some((addr - self.addrs.start) as usize)
+        } else {
+            None
+        }
+    }
+
+    /// Returns the pointer to `addr` in the mapping, or null if it isn't
+    /// mapped.
+    #[inline]
+    pub fn ptr(&self, addr: u64) -> *const u8 {
+        match self.offset(addr) {
+            Some(offset) => self.bytes()[offset..].as_ptr(),
+            None => std::ptr::null(),
+        }
+    }
+
+    /// Returns the address in the image that `ptr` points to, or `None` if
+    /// it doesn't point into the mapping.
+    #[inline]
+    pub fn addr(&self, ptr: *const u8) -> Option<u64> {
+        let start = self.memory.as_ptr() as usize;
+        let offset = (ptr as usize).checked_sub(start)? as u64;
+        let addr = self.addrs.start + offset;
+        if self.addrs.contains(&addr) {
+            Some(addr)
+        } else {
+            None
+        }
+    }
+
+    /// Returns a reference to the `T` at `addr`, or `None` if it isn't
+    /// mapped or is misaligned.
+    ///
+    /// # Safety
+    ///
+    /// The bytes at `addr` must be a valid `T`, and any relative pointers
+    /// that are followed through it must have targets within the mapping.
+    #[inline]
+    #[allow(clippy::manual_is_multiple_of)]
+    pub unsafe fn get<T>(&self, addr: u64) -> Option<&T> {
+        let end = addr.checked_add(mem::size_of::<T>() as u64)?;
+        if end > self.addrs.end {
+            return None;
+        }
+        let ptr = self.ptr(addr) as *const T;
+        if ptr.is_null() || (ptr as usize) % mem::align_of::<T>() != 0 {
+            return None;
+        }
+        Some(&*ptr)
+    }
+
+    /// Returns the context descriptor at `addr`, such as the target of a
+    /// record in `swift5_types`.
+    ///
+    /// # Safety
+    ///
+    /// There must be a context descriptor at `addr`, emitted by a compiler
+    /// that the metadata layouts of `swift_sys` support.
+    #[inline]
+    pub unsafe fn context_descriptor(
+        &self,
+        addr: u64,
+    ) -> Option<&ContextDescriptor> {
+        self.get(addr)
+    }
+
+    /// Returns the protocol conformance descriptor at `addr`, such as the
+    /// target of a record in `swift5_protocol_conformances`.
+    ///
+    /// # Safety
+    ///
+    /// There must be a conformance descriptor at `addr`, emitted by a
+    /// compiler that the metadata layouts of `swift_sys` support.
+    #[inline]
+    pub unsafe fn conformance_descriptor(
+        &self,
+        addr: u64,
+    ) -> Option<&ProtocolConformanceDescriptor> {
+        self.get(addr)
+    }
+
+    /// Returns an iterator over the descriptors of `swift5_assocty`.
+    ///
+    /// # Safety
+    ///
+    /// The section must have been emitted by a compiler that the metadata
+    /// layouts of `swift_sys` support.
+    #[inline]
+    pub unsafe fn associated_type_descriptors(
+        &self,
+    ) -> impl Iterator<Item = &AssociatedTypeDescriptor> + '_ {
+        self.descriptors(SwiftSection::AssocTy, AssociatedTypeDescriptor::size)
+    }
+
+    /// Returns an iterator over the descriptors of `swift5_builtin`.
+    ///
+    /// # Safety
+    ///
+    /// The section must have been emitted by a compiler that the metadata
+    /// layouts of `swift_sys` support.
+    #[inline]
+    pub unsafe fn builtin_type_descriptors(
+        &self,
+    ) -> impl Iterator<Item = &BuiltinTypeDescriptor> + '_ {
+        self.descriptors(SwiftSection::Builtin, |_| {
+            mem::size_of::<BuiltinTypeDescriptor>()
+        })
+    }
+
+    /// Returns an iterator over the descriptors of `swift5_capture`.
+    ///
+    /// # Safety
+    ///
+    /// The section must have been emitted by a compiler that the metadata
+    /// layouts of `swift_sys` support.
+    #[inline]
+    pub unsafe fn capture_descriptors(
+        &self,
+    ) -> impl Iterator<Item = &CaptureDescriptor> + '_ {
+        self.descriptors(SwiftSection::Capture, CaptureDescriptor::size)
+    }
+
+    /// Returns an iterator over the consecutive descriptors of the section
+    /// `kind`, where `size` returns the size of a descriptor and its trailing
+    /// records.
+    unsafe fn descriptors<T>(
+        &self,
+        kind: SwiftSection,
+        size: impl Fn(&T) -> usize + 'a,
+    ) -> impl Iterator<Item = &T> + '_
+    where
+        T: 'a,
+    {
+        let addrs = self.image.section_addrs(kind);
+        let mut addr = addrs.start;
+        std::iter::from_fn(move || {
+            let end = addr.checked_add(mem::size_of::<T>() as u64)?;
+            if end > addrs.end {
+                return None;
+            }
+            let descriptor = self.get::<T>(addr)?;
+            addr = addr.checked_add(size(descriptor) as u64)?;
+            if addr > addrs.end {
+                return None;
+            }
+            Some(descriptor)
+        })
+    }
+}
@@ -0,0 +1,166 @@
+//! Naming the descriptors and types of a mapped image.
+
+use crate::{MappedImage, Reference};
+use std::{ascii, iter};
+use swift_rt::sym::{
+    self, DemangleError, Node, NodeKind, SymbolicReference,
+    SymbolicReferenceKind,
+};
+use swift_sys::metadata::context::ContextDescriptor;
+
+impl MappedImage<'_> {
+    /// Returns the fully qualified name of `descriptor`, such as
+    /// `Foo.Bar.Baz`.
+    ///
+    /// Members of extensions are qualified by the name of the extended type.
+    /// Anonymous contexts, such as those of private declarations, are
+    /// skipped.
+    pub fn context_name(&self, descriptor: &ContextDescriptor) -> String {
+        let mut components = Vec::new();
+        for context in iter::once(descriptor).chain(descriptor.parents()) {
+            if let Some(name) = context.name() {
+                components.push(name.to_string_lossy().into_owned());
+            } else if let Some(extension) = context.as_extension() {
+                // The extended type's name is already qualified.
+                components.push(match extension.extended_context() {
+                    Some(extended) => self.type_name(extended),
+                    None => String::from("<unknown>"),
+                });
+                break;
+            }
+        }
+        components.reverse();
+        components.join(".")
+    }
+
+    /// Returns the qualified name of the context descriptor that `reference`
+    /// refers to, such as `Swift.Hashable`.
+    ///
+    /// Descriptors imported from other images are named by demangling their
+    /// symbol. Addresses that aren't mapped are formatted as `<0x...>`.
+    ///
+    /// # Safety
+    ///
+    /// `reference` must refer to a context descriptor, such as the protocol
+    /// of a conformance.
+    pub unsafe fn reference_name(&self, reference: Reference) -> String {
+        match reference {
+            Reference::Addr(addr) => match self.context_descriptor(addr) {
+                Some(descriptor) => self.context_name(descriptor),
+                None => format!("<0x{:x}>", addr),
+            },
+            Reference::Symbol(symbol) => descriptor_symbol_name(symbol),
+        }
+    }
+
+    /// Returns the demangled name of the type mangled as `mangled`, which is
+    /// a slice of the mapping such as the type of a field.
+    ///
+    /// Types declared in the same image are usually mangled as a symbolic
+    /// reference to their descriptor, which are resolved as by
+    /// [`parse_type`](#method.parse_type). Names that can't be demangled are
+    /// returned with their non-printable bytes escaped.
+    pub fn type_name(&self, mangled: &[u8]) -> String {
+        match self.parse_type(mangled) {
+            Ok(node) => node.to_string(),
+            Err(_) => escape(mangled),
+        }
+    }
+
+    /// Returns the demangled name of the type mangled at `addr`, or `None`
+    /// if there is no mangled name at `addr`.
+    ///
+    /// See [`type_name`](#method.type_name) for how names are resolved.
+    pub fn type_name_at(&self, addr: u64) -> Option<String> {
+        let mangled = self.image().mangled_name(addr)?;
+        Some(match self.parse_type_in_image(addr, mangled) {
+            Ok(node) => node.to_string(),
+            Err(_) => escape(mangled),
+        })
+    }
+
+    /// Parses the type mangled as `mangled`, which is a slice of the mapping,
+    /// into a tree of nodes.
+    ///
+    /// Symbolic references to context descriptors are resolved to the
+    /// descriptors in the mapping, or to those named by the symbols that the
+    /// dynamic linker would bind their pointers to.
+    pub fn parse_type(&self, mangled: &[u8]) -> Result<Node, DemangleError> {
+        match self.addr(mangled.as_ptr()) {
+            Some(addr) => self.parse_type_in_image(addr, mangled),
+            None => sym::parse_type(mangled),
+        }
+    }
+
+    /// Parses the type mangled as `mangled`, which is at `addr`.
+    fn parse_type_in_image(
+        &self,
+        addr: u64,
+        mangled: &[u8],
+    ) -> Result<Node, DemangleError> {
+        sym::parse_type_with(mangled, |reference| self.resolve(addr, reference))
+    }
+
+    /// Resolves `reference` within the mangled name at `base`.
+    fn resolve(
+        &self,
+        base: u64,
+        reference: &SymbolicReference,
+    ) -> Option<Node> {
+        if reference.kind() != SymbolicReferenceKind::Context {
+            return None;
+        }
+        let image = self.image();
+        let target = reference.target(base);
+        let target = if reference.is_indirect() {
+            image.indirect(target)?
+        } else {
+            Reference::Addr(target)
+        };
+        match target {
+            Reference::Addr(addr) => {
+                // Safety: context references refer to context descriptors.
+                let descriptor = unsafe { self.context_descriptor(addr)? };
+                sym::context_node(descriptor, |extended| {
+                    self.parse_type(extended).ok()
+                })
+            }
+            Reference::Symbol(symbol) => descriptor_symbol_node(symbol),
+        }
+    }
+}
+
+/// Returns the name of the entity that the descriptor symbol `symbol` refers
+/// to, such as `Swift.Hashable` for `$sSHMp`, or `symbol` itself if it can't
+/// be demangled.
+pub fn descriptor_symbol_name(symbol: &str) -> String {
+    match descriptor_symbol_node(symbol) {
+        Some(entity) => entity.to_string(),
+        None => match sym::demangle(symbol.as_bytes()) {
+            Ok(name) => name,
+            Err(_) => symbol.to_owned(),
+        },
+    }
+}
+
+/// Returns the node of the entity that the descriptor symbol `symbol` refers
+/// to, or `None` if it isn't the symbol of a descriptor.
+fn descriptor_symbol_node(symbol: &str) -> Option<Node> {
+    let node = sym::parse(symbol.as_bytes()).ok()?;
+    let entity = node.child(0).filter(|entity| {
+        matches!(
+            entity.kind(),
+            NodeKind::NominalTypeDescriptor
+                | NodeKind::ProtocolDescriptor
+                | NodeKind::ModuleDescriptor
+        )
+    })?;
+    entity.child(0).cloned()
+}
+
+/// Returns `mangled` with its symbolic references and other non-printable
+/// bytes escaped.
+fn escape(mangled: &[u8]) -> String {
+    let escaped = mangled.iter().flat_map(|&b| ascii::escape_default(b));
+    String::from_utf8(escaped.collect()).unwrap_or_default()
+}
@@ -0,0 +1,450 @@
+//! Records of the `swift5_types`, `swift5_protocols` and
+//! `swift5_protocol_conformances` sections, and the conformance descriptors
+//! they refer to.
+
+// Based on:
+// include/swift/ABI/Metadata.h
+// include/swift/ABI/MetadataValues.h
+
+use crate::{Image, Reference, SwiftSection};
+use std::mem;
+use swift_sys::{
+    metadata::{
+        conformance::{
+            self, ConformanceFlags, ProtocolConformanceDescriptor,
+            ResilientWitnessesHeader, TypeReferenceKind,
+        },
+        context::{
+            ContextDescriptor, GenericRequirementDescriptor,
+            GenericRequirementFlags, GenericRequirementKind,
+        },
+    },
+    ptr::RelativeIndirectablePointer,
+};
+
+/// An entry of `swift5_types`, which refers to the descriptor of a nominal
+/// type defined in the image.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub struct TypeRecord<'a> {
+    /// The address of the record.
+    pub addr: u64,
+    /// How the record refers to the type.
+    pub kind: TypeReferenceKind,
+    /// The type descriptor, Objective-C class name or class object, or `None`
+    /// if it couldn't be resolved.
+    pub target: Option<Reference<'a>>,
+}
+
+/// An entry of `swift5_protocols`, which refers to the descriptor of a
+/// protocol defined in the image.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub struct ProtocolRecord<'a> {
+    /// The address of the record.
+    pub addr: u64,
+    /// The protocol descriptor, or `None` if it couldn't be resolved.
+    pub descriptor: Option<Reference<'a>>,
+}
+
+/// An entry of `swift5_protocol_conformances`, which refers to the
+/// descriptor of a protocol conformance declared in the image.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub struct ConformanceRecord {
+    /// The address of the record.
+    pub addr: u64,
+    /// The address of the conformance descriptor, or `None` if it couldn't be
+    /// resolved.
+    pub descriptor: Option<u64>,
+}
+
+/// A protocol conformance descriptor, which describes how a type conforms to
+/// a protocol.
+#[derive(Clone, Debug, PartialEq, Eq, Hash)]
+pub struct ConformanceDescriptor<'a> {
+    /// The address of the descriptor.
+    pub addr: u64,
+    /// The protocol descriptor, or `None` if it couldn't be resolved.
+    pub protocol: Option<Reference<'a>>,
+    /// How the descriptor refers to the conforming type.
+    pub type_kind: TypeReferenceKind,
+    /// The type descriptor, Objective-C class name or class object of the
+    /// conforming type, or `None` if it couldn't be resolved.
+    pub type_ref: Option<Reference<'a>>,
+    /// The address of the witness table, or of the pattern used to
+    /// instantiate it.
+    pub witness_table_pattern: Option<u64>,
+    /// Flags describing the conformance.
+    pub flags: ConformanceFlags,
+    /// The context that declares a retroactive conformance.
+    pub retroactive_context: Option<Reference<'a>>,
+    /// The requirements that the conforming type's generic arguments must
+    /// satisfy for the conformance to apply.
+    pub conditional_requirements: Vec<GenericRequirement<'a>>,
+    /// The witnesses for requirements of a resilient protocol.
+    pub resilient_witnesses: Vec<ResilientWitness<'a>>,
+    /// The address of the generic witness table, which instantiates the
+    /// witness table at runtime.
+    pub generic_witness_table: Option<u64>,
+}
+
+/// A generic requirement, such as a conditional requirement of a
+/// conformance.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub struct GenericRequirement<'a> {
+    /// The address of the requirement.
+    pub addr: u64,
+    /// Flags describing the requirement, including its kind.
+    pub flags: GenericRequirementFlags,
+    /// The address of the mangled name of the constrained parameter.
+    pub param: Option<u64>,
+    /// The protocol of a protocol requirement, the mangled name of the type
+    /// of a same-type or base class requirement, or the conformance
+    /// descriptor of a same-conformance requirement.
+    pub content: Option<Reference<'a>>,
+}
+
+/// The witness for a requirement of a resilient protocol.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub struct ResilientWitness<'a> {
+    /// The protocol requirement that is witnessed.
+    pub requirement: Option<Reference<'a>>,
+    /// The address of the implementation of the requirement.
+    pub witness: Option<u64>,
+}
+
+/// The size of each record, which is a single relative pointer.
+const RECORD_SIZE: u64 = 4;
+
+impl Image {
+    /// Returns the addresses of the records in the array section `kind`.
+    fn record_addrs(
+        &self,
+        kind: SwiftSection,
+    ) -> impl Iterator<Item = u64> + '_ {
+        let addrs = self.section_addrs(kind);
+        let count = (addrs.end - addrs.start) / RECORD_SIZE;
+        (0..count).map(move |i| addrs.start + i * RECORD_SIZE)
+    }
+
+    /// Returns an iterator over the records of `swift5_types`.
+    pub fn types(&self) -> impl Iterator<Item = TypeRecord<'_>> + '_ {
+        self.record_addrs(SwiftSection::Types).map(move |addr| {
+            let bits = self.read_u32(addr).unwrap_or(0);
+            let kind = TypeReferenceKind(bits & 0x3);
+            let target = self.relative(addr, 0x3);
+            let target = if kind.is_indirect() {
+                target.and_then(|slot| self.indirect(slot))
+            } else {
+                target.map(Reference::Addr)
+            };
+            TypeRecord { addr, kind, target }
+        })
+    }
+
+    /// Returns an iterator over the records of `swift5_protocols`.
+    pub fn protocols(&self) -> impl Iterator<Item = ProtocolRecord<'_>> + '_ {
+        self.record_addrs(SwiftSection::Protocols).map(move |addr| {
+            // The next bit after the indirect bit is reserved.
+            let descriptor = self.indirectable(addr, 0x2);
+            ProtocolRecord { addr, descriptor }
+        })
+    }
+
+    /// Returns an iterator over the records of
+    /// `swift5_protocol_conformances`.
+    pub fn conformances(&self) -> impl Iterator<Item = ConformanceRecord> + '_ {
+        self.record_addrs(SwiftSection::ProtocolConformances)
+            .map(move |addr| ConformanceRecord {
+                addr,
+                descriptor: self.relative(addr, 0),
+            })
+    }
+
+    /// Returns an iterator over the descriptors referred to by the records of
+    /// `swift5_protocol_conformances`.
+    ///
+    /// # Examples
+    ///
+    /// Finding the types that conform to `Equatable`:
+    ///
+    /// ```no_run
+    /// use swift_reflection::{Image, Reference};
+    ///
+    /// let image = Image::open("libFoo.so")?;
+    /// for conformance in image.conformance_descriptors() {
+    ///     if conformance.protocol == Some(Reference::Symbol("$sSQMp")) {
+    ///         println!("{:?}", conformance.type_ref);
+    ///     }
+    /// }
+    /// # Ok::<(), swift_reflection::Error>(())
+    /// ```
+    pub fn conformance_descriptors(
+        &self,
+    ) -> impl Iterator<Item = ConformanceDescriptor<'_>> + '_ {
+        self.conformances().filter_map(move |record| {
+            self.conformance_descriptor(record.descriptor?)
+        })
+    }
+
+    /// Reads the protocol conformance descriptor at `addr`.
+    ///
+    /// The descriptor is read from the file with the layout of
+    /// [`ProtocolConformanceDescriptor`], so that references to other images
+    /// can be resolved. Native images can instead be accessed in place
+    /// through [`MappedImage::conformance_descriptor`].
+    ///
+    /// [`ProtocolConformanceDescriptor`]: ../swift_sys/metadata/conformance/struct.ProtocolConformanceDescriptor.html
+    /// [`MappedImage::conformance_descriptor`]: struct.MappedImage.html#method.conformance_descriptor
+    pub fn conformance_descriptor(
+        &self,
+        addr: u64,
+    ) -> Option<ConformanceDescriptor<'_>> {
+        type Descriptor = ProtocolConformanceDescriptor;
+
+        let flags = ConformanceFlags(
+            self.read_u32(addr + Descriptor::FLAGS_OFFSET as u64)?,
+        );
+        let type_kind = flags.type_reference_kind();
+        let type_ref =
+            self.relative(addr + Descriptor::TYPE_REF_OFFSET as u64, 0);
+        let type_ref = if type_kind.is_indirect() {
+            type_ref.and_then(|slot| self.indirect(slot))
+        } else {
+            type_ref.map(Reference::Addr)
+        };
+
+        // Trailing objects follow the descriptor in the order documented by
+        // `ProtocolConformanceDescriptor`.
+        let mut next = addr + mem::size_of::<Descriptor>() as u64;
+        let retroactive_context = if flags.is_retroactive() {
+            type Context = RelativeIndirectablePointer<ContextDescriptor>;
+            let context = self.indirectable(next, 0);
+            next += mem::size_of::<Context>() as u64;
+            context
+        } else {
+            None
+        };
+
+        let requirement_size =
+            mem::size_of::<GenericRequirementDescriptor>() as u64;
+        let conditional_requirements = (0..flags
+            .num_conditional_requirements())
+            .map(|_| {
+                let requirement = self.generic_requirement(next);
+                next += requirement_size;
+                requirement
+            })
+            .collect::<Option<Vec<_>>>()?;
+
+        let mut resilient_witnesses = Vec::new();
+        if flags.has_resilient_witnesses() {
+            let count = self.read_u32(next)?;
+            next += mem::size_of::<ResilientWitnessesHeader>() as u64;
+
+            // The count comes from the file, so it's bounded by the witnesses
+            // that fit in the rest of the section.
+            let witness_size =
+                mem::size_of::<conformance::ResilientWitness>() as u64;
+            let available = self.bytes_from(next).map_or(0, <[u8]>::len);
+            let count = u64::from(count).min(available as u64 / witness_size);
+            for _ in 0..count {
+                let witness =
+                    next + conformance::ResilientWitness::WITNESS_OFFSET as u64;
+                if self.read_u32(next).is_none()
+                    || self.read_u32(witness).is_none()
+                {
+                    break;
+                }
+                resilient_witnesses.push(ResilientWitness {
+                    requirement: self.indirectable(next, 0),
+                    witness: self.relative(witness, 0),
+                });
+                next += witness_size;
+            }
+        }
+
+        let generic_witness_table = if flags.has_generic_witness_table() {
+            Some(next)
+        } else {
+            None
+        };
+
+        Some(ConformanceDescriptor {
+            addr,
+            protocol: self.indirectable(addr, 0),
+            type_kind,
+            type_ref,
+            witness_table_pattern: self.relative(
+                addr + Descriptor::WITNESS_TABLE_PATTERN_OFFSET as u64,
+                0,
+            ),
+            flags,
+            retroactive_context,
+            conditional_requirements,
+            resilient_witnesses,
+            generic_witness_table,
+        })
+    }
+
+    /// Reads the generic requirement descriptor at `addr`, such as one in
+    /// the generic context of a type descriptor.
+    pub fn generic_requirement(
+        &self,
+        addr: u64,
+    ) -> Option<GenericRequirement<'_>> {
+        let flags = GenericRequirementFlags(self.read_u32(addr)?);
+        let content = match flags.kind() {
+            // The next bit after the indirect bit marks an Objective-C
+            // protocol.
+            GenericRequirementKind::PROTOCOL => {
+                self.indirectable(addr + 8, 0x2)
+            }
+            GenericRequirementKind::SAME_CONFORMANCE => {
+                self.indirectable(addr + 8, 0)
+            }
+            GenericRequirementKind::SAME_TYPE
+            | GenericRequirementKind::BASE_CLASS => {
+                self.relative(addr + 8, 0).map(Reference::Addr)
+            }
+            _ => None,
+        };
+        Some(GenericRequirement {
+            addr,
+            flags,
+            param: self.relative(addr + 4, 0),
+            content,
+        })
+    }
+
+    /// Resolves the relative pointer at `addr` whose low bit marks a pointer
+    /// to the target, clearing the other bits in `mask` from the offset.
+    fn indirectable(&self, addr: u64, mask: i32) -> Option<Reference<'_>> {
+        let is_indirect = self.read_u32(addr)? & 1 != 0;
+        let target = self.relative(addr, mask | 1)?;
+        if is_indirect {
+            self.indirect(target)
+        } else {
+            Some(Reference::Addr(target))
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{
+        elf::{Endian, SectionType},
+        fixture::{Bytes, ElfBuilder},
+    };
+
+    const TEXT: u64 = 0x1000;
+    const SLOTS: u64 = 0x2000;
+
+    /// Returns an image with `descriptor` at `TEXT` and pointers to 0x3000,
+    /// 0x3100 and 0x3200 at `SLOTS`.
+    fn image(descriptor: Bytes) -> Image {
+        let mut slots = Bytes::new(Endian::Little);
+        slots.u64(0x3000).u64(0x3100).u64(0x3200);
+
+        let mut builder = ElfBuilder::new(true, Endian::Little);
+        builder.section(".text", SectionType::PROGBITS, TEXT, descriptor.data);
+        builder.section(".data", SectionType::PROGBITS, SLOTS, slots.data);
+        Image::parse(builder.build()).unwrap()
+    }
+
+    #[test]
+    fn conformance_descriptor_trailing_objects() {
+        let slots = (SLOTS - TEXT) as usize;
+        let flags = ConformanceFlags(
+            TypeReferenceKind::INDIRECT_TYPE_DESCRIPTOR.0 << 3
+                | 1 << 6 // retroactive
+                | 2 << 8 // conditional requirements
+                | 1 << 16 // resilient witnesses
+                | 1 << 17, // generic witness table
+        );
+
+        let mut descriptor = Bytes::new(Endian::Little);
+        descriptor
+            .rel(slots, 1) // protocol
+            .rel(slots + 8, 0) // type
+            .rel(0x200, 0) // witness table pattern
+            .u32(flags.0)
+            .rel(0x300, 0); // retroactive context
+
+        // A protocol requirement on an Objective-C protocol, and a same-type
+        // requirement.
+        descriptor.u32(0).rel(0x400, 0).rel(0x500, 2);
+        descriptor.u32(1).rel(0x410, 0).rel(0x600, 0);
+
+        descriptor.u32(2);
+        descriptor.rel(slots + 16, 1).rel(0x700, 0);
+        descriptor.rel(0x800, 0).rel(0x900, 0);
+        assert_eq!(descriptor.data.len(), 64);
+
+        // The generic witness table.
+        descriptor.u32(0).u32(0).u32(0);
+
+        let image = image(descriptor);
+        let conformance = image.conformance_descriptor(TEXT).unwrap();
+        assert_eq!(
+            conformance,
+            ConformanceDescriptor {
+                addr: TEXT,
+                protocol: Some(Reference::Addr(0x3000)),
+                type_kind: TypeReferenceKind::INDIRECT_TYPE_DESCRIPTOR,
+                type_ref: Some(Reference::Addr(0x3100)),
+                witness_table_pattern: Some(TEXT + 0x200),
+                flags,
+                retroactive_context: Some(Reference::Addr(TEXT + 0x300)),
+                conditional_requirements: vec![
+                    GenericRequirement {
+                        addr: TEXT + 20,
+                        flags: GenericRequirementFlags(0),
+                        param: Some(TEXT + 0x400),
+                        content: Some(Reference::Addr(TEXT + 0x500)),
+                    },
+                    GenericRequirement {
+                        addr: TEXT + 32,
+                        flags: GenericRequirementFlags(1),
+                        param: Some(TEXT + 0x410),
+                        content: Some(Reference::Addr(TEXT + 0x600)),
+                    },
+                ],
+                resilient_witnesses: vec![
+                    ResilientWitness {
+                        requirement: Some(Reference::Addr(0x3200)),
+                        witness: Some(TEXT + 0x700),
+                    },
+                    ResilientWitness {
+                        requirement: Some(Reference::Addr(TEXT + 0x800)),
+                        witness: Some(TEXT + 0x900),
+                    },
+                ],
+                generic_witness_table: Some(TEXT + 64),
+            },
+        );
+    }
+
+    #[test]
+    fn resilient_witnesses_are_bounded_by_the_section() {
+        let flags = ConformanceFlags(1 << 16 | 1 << 17);
+
+        let mut descriptor = Bytes::new(Endian::Little);
+        descriptor.u32(0).rel(0x100, 0).u32(0).u32(flags.0);
+        descriptor.u32(100);
+        descriptor.rel(0x200, 0).rel(0x300, 0);
+        descriptor.rel(0x400, 0).rel(0x500, 0);
+
+        let image = image(descriptor);
+        let conformance = image.conformance_descriptor(TEXT).unwrap();
+        assert_eq!(conformance.protocol, None);
+        assert_eq!(conformance.type_kind, TypeReferenceKind(0));
+        assert_eq!(conformance.type_ref, Some(Reference::Addr(TEXT + 0x100)));
+        assert_eq!(conformance.retroactive_context, None);
+        assert!(conformance.conditional_requirements.is_empty());
+
+        let witnesses = conformance.resilient_witnesses;
+        assert_eq!(witnesses.len(), 2);
+        assert_eq!(witnesses[1].witness, Some(TEXT + 0x500));
+        assert_eq!(conformance.generic_witness_table, Some(TEXT + 36));
+    }
+}
@@ -0,0 +1,67 @@
+use std::{ops::Range, path::PathBuf};
+
+#[cfg(any(target_os = "linux", target_os = "android"))]
+use std::{fs, io};
+
+/// A mapping of a process's memory, as listed in `/proc/<pid>/maps`.
+#[derive(Clone, Debug, PartialEq, Eq, Hash)]
+pub struct MemoryMap {
+    /// The range of addresses that are mapped.
+    pub addrs: Range<u64>,
+    /// The permissions of the mapping, such as `r-xp`.
+    pub permissions: String,
+    /// The offset of the mapping within its file.
+    pub offset: u64,
+    /// The file that's mapped, or `None` if the mapping is anonymous.
+    ///
+    /// Pseudo-paths such as `[heap]` are excluded.
+    pub path: Option<PathBuf>,
+}
+
+impl MemoryMap {
+    /// Parses a line of `/proc/<pid>/maps`, such as:
+    ///
+    /// ```text
+    /// 7f2c1a000000-7f2c1a021000 r--p 00000000 08:01 1234 /usr/lib/libswiftCore.so
+    /// ```
+    pub fn parse(line: &str) -> Option<Self> {
+        let mut fields = line.splitn(6, ' ');
+        let addrs = fields.next()?;
+        let permissions = fields.next()?.to_owned();
+        let offset = u64::from_str_radix(fields.next()?, 16).ok()?;
+        let _device = fields.next()?;
+        let _inode = fields.next()?;
+
+        let (start, end) = addrs.split_at(addrs.find('-')?);
+        let start = u64::from_str_radix(start, 16).ok()?;
+        let end = u64::from_str_radix(&end[1..], 16).ok()?;
+
+        // The path is padded to a column and may contain spaces.
+        let path = fields.next().unwrap_or("").trim_start();
+        let path = if path.is_empty() || path.starts_with('[') {
+            None
+        } else {
+            Some(PathBuf::from(path))
+        };
+
+        Some(Self {
+            addrs: start..end,
+            permissions,
+            offset,
+            path,
+        })
+    }
+
+    /// Returns whether the mapping can be read.
+    #[inline]
+    pub fn is_readable(&self) -> bool {
+        self.permissions.starts_with('r')
+    }
+}
+
+/// Reads the memory mappings of the process `pid` from `/proc/<pid>/maps`.
+#[cfg(any(target_os = "linux", target_os = "android"))]
+pub fn read_maps(pid: u32) -> io::Result<Vec<MemoryMap>> {
+    let maps = fs::read_to_string(format!("/proc/{}/maps", pid))?;
+    Ok(maps.lines().filter_map(MemoryMap::parse).collect())
+}
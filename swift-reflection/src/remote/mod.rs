@@ -0,0 +1,703 @@
+//! Reading the Swift metadata and heap objects of another process.
+//!
+//! A [`RemoteProcess`](struct.RemoteProcess.html) reads memory through a
+//! [`MemoryReader`](trait.MemoryReader.html), which may be a running process
+//! on Linux, a core dump, or a buffer of bytes. Swift images are located by
+//! opening the files that the process has mapped into memory, and metadata is
+//! then read at its address in the process.
+//!
+//! The process is assumed to have the pointer size and byte order of the
+//! current target, and to use Swift's runtime without Objective-C interop.
+//!
+//! # Examples
+//!
+//! Dumping a heap object of a running process:
+//!
+//! ```no_run
+//! use swift_reflection::remote::RemoteProcess;
+//!
+//! # let (pid, addr) = (0, 0);
+//! let process = RemoteProcess::attach(pid)?;
+//! let object = process.object(addr)?;
+//! println!("{}", object.type_name.as_deref().unwrap_or("<unknown>"));
+//! for field in &object.fields {
+//!     println!("  {} at {:?}", field.name, object.field_addr(field));
+//! }
+//! # Ok::<(), swift_reflection::Error>(())
+//! ```
+
+mod maps;
+mod reader;
+
+pub use maps::MemoryMap;
+pub use reader::{BufferReader, MemoryReader};
+
+#[cfg(any(target_os = "linux", target_os = "android"))]
+pub use maps::read_maps;
+#[cfg(any(target_os = "linux", target_os = "android"))]
+pub use reader::ProcessMemory;
+
+use crate::{
+    image::mangled_name_len, Error, Image, SwiftSection, TypeReferenceKind,
+};
+use std::{
+    ops::Range,
+    path::{Path, PathBuf},
+};
+use swift_sys::metadata::{
+    context::{
+        ContextDescriptorFlags, ContextDescriptorKind,
+        TypeContextDescriptorFlags,
+    },
+    field::FieldRecordFlags,
+    MetadataKind,
+};
+
+/// The size of a pointer in the process.
+const WORD: u64 = std::mem::size_of::<usize>() as u64;
+
+/// The offset of the descriptor in class metadata, after the kind and
+/// superclass words and six 32-bit layout fields.
+const CLASS_DESCRIPTION_OFFSET: u64 = 2 * WORD + 24;
+
+/// The most parents or superclasses followed before metadata is considered
+/// malformed, which guards against cycles.
+const MAX_DEPTH: usize = 256;
+
+/// An image that's mapped into the memory of a process.
+#[derive(Clone, Debug)]
+pub struct RemoteImage {
+    path: PathBuf,
+    image: Image,
+    base_address: u64,
+}
+
+impl RemoteImage {
+    /// Creates an image that's loaded at `base_address`, which is added to
+    /// the addresses of `image` to get its addresses in memory.
+    #[inline]
+    pub fn new(path: PathBuf, image: Image, base_address: u64) -> Self {
+        Self {
+            path,
+            image,
+            base_address,
+        }
+    }
+
+    /// Opens the files mapped by `maps` that contain Swift metadata, such as
+    /// those listed in `/proc/<pid>/maps`.
+    ///
+    /// Files that can't be read or aren't ELF files are skipped.
+    pub fn locate(maps: &[MemoryMap]) -> Vec<Self> {
+        let mut paths: Vec<&Path> = Vec::new();
+        for path in maps.iter().filter_map(|map| map.path.as_deref()) {
+            if !paths.contains(&path) {
+                paths.push(path);
+            }
+        }
+
+        paths
+            .into_iter()
+            .filter_map(|path| {
+                let image = Image::open(path).ok()?;
+                let section = SwiftSection::ALL
+                    .iter()
+                    .find_map(|&kind| image.section(kind))?;
+
+                // Find where the section's contents are mapped.
+                let map = maps.iter().find(|map| {
+                    let len = map.addrs.end - map.addrs.start;
+                    map.path.as_deref() == Some(path)
+                        && (map.offset..map.offset + len)
+                            .contains(&section.offset)
+                })?;
+                let addr = map.addrs.start + (section.offset - map.offset);
+                let base_address = addr.wrapping_sub(section.addr);
+
+                Some(Self::new(path.to_owned(), image, base_address))
+            })
+            .collect()
+    }
+
+    /// Returns the path of the image's file.
+    #[inline]
+    pub fn path(&self) -> &Path {
+        &self.path
+    }
+
+    /// Returns the contents of the image's file.
+    #[inline]
+    pub fn image(&self) -> &Image {
+        &self.image
+    }
+
+    /// Returns the difference between addresses in memory and addresses in
+    /// the image's file.
+    #[inline]
+    pub fn base_address(&self) -> u64 {
+        self.base_address
+    }
+
+    /// Returns the range of addresses in memory occupied by the Swift
+    /// metadata section `kind`, which is empty if the image doesn't contain
+    /// it.
+    pub fn section_addrs(&self, kind: SwiftSection) -> Range<u64> {
+        let addrs = self.image.section_addrs(kind);
+        addrs.start.wrapping_add(self.base_address)
+            ..addrs.end.wrapping_add(self.base_address)
+    }
+
+    /// Returns whether `addr` in memory is within a section of the image.
+    pub fn contains(&self, addr: u64) -> bool {
+        let addr = addr.wrapping_sub(self.base_address);
+        self.image.elf().sections().iter().any(|section| {
+            section.addr != 0 && section.addr_range().contains(&addr)
+        })
+    }
+}
+
+/// A mangled name read from memory.
+#[derive(Clone, Debug, PartialEq, Eq, Hash)]
+pub struct MangledName {
+    /// The address of the name, which its symbolic references are relative
+    /// to.
+    pub addr: u64,
+    /// The bytes of the name, excluding the nul byte.
+    pub bytes: Vec<u8>,
+}
+
+/// A stored property or enum case of a type in another process.
+#[derive(Clone, Debug, PartialEq, Eq, Hash)]
+pub struct RemoteField {
+    /// The name of the field.
+    pub name: String,
+    /// Flags describing the field.
+    pub flags: FieldRecordFlags,
+    /// The mangled type of the field, or `None` if it's an enum case without
+    /// a payload.
+    pub mangled_type: Option<MangledName>,
+    /// The offset of the field's value within an instance, or `None` if it's
+    /// unknown, such as for enum cases.
+    pub offset: Option<u64>,
+}
+
+/// A heap object in another process, such as a class instance.
+#[derive(Clone, Debug, PartialEq, Eq, Hash)]
+pub struct RemoteObject {
+    /// The address of the object.
+    pub addr: u64,
+    /// The address of the object's type metadata.
+    pub metadata: u64,
+    /// The kind of the object's type metadata.
+    pub kind: MetadataKind,
+    /// The address of the descriptor of the object's type, or `None` if it
+    /// has none.
+    pub descriptor: Option<u64>,
+    /// The qualified name of the object's type, such as `Foo.Bar`, or `None`
+    /// if it has no descriptor.
+    pub type_name: Option<String>,
+    /// The stored properties of the object, starting with those of its
+    /// superclasses.
+    pub fields: Vec<RemoteField>,
+}
+
+impl RemoteObject {
+    /// Returns the address of the value of `field`, or `None` if its offset
+    /// is unknown.
+    #[inline]
+    pub fn field_addr(&self, field: &RemoteField) -> Option<u64> {
+        Some(self.addr.wrapping_add(field.offset?))
+    }
+}
+
+/// A process whose Swift metadata is read through a
+/// [`MemoryReader`](trait.MemoryReader.html).
+#[derive(Debug)]
+pub struct RemoteProcess<R> {
+    reader: R,
+    images: Vec<RemoteImage>,
+}
+
+#[cfg(any(target_os = "linux", target_os = "android"))]
+impl RemoteProcess<ProcessMemory> {
+    /// Attaches to the running process `pid`, reading its memory through
+    /// `/proc/<pid>/mem` and locating its images through `/proc/<pid>/maps`.
+    pub fn attach(pid: u32) -> Result<Self, Error> {
+        let reader = ProcessMemory::open(pid)?;
+        let maps = read_maps(pid)?;
+        Ok(Self::new(reader, RemoteImage::locate(&maps)))
+    }
+}
+
+impl<R: MemoryReader> RemoteProcess<R> {
+    /// Creates a process whose memory is read by `reader` and contains
+    /// `images`.
+    #[inline]
+    pub fn new(reader: R, images: Vec<RemoteImage>) -> Self {
+        Self { reader, images }
+    }
+
+    /// Returns the reader of the process's memory.
+    #[inline]
+    pub fn reader(&self) -> &R {
+        &self.reader
+    }
+
+    /// Returns the images of the process that contain Swift metadata.
+    #[inline]
+    pub fn images(&self) -> &[RemoteImage] {
+        &self.images
+    }
+
+    /// Returns the image that contains `addr`, or `None` if it isn't within
+    /// an image, such as when it's on the heap.
+    #[inline]
+    pub fn image_containing(&self, addr: u64) -> Option<&RemoteImage> {
+        self.images.iter().find(|image| image.contains(addr))
+    }
+
+    /// Returns the addresses of the nominal type descriptors referred to by
+    /// the `swift5_types` sections of the process's images.
+    pub fn type_descriptors(&self) -> Result<Vec<u64>, Error> {
+        let mut descriptors = Vec::new();
+        for image in &self.images {
+            let addrs = image.section_addrs(SwiftSection::Types);
+            for addr in (addrs.start..addrs.end).step_by(4) {
+                let bits = self.reader.read_u32(addr)?;
+                let target = match self.relative(addr, 0x3)? {
+                    Some(target) => target,
+                    None => continue,
+                };
+                let descriptor = match TypeReferenceKind(bits & 0x3) {
+                    TypeReferenceKind::DIRECT_TYPE_DESCRIPTOR => target,
+                    TypeReferenceKind::INDIRECT_TYPE_DESCRIPTOR => {
+                        self.reader.read_pointer(target)?
+                    }
+                    _ => continue,
+                };
+                if descriptor != 0 {
+                    descriptors.push(descriptor);
+                }
+            }
+        }
+        Ok(descriptors)
+    }
+
+    /// Reads the heap object at `addr`, resolving its type and fields.
+    pub fn object(&self, addr: u64) -> Result<RemoteObject, Error> {
+        let metadata = self.reader.read_pointer(addr)?;
+        let kind = self.metadata_kind(metadata)?;
+        let descriptor = self.type_descriptor(metadata)?;
+        let type_name = match descriptor {
+            Some(descriptor) => Some(self.type_name(descriptor)?),
+            None => None,
+        };
+        Ok(RemoteObject {
+            addr,
+            metadata,
+            kind,
+            descriptor,
+            type_name,
+            fields: self.fields(metadata)?,
+        })
+    }
+
+    /// Reads the kind of the type metadata at `metadata`.
+    ///
+    /// Class metadata whose kind is an isa pointer is reported as
+    /// [`MetadataKind::CLASS`](../../swift_sys/metadata/struct.MetadataKind.html#associatedconstant.CLASS).
+    pub fn metadata_kind(&self, metadata: u64) -> Result<MetadataKind, Error> {
+        let kind = self.reader.read_pointer(metadata)?;
+        if kind > u64::from(MetadataKind::LAST_ENUMERATED.0) {
+            Ok(MetadataKind::CLASS)
+        } else {
+            Ok(MetadataKind(kind as u32))
+        }
+    }
+
+    /// Reads the address of the nominal type descriptor of the type metadata
+    /// at `metadata`, or returns `None` if its type isn't a nominal type.
+    pub fn type_descriptor(&self, metadata: u64) -> Result<Option<u64>, Error> {
+        let kind = self.metadata_kind(metadata)?;
+        let offset = if kind == MetadataKind::CLASS {
+            CLASS_DESCRIPTION_OFFSET
+        } else if kind == MetadataKind::STRUCT
+            || kind == MetadataKind::ENUM
+            || kind == MetadataKind::OPTIONAL
+        {
+            WORD
+        } else {
+            return Ok(None);
+        };
+        match self.reader.read_pointer(metadata + offset)? {
+            0 => Ok(None),
+            descriptor => Ok(Some(descriptor)),
+        }
+    }
+
+    /// Reads the name of the context descriptor at `descriptor`, or returns
+    /// `None` if it's unnamed, such as an extension.
+    pub fn context_name(
+        &self,
+        descriptor: u64,
+    ) -> Result<Option<String>, Error> {
+        let flags = ContextDescriptorFlags(self.reader.read_u32(descriptor)?);
+        let kind = flags.kind();
+        if kind != ContextDescriptorKind::MODULE
+            && kind != ContextDescriptorKind::PROTOCOL
+            && !kind.is_type()
+        {
+            return Ok(None);
+        }
+        match self.relative(descriptor + 8, 0)? {
+            Some(name) => {
+                let name = self.reader.read_c_str(name)?;
+                Ok(Some(String::from_utf8_lossy(&name).into_owned()))
+            }
+            None => Ok(None),
+        }
+    }
+
+    /// Reads the parent of the context descriptor at `descriptor`, or
+    /// returns `None` if it's a module.
+    pub fn context_parent(
+        &self,
+        descriptor: u64,
+    ) -> Result<Option<u64>, Error> {
+        let addr = descriptor + 4;
+        let is_indirect = self.reader.read_u32(addr)? & 1 != 0;
+        match self.relative(addr, 1)? {
+            Some(slot) if is_indirect => {
+                match self.reader.read_pointer(slot)? {
+                    0 => Ok(None),
+                    parent => Ok(Some(parent)),
+                }
+            }
+            parent => Ok(parent),
+        }
+    }
+
+    /// Reads the qualified name of the context descriptor at `descriptor`,
+    /// such as `Foo.Bar.Baz`.
+    ///
+    /// Unnamed contexts, such as extensions, are skipped and generic
+    /// arguments are excluded.
+    pub fn type_name(&self, descriptor: u64) -> Result<String, Error> {
+        let mut names = Vec::new();
+        let mut context = Some(descriptor);
+        while let Some(descriptor) = context {
+            if names.len() == MAX_DEPTH {
+                return Err(Error::Malformed);
+            }
+            if let Some(name) = self.context_name(descriptor)? {
+                names.push(name);
+            }
+            context = self.context_parent(descriptor)?;
+        }
+        names.reverse();
+        Ok(names.join("."))
+    }
+
+    /// Reads the stored properties or enum cases of the type whose metadata
+    /// is at `metadata`, starting with those of its superclasses.
+    ///
+    /// Offsets are read from the metadata's field offset vector, and are
+    /// unknown for enum cases and for classes with a resilient superclass.
+    pub fn fields(&self, metadata: u64) -> Result<Vec<RemoteField>, Error> {
+        let mut classes = Vec::new();
+        let mut metadata = Some(metadata);
+        while let Some(addr) = metadata {
+            if classes.len() == MAX_DEPTH {
+                return Err(Error::Malformed);
+            }
+            classes.push(addr);
+            metadata = if self.metadata_kind(addr)? == MetadataKind::CLASS {
+                match self.reader.read_pointer(addr + WORD)? {
+                    0 => None,
+                    superclass => Some(superclass),
+                }
+            } else {
+                None
+            };
+        }
+
+        let mut fields = Vec::new();
+        for &metadata in classes.iter().rev() {
+            self.push_fields(metadata, &mut fields)?;
+        }
+        Ok(fields)
+    }
+
+    /// Pushes the fields declared by the type whose metadata is at
+    /// `metadata` onto `fields`.
+    fn push_fields(
+        &self,
+        metadata: u64,
+        fields: &mut Vec<RemoteField>,
+    ) -> Result<(), Error> {
+        let descriptor = match self.type_descriptor(metadata)? {
+            Some(descriptor) => descriptor,
+            None => return Ok(()),
+        };
+        let field_descriptor = match self.relative(descriptor + 16, 0)? {
+            Some(field_descriptor) => field_descriptor,
+            None => return Ok(()),
+        };
+        let record_size = self.reader.read_u16(field_descriptor + 10)?;
+        let num_fields = self.reader.read_u32(field_descriptor + 12)?;
+        if record_size != 12 {
+            return Ok(());
+        }
+
+        let flags = ContextDescriptorFlags(self.reader.read_u32(descriptor)?);
+        let type_flags =
+            TypeContextDescriptorFlags(flags.kind_specific_flags());
+        let offsets = match flags.kind() {
+            ContextDescriptorKind::STRUCT => {
+                Some((self.reader.read_u32(descriptor + 24)?, 4))
+            }
+            ContextDescriptorKind::CLASS
+                if !type_flags.class_has_resilient_superclass() =>
+            {
+                Some((self.reader.read_u32(descriptor + 40)?, WORD))
+            }
+            _ => None,
+        };
+        // An offset of 0 means the metadata has no field offset vector.
+        let offsets = offsets
+            .filter(|&(words, _)| words != 0)
+            .map(|(words, size)| (metadata + u64::from(words) * WORD, size));
+
+        for i in 0..u64::from(num_fields) {
+            let record = field_descriptor + 16 + i * 12;
+            let flags = FieldRecordFlags(self.reader.read_u32(record)?);
+            let mangled_type = match self.relative(record + 4, 0)? {
+                Some(addr) => Some(MangledName {
+                    addr,
+                    bytes: self.read_mangled_name(addr)?,
+                }),
+                None => None,
+            };
+            let name = match self.relative(record + 8, 0)? {
+                Some(name) => self.reader.read_c_str(name)?,
+                None => Vec::new(),
+            };
+            let offset = match offsets {
+                Some((vector, 4)) => {
+                    Some(u64::from(self.reader.read_u32(vector + i * 4)?))
+                }
+                Some((vector, size)) => {
+                    Some(self.reader.read_pointer(vector + i * size)?)
+                }
+                None => None,
+            };
+            fields.push(RemoteField {
+                name: String::from_utf8_lossy(&name).into_owned(),
+                flags,
+                mangled_type,
+                offset,
+            });
+        }
+        Ok(())
+    }
+
+    /// Reads the mangled name at `addr`, which may contain symbolic
+    /// references.
+    pub fn read_mangled_name(&self, addr: u64) -> Result<Vec<u8>, Error> {
+        const CHUNK: u64 = 64;
+
+        // Read aligned chunks until the name is complete, since symbolic
+        // references may contain nul bytes.
+        let mut bytes = Vec::new();
+        let mut end = addr;
+        loop {
+            if let Some(len) = mangled_name_len(&bytes, WORD as usize) {
+                bytes.truncate(len);
+                return Ok(bytes);
+            }
+            let len = CHUNK - end % CHUNK;
+            let start = bytes.len();
+            bytes.resize(start + len as usize, 0);
+            self.reader.read(end, &mut bytes[start..])?;
+            end += len;
+        }
+    }
+
+    /// Resolves the relative pointer at `addr`, returning `None` if it's
+    /// null.
+    ///
+    /// Bits in `mask` are cleared from the offset before it's applied.
+    pub fn relative(&self, addr: u64, mask: i32) -> Result<Option<u64>, Error> {
+        let offset = self.reader.read_i32(addr)? & !mask;
+        if offset == 0 {
+            Ok(None)
+        } else {
+            Ok(Some(addr.wrapping_add(offset as i64 as u64)))
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{
+        elf::{Elf, Endian, SectionType},
+        fixture::{Bytes, ElfBuilder},
+    };
+    use std::{fs, process};
+    use swift_sys::metadata::field::FieldRecordFlags;
+
+    const BASE: u64 = 0x10000;
+    const IS_64: bool = WORD == 8;
+
+    fn native() -> Bytes {
+        Bytes::new(if cfg!(target_endian = "little") {
+            Endian::Little
+        } else {
+            Endian::Big
+        })
+    }
+
+    /// Pads `memory` with zeros up to `offset` from `BASE`.
+    fn at(memory: &mut Bytes, offset: u64) -> &mut Bytes {
+        assert!(memory.data.len() <= offset as usize);
+        memory.data.resize(offset as usize, 0);
+        memory
+    }
+
+    /// Returns a process whose memory at `BASE` is `memory`.
+    fn process(mut memory: Bytes) -> RemoteProcess<BufferReader> {
+        // Mangled names are read in aligned chunks.
+        memory.align(64);
+        RemoteProcess::new(BufferReader::new(BASE, memory.data), Vec::new())
+    }
+
+    #[test]
+    fn object_resolves_class_metadata() {
+        let mut memory = native();
+
+        // The object, whose header starts with its metadata.
+        memory.word(IS_64, BASE + 0x100).word(IS_64, 2);
+
+        // The class metadata, with no superclass and a field offset vector
+        // at word 10.
+        at(&mut memory, 0x100).word(IS_64, 0).word(IS_64, 0);
+        at(&mut memory, 0x100 + CLASS_DESCRIPTION_OFFSET);
+        memory.word(IS_64, BASE + 0x200);
+        at(&mut memory, 0x100 + 10 * WORD).word(IS_64, 16);
+
+        // The class descriptor: flags, parent, name, access function, fields
+        // and, at offset 40, the start of the field offset vector.
+        let class = u32::from(ContextDescriptorKind::CLASS.0);
+        at(&mut memory, 0x200)
+            .u32(class)
+            .rel(0x300, 0)
+            .rel(0x380, 0);
+        memory.u32(0).rel(0x400, 0);
+        at(&mut memory, 0x228).u32(10);
+
+        // The module descriptor.
+        at(&mut memory, 0x300).u32(0).u32(0).rel(0x390, 0);
+        at(&mut memory, 0x380).data.extend_from_slice(b"Bar\0");
+        at(&mut memory, 0x390).data.extend_from_slice(b"Foo\0");
+
+        // The field descriptor and its one record.
+        at(&mut memory, 0x400).u32(0).u32(0).u16(1).u16(12).u32(1);
+        memory.u32(0).rel(0x480, 0).rel(0x490, 0);
+        at(&mut memory, 0x480).data.extend_from_slice(b"Si\0");
+        at(&mut memory, 0x490).data.extend_from_slice(b"x\0");
+
+        let object = process(memory).object(BASE).unwrap();
+        assert_eq!(
+            object,
+            RemoteObject {
+                addr: BASE,
+                metadata: BASE + 0x100,
+                kind: MetadataKind::CLASS,
+                descriptor: Some(BASE + 0x200),
+                type_name: Some("Foo.Bar".to_owned()),
+                fields: vec![RemoteField {
+                    name: "x".to_owned(),
+                    flags: FieldRecordFlags(0),
+                    mangled_type: Some(MangledName {
+                        addr: BASE + 0x480,
+                        bytes: b"Si".to_vec(),
+                    }),
+                    offset: Some(16),
+                }],
+            },
+        );
+        assert_eq!(object.field_addr(&object.fields[0]), Some(BASE + 16));
+    }
+
+    #[test]
+    fn superclass_cycle_is_malformed() {
+        let mut memory = native();
+        memory.word(IS_64, BASE + 0x100);
+
+        // Two classes without descriptors that are each other's superclass.
+        at(&mut memory, 0x100)
+            .word(IS_64, 0)
+            .word(IS_64, BASE + 0x200);
+        at(&mut memory, 0x200)
+            .word(IS_64, 0)
+            .word(IS_64, BASE + 0x100);
+        at(&mut memory, 0x200 + CLASS_DESCRIPTION_OFFSET).word(IS_64, 0);
+
+        let process = process(memory);
+        assert_eq!(process.type_descriptor(BASE + 0x100).unwrap(), None);
+        assert!(matches!(
+            process.fields(BASE + 0x100),
+            Err(Error::Malformed)
+        ));
+        assert!(matches!(process.object(BASE), Err(Error::Malformed)));
+    }
+
+    #[test]
+    fn locate_finds_base_address() {
+        let mut builder = ElfBuilder::new(IS_64, native().endian);
+        builder.section(".text", SectionType::PROGBITS, 0x1000, vec![0; 16]);
+        builder.section(
+            "swift5_types",
+            SectionType::PROGBITS,
+            0x2000,
+            vec![0; 8],
+        );
+        let data = builder.build();
+        let offset = Elf::parse(data.clone())
+            .unwrap()
+            .section("swift5_types")
+            .unwrap()
+            .offset;
+
+        let path = std::env::temp_dir()
+            .join(format!("swift-reflection-locate-{}.so", process::id()));
+        fs::write(&path, data).unwrap();
+
+        // The first mapping of the file ends before the section.
+        let maps = [
+            "7f0000000000-7f0000000010 r--p 00000000 08:01 1 PATH",
+            "7f0000001000-7f0000002000 rw-p 00000000 00:00 0 [heap]",
+            "7f0000005000-7f0000006000 r--p 00000010 08:01 1 PATH",
+            "7f0000007000-7f0000008000 r--p 00000000 08:01 2 /nonexistent.so",
+        ];
+        let maps = maps
+            .iter()
+            .map(|line| line.replace("PATH", path.to_str().unwrap()))
+            .filter_map(|line| MemoryMap::parse(&line))
+            .collect::<Vec<_>>();
+        let images = RemoteImage::locate(&maps);
+        fs::remove_file(&path).unwrap();
+
+        let addr = 0x7F00_0000_5000 + (offset - 0x10);
+        assert_eq!(images.len(), 1);
+        assert_eq!(images[0].path(), path);
+        assert_eq!(images[0].base_address(), addr - 0x2000);
+        assert_eq!(
+            images[0].section_addrs(SwiftSection::Types),
+            addr..addr + 8
+        );
+        assert!(images[0].contains(addr - 0x1000));
+        assert!(!images[0].contains(addr + 8));
+    }
+}
@@ -0,0 +1,269 @@
+use crate::elf;
+use std::{convert::TryFrom, io, mem};
+
+#[cfg(any(target_os = "linux", target_os = "android"))]
+use std::{fs::File, os::unix::fs::FileExt};
+
+/// Reads the memory of a process, which may be running or captured in a file
+/// such as a core dump.
+///
+/// Values are read with the pointer size and byte order of the current
+/// target.
+pub trait MemoryReader {
+    /// Fills `buf` with the bytes at `addr`, failing if any of them can't be
+    /// read.
+    fn read(&self, addr: u64, buf: &mut [u8]) -> io::Result<()>;
+
+    /// Reads the `u16` at `addr`.
+    #[inline]
+    fn read_u16(&self, addr: u64) -> io::Result<u16> {
+        let mut bytes = [0; 2];
+        self.read(addr, &mut bytes)?;
+        Ok(u16::from_ne_bytes(bytes))
+    }
+
+    /// Reads the `u32` at `addr`.
+    #[inline]
+    fn read_u32(&self, addr: u64) -> io::Result<u32> {
+        let mut bytes = [0; 4];
+        self.read(addr, &mut bytes)?;
+        Ok(u32::from_ne_bytes(bytes))
+    }
+
+    /// Reads the `i32` at `addr`, such as the offset of a relative pointer.
+    #[inline]
+    fn read_i32(&self, addr: u64) -> io::Result<i32> {
+        self.read_u32(addr).map(|n| n as i32)
+    }
+
+    /// Reads the pointer-sized value at `addr`.
+    #[inline]
+    fn read_pointer(&self, addr: u64) -> io::Result<u64> {
+        let mut bytes = [0; mem::size_of::<usize>()];
+        self.read(addr, &mut bytes)?;
+        Ok(usize::from_ne_bytes(bytes) as u64)
+    }
+
+    /// Reads the nul-terminated string at `addr`, excluding the nul byte.
+    fn read_c_str(&self, addr: u64) -> io::Result<Vec<u8>> {
+        // Reading in aligned chunks never crosses into an unmapped page.
+        const CHUNK: u64 = 64;
+
+        let mut string = Vec::new();
+        let mut addr = addr;
+        loop {
+            let len = CHUNK - addr % CHUNK;
+            let mut chunk = [0; CHUNK as usize];
+            let chunk = &mut chunk[..len as usize];
+            self.read(addr, chunk)?;
+            match chunk.iter().position(|&byte| byte == 0) {
+                Some(end) => {
+                    string.extend_from_slice(&chunk[..end]);
+                    return Ok(string);
+                }
+                None => string.extend_from_slice(chunk),
+            }
+            addr += len;
+        }
+    }
+}
+
+impl<R: MemoryReader + ?Sized> MemoryReader for &R {
+    #[inline]
+    fn read(&self, addr: u64, buf: &mut [u8]) -> io::Result<()> {
+        (**self).read(addr, buf)
+    }
+
+    #[inline]
+    fn read_c_str(&self, addr: u64) -> io::Result<Vec<u8>> {
+        (**self).read_c_str(addr)
+    }
+}
+
+impl<R: MemoryReader + ?Sized> MemoryReader for Box<R> {
+    #[inline]
+    fn read(&self, addr: u64, buf: &mut [u8]) -> io::Result<()> {
+        (**self).read(addr, buf)
+    }
+
+    #[inline]
+    fn read_c_str(&self, addr: u64) -> io::Result<Vec<u8>> {
+        (**self).read_c_str(addr)
+    }
+}
+
+/// Reads a buffer of bytes as if it were memory starting at an address.
+///
+/// This is useful for reading a snapshot of memory, such as a test fixture.
+#[derive(Clone, Debug)]
+pub struct BufferReader {
+    addr: u64,
+    bytes: Vec<u8>,
+}
+
+impl BufferReader {
+    /// Creates a reader of `bytes` as the memory starting at `addr`.
+    #[inline]
+    pub fn new(addr: u64, bytes: Vec<u8>) -> Self {
+        Self { addr, bytes }
+    }
+
+    /// Returns the address of the first byte.
+    #[inline]
+    pub fn addr(&self) -> u64 {
+        self.addr
+    }
+
+    /// Returns the bytes being read.
+    #[inline]
+    pub fn bytes(&self) -> &[u8] {
+        &self.bytes
+    }
+
+    /// Returns the bytes from `addr` to the end of the buffer.
+    #[inline]
+    fn bytes_from(&self, addr: u64) -> Option<&[u8]> {
+        let start = usize::try_from(addr.checked_sub(self.addr)?).ok()?;
+        self.bytes.get(start..)
+    }
+}
+
+impl MemoryReader for BufferReader {
+    fn read(&self, addr: u64, buf: &mut [u8]) -> io::Result<()> {
+        match self
+            .bytes_from(addr)
+            .and_then(|bytes| bytes.get(..buf.len()))
+        {
+            Some(bytes) => {
+                buf.copy_from_slice(bytes);
+                Ok(())
+            }
+            None => Err(unreadable(addr)),
+        }
+    }
+
+    fn read_c_str(&self, addr: u64) -> io::Result<Vec<u8>> {
+        // The buffer needn't end on a chunk boundary, so the string is found
+        // in place rather than read in chunks.
+        let bytes = self.bytes_from(addr).ok_or_else(|| unreadable(addr))?;
+        match elf::c_str(bytes) {
+            Some(string) => Ok(string.to_vec()),
+            None => Err(unreadable(self.addr + self.bytes.len() as u64)),
+        }
+    }
+}
+
+/// Reads the memory of a running process through `/proc/<pid>/mem`.
+///
+/// Reading another process requires permission to trace it, such as by
+/// running as the same user with `ptrace` allowed.
+#[cfg(any(target_os = "linux", target_os = "android"))]
+#[derive(Debug)]
+pub struct ProcessMemory {
+    pid: u32,
+    file: File,
+}
+
+#[cfg(any(target_os = "linux", target_os = "android"))]
+impl ProcessMemory {
+    /// Opens the memory of the process `pid`.
+    #[inline]
+    pub fn open(pid: u32) -> io::Result<Self> {
+        let file = File::open(format!("/proc/{}/mem", pid))?;
+        Ok(Self { pid, file })
+    }
+
+    /// Returns the ID of the process.
+    #[inline]
+    pub fn pid(&self) -> u32 {
+        self.pid
+    }
+}
+
+#[cfg(any(target_os = "linux", target_os = "android"))]
+impl MemoryReader for ProcessMemory {
+    #[inline]
+    fn read(&self, addr: u64, buf: &mut [u8]) -> io::Result<()> {
+        self.file.read_exact_at(buf, addr).map_err(|error| {
+            // Unmapped addresses fail with `EIO`, which alone doesn't say
+            // what couldn't be read.
+            io::Error::new(error.kind(), format!("{} at {:#x}", error, addr))
+        })
+    }
+}
+
+/// Returns an error for bytes at `addr` that couldn't be read.
+pub(crate) fn unreadable(addr: u64) -> io::Error {
+    io::Error::new(
+        io::ErrorKind::UnexpectedEof,
+        format!("Failed to read memory at {:#x}", addr),
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::cell::RefCell;
+
+    /// Reads a buffer through `MemoryReader::read` alone, recording each
+    /// read.
+    struct Chunked {
+        buffer: BufferReader,
+        reads: RefCell<Vec<(u64, usize)>>,
+    }
+
+    impl Chunked {
+        fn new(bytes: Vec<u8>) -> Self {
+            Self {
+                buffer: BufferReader::new(0x1000, bytes),
+                reads: RefCell::new(Vec::new()),
+            }
+        }
+    }
+
+    impl MemoryReader for Chunked {
+        fn read(&self, addr: u64, buf: &mut [u8]) -> io::Result<()> {
+            self.reads.borrow_mut().push((addr, buf.len()));
+            self.buffer.read(addr, buf)
+        }
+    }
+
+    #[test]
+    fn read_c_str_across_chunks() {
+        let mut bytes = vec![0; 128];
+        bytes[60..72].copy_from_slice(b"hello, world");
+
+        let reader = Chunked::new(bytes);
+        assert_eq!(reader.read_c_str(0x103C).unwrap(), b"hello, world");
+        assert_eq!(*reader.reads.borrow(), [(0x103C, 4), (0x1040, 64)]);
+    }
+
+    #[test]
+    fn read_c_str_at_end_of_buffer() {
+        let mut bytes = vec![0; 128];
+        bytes[120..127].copy_from_slice(b"1234567");
+
+        let reader = Chunked::new(bytes.clone());
+        assert_eq!(reader.read_c_str(0x1078).unwrap(), b"1234567");
+        assert_eq!(reader.read_c_str(0x107F).unwrap(), b"");
+        assert!(reader.read_c_str(0x1080).is_err());
+
+        bytes[127] = b'8';
+        let reader = Chunked::new(bytes);
+        let error = reader.read_c_str(0x1078).unwrap_err();
+        assert_eq!(error.kind(), io::ErrorKind::UnexpectedEof);
+        assert_eq!(*reader.reads.borrow(), [(0x1078, 8), (0x1080, 64)]);
+    }
+
+    #[test]
+    fn buffer_read_c_str_before_chunk_boundary() {
+        let reader = BufferReader::new(0x1000, b"abc\0de".to_vec());
+        assert_eq!(reader.read_c_str(0x1000).unwrap(), b"abc");
+        assert_eq!(reader.read_c_str(0x1003).unwrap(), b"");
+        assert!(reader.read_c_str(0x1004).is_err());
+        assert!(reader.read_c_str(0xFFF).is_err());
+
+        let reader: Box<dyn MemoryReader> = Box::new(reader);
+        assert_eq!(reader.read_c_str(0x1000).unwrap(), b"abc");
+    }
+}
@@ -0,0 +1,96 @@
+//! Protocols and protocol conformances declared by the images loaded in the
+//! current process.
+
+use crate::sys::{
+    metadata::{
+        conformance::{
+            ProtocolConformanceDescriptor, ProtocolConformanceRecord,
+        },
+        context::{ContextDescriptor, ProtocolDescriptor, ProtocolRecord},
+    },
+    section::{self, MetadataSections},
+};
+use std::{ffi::c_void, ptr};
+
+/// Returns the metadata sections of every image registered with the runtime.
+pub(crate) fn metadata_sections() -> Vec<&'static MetadataSections> {
+    unsafe extern "C" fn push(
+        sections: *const MetadataSections,
+        context: *mut c_void,
+    ) -> bool {
+        let all = &mut *(context as *mut Vec<&'static MetadataSections>);
+        if let Some(sections) = sections.as_ref() {
+            all.push(sections);
+        }
+        true
+    }
+
+    let mut all = Vec::new();
+    unsafe {
+        section::swift_enumerateAllMetadataSections(
+            push,
+            &mut all as *mut Vec<_> as *mut c_void,
+        );
+    }
+    all
+}
+
+/// Returns an iterator over the protocols declared by loaded images.
+///
+/// # Examples
+///
+/// ```no_run
+/// use swift_rt::conformance;
+///
+/// let equatable = conformance::protocols()
+///     .find(|p| p.name().to_bytes() == b"Equatable")
+///     .unwrap();
+/// ```
+pub fn protocols() -> impl Iterator<Item = &'static ProtocolDescriptor> {
+    metadata_sections().into_iter().flat_map(|sections| {
+        let records: &[ProtocolRecord] =
+            unsafe { sections.swift5_protocols.as_slice() };
+        records.iter().filter_map(ProtocolRecord::protocol)
+    })
+}
+
+/// Returns an iterator over the protocol conformances declared by loaded
+/// images.
+pub fn conformances(
+) -> impl Iterator<Item = &'static ProtocolConformanceDescriptor> {
+    metadata_sections().into_iter().flat_map(|sections| {
+        let records: &[ProtocolConformanceRecord] =
+            unsafe { sections.swift5_protocol_conformances.as_slice() };
+        records
+            .iter()
+            .filter_map(ProtocolConformanceRecord::descriptor)
+    })
+}
+
+/// Returns an iterator over the descriptors of the types that conform to
+/// `protocol`, including conditional conformances.
+///
+/// # Examples
+///
+/// ```no_run
+/// use swift_rt::conformance;
+///
+/// let hashable = conformance::protocols()
+///     .find(|p| p.name().to_bytes() == b"Hashable")
+///     .unwrap();
+/// for ty in conformance::conforming_types(hashable) {
+///     println!("{:?}", ty.name());
+/// }
+/// ```
+pub fn conforming_types(
+    protocol: &ProtocolDescriptor,
+) -> impl Iterator<Item = &'static ContextDescriptor> + '_ {
+    conformances().filter_map(move |conformance| {
+        // Protocol descriptors are unique, so they're compared by address.
+        if ptr::eq(conformance.protocol()?, protocol) {
+            conformance.type_descriptor()
+        } else {
+            None
+        }
+    })
+}
@@ -0,0 +1,338 @@
+//! Reading and constructing values of enums whose types are only known at
+//! runtime.
+
+use crate::{
+    field::{self, Field},
+    obj::MetaType,
+    sys::{
+        heap::{self, HeapObject},
+        metadata::{
+            context::{ContextDescriptor, EnumDescriptor},
+            lookup,
+            value_witness::EnumValueWitnessTable,
+            EnumMetadata,
+        },
+    },
+    value::OpaqueSwiftValue,
+};
+use std::{
+    borrow::Cow,
+    fmt,
+    os::raw::{c_char, c_void},
+};
+
+/// An enum type, including `Optional`, whose values are manipulated through
+/// its enum value witnesses.
+///
+/// # Examples
+///
+/// Reading the case of a value:
+///
+/// ```no_run
+/// use swift_rt::{enum_type::EnumType, obj::MetaType, value::OpaqueSwiftValue};
+///
+/// # fn get_value() -> OpaqueSwiftValue { unimplemented!() }
+/// let ty = MetaType::from_name("Swift.Optional<Swift.Int>").unwrap();
+/// let optional = EnumType::new(ty).unwrap();
+///
+/// let value: OpaqueSwiftValue = get_value();
+/// let case = optional.case_of(&value).unwrap();
+/// if let Some(payload) = optional.payload(&value) {
+///     println!("{}({:?})", case.name(), payload);
+/// } else {
+///     println!("{}", case.name());
+/// }
+/// ```
+#[derive(Clone, Copy)]
+pub struct EnumType {
+    ty: &'static MetaType,
+    metadata: &'static EnumMetadata,
+    witnesses: &'static EnumValueWitnessTable,
+}
+
+impl fmt::Debug for EnumType {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.debug_tuple("EnumType")
+            .field(&self.ty.name(true))
+            .finish()
+    }
+}
+
+impl EnumType {
+    /// Returns `ty` as an enum type, or `None` if it isn't an enum.
+    pub fn new(ty: &'static MetaType) -> Option<Self> {
+        let metadata = ty.metadata().as_enum()?;
+        let witnesses = metadata.enum_value_witnesses()?;
+        Some(Self {
+            ty,
+            metadata,
+            witnesses,
+        })
+    }
+
+    /// Returns the enum as a type.
+    #[inline]
+    pub fn meta_type(&self) -> &'static MetaType {
+        self.ty
+    }
+
+    /// Returns the metadata of the enum.
+    #[inline]
+    pub fn metadata(&self) -> &'static EnumMetadata {
+        self.metadata
+    }
+
+    /// Returns the descriptor of the enum.
+    #[inline]
+    pub fn descriptor(&self) -> Option<&'static EnumDescriptor> {
+        self.metadata.description()
+    }
+
+    /// Returns the number of cases of the enum.
+    #[inline]
+    pub fn num_cases(&self) -> u32 {
+        self.descriptor().map_or(0, |d| d.num_cases())
+    }
+
+    /// Returns the case whose tag is `tag`, or `None` if there is none or the
+    /// enum has no reflection metadata.
+    ///
+    /// Cases with payloads have the lowest tags, followed by those without,
+    /// each in declaration order.
+    pub fn case(&self, tag: u32) -> Option<EnumCase> {
+        let field = field::fields(self.descriptor()?).nth(tag as usize)?;
+        Some(EnumCase {
+            enum_type: *self,
+            tag,
+            field,
+        })
+    }
+
+    /// Returns the case named `name`.
+    pub fn case_named(&self, name: &str) -> Option<EnumCase> {
+        self.cases().find(|case| case.name() == name)
+    }
+
+    /// Returns an iterator over the cases of the enum, in the order of their
+    /// tags.
+    pub fn cases(&self) -> impl Iterator<Item = EnumCase> {
+        let this = *self;
+        (0..self.num_cases()).map_while(move |tag| this.case(tag))
+    }
+
+    /// Returns the tag of the case of `value`.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `value` isn't of this enum type.
+    pub fn tag(&self, value: &OpaqueSwiftValue) -> u32 {
+        self.assert_is_type_of(value);
+        unsafe {
+            (self.witnesses.get_enum_tag)(
+                value.as_ptr() as *mut c_void,
+                self.self_ptr(),
+            )
+        }
+    }
+
+    /// Returns the case of `value`, or `None` if the enum has no reflection
+    /// metadata.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `value` isn't of this enum type.
+    #[inline]
+    pub fn case_of(&self, value: &OpaqueSwiftValue) -> Option<EnumCase> {
+        self.case(self.tag(value))
+    }
+
+    /// Returns a copy of the payload of `value`, or `None` if its case has no
+    /// payload.
+    ///
+    /// The payloads of `indirect` cases are copied out of their boxes.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `value` isn't of this enum type.
+    pub fn payload(
+        &self,
+        value: &OpaqueSwiftValue,
+    ) -> Option<OpaqueSwiftValue> {
+        let case = self.case_of(value)?;
+        let payload_type = case.payload_type()?;
+
+        // Projecting is destructive, so it's done on a copy.
+        let mut copy = value.clone();
+        unsafe {
+            (self.witnesses.destructive_project_enum_data)(
+                copy.as_mut_ptr(),
+                self.self_ptr(),
+            );
+            let payload = if case.is_indirect() {
+                let object = *(copy.as_ptr() as *const *mut HeapObject);
+                let src = heap::swift_projectBox(object) as *const c_void;
+                let payload = OpaqueSwiftValue::copy_from(payload_type, src);
+                (self.witnesses.destructive_inject_enum_data)(
+                    copy.as_mut_ptr(),
+                    case.tag,
+                    self.self_ptr(),
+                );
+                payload
+            } else {
+                let payload = OpaqueSwiftValue::take_from(
+                    payload_type,
+                    copy.as_mut_ptr(),
+                );
+                copy.forget();
+                payload
+            };
+            Some(payload)
+        }
+    }
+
+    /// Creates a value of the enum with the case `case` and its payload.
+    ///
+    /// The payloads of `indirect` cases are moved into new boxes.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `case` isn't of this enum, or if `payload` is missing or of
+    /// the wrong type for `case`.
+    pub fn new_value(
+        &self,
+        case: &EnumCase,
+        payload: Option<OpaqueSwiftValue>,
+    ) -> OpaqueSwiftValue {
+        assert!(
+            case.enum_type.ty.as_ptr() == self.ty.as_ptr(),
+            "case {} isn't of type {}",
+            case.name(),
+            self.ty.name(true),
+        );
+        let payload_type = case.payload_type();
+        assert!(
+            case.has_payload() == payload.is_some()
+                && payload_type.map(MetaType::as_ptr)
+                    == payload.as_ref().map(|p| p.meta_type().as_ptr()),
+            "invalid payload for case {} of type {}",
+            case.name(),
+            self.ty.name(true),
+        );
+
+        unsafe {
+            let mut value = OpaqueSwiftValue::uninit(self.ty);
+            if let (Some(payload), Some(payload_type)) = (payload, payload_type)
+            {
+                if case.is_indirect() {
+                    let pair = heap::swift_allocBox(payload_type.as_ptr());
+                    payload.move_into(pair.buffer as *mut c_void);
+                    *(value.as_mut_ptr() as *mut *mut HeapObject) = pair.object;
+                } else {
+                    payload.move_into(value.as_mut_ptr());
+                }
+            }
+            (self.witnesses.destructive_inject_enum_data)(
+                value.as_mut_ptr(),
+                case.tag,
+                self.self_ptr(),
+            );
+            value
+        }
+    }
+
+    /// Returns the metadata pointer passed as `self` to value witnesses.
+    #[inline]
+    fn self_ptr(&self) -> *mut c_void {
+        self.ty.as_ptr() as *mut c_void
+    }
+
+    #[inline]
+    fn assert_is_type_of(&self, value: &OpaqueSwiftValue) {
+        assert!(
+            value.meta_type().as_ptr() == self.ty.as_ptr(),
+            "expected a value of type {}, found {}",
+            self.ty.name(true),
+            value.meta_type().name(true),
+        );
+    }
+}
+
+/// A case of an enum.
+///
+/// Created by [`EnumType::case`](struct.EnumType.html#method.case).
+#[derive(Clone, Copy)]
+pub struct EnumCase {
+    enum_type: EnumType,
+    tag: u32,
+    field: Field<'static>,
+}
+
+impl fmt::Debug for EnumCase {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.debug_struct("EnumCase")
+            .field("name", &self.name())
+            .field("tag", &self.tag)
+            .finish()
+    }
+}
+
+impl EnumCase {
+    /// Returns the enum that the case belongs to.
+    #[inline]
+    pub fn enum_type(&self) -> EnumType {
+        self.enum_type
+    }
+
+    /// Returns the tag of the case.
+    #[inline]
+    pub fn tag(&self) -> u32 {
+        self.tag
+    }
+
+    /// Returns the name of the case, such as `some`.
+    #[inline]
+    pub fn name(&self) -> Cow<'static, str> {
+        self.field.name()
+    }
+
+    /// Returns the field record of the case.
+    #[inline]
+    pub fn field(&self) -> Field<'static> {
+        self.field
+    }
+
+    /// Returns whether the case is `indirect`, storing its payload in a box.
+    #[inline]
+    pub fn is_indirect(&self) -> bool {
+        self.field.is_indirect_case()
+    }
+
+    /// Returns whether the case has a payload.
+    #[inline]
+    pub fn has_payload(&self) -> bool {
+        self.field.mangled_type().is_some()
+    }
+
+    /// Returns the type of the case's payload, with the enum's generic
+    /// arguments substituted, or `None` if it has no payload or the type
+    /// can't be found.
+    ///
+    /// Cases with multiple associated values have a tuple as their payload.
+    pub fn payload_type(&self) -> Option<&'static MetaType> {
+        let mangled = self.field.mangled_type()?;
+        let descriptor = self.enum_type.descriptor()?;
+        let generic_args = self.enum_type.metadata.generic_args();
+        unsafe {
+            // Symbolic references in the name are relative to its original
+            // location, and generic parameters refer to the enum's key
+            // arguments, which are contiguous within its metadata.
+            let metadata = lookup::swift_getTypeByMangledNameInContext(
+                mangled.as_ptr() as *const c_char,
+                mangled.len(),
+                &descriptor.base as *const _ as *const ContextDescriptor,
+                generic_args.as_ptr() as *const *const c_void,
+            );
+            metadata.as_ref().map(MetaType::from_metadata)
+        }
+    }
+}
@@ -0,0 +1,238 @@
+//! The stored properties of structs and classes and the cases of enums.
+
+use crate::sym::{self, DemangleError, Node, NodeKind};
+use crate::sys::metadata::{
+    context::TypeContextDescriptor,
+    field::{FieldDescriptor, FieldRecord, FieldRecordFlags},
+};
+use std::{fmt, slice};
+
+/// How a field references its value.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub enum ReferenceStorage {
+    /// The field owns its value, which is the default.
+    Strong,
+    /// The field is `weak`.
+    Weak,
+    /// The field is `unowned`.
+    Unowned,
+    /// The field is `unowned(unsafe)`.
+    Unmanaged,
+}
+
+impl Default for ReferenceStorage {
+    #[inline]
+    fn default() -> Self {
+        Self::Strong
+    }
+}
+
+impl ReferenceStorage {
+    /// Returns the storage of the field whose type is mangled as
+    /// `mangled_type`, which ends with an operator such as `Xw` unless the
+    /// storage is strong.
+    pub fn of_mangled_type(mangled_type: &[u8]) -> Self {
+        // Track the last two bytes outside of symbolic references, which may
+        // contain any bytes.
+        let mut last = [0u8; 2];
+        let mut i = 0;
+        while let Some(&byte) = mangled_type.get(i) {
+            i += match byte {
+                0x01..=0x17 => {
+                    last = [0; 2];
+                    1 + 4
+                }
+                0x18..=0x1F => {
+                    last = [0; 2];
+                    1 + std::mem::size_of::<usize>()
+                }
+                _ => {
+                    last = [last[1], byte];
+                    1
+                }
+            };
+        }
+        match &last {
+            b"Xw" => Self::Weak,
+            b"Xo" => Self::Unowned,
+            b"Xu" => Self::Unmanaged,
+            _ => Self::Strong,
+        }
+    }
+}
+
+/// A stored property of a struct or class, or a case of an enum.
+#[derive(Clone, Copy)]
+pub struct Field<'a> {
+    record: &'a FieldRecord,
+}
+
+impl fmt::Debug for Field<'_> {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        let mut debug = f.debug_struct("Field");
+        debug.field("name", &self.name());
+        match self.type_name() {
+            Some(Ok(ty)) => debug.field("type", &ty),
+            _ => debug.field("mangled_type", &self.mangled_type()),
+        };
+        debug.field("flags", &self.flags()).finish()
+    }
+}
+
+impl<'a> Field<'a> {
+    /// Wraps the record of a field.
+    #[inline]
+    pub fn new(record: &'a FieldRecord) -> Self {
+        Self { record }
+    }
+
+    /// Returns the record of the field.
+    #[inline]
+    pub fn record(&self) -> &'a FieldRecord {
+        self.record
+    }
+
+    /// Returns the name of the field, replacing invalid UTF-8 with `�`.
+    #[inline]
+    pub fn name(&self) -> std::borrow::Cow<'a, str> {
+        self.record.name().to_string_lossy()
+    }
+
+    /// Returns the flags of the field.
+    #[inline]
+    pub fn flags(&self) -> FieldRecordFlags {
+        self.record.flags
+    }
+
+    /// Returns whether the field is a `var` rather than a `let`.
+    #[inline]
+    pub fn is_var(&self) -> bool {
+        self.flags().is_var()
+    }
+
+    /// Returns whether the field is an `indirect` enum case.
+    #[inline]
+    pub fn is_indirect_case(&self) -> bool {
+        self.flags().is_indirect_case()
+    }
+
+    /// Returns how the field references its value.
+    #[inline]
+    pub fn storage(&self) -> ReferenceStorage {
+        self.mangled_type()
+            .map(ReferenceStorage::of_mangled_type)
+            .unwrap_or_default()
+    }
+
+    /// Returns the mangled type of the field, or `None` if it's an enum case
+    /// without a payload.
+    #[inline]
+    pub fn mangled_type(&self) -> Option<&'a [u8]> {
+        self.record.mangled_type_name()
+    }
+
+    /// Parses the mangled type of the field into a tree of nodes, or returns
+    /// `None` if it's an enum case without a payload.
+    ///
+    /// Weak and unowned fields have their storage as the type's outermost
+    /// node, such as [`NodeKind::Weak`](../sym/enum.NodeKind.html#variant.Weak).
+    ///
+    /// Types that contain symbolic references, such as types declared in the
+    /// same image, fail to parse with an
+    /// [`UnresolvedSymbolicReference`](../sym/enum.DemangleErrorKind.html#variant.UnresolvedSymbolicReference)
+    /// error. Fields of loaded images can resolve them with
+    /// [`sym::parse_type_in_process`](../sym/fn.parse_type_in_process.html).
+    #[inline]
+    pub fn type_node(&self) -> Option<Result<Node, DemangleError>> {
+        self.mangled_type().map(sym::parse_type)
+    }
+
+    /// Demangles the type of the field, such as `Swift.Int`, or returns
+    /// `None` if it's an enum case without a payload.
+    ///
+    /// The storage of weak and unowned fields is excluded; see
+    /// [`storage`](#method.storage).
+    pub fn type_name(&self) -> Option<Result<String, DemangleError>> {
+        let node = match self.type_node()? {
+            Ok(node) => node,
+            Err(error) => return Some(Err(error)),
+        };
+        let mut ty = &node;
+        while let Some(inner) = ty
+            .child(0)
+            .filter(|_| is_storage(ty.kind()) || ty.kind() == NodeKind::Type)
+        {
+            ty = inner;
+        }
+        Some(Ok(ty.to_string()))
+    }
+}
+
+#[inline]
+fn is_storage(kind: NodeKind) -> bool {
+    matches!(
+        kind,
+        NodeKind::Weak | NodeKind::Unowned | NodeKind::Unmanaged
+    )
+}
+
+/// An iterator over the fields of a type.
+///
+/// Created by [`fields`](fn.fields.html).
+#[derive(Clone, Debug)]
+pub struct Fields<'a> {
+    records: slice::Iter<'a, FieldRecord>,
+}
+
+impl<'a> Fields<'a> {
+    /// Returns an iterator over the fields described by `descriptor`.
+    #[inline]
+    pub fn new(descriptor: &'a FieldDescriptor) -> Self {
+        Self {
+            records: descriptor.records().iter(),
+        }
+    }
+}
+
+impl<'a> Iterator for Fields<'a> {
+    type Item = Field<'a>;
+
+    #[inline]
+    fn next(&mut self) -> Option<Self::Item> {
+        self.records.next().map(Field::new)
+    }
+
+    #[inline]
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        self.records.size_hint()
+    }
+}
+
+impl ExactSizeIterator for Fields<'_> {}
+
+/// Returns an iterator over the stored properties or enum cases of the type
+/// described by `descriptor`, which is empty if the type has no reflection
+/// metadata.
+///
+/// The descriptor may be that of a type in the current process, such as from
+/// [`MetaType::descriptor`](../obj/struct.MetaType.html#method.descriptor),
+/// or one read from a binary that was mapped into memory with its original
+/// layout.
+///
+/// # Examples
+///
+/// ```no_run
+/// use swift_rt::{field, obj::MetaType};
+///
+/// let ty = MetaType::from_name("Swift.ClosedRange<Swift.Int>").unwrap();
+/// for field in field::fields(ty.descriptor().unwrap()) {
+///     println!("{}: {:?}", field.name(), field.type_name());
+/// }
+/// ```
+#[inline]
+pub fn fields(descriptor: &TypeContextDescriptor) -> Fields<'_> {
+    match descriptor.field_descriptor() {
+        Some(fields) => Fields::new(fields),
+        None => Fields { records: [].iter() },
+    }
+}
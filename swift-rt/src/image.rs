@@ -0,0 +1,239 @@
+//! The images loaded in the current process that contain Swift code.
+//!
+//! Images are found with `dl_iterate_phdr` and matched with the metadata
+//! sections that they register with the runtime when they're loaded.
+
+use crate::conformance::metadata_sections;
+use crate::sys::{
+    metadata::{
+        conformance::{
+            ProtocolConformanceDescriptor, ProtocolConformanceRecord,
+        },
+        context::{
+            ContextDescriptor, ModuleContextDescriptor, ProtocolDescriptor,
+            ProtocolRecord, TypeContextDescriptor, TypeMetadataRecord,
+        },
+    },
+    section::MetadataSections,
+};
+use std::{
+    ffi::{c_void, CStr, OsStr},
+    ops::Range,
+    os::{raw::c_int, unix::ffi::OsStrExt},
+    path::{Path, PathBuf},
+    ptr,
+};
+
+/// An object file loaded by the dynamic linker.
+struct Object {
+    path: Option<PathBuf>,
+    base_address: usize,
+    segments: Vec<Range<usize>>,
+}
+
+/// Returns the objects loaded by the dynamic linker, starting with the main
+/// program.
+fn objects() -> Vec<Object> {
+    unsafe extern "C" fn push(
+        info: *mut libc::dl_phdr_info,
+        _size: usize,
+        context: *mut c_void,
+    ) -> c_int {
+        let all = &mut *(context as *mut Vec<Object>);
+        let info = &*info;
+
+        let headers = if info.dlpi_phdr.is_null() {
+            &[]
+        } else {
+            std::slice::from_raw_parts(
+                info.dlpi_phdr,
+                usize::from(info.dlpi_phnum),
+            )
+        };
+        let base_address = info.dlpi_addr as usize;
+        let segments = headers
+            .iter()
+            .filter(|header| header.p_type == libc::PT_LOAD)
+            .map(|header| {
+                let start = base_address + header.p_vaddr as usize;
+                start..start + header.p_memsz as usize
+            })
+            .collect();
+
+        // The main program has an empty name.
+        let path = info
+            .dlpi_name
+            .as_ref()
+            .map(|name| CStr::from_ptr(name).to_bytes())
+            .filter(|name| !name.is_empty())
+            .map(|name| PathBuf::from(OsStr::from_bytes(name)))
+            .or_else(|| {
+                if all.is_empty() {
+                    std::env::current_exe().ok()
+                } else {
+                    None
+                }
+            });
+
+        all.push(Object {
+            path,
+            base_address,
+            segments,
+        });
+        0
+    }
+
+    let mut all = Vec::new();
+    unsafe {
+        libc::dl_iterate_phdr(
+            Some(push),
+            &mut all as *mut Vec<Object> as *mut c_void,
+        );
+    }
+    all
+}
+
+/// A loaded image that contains Swift code, such as the main program or a
+/// shared library.
+#[derive(Debug)]
+pub struct Image {
+    path: Option<PathBuf>,
+    base_address: usize,
+    segments: Vec<Range<usize>>,
+    sections: &'static MetadataSections,
+}
+
+impl Image {
+    /// Returns the path of the image, or `None` if it isn't known.
+    #[inline]
+    pub fn path(&self) -> Option<&Path> {
+        self.path.as_deref()
+    }
+
+    /// Returns the address at which the image was loaded, which is added to
+    /// the addresses in its file.
+    #[inline]
+    pub fn base_address(&self) -> usize {
+        self.base_address
+    }
+
+    /// Returns the ranges of addresses of the segments of the image.
+    #[inline]
+    pub fn segments(&self) -> &[Range<usize>] {
+        &self.segments
+    }
+
+    /// Returns whether `addr` is within a segment of the image.
+    #[inline]
+    pub fn contains(&self, addr: *const c_void) -> bool {
+        let addr = addr as usize;
+        self.segments.iter().any(|segment| segment.contains(&addr))
+    }
+
+    /// Returns the metadata sections that the image registered with the
+    /// runtime.
+    #[inline]
+    pub fn sections(&self) -> &'static MetadataSections {
+        self.sections
+    }
+
+    /// Returns an iterator over the descriptors of the nominal types declared
+    /// by the image.
+    pub fn types(
+        &self,
+    ) -> impl Iterator<Item = &'static TypeContextDescriptor> {
+        let records: &[TypeMetadataRecord] =
+            unsafe { self.sections.swift5_type_metadata.as_slice() };
+        records.iter().filter_map(TypeMetadataRecord::descriptor)
+    }
+
+    /// Returns an iterator over the protocols declared by the image.
+    pub fn protocols(
+        &self,
+    ) -> impl Iterator<Item = &'static ProtocolDescriptor> {
+        let records: &[ProtocolRecord] =
+            unsafe { self.sections.swift5_protocols.as_slice() };
+        records.iter().filter_map(ProtocolRecord::protocol)
+    }
+
+    /// Returns an iterator over the protocol conformances declared by the
+    /// image.
+    pub fn conformances(
+        &self,
+    ) -> impl Iterator<Item = &'static ProtocolConformanceDescriptor> {
+        let records: &[ProtocolConformanceRecord] =
+            unsafe { self.sections.swift5_protocol_conformances.as_slice() };
+        records
+            .iter()
+            .filter_map(ProtocolConformanceRecord::descriptor)
+    }
+
+    /// Returns the modules whose types and protocols are declared by the
+    /// image, in the order they're first found.
+    ///
+    /// An image usually contains a single module, but may contain more, such
+    /// as when Clang modules are imported.
+    pub fn modules(&self) -> Vec<&'static ModuleContextDescriptor> {
+        let types = self.types().map(|ty| &**ty);
+        let protocols = self.protocols().map(|p| &**p);
+
+        let mut modules: Vec<&'static ModuleContextDescriptor> = Vec::new();
+        for module in
+            types.chain(protocols).filter_map(ContextDescriptor::module)
+        {
+            // Module descriptors may be duplicated across images, but are
+            // unique within one.
+            if !modules.iter().any(|&m| ptr::eq(m, module)) {
+                modules.push(module);
+            }
+        }
+        modules
+    }
+}
+
+/// Returns the images in the current process that have registered their
+/// Swift metadata with the runtime.
+///
+/// # Examples
+///
+/// Listing every Swift type in the process:
+///
+/// ```no_run
+/// use swift_rt::image;
+///
+/// for image in image::images() {
+///     println!("{:?}", image.path());
+///     for module in image.modules() {
+///         println!("  module {:?}", module.name());
+///     }
+///     for ty in image.types() {
+///         println!("  {:?}", ty.name());
+///     }
+/// }
+/// ```
+pub fn images() -> impl Iterator<Item = Image> {
+    let objects = objects();
+    metadata_sections().into_iter().map(move |sections| {
+        let base = sections.base_address as usize;
+        let object = objects.iter().find(|object| {
+            object
+                .segments
+                .iter()
+                .any(|segment| segment.contains(&base))
+        });
+        match object {
+            Some(object) => Image {
+                path: object.path.clone(),
+                base_address: object.base_address,
+                segments: object.segments.clone(),
+                sections,
+            },
+            None => Image {
+                path: None,
+                base_address: base,
+                segments: Vec::new(),
+                sections,
+            },
+        }
+    })
+}
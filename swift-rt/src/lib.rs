@@ -6,5 +6,14 @@
 
 extern crate swift_sys as sys;
 
+#[cfg(any(target_os = "linux", target_os = "android"))]
+pub mod conformance;
+pub mod enum_type;
+pub mod field;
+#[cfg(any(target_os = "linux", target_os = "android"))]
+pub mod image;
+pub mod metadata;
 pub mod obj;
+pub mod optional;
 pub mod sym;
+pub mod value;
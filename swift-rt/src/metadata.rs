@@ -0,0 +1,226 @@
+//! Typed views of type metadata and the memory layout of types.
+
+use crate::{
+    obj::MetaType,
+    sys::metadata::{
+        value_witness::ValueWitnessTable, ClassMetadata, EnumMetadata,
+        ExistentialMetatypeMetadata, ExistentialTypeMetadata,
+        ForeignClassMetadata, FunctionTypeMetadata, Metadata, MetadataKind,
+        MetatypeMetadata, StructMetadata, TupleTypeMetadata,
+    },
+};
+use std::alloc::Layout;
+
+/// The size, stride and alignment of a type, as in Swift's `MemoryLayout`.
+///
+/// # Examples
+///
+/// Allocating a buffer for an array of values:
+///
+/// ```no_run
+/// use swift_rt::{metadata::MemoryLayout, obj::MetaType};
+/// use std::alloc;
+///
+/// let ty = MetaType::from_name("Swift.String").unwrap();
+/// let layout = MemoryLayout::of(ty).array(16).unwrap();
+/// let buffer = unsafe { alloc::alloc(layout) };
+/// # unsafe { alloc::dealloc(buffer, layout) };
+/// ```
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub struct MemoryLayout {
+    /// The contiguous memory footprint of a value in bytes, excluding
+    /// trailing padding.
+    pub size: usize,
+    /// The number of bytes between the starts of consecutive values in an
+    /// array, which is at least 1.
+    pub stride: usize,
+    /// The required alignment of values in bytes.
+    pub alignment: usize,
+}
+
+impl MemoryLayout {
+    /// Returns the memory layout of values of `ty`.
+    #[inline]
+    pub fn of(ty: &MetaType) -> Self {
+        let witnesses = ty.value_witnesses();
+        Self {
+            size: witnesses.size,
+            stride: witnesses.stride,
+            alignment: witnesses.flags.alignment(),
+        }
+    }
+
+    /// Returns the layout for allocating a single value.
+    ///
+    /// The size of zero-sized types is rounded up to their stride, so that
+    /// the layout can be passed to a global allocator.
+    #[inline]
+    pub fn layout(&self) -> Layout {
+        let size = if self.size == 0 {
+            self.stride
+        } else {
+            self.size
+        };
+        Layout::from_size_align(size, self.alignment)
+            .expect("type metadata has an invalid layout")
+    }
+
+    /// Returns the layout for allocating `count` contiguous values, or `None`
+    /// if its size overflows.
+    #[inline]
+    pub fn array(&self, count: usize) -> Option<Layout> {
+        let size = self.stride.checked_mul(count)?;
+        Layout::from_size_align(size, self.alignment).ok()
+    }
+}
+
+/// The metadata of a type, downcast by its kind.
+///
+/// Created by [`MetaType::view`](../obj/struct.MetaType.html#method.view).
+///
+/// # Examples
+///
+/// ```no_run
+/// use swift_rt::{metadata::MetadataView, obj::MetaType};
+///
+/// let ty = MetaType::from_mangled_name("Si_SSt", &[]).unwrap();
+/// if let MetadataView::Tuple(tuple) = ty.view() {
+///     for element in tuple.elements() {
+///         let element_type = MetaType::from_metadata(element.metadata());
+///         println!("{} at {}", element_type.name(true), element.offset());
+///     }
+/// }
+/// ```
+#[derive(Clone, Copy, Debug)]
+pub enum MetadataView<'a> {
+    /// A struct.
+    Struct(&'a StructMetadata),
+    /// An enum other than `Optional`.
+    Enum(&'a EnumMetadata),
+    /// An `Optional`.
+    Optional(&'a EnumMetadata),
+    /// A Swift class.
+    Class(&'a ClassMetadata),
+    /// A foreign class, such as a Core Foundation class.
+    ForeignClass(&'a ForeignClassMetadata),
+    /// A tuple.
+    Tuple(&'a TupleTypeMetadata),
+    /// A function type.
+    Function(&'a FunctionTypeMetadata),
+    /// An existential type, such as `Any`.
+    Existential(&'a ExistentialTypeMetadata),
+    /// A metatype, such as `Int.Type`.
+    Metatype(&'a MetatypeMetadata),
+    /// An existential metatype, such as `Any.Type`.
+    ExistentialMetatype(&'a ExistentialMetatypeMetadata),
+    /// Any other kind of type, such as an Objective-C class or an opaque
+    /// builtin type.
+    Other(&'a Metadata),
+}
+
+impl<'a> MetadataView<'a> {
+    /// Returns the metadata viewed by `self` without its kind-specific
+    /// fields.
+    pub fn metadata(&self) -> &'a Metadata {
+        match *self {
+            Self::Struct(metadata) => metadata,
+            Self::Enum(metadata) | Self::Optional(metadata) => metadata,
+            Self::Class(metadata) => metadata,
+            Self::ForeignClass(metadata) => metadata,
+            Self::Tuple(metadata) => metadata,
+            Self::Function(metadata) => metadata,
+            Self::Existential(metadata) => metadata,
+            Self::Metatype(metadata) => metadata,
+            Self::ExistentialMetatype(metadata) => metadata,
+            Self::Other(metadata) => metadata,
+        }
+    }
+}
+
+impl MetaType {
+    /// Returns the type whose metadata is `metadata`, such as the type of a
+    /// tuple element.
+    ///
+    /// This is safe because `Metadata` can't be created in Rust, so any
+    /// reference to it refers to metadata emitted by Swift.
+    #[inline]
+    pub fn from_metadata(metadata: &Metadata) -> &Self {
+        unsafe { Self::from_ptr(metadata) }
+    }
+
+    /// Returns the metadata of the type.
+    #[inline]
+    pub fn metadata(&self) -> &Metadata {
+        unsafe { &*self.as_ptr() }
+    }
+
+    /// Returns the kind of the type's metadata.
+    #[inline]
+    pub fn kind(&self) -> MetadataKind {
+        self.metadata().kind()
+    }
+
+    /// Returns the value witness table of the type, which describes its
+    /// layout and how its values are copied and destroyed.
+    #[inline]
+    pub fn value_witnesses(&self) -> &ValueWitnessTable {
+        self.metadata().value_witnesses()
+    }
+
+    /// Returns the size, stride and alignment of the type.
+    #[inline]
+    pub fn memory_layout(&self) -> MemoryLayout {
+        MemoryLayout::of(self)
+    }
+
+    /// Returns the size of values of the type in bytes, as in
+    /// `MemoryLayout<T>.size`.
+    #[inline]
+    pub fn size(&self) -> usize {
+        self.value_witnesses().size
+    }
+
+    /// Returns the distance in bytes between values of the type in an array,
+    /// as in `MemoryLayout<T>.stride`.
+    #[inline]
+    pub fn stride(&self) -> usize {
+        self.value_witnesses().stride
+    }
+
+    /// Returns the required alignment of values of the type in bytes, as in
+    /// `MemoryLayout<T>.alignment`.
+    #[inline]
+    pub fn alignment(&self) -> usize {
+        self.value_witnesses().flags.alignment()
+    }
+
+    /// Returns the metadata of the type, downcast by its kind.
+    pub fn view(&self) -> MetadataView<'_> {
+        let metadata = self.metadata();
+        if let Some(value) = metadata.as_struct() {
+            MetadataView::Struct(value)
+        } else if let Some(value) = metadata.as_enum() {
+            if metadata.kind() == MetadataKind::OPTIONAL {
+                MetadataView::Optional(value)
+            } else {
+                MetadataView::Enum(value)
+            }
+        } else if let Some(class) = metadata.as_class() {
+            MetadataView::Class(class)
+        } else if let Some(class) = metadata.as_foreign_class() {
+            MetadataView::ForeignClass(class)
+        } else if let Some(tuple) = metadata.as_tuple() {
+            MetadataView::Tuple(tuple)
+        } else if let Some(function) = metadata.as_function() {
+            MetadataView::Function(function)
+        } else if let Some(existential) = metadata.as_existential() {
+            MetadataView::Existential(existential)
+        } else if let Some(metatype) = metadata.as_metatype() {
+            MetadataView::Metatype(metatype)
+        } else if let Some(metatype) = metadata.as_existential_metatype() {
+            MetadataView::ExistentialMetatype(metatype)
+        } else {
+            MetadataView::Other(metadata)
+        }
+    }
+}
@@ -1,7 +1,21 @@
 //! Swift objects.
 
-use crate::sys::heap::{self, HeapObject, Metadata, WeakReference};
-use std::{fmt, os::raw::c_int, ptr::NonNull};
+use crate::{
+    sym::{Context, Mangler, NominalKind, Type},
+    sys::{
+        heap::{self, HeapObject, Metadata, WeakReference},
+        metadata::{
+            context::{self, GenericParamDescriptor, TypeContextDescriptor},
+            lookup::{self, GenericEnvironment, GenericEnvironmentFlags},
+        },
+    },
+};
+use std::{
+    convert::TryFrom,
+    fmt,
+    os::raw::{c_char, c_int, c_void},
+    ptr::{self, NonNull},
+};
 
 /// An object that may be owned or unowned.
 #[repr(transparent)]
@@ -35,6 +49,15 @@ impl Object {
     pub fn weak_retain_count(&self) -> usize {
         unsafe { heap::swift_weakRetainCount(self.as_ptr()) }
     }
+
+    /// Returns the type of `self`, as in `type(of:)`.
+    #[inline]
+    pub fn meta_type(&self) -> &MetaType {
+        unsafe {
+            let metadata: &Metadata = &*self.0.metadata;
+            MetaType::from_ptr(metadata)
+        }
+    }
 }
 
 /// An owned object.
@@ -347,8 +370,14 @@ impl Weak {
 }
 
 /// The metadata of some type; synonymous with `Any.Type`.
+#[repr(transparent)]
 pub struct MetaType(Metadata);
 
+// Safe because type metadata is immutable once it's been returned by the
+// runtime.
+unsafe impl Send for MetaType {}
+unsafe impl Sync for MetaType {}
+
 impl fmt::Debug for MetaType {
     #[inline]
     fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
@@ -359,6 +388,100 @@ impl fmt::Debug for MetaType {
 }
 
 impl MetaType {
+    /// Returns the metadata referenced by `ptr`.
+    ///
+    /// # Safety
+    ///
+    /// `ptr` must reference valid type metadata that lives for `'a`.
+    #[inline]
+    pub unsafe fn from_ptr<'a>(ptr: *const Metadata) -> &'a Self {
+        &*(ptr as *const Self)
+    }
+
+    /// Returns `self` as a `*const` pointer suitable for FFI.
+    #[inline]
+    pub const fn as_ptr(&self) -> *const Metadata {
+        self as *const Self as *const Metadata
+    }
+
+    /// Looks up the type with the mangled name `name`, such as `Si` or
+    /// `SaySSG`, which doesn't include a symbol prefix.
+    ///
+    /// Generic parameters referenced by `name` are substituted by
+    /// `generic_args` in order, where `x` refers to the first argument and
+    /// `q_` to the second. Names may be built from a
+    /// [`Type`](../sym/enum.Type.html) via
+    /// [`Mangler::mangle_type`](../sym/struct.Mangler.html#method.mangle_type).
+    ///
+    /// Returns `None` if the type isn't found or if `name` references generic
+    /// parameters not in `generic_args`.
+    ///
+    /// # Examples
+    ///
+    /// ```no_run
+    /// use swift_rt::obj::MetaType;
+    ///
+    /// let int = MetaType::from_mangled_name("Si", &[]).unwrap();
+    /// let array = MetaType::from_mangled_name("SayxG", &[int]).unwrap();
+    /// assert_eq!(array.name(true), "Swift.Array<Swift.Int>");
+    /// ```
+    pub fn from_mangled_name(
+        name: &str,
+        generic_args: &[&MetaType],
+    ) -> Option<&'static MetaType> {
+        // `&MetaType` has the same representation as `*const Metadata`.
+        let args = generic_args.as_ptr() as *const *const c_void;
+        let name_ptr = name.as_ptr() as *const c_char;
+
+        let metadata = if generic_args.is_empty() {
+            unsafe {
+                lookup::swift_getTypeByMangledNameInContext(
+                    name_ptr,
+                    name.len(),
+                    ptr::null(),
+                    ptr::null(),
+                )
+            }
+        } else {
+            let env = generic_environment(generic_args.len())?;
+            unsafe {
+                lookup::swift_getTypeByMangledNameInEnvironment(
+                    name_ptr,
+                    name.len(),
+                    env.as_ptr() as *const GenericEnvironment,
+                    args,
+                )
+            }
+        };
+
+        if metadata.is_null() {
+            None
+        } else {
+            Some(unsafe { Self::from_ptr(metadata) })
+        }
+    }
+
+    /// Looks up the type with the fully qualified name `name`, such as
+    /// `Swift.Array<Swift.Int>`, similar to `_typeByName` in Swift.
+    ///
+    /// This accepts the names returned by [`name`](#method.name) for structs,
+    /// enums and classes, including nested types and generic arguments.
+    /// Returns `None` if the type isn't found or if `name` refers to another
+    /// kind of type, such as a tuple or function.
+    ///
+    /// # Examples
+    ///
+    /// ```no_run
+    /// use swift_rt::obj::MetaType;
+    ///
+    /// let ty = MetaType::from_name("Swift.Dictionary<Swift.String, Swift.Int>");
+    /// assert!(ty.is_some());
+    /// ```
+    pub fn from_name(name: &str) -> Option<&'static MetaType> {
+        let mut mangler = Mangler::new();
+        resolve_type_name(name.trim(), &mut mangler).map(|(ty, _)| ty)
+    }
+
     /// Returns the name of the underlying type.
     #[inline]
     pub fn name(&self, qualified: bool) -> &str {
@@ -367,4 +490,123 @@ impl MetaType {
             std::str::from_utf8_unchecked(name.into_bytes())
         }
     }
+
+    /// Returns the descriptor of the underlying nominal type, or `None` if it
+    /// isn't a struct, enum or class.
+    ///
+    /// The descriptor's [`field_descriptor`] lists the type's stored
+    /// properties or enum cases, as does [`field::fields`].
+    ///
+    /// [`field_descriptor`]: ../../swift_sys/metadata/context/struct.TypeContextDescriptor.html#method.field_descriptor
+    /// [`field::fields`]: ../field/fn.fields.html
+    #[inline]
+    pub fn descriptor(&self) -> Option<&'static TypeContextDescriptor> {
+        unsafe { context::swift_getTypeContextDescriptor(&self.0).as_ref() }
+    }
+}
+
+/// Returns a generic environment with `count` type parameters at depth 0 and
+/// no requirements, stored in words to ensure its alignment.
+fn generic_environment(count: usize) -> Option<Vec<u32>> {
+    let count = u16::try_from(count).ok()?;
+
+    // The flags are followed by the parameter count and a descriptor per
+    // parameter, each marking a key argument.
+    let mut bytes = Vec::with_capacity(2 + usize::from(count));
+    bytes.extend_from_slice(&count.to_ne_bytes());
+    bytes.resize(bytes.capacity(), GenericParamDescriptor::KEY_ARGUMENT);
+
+    let mut env = vec![GenericEnvironmentFlags::new(1, 0).0];
+    env.extend(bytes.chunks(4).map(|chunk| {
+        let mut word = [0; 4];
+        word[..chunk.len()].copy_from_slice(chunk);
+        u32::from_ne_bytes(word)
+    }));
+    Some(env)
+}
+
+/// Resolves the fully qualified type name `name`, returning its metadata along
+/// with the type description that it was found by.
+fn resolve_type_name(
+    name: &str,
+    mangler: &mut Mangler,
+) -> Option<(&'static MetaType, Type)> {
+    let (path, args) = match name.find('<') {
+        Some(start) => {
+            let args = name[(start + 1)..].strip_suffix('>')?;
+            (&name[..start], split_generic_args(args)?)
+        }
+        None => (name, Vec::new()),
+    };
+
+    let mut components = path.split('.');
+    let module = components.next().filter(|m| is_identifier(m))?;
+    let names: Vec<&str> = components.collect();
+    if names.is_empty() || !names.iter().all(|name| is_identifier(name)) {
+        return None;
+    }
+
+    let mut arg_types = Vec::with_capacity(args.len());
+    for arg in args {
+        let (_, ty) = resolve_type_name(arg, mangler)?;
+        arg_types.push(ty);
+    }
+
+    // The name doesn't say what kind of type each component is, so each one
+    // is found in turn by trying every kind. Only the innermost type may have
+    // generic arguments, so enclosing types are found without any.
+    const KINDS: [NominalKind; 3] =
+        [NominalKind::Struct, NominalKind::Enum, NominalKind::Class];
+    let mut lookup = |ty: &Type| {
+        let mangled = mangler.mangle_type(ty).ok()?;
+        MetaType::from_mangled_name(&mangled, &[])
+    };
+
+    let (innermost, parents) = names.split_last()?;
+    let mut context = Context::module(module);
+    for name in parents {
+        context = KINDS.iter().find_map(|&kind| {
+            let parent = context.clone().nested(kind, *name);
+            lookup(&Type::Nominal(parent.clone()))?;
+            Some(parent)
+        })?;
+    }
+    KINDS.iter().find_map(|&kind| {
+        let ty = Type::Nominal(
+            context
+                .clone()
+                .nested(kind, *innermost)
+                .generic_args(arg_types.clone()),
+        );
+        Some((lookup(&ty)?, ty))
+    })
+}
+
+/// Splits the comma-separated generic arguments in `args` that aren't nested
+/// within other arguments.
+fn split_generic_args(args: &str) -> Option<Vec<&str>> {
+    let mut result = Vec::new();
+    let mut depth = 0usize;
+    let mut start = 0;
+    for (i, ch) in args.char_indices() {
+        match ch {
+            '<' => depth += 1,
+            '>' => depth = depth.checked_sub(1)?,
+            ',' if depth == 0 => {
+                result.push(args[start..i].trim());
+                start = i + 1;
+            }
+            _ => {}
+        }
+    }
+    if depth != 0 {
+        return None;
+    }
+    result.push(args[start..].trim());
+    Some(result)
+}
+
+#[inline]
+fn is_identifier(name: &str) -> bool {
+    !name.is_empty() && name.chars().all(|ch| ch == '_' || ch.is_alphanumeric())
 }
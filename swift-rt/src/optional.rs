@@ -0,0 +1,670 @@
+//! The layout of `Optional` and other enums with a single payload case.
+//!
+//! An enum with one payload case and `n` empty cases, like `Optional`, stores
+//! the empty cases in the extra inhabitants of its payload: bit patterns that
+//! aren't valid values, such as a null pointer. Cases that don't fit are
+//! distinguished by extra tag bytes after the payload.
+
+// Based on:
+// include/swift/Runtime/EnumImpl.h
+// stdlib/public/runtime/Enum.cpp
+
+use crate::{
+    metadata::MemoryLayout,
+    obj::{MetaType, Owned},
+    sys::metadata::MetadataKind,
+    value::OpaqueSwiftValue,
+};
+use std::{
+    fmt,
+    mem::{self, ManuallyDrop, MaybeUninit},
+    os::raw::c_void,
+    ptr::{self, NonNull},
+};
+
+/// An enum with a single payload case of a type known at runtime, followed
+/// by a number of empty cases.
+///
+/// # Examples
+///
+/// Computing the layout of `Int?`:
+///
+/// ```no_run
+/// use swift_rt::{obj::MetaType, optional::SinglePayloadEnum};
+///
+/// let int = MetaType::from_name("Swift.Int").unwrap();
+/// let layout = SinglePayloadEnum::optional(int).memory_layout();
+/// assert_eq!(layout.size, 9);
+/// assert_eq!(layout.stride, 16);
+/// ```
+#[derive(Clone, Copy, Debug)]
+pub struct SinglePayloadEnum {
+    payload: &'static MetaType,
+    empty_cases: u32,
+}
+
+impl SinglePayloadEnum {
+    /// Returns the enum with a payload of `payload` and `empty_cases` cases
+    /// without a payload.
+    #[inline]
+    pub fn new(payload: &'static MetaType, empty_cases: u32) -> Self {
+        Self {
+            payload,
+            empty_cases,
+        }
+    }
+
+    /// Returns the layout of `Optional<Wrapped>`, whose only empty case is
+    /// `none`.
+    #[inline]
+    pub fn optional(wrapped: &'static MetaType) -> Self {
+        Self::new(wrapped, 1)
+    }
+
+    /// Returns the type of the payload.
+    #[inline]
+    pub fn payload_type(&self) -> &'static MetaType {
+        self.payload
+    }
+
+    /// Returns the number of cases without a payload.
+    #[inline]
+    pub fn empty_cases(&self) -> u32 {
+        self.empty_cases
+    }
+
+    /// Returns the number of bytes after the payload that distinguish the
+    /// empty cases that don't fit in the payload's extra inhabitants.
+    #[inline]
+    pub fn extra_tag_bytes(&self) -> usize {
+        extra_tag_bytes(
+            self.payload.size(),
+            self.payload.extra_inhabitant_count(),
+            self.empty_cases,
+        )
+    }
+
+    /// Returns the number of extra inhabitants left over for enums that
+    /// contain this one.
+    #[inline]
+    pub fn extra_inhabitant_count(&self) -> u32 {
+        self.payload
+            .extra_inhabitant_count()
+            .saturating_sub(self.empty_cases)
+    }
+
+    /// Returns the size, stride and alignment of the enum.
+    pub fn memory_layout(&self) -> MemoryLayout {
+        let payload = self.payload.memory_layout();
+        let size = payload.size + self.extra_tag_bytes();
+        let align_mask = payload.alignment - 1;
+        MemoryLayout {
+            size,
+            stride: ((size + align_mask) & !align_mask).max(1),
+            alignment: payload.alignment,
+        }
+    }
+
+    /// Returns the index of the empty case of the enum value at `value`, or
+    /// `None` if it's the payload case.
+    ///
+    /// # Safety
+    ///
+    /// `value` must point to an initialized value of the enum.
+    #[inline]
+    pub unsafe fn empty_case(&self, value: *const c_void) -> Option<u32> {
+        let witnesses = self.payload.value_witnesses();
+        let tag = (witnesses.get_enum_tag_single_payload)(
+            value,
+            self.empty_cases,
+            self.payload.as_ptr() as *mut c_void,
+        );
+        tag.checked_sub(1)
+    }
+
+    /// Initializes the enum value at `value` to the empty case at `index`.
+    ///
+    /// # Safety
+    ///
+    /// `value` must be valid for writes of the enum's size and must not hold
+    /// an initialized value.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `index` isn't less than the number of empty cases.
+    #[inline]
+    pub unsafe fn store_empty_case(&self, value: *mut c_void, index: u32) {
+        assert!(index < self.empty_cases, "empty case index out of range");
+        self.store_tag(value, index + 1);
+    }
+
+    /// Completes the enum value at `value` as the payload case, clearing any
+    /// extra tag bytes.
+    ///
+    /// # Safety
+    ///
+    /// `value` must be valid for writes of the enum's size and must start
+    /// with an initialized payload.
+    #[inline]
+    pub unsafe fn store_payload_case(&self, value: *mut c_void) {
+        self.store_tag(value, 0);
+    }
+
+    #[inline]
+    unsafe fn store_tag(&self, value: *mut c_void, tag: u32) {
+        let witnesses = self.payload.value_witnesses();
+        (witnesses.store_enum_tag_single_payload)(
+            value,
+            tag,
+            self.empty_cases,
+            self.payload.as_ptr() as *mut c_void,
+        );
+    }
+}
+
+/// Returns the number of extra tag bytes of a single payload enum whose
+/// payload has `payload_size` bytes and `extra_inhabitants` extra
+/// inhabitants.
+fn extra_tag_bytes(
+    payload_size: usize,
+    extra_inhabitants: u32,
+    empty_cases: u32,
+) -> usize {
+    if empty_cases <= extra_inhabitants {
+        return 0;
+    }
+
+    // Empty cases beyond the extra inhabitants are stored in the payload with
+    // a non-zero tag, so a payload of 4 bytes or more fits them all.
+    let cases = u64::from(empty_cases - extra_inhabitants);
+    let tags = if payload_size >= 4 {
+        2
+    } else {
+        let bits = payload_size * 8;
+        1 + ((cases + (1 << bits) - 1) >> bits)
+    };
+    match tags {
+        0..=1 => 0,
+        2..=255 => 1,
+        256..=65535 => 2,
+        _ => 4,
+    }
+}
+
+impl MetaType {
+    /// Returns the number of bit patterns of the type that aren't valid
+    /// values, which enums use to store cases without a payload.
+    #[inline]
+    pub fn extra_inhabitant_count(&self) -> u32 {
+        self.value_witnesses().extra_inhabitant_count
+    }
+
+    /// Returns the type `Optional<Self>`.
+    #[inline]
+    pub fn optional(&self) -> Option<&'static MetaType> {
+        MetaType::from_mangled_name("xSg", &[self])
+    }
+}
+
+/// An owned `Optional` whose payload type is only known at runtime.
+///
+/// # Examples
+///
+/// ```no_run
+/// use swift_rt::{optional::OpaqueOptional, value::OpaqueSwiftValue};
+///
+/// # fn get_value() -> OpaqueSwiftValue { unimplemented!() }
+/// let value: OpaqueSwiftValue = get_value();
+/// let optional = OpaqueOptional::some(value);
+/// assert!(optional.is_some());
+///
+/// let none = OpaqueOptional::none(optional.payload_type());
+/// assert!(none.into_option().is_none());
+/// ```
+#[derive(Clone)]
+pub struct OpaqueOptional {
+    payload: &'static MetaType,
+    value: OpaqueSwiftValue,
+}
+
+impl fmt::Debug for OpaqueOptional {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.debug_struct("OpaqueOptional")
+            .field("payload_type", &self.payload.name(true))
+            .field("is_some", &self.is_some())
+            .finish()
+    }
+}
+
+impl OpaqueOptional {
+    /// Returns `nil` as an optional of `payload`.
+    pub fn none(payload: &'static MetaType) -> Self {
+        let ty = payload.optional().expect("missing Optional metadata");
+        unsafe {
+            let mut value = OpaqueSwiftValue::uninit(ty);
+            SinglePayloadEnum::optional(payload)
+                .store_empty_case(value.as_mut_ptr(), 0);
+            Self { payload, value }
+        }
+    }
+
+    /// Wraps `payload` in an optional.
+    pub fn some(payload: OpaqueSwiftValue) -> Self {
+        let payload_type = payload.meta_type();
+        let ty = payload_type.optional().expect("missing Optional metadata");
+        unsafe {
+            let mut value = OpaqueSwiftValue::uninit(ty);
+            payload.move_into(value.as_mut_ptr());
+            SinglePayloadEnum::optional(payload_type)
+                .store_payload_case(value.as_mut_ptr());
+            Self {
+                payload: payload_type,
+                value,
+            }
+        }
+    }
+
+    /// Returns `value` as an optional, or gives it back if it isn't of an
+    /// `Optional` type.
+    pub fn from_value(
+        value: OpaqueSwiftValue,
+    ) -> Result<Self, OpaqueSwiftValue> {
+        let metadata = value.meta_type().metadata();
+        let payload = match metadata.as_enum() {
+            Some(optional) if metadata.kind() == MetadataKind::OPTIONAL => {
+                optional.generic_args().first().copied()
+            }
+            _ => None,
+        };
+        match payload {
+            Some(payload) => Ok(Self {
+                payload: MetaType::from_metadata(payload),
+                value,
+            }),
+            None => Err(value),
+        }
+    }
+
+    /// Returns the type of the payload.
+    #[inline]
+    pub fn payload_type(&self) -> &'static MetaType {
+        self.payload
+    }
+
+    /// Returns whether the optional holds a payload.
+    #[inline]
+    pub fn is_some(&self) -> bool {
+        let layout = SinglePayloadEnum::optional(self.payload);
+        unsafe { layout.empty_case(self.value.as_ptr()).is_none() }
+    }
+
+    /// Returns whether the optional is `nil`.
+    #[inline]
+    pub fn is_none(&self) -> bool {
+        !self.is_some()
+    }
+
+    /// Returns a copy of the payload, or `None` if the optional is `nil`.
+    #[inline]
+    pub fn get(&self) -> Option<OpaqueSwiftValue> {
+        if self.is_some() {
+            // The payload is at the start of the optional.
+            Some(unsafe {
+                OpaqueSwiftValue::copy_from(self.payload, self.value.as_ptr())
+            })
+        } else {
+            None
+        }
+    }
+
+    /// Returns the payload, or `None` if the optional is `nil`.
+    pub fn into_option(self) -> Option<OpaqueSwiftValue> {
+        if self.is_none() {
+            return None;
+        }
+        let mut value = self.value;
+        unsafe {
+            let payload =
+                OpaqueSwiftValue::take_from(self.payload, value.as_mut_ptr());
+            value.forget();
+            Some(payload)
+        }
+    }
+
+    /// Returns the optional as a value of type `Optional<Wrapped>`.
+    #[inline]
+    pub fn as_value(&self) -> &OpaqueSwiftValue {
+        &self.value
+    }
+
+    /// Returns the optional as a value of type `Optional<Wrapped>`.
+    #[inline]
+    pub fn into_value(self) -> OpaqueSwiftValue {
+        self.value
+    }
+}
+
+/// A type whose `Optional` has a layout that's known at compile time.
+///
+/// # Safety
+///
+/// `Self` must have the layout of a Swift type, and `Optional<Self>` must have
+/// the same representation as `Optional` of that type.
+pub unsafe trait OptionalPayload: Sized {
+    /// The extra tag byte after the payload, or `()` if `nil` is stored in an
+    /// extra inhabitant of the payload.
+    type Tag: Copy;
+
+    /// Returns the payload and tag of `nil`.
+    fn none() -> (MaybeUninit<Self>, Self::Tag);
+
+    /// Returns the tag of values that hold a payload.
+    fn some_tag() -> Self::Tag;
+
+    /// Returns whether `payload` and `tag` are `nil`.
+    fn is_none(payload: &MaybeUninit<Self>, tag: Self::Tag) -> bool;
+}
+
+/// Implements `OptionalPayload` for types without extra inhabitants, whose
+/// `nil` has a zeroed payload and a tag of 1.
+macro_rules! tagged_payload {
+    ($($ty:ty),+ $(,)?) => {$(
+        unsafe impl OptionalPayload for $ty {
+            type Tag = u8;
+
+            #[inline]
+            fn none() -> (MaybeUninit<Self>, u8) {
+                (MaybeUninit::zeroed(), 1)
+            }
+
+            #[inline]
+            fn some_tag() -> u8 {
+                0
+            }
+
+            #[inline]
+            fn is_none(_: &MaybeUninit<Self>, tag: u8) -> bool {
+                tag != 0
+            }
+        }
+    )+};
+}
+
+tagged_payload! {
+    (), i8, i16, i32, i64, isize, u8, u16, u32, u64, usize, f32, f64,
+}
+
+/// Implements `OptionalPayload` for non-null pointers, whose `nil` is null.
+macro_rules! pointer_payload {
+    ($(impl$([$($params:tt)*])? for $ty:ty;)+) => {$(
+        unsafe impl$(<$($params)*>)? OptionalPayload for $ty {
+            type Tag = ();
+
+            #[inline]
+            fn none() -> (MaybeUninit<Self>, ()) {
+                (MaybeUninit::zeroed(), ())
+            }
+
+            #[inline]
+            fn some_tag() {}
+
+            #[inline]
+            fn is_none(payload: &MaybeUninit<Self>, _: ()) -> bool {
+                let ptr = payload.as_ptr() as *const *const c_void;
+                unsafe { (*ptr).is_null() }
+            }
+        }
+    )+};
+}
+
+pointer_payload! {
+    impl['a, T] for &'a T;
+    impl['a, T] for &'a mut T;
+    impl[T] for NonNull<T>;
+    impl for Owned;
+}
+
+/// `Bool` has 254 extra inhabitants, the first of which is `nil`.
+unsafe impl OptionalPayload for bool {
+    type Tag = ();
+
+    #[inline]
+    fn none() -> (MaybeUninit<Self>, ()) {
+        let mut payload = MaybeUninit::<bool>::uninit();
+        unsafe { (payload.as_mut_ptr() as *mut u8).write(2) };
+        (payload, ())
+    }
+
+    #[inline]
+    fn some_tag() {}
+
+    #[inline]
+    fn is_none(payload: &MaybeUninit<Self>, _: ()) -> bool {
+        unsafe { *(payload.as_ptr() as *const u8) == 2 }
+    }
+}
+
+/// An `Optional` with the same representation as in Swift.
+///
+/// Its alignment and stride match Swift's. Its size is Swift's stride, so it
+/// may be larger than Swift's size when there's an extra tag byte: `Int?`
+/// has a size of 9 in Swift but 16 in Rust.
+///
+/// # Examples
+///
+/// ```
+/// use swift_rt::optional::Optional;
+///
+/// let x = Optional::some(42isize);
+/// assert_eq!(x.as_ref(), Some(&42));
+///
+/// let y = Optional::<bool>::none();
+/// assert_eq!(std::mem::size_of_val(&y), 1);
+/// assert_eq!(y.into_option(), None);
+/// ```
+#[repr(C)]
+pub struct Optional<T: OptionalPayload> {
+    payload: MaybeUninit<T>,
+    tag: T::Tag,
+}
+
+impl<T: OptionalPayload> Drop for Optional<T> {
+    #[inline]
+    fn drop(&mut self) {
+        if self.is_some() {
+            unsafe { ptr::drop_in_place(self.payload.as_mut_ptr()) };
+        }
+    }
+}
+
+impl<T: OptionalPayload + Clone> Clone for Optional<T> {
+    #[inline]
+    fn clone(&self) -> Self {
+        self.as_ref().cloned().into()
+    }
+}
+
+impl<T: OptionalPayload + fmt::Debug> fmt::Debug for Optional<T> {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        self.as_ref().fmt(f)
+    }
+}
+
+impl<T: OptionalPayload> Default for Optional<T> {
+    #[inline]
+    fn default() -> Self {
+        Self::none()
+    }
+}
+
+impl<T: OptionalPayload> From<Option<T>> for Optional<T> {
+    #[inline]
+    fn from(option: Option<T>) -> Self {
+        match option {
+            Some(payload) => Self::some(payload),
+            None => Self::none(),
+        }
+    }
+}
+
+impl<T: OptionalPayload> From<Optional<T>> for Option<T> {
+    #[inline]
+    fn from(optional: Optional<T>) -> Self {
+        optional.into_option()
+    }
+}
+
+impl<T: OptionalPayload> Optional<T> {
+    /// Returns `nil`.
+    #[inline]
+    pub fn none() -> Self {
+        let (payload, tag) = T::none();
+        Self { payload, tag }
+    }
+
+    /// Wraps `payload`.
+    #[inline]
+    pub fn some(payload: T) -> Self {
+        Self {
+            payload: MaybeUninit::new(payload),
+            tag: T::some_tag(),
+        }
+    }
+
+    /// Returns whether `self` holds a payload.
+    #[inline]
+    pub fn is_some(&self) -> bool {
+        !self.is_none()
+    }
+
+    /// Returns whether `self` is `nil`.
+    #[inline]
+    pub fn is_none(&self) -> bool {
+        T::is_none(&self.payload, self.tag)
+    }
+
+    /// Returns a reference to the payload, or `None` if `self` is `nil`.
+    #[inline]
+    pub fn as_ref(&self) -> Option<&T> {
+        if self.is_some() {
+            Some(unsafe { &*self.payload.as_ptr() })
+        } else {
+            None
+        }
+    }
+
+    /// Returns a mutable reference to the payload, or `None` if `self` is
+    /// `nil`.
+    #[inline]
+    pub fn as_mut(&mut self) -> Option<&mut T> {
+        if self.is_some() {
+            Some(unsafe { &mut *self.payload.as_mut_ptr() })
+        } else {
+            None
+        }
+    }
+
+    /// Takes the payload out of `self`, leaving `nil` in its place.
+    #[inline]
+    pub fn take(&mut self) -> Option<T> {
+        mem::take(self).into_option()
+    }
+
+    /// Returns the payload, or `None` if `self` is `nil`.
+    #[inline]
+    pub fn into_option(self) -> Option<T> {
+        let this = ManuallyDrop::new(self);
+        if this.is_some() {
+            Some(unsafe { this.payload.as_ptr().read() })
+        } else {
+            None
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn extra_tag_bytes_of_empty_payload() {
+        assert_eq!(extra_tag_bytes(0, 0, 1), 1);
+        assert_eq!(extra_tag_bytes(0, 0, 254), 1);
+        assert_eq!(extra_tag_bytes(0, 0, 255), 2);
+        assert_eq!(extra_tag_bytes(0, 0, 300), 2);
+        assert_eq!(extra_tag_bytes(0, 0, 65535), 4);
+    }
+
+    #[test]
+    fn extra_tag_bytes_of_small_payloads() {
+        // Each tag value above 0 holds 256 cases in a 1-byte payload.
+        assert_eq!(extra_tag_bytes(1, 0, 254 * 256), 1);
+        assert_eq!(extra_tag_bytes(1, 0, 254 * 256 + 1), 2);
+        assert_eq!(extra_tag_bytes(1, 0, 65534 * 256), 2);
+        assert_eq!(extra_tag_bytes(1, 0, 65534 * 256 + 1), 4);
+
+        // And 65536 cases in a 2-byte payload.
+        assert_eq!(extra_tag_bytes(2, 0, 254 * 65536), 1);
+        assert_eq!(extra_tag_bytes(2, 0, 254 * 65536 + 1), 2);
+        assert_eq!(extra_tag_bytes(2, 0, 65534 * 65536), 2);
+        assert_eq!(extra_tag_bytes(2, 0, 65534 * 65536 + 1), 4);
+    }
+
+    #[test]
+    fn extra_tag_bytes_of_large_payloads() {
+        assert_eq!(extra_tag_bytes(4, 0, 1), 1);
+        assert_eq!(extra_tag_bytes(4, 0, u32::MAX), 1);
+        assert_eq!(extra_tag_bytes(16, 0, u32::MAX), 1);
+    }
+
+    #[test]
+    fn extra_tag_bytes_of_extra_inhabitants() {
+        assert_eq!(extra_tag_bytes(8, 4096, 1), 0);
+        assert_eq!(extra_tag_bytes(1, 254, 254), 0);
+        assert_eq!(extra_tag_bytes(0, 0, 0), 0);
+
+        // Only the cases that don't fit need tags.
+        assert_eq!(extra_tag_bytes(1, 254, 255), 1);
+        assert_eq!(extra_tag_bytes(0, 1, 256), 2);
+    }
+
+    /// Returns the first `len` bytes of `optional`.
+    fn bytes<T: OptionalPayload>(optional: &Optional<T>, len: usize) -> &[u8] {
+        let start = optional as *const Optional<T> as *const u8;
+        unsafe { std::slice::from_raw_parts(start, len) }
+    }
+
+    #[test]
+    fn optional_layouts() {
+        let word = mem::size_of::<usize>();
+
+        assert_eq!(mem::size_of::<Optional<isize>>(), 2 * word);
+        assert_eq!(mem::align_of::<Optional<isize>>(), word);
+        let none = Optional::<isize>::none();
+        assert_eq!(bytes(&none, word), &[0; 8][..word]);
+        assert_eq!(bytes(&none, word + 1)[word], 1);
+        assert_eq!(bytes(&Optional::some(-1isize), word + 1)[word], 0);
+
+        assert_eq!(mem::size_of::<Optional<bool>>(), 1);
+        assert_eq!(mem::align_of::<Optional<bool>>(), 1);
+        assert_eq!(bytes(&Optional::<bool>::none(), 1), [2]);
+        assert_eq!(bytes(&Optional::some(true), 1), [1]);
+
+        assert_eq!(mem::size_of::<Optional<()>>(), 1);
+        assert_eq!(mem::align_of::<Optional<()>>(), 1);
+        assert_eq!(bytes(&Optional::<()>::none(), 1), [1]);
+        assert_eq!(bytes(&Optional::some(()), 1), [0]);
+
+        assert_eq!(mem::size_of::<Optional<&u64>>(), word);
+        assert_eq!(mem::align_of::<Optional<&u64>>(), word);
+        assert_eq!(bytes(&Optional::<&u64>::none(), word), &[0; 8][..word]);
+
+        assert_eq!(mem::size_of::<Optional<Owned>>(), word);
+        assert_eq!(mem::align_of::<Optional<Owned>>(), word);
+
+        // Dropping an `Owned` calls into Swift's runtime, which tests don't
+        // link to.
+        let none = ManuallyDrop::new(Optional::<Owned>::none());
+        assert_eq!(bytes(&none, word), &[0; 8][..word]);
+    }
+}
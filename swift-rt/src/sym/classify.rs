@@ -0,0 +1,405 @@
+//! Classification of symbols by the kind of entity they refer to.
+
+use super::{Node, NodeKind};
+
+/// What a symbol refers to, as returned by [`classify`](fn.classify.html).
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub enum SymbolKind {
+    /// Type metadata (`N`).
+    TypeMetadata,
+    /// A type metadata accessor function (`Ma`).
+    TypeMetadataAccessor,
+    /// Full type metadata, including the value witness table pointer (`Mf`).
+    FullTypeMetadata,
+    /// An Objective-C metaclass (`Mm`).
+    Metaclass,
+    /// A nominal type descriptor (`Mn`).
+    NominalTypeDescriptor,
+    /// A protocol descriptor (`Mp`).
+    ProtocolDescriptor,
+    /// An extension descriptor (`MXE`).
+    ExtensionDescriptor,
+    /// A module descriptor (`MXM`).
+    ModuleDescriptor,
+    /// A value witness table (`WV`).
+    ValueWitnessTable,
+    /// A single value witness function (`w`).
+    ValueWitness,
+    /// A protocol witness table (`WP`).
+    ProtocolWitnessTable,
+    /// A protocol witness table accessor (`Wa`, `Wl`).
+    ProtocolWitnessTableAccessor,
+    /// A protocol conformance descriptor (`Mc`).
+    ProtocolConformanceDescriptor,
+    /// A protocol witness thunk (`TW`).
+    ProtocolWitness,
+    /// A method descriptor (`Tq`).
+    MethodDescriptor,
+    /// A dispatch thunk (`Tj`).
+    DispatchThunk,
+    /// A property descriptor (`MV`).
+    PropertyDescriptor,
+    /// A field offset variable (`Wv`).
+    FieldOffset,
+    /// A reflection field descriptor (`MF`).
+    FieldDescriptor,
+    /// A function or method.
+    Function,
+    /// An allocating initializer (`fC`).
+    Allocator,
+    /// A non-allocating initializer (`fc`).
+    Constructor,
+    /// A non-deallocating destructor (`fd`).
+    Destructor,
+    /// A deallocating destructor (`fD`).
+    Deallocator,
+    /// A global variable.
+    Variable,
+    /// A getter (`g`).
+    Getter,
+    /// A setter (`s`).
+    Setter,
+    /// A modify coroutine (`M`).
+    Modify,
+    /// A read coroutine (`r`).
+    Read,
+    /// An addressor (`lu`, `au`, etc).
+    Addressor,
+    /// A `willSet` observer (`w`).
+    WillSet,
+    /// A `didSet` observer (`W`).
+    DidSet,
+    /// A variable initializer (`fi`).
+    Initializer,
+    /// A default argument generator (`fA`).
+    DefaultArgumentInitializer,
+    /// An explicit or implicit closure.
+    Closure,
+    /// A reabstraction thunk (`Tr`, `TR`).
+    ReabstractionThunk,
+    /// A partial application forwarder (`TA`).
+    PartialApplyForwarder,
+    /// A curry thunk (`Tc`).
+    CurryThunk,
+    /// Any other kind of symbol.
+    Other,
+}
+
+/// The classification of a symbol: its kind and the entity it refers to.
+///
+/// # Examples
+///
+/// ```
+/// use swift_rt::sym::{self, SymbolKind};
+///
+/// let info = sym::classify(b"$s4main3FooV1xSivg").unwrap();
+/// assert_eq!(info.kind(), SymbolKind::Getter);
+/// assert_eq!(info.context(), ["main", "Foo"]);
+/// assert_eq!(info.name(), Some("x"));
+/// ```
+#[derive(Clone, Debug, PartialEq, Eq, Hash)]
+pub struct SymbolInfo {
+    kind: SymbolKind,
+    context: Vec<String>,
+    name: Option<String>,
+    is_static: bool,
+    is_specialized: bool,
+}
+
+impl SymbolInfo {
+    /// Classifies the tree of a demangled symbol, as returned by
+    /// [`parse`](fn.parse.html).
+    pub fn of(global: &Node) -> Self {
+        let mut info = Self {
+            kind: SymbolKind::Other,
+            context: Vec::new(),
+            name: None,
+            is_static: false,
+            is_specialized: false,
+        };
+
+        let mut main = None;
+        for child in global.children() {
+            match child.kind() {
+                NodeKind::GenericSpecialization
+                | NodeKind::GenericSpecializationNotReAbstracted
+                | NodeKind::FunctionSignatureSpecialization => {
+                    info.is_specialized = true;
+                }
+                NodeKind::PartialApplyForwarder
+                | NodeKind::PartialApplyObjCForwarder => {
+                    // Forwarders wrap the function they apply.
+                    let forwarded = Self::of(child);
+                    return Self {
+                        kind: SymbolKind::PartialApplyForwarder,
+                        is_specialized: info.is_specialized
+                            || forwarded.is_specialized,
+                        ..forwarded
+                    };
+                }
+                NodeKind::Suffix => {}
+                kind if kind.is_function_attr() => {}
+                _ if main.is_none() => main = Some(child),
+                _ => {}
+            }
+        }
+        let mut main = match main {
+            Some(main) => main,
+            None => return info,
+        };
+        if main.kind() == NodeKind::Static {
+            info.is_static = true;
+            main = match main.child(0) {
+                Some(entity) => entity,
+                None => return info,
+            };
+        }
+
+        info.kind = symbol_kind(main.kind());
+        if let Some(entity) = entity(main) {
+            info.set_entity(entity);
+        }
+        info
+    }
+
+    /// Returns what the symbol refers to.
+    #[inline]
+    pub fn kind(&self) -> SymbolKind {
+        self.kind
+    }
+
+    /// Returns the names of the module and declarations enclosing the entity,
+    /// outermost first.
+    ///
+    /// Declarations within extensions are reported within the extended type.
+    #[inline]
+    pub fn context(&self) -> &[String] {
+        &self.context
+    }
+
+    /// Returns the name of the entity, such as the type of a metadata
+    /// accessor, or `None` if it's unnamed, such as an initializer or a
+    /// tuple type.
+    #[inline]
+    pub fn name(&self) -> Option<&str> {
+        self.name.as_deref()
+    }
+
+    /// Returns whether the entity is a `static` or `class` member.
+    #[inline]
+    pub fn is_static(&self) -> bool {
+        self.is_static
+    }
+
+    /// Returns whether the symbol is a specialization of a generic or
+    /// optimized function.
+    #[inline]
+    pub fn is_specialized(&self) -> bool {
+        self.is_specialized
+    }
+
+    fn set_entity(&mut self, entity: &Node) {
+        match entity.kind() {
+            NodeKind::Static => {
+                self.is_static = true;
+                if let Some(entity) = entity.child(0) {
+                    self.set_entity(entity);
+                }
+            }
+            NodeKind::Type => {
+                if let Some(entity) = entity.child(0) {
+                    self.set_entity(entity);
+                }
+            }
+            NodeKind::Module => {
+                self.name = entity.text().map(str::to_owned);
+            }
+            NodeKind::Extension => {
+                if let Some(extended) = entity.child(1) {
+                    self.set_entity(extended);
+                }
+            }
+            kind if kind.is_accessor() => {
+                if let Some(storage) = entity.child(0) {
+                    self.set_entity(storage);
+                }
+            }
+            kind if is_bound_generic(kind) => {
+                if let Some(unbound) = entity.child(0) {
+                    self.set_entity(unbound);
+                }
+            }
+            kind if kind.is_context() => {
+                if let Some(context) = entity.child(0) {
+                    push_context(context, &mut self.context);
+                }
+                self.name =
+                    entity.child(1).and_then(decl_name).map(str::to_owned);
+            }
+            _ => {}
+        }
+    }
+}
+
+/// Returns the declaration or type that the main node of a symbol refers to.
+fn entity(main: &Node) -> Option<&Node> {
+    if main.kind().is_context() {
+        return Some(main);
+    }
+    let children = main.children();
+    children
+        .iter()
+        .find(|child| child.kind().is_context())
+        .or_else(|| children.iter().find(|c| c.kind() == NodeKind::Type))
+        .or_else(|| {
+            let conformance = children
+                .iter()
+                .find(|c| c.kind() == NodeKind::ProtocolConformance)?;
+            conformance.child(0)
+        })
+}
+
+/// Appends the names of `context` and its parents to `path`, outermost first.
+fn push_context(context: &Node, path: &mut Vec<String>) {
+    match context.kind() {
+        NodeKind::Module => {
+            path.extend(context.text().map(str::to_owned));
+            return;
+        }
+        NodeKind::Extension => {
+            if let Some(extended) = context.child(1) {
+                push_context(extended, path);
+            }
+            return;
+        }
+        kind if kind == NodeKind::Type || is_bound_generic(kind) => {
+            if let Some(unbound) = context.child(0) {
+                push_context(unbound, path);
+            }
+            return;
+        }
+        _ => {}
+    }
+
+    if let Some(parent) = context.child(0) {
+        push_context(parent, path);
+    }
+    if !context.kind().is_accessor() {
+        path.extend(context.child(1).and_then(decl_name).map(str::to_owned));
+    }
+}
+
+/// Returns the identifier of a declaration name node.
+fn decl_name(name: &Node) -> Option<&str> {
+    match name.kind() {
+        NodeKind::Identifier
+        | NodeKind::PrefixOperator
+        | NodeKind::PostfixOperator
+        | NodeKind::InfixOperator => name.text(),
+        NodeKind::PrivateDeclName | NodeKind::LocalDeclName => {
+            name.child(1)?.text()
+        }
+        _ => None,
+    }
+}
+
+#[inline]
+fn is_bound_generic(kind: NodeKind) -> bool {
+    use NodeKind::*;
+    matches!(
+        kind,
+        BoundGenericStructure
+            | BoundGenericClass
+            | BoundGenericEnum
+            | BoundGenericProtocol
+            | BoundGenericTypeAlias
+    )
+}
+
+fn symbol_kind(kind: NodeKind) -> SymbolKind {
+    use NodeKind as N;
+    use SymbolKind as S;
+    match kind {
+        N::TypeMetadata => S::TypeMetadata,
+        N::TypeMetadataAccessFunction => S::TypeMetadataAccessor,
+        N::FullTypeMetadata => S::FullTypeMetadata,
+        N::Metaclass => S::Metaclass,
+        N::NominalTypeDescriptor => S::NominalTypeDescriptor,
+        N::ProtocolDescriptor => S::ProtocolDescriptor,
+        N::ExtensionDescriptor => S::ExtensionDescriptor,
+        N::ModuleDescriptor => S::ModuleDescriptor,
+        N::ValueWitnessTable => S::ValueWitnessTable,
+        N::ValueWitness => S::ValueWitness,
+        N::ProtocolWitnessTable
+        | N::ProtocolWitnessTablePattern
+        | N::GenericProtocolWitnessTable
+        | N::ResilientProtocolWitnessTable => S::ProtocolWitnessTable,
+        N::ProtocolWitnessTableAccessor
+        | N::LazyProtocolWitnessTableAccessor => {
+            S::ProtocolWitnessTableAccessor
+        }
+        N::ProtocolConformanceDescriptor => S::ProtocolConformanceDescriptor,
+        N::ProtocolWitness => S::ProtocolWitness,
+        N::MethodDescriptor => S::MethodDescriptor,
+        N::DispatchThunk => S::DispatchThunk,
+        N::PropertyDescriptor => S::PropertyDescriptor,
+        N::FieldOffset => S::FieldOffset,
+        N::ReflectionMetadataFieldDescriptor => S::FieldDescriptor,
+        N::Function => S::Function,
+        N::Allocator => S::Allocator,
+        N::Constructor => S::Constructor,
+        N::Destructor => S::Destructor,
+        N::Deallocator => S::Deallocator,
+        N::Variable => S::Variable,
+        N::Getter | N::GlobalGetter => S::Getter,
+        N::Setter => S::Setter,
+        N::ModifyAccessor => S::Modify,
+        N::ReadAccessor => S::Read,
+        N::WillSet => S::WillSet,
+        N::DidSet => S::DidSet,
+        N::Initializer => S::Initializer,
+        N::DefaultArgumentInitializer => S::DefaultArgumentInitializer,
+        N::ExplicitClosure | N::ImplicitClosure => S::Closure,
+        N::ReabstractionThunk | N::ReabstractionThunkHelper => {
+            S::ReabstractionThunk
+        }
+        N::PartialApplyForwarder | N::PartialApplyObjCForwarder => {
+            S::PartialApplyForwarder
+        }
+        N::CurryThunk => S::CurryThunk,
+        kind if kind.is_accessor() => S::Addressor,
+        _ => S::Other,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::super::classify;
+    use super::*;
+
+    #[test]
+    fn entities() {
+        let info = classify(b"$s4main3FooV3baryySiFTj").unwrap();
+        assert_eq!(info.kind(), SymbolKind::DispatchThunk);
+        assert_eq!(info.context(), ["main", "Foo"]);
+        assert_eq!(info.name(), Some("bar"));
+
+        let info = classify(b"$s4main3FooVMa").unwrap();
+        assert_eq!(info.kind(), SymbolKind::TypeMetadataAccessor);
+        assert_eq!(info.context(), ["main"]);
+        assert_eq!(info.name(), Some("Foo"));
+    }
+
+    #[test]
+    fn partial_apply_forwarders() {
+        let info = classify(b"$s4main3FooV3baryySiFTA").unwrap();
+        assert_eq!(info.kind(), SymbolKind::PartialApplyForwarder);
+        assert_eq!(info.context(), ["main", "Foo"]);
+        assert_eq!(info.name(), Some("bar"));
+        assert!(!info.is_specialized());
+
+        let info = classify(b"$s4main3fooyyFTATm").unwrap();
+        assert_eq!(info.kind(), SymbolKind::PartialApplyForwarder);
+        assert_eq!(info.name(), Some("foo"));
+    }
+}
@@ -0,0 +1,2417 @@
+//! Parser for the Swift 5 mangling grammar.
+
+// Based on:
+// lib/Demangling/Demangler.cpp
+// docs/ABI/Mangling.rst
+
+use super::{
+    node::{Node, NodeKind},
+    old_demangler, punycode, symbolic, DemangleError, DemangleErrorKind,
+    DemangleOptions, ManglingGeneration, SymbolicReference,
+    SymbolicReferenceKind,
+};
+use std::convert::TryFrom;
+
+type Result<T> = std::result::Result<T, DemangleError>;
+
+/// The maximum number of words that identifiers may be built from.
+pub(crate) const MAX_NUM_WORDS: usize = 26;
+
+/// The maximum repeat count of merged substitutions, as in `S2i`.
+pub(crate) const MAX_REPEAT_COUNT: u64 = 2048;
+
+/// The maximum depth of the nodes of a symbol, which bounds the recursion of
+/// everything that walks them.
+pub(crate) const MAX_DEPTH: usize = 1024;
+
+/// Returns the mangling generation and prefix length of `symbol`, or `None`
+/// if it has no Swift mangling prefix.
+pub(crate) fn prefix(symbol: &[u8]) -> Option<(ManglingGeneration, usize)> {
+    const PREFIXES: &[(&[u8], ManglingGeneration)] = &[
+        (b"$s", ManglingGeneration::Swift5),
+        (b"$S", ManglingGeneration::Swift4_2),
+        (b"_T0", ManglingGeneration::Swift4),
+        (b"_T", ManglingGeneration::Swift3),
+    ];
+
+    // Symbols on Darwin platforms have an extra leading underscore.
+    let unprefixed = match symbol.split_first() {
+        Some((b'_', rest)) => Some(rest),
+        _ => None,
+    };
+
+    PREFIXES.iter().find_map(|&(prefix, generation)| {
+        if symbol.starts_with(prefix) {
+            Some((generation, prefix.len()))
+        } else if unprefixed?.starts_with(prefix) {
+            Some((generation, prefix.len() + 1))
+        } else {
+            None
+        }
+    })
+}
+
+/// Returns the length of the Swift mangling prefix of `symbol`, or 0 if it has
+/// none.
+#[inline]
+pub(crate) fn prefix_len(symbol: &[u8]) -> usize {
+    prefix(symbol).map_or(0, |(_, len)| len)
+}
+
+/// Demangles a whole symbol, such as `$s4main3fooyyF`.
+pub(crate) fn demangle_symbol(symbol: &[u8]) -> Result<Node> {
+    let (generation, prefix_len) = prefix(symbol)
+        .ok_or_else(|| DemangleError::new(DemangleErrorKind::NotMangled, 0))?;
+    if generation == ManglingGeneration::Swift3 {
+        return old_demangler::demangle_symbol(symbol, prefix_len);
+    }
+
+    let mut dem = Demangler::new(symbol, prefix_len);
+    dem.old_function_types = generation == ManglingGeneration::Swift4;
+    if let Err(error) = dem.parse_and_push_nodes() {
+        return Err(error.with_partial(dem.partial()));
+    }
+
+    let mut global = Node::new(NodeKind::Global);
+    let mut attrs = Vec::new();
+    while let Some(attr) = dem.pop_if(NodeKind::is_function_attr) {
+        attrs.push(attr);
+    }
+
+    let mut rest = dem.stack.drain(..).map(|node| match node.kind() {
+        NodeKind::Type => node.into_children().remove(0),
+        _ => node,
+    });
+
+    // Partial application forwarders wrap everything that follows them.
+    let mut forwarder = None;
+    for attr in attrs {
+        match attr.kind() {
+            NodeKind::PartialApplyForwarder
+            | NodeKind::PartialApplyObjCForwarder
+                if forwarder.is_none() =>
+            {
+                forwarder = Some(attr)
+            }
+            _ => match &mut forwarder {
+                Some(forwarder) => forwarder.push(attr),
+                None => global.push(attr),
+            },
+        }
+    }
+    match forwarder {
+        Some(mut forwarder) => {
+            forwarder.children_mut().extend(&mut rest);
+            global.push(forwarder);
+        }
+        None => global.children_mut().extend(&mut rest),
+    }
+
+    if global.children().is_empty() {
+        Err(DemangleError::new(
+            DemangleErrorKind::UnexpectedEnd,
+            symbol.len(),
+        ))
+    } else {
+        Ok(global)
+    }
+}
+
+/// Resolves a symbolic reference into the node of the entity it refers to.
+pub(crate) type Resolver<'r> =
+    &'r mut dyn FnMut(&SymbolicReference) -> Option<Node>;
+
+/// Demangles a bare mangled type, such as `Si` or `SaySSG`, whose symbolic
+/// references are resolved by `resolver`.
+pub(crate) fn demangle_type<'a>(
+    mangled: &'a [u8],
+    resolver: Option<Resolver<'a>>,
+) -> Result<Node> {
+    let mut dem = Demangler::new(mangled, 0);
+    dem.resolver = resolver;
+    dem.parse_and_push_nodes()?;
+
+    match dem.pop_kind(NodeKind::Type) {
+        Some(ty) if dem.stack.is_empty() => Ok(ty),
+        _ => Err(dem.error()),
+    }
+}
+
+#[inline]
+fn is_word_start(ch: u8) -> bool {
+    !ch.is_ascii_digit() && ch != b'_' && ch != 0
+}
+
+#[inline]
+fn is_word_end(ch: u8, prev: u8) -> bool {
+    ch == b'_'
+        || ch == 0
+        || (!prev.is_ascii_uppercase() && ch.is_ascii_uppercase())
+}
+
+/// Calls `f` with the `(start, len)` of every word within `ident`.
+///
+/// Words are substrings that identifiers may share through word
+/// substitutions. They begin at a non-digit and end before an `_` or an
+/// uppercase letter that follows a non-uppercase one.
+pub(crate) fn for_each_word(ident: &[u8], mut f: impl FnMut(usize, usize)) {
+    let mut word_start = None;
+    for pos in 0..=ident.len() {
+        let ch = ident.get(pos).copied().unwrap_or(0);
+        if let Some(start) = word_start {
+            if is_word_end(ch, ident[pos - 1]) {
+                f(start, pos - start);
+                word_start = None;
+            }
+        }
+        if word_start.is_none() && is_word_start(ch) {
+            word_start = Some(pos);
+        }
+    }
+}
+
+/// Demangling state.
+pub(crate) struct Demangler<'a> {
+    text: &'a [u8],
+    pos: usize,
+    pub(crate) stack: Vec<Node>,
+    substitutions: Vec<Node>,
+    words: Vec<&'a [u8]>,
+    /// Whether argument labels are part of parameter tuples rather than a
+    /// separate list, as in Swift 4.0 (`_T0`).
+    old_function_types: bool,
+    /// Resolves symbolic references, which fail to demangle without one.
+    resolver: Option<Resolver<'a>>,
+}
+
+impl<'a> Demangler<'a> {
+    pub(crate) fn new(text: &'a [u8], pos: usize) -> Self {
+        Self {
+            text,
+            pos,
+            stack: Vec::new(),
+            substitutions: Vec::new(),
+            words: Vec::new(),
+            old_function_types: false,
+            resolver: None,
+        }
+    }
+
+    #[inline]
+    fn error(&self) -> DemangleError {
+        let kind = if self.pos < self.text.len() {
+            DemangleErrorKind::Malformed
+        } else {
+            DemangleErrorKind::UnexpectedEnd
+        };
+        DemangleError::new(kind, self.pos)
+    }
+
+    #[inline]
+    fn fail<T>(&self) -> Result<T> {
+        Err(self.error())
+    }
+
+    /// Returns an error for an entity that extends past the end of the text.
+    fn truncated(&self) -> DemangleError {
+        let offset = self.text.len();
+        DemangleError::new(DemangleErrorKind::UnexpectedEnd, offset)
+    }
+
+    /// Fails on the operator `ch` that was just read, which is 0 at the end of
+    /// the text.
+    fn unknown_operator<T>(&self, ch: u8) -> Result<T> {
+        if ch == 0 {
+            return self.fail();
+        }
+        let offset = self.pos - 1;
+        Err(DemangleError::new(
+            DemangleErrorKind::UnknownOperator,
+            offset,
+        ))
+    }
+
+    fn invalid_substitution<T>(&self) -> Result<T> {
+        let offset = self.pos.saturating_sub(1);
+        Err(DemangleError::new(
+            DemangleErrorKind::InvalidSubstitution,
+            offset,
+        ))
+    }
+
+    /// Returns the demangled text of the nodes parsed so far.
+    fn partial(&self) -> String {
+        let options = DemangleOptions::new();
+        let parts: Vec<_> =
+            self.stack.iter().map(|node| options.print(node)).collect();
+        parts.join(" ")
+    }
+
+    // Cursor ----------------------------------------------------------------
+
+    #[inline]
+    fn peek(&self) -> u8 {
+        self.text.get(self.pos).copied().unwrap_or(0)
+    }
+
+    #[inline]
+    fn next(&mut self) -> u8 {
+        let ch = self.peek();
+        if self.pos < self.text.len() {
+            self.pos += 1;
+        }
+        ch
+    }
+
+    #[inline]
+    fn next_if(&mut self, ch: u8) -> bool {
+        if self.peek() == ch && self.pos < self.text.len() {
+            self.pos += 1;
+            true
+        } else {
+            false
+        }
+    }
+
+    #[inline]
+    fn push_back(&mut self) {
+        self.pos -= 1;
+    }
+
+    fn demangle_natural(&mut self) -> Option<u64> {
+        if !self.peek().is_ascii_digit() {
+            return None;
+        }
+        let mut n: u64 = 0;
+        while self.peek().is_ascii_digit() {
+            let digit = u64::from(self.next() - b'0');
+            n = n.checked_mul(10)?.checked_add(digit)?;
+        }
+        Some(n)
+    }
+
+    /// Demangles `'_'` as 0 or `NATURAL '_'` as `NATURAL + 1`.
+    fn demangle_index(&mut self) -> Result<u64> {
+        if self.next_if(b'_') {
+            return Ok(0);
+        }
+        match self.demangle_natural() {
+            Some(n) if self.next_if(b'_') => Ok(n + 1),
+            _ => self.fail(),
+        }
+    }
+
+    fn demangle_index_node(&mut self) -> Result<Node> {
+        let index = self.demangle_index()?;
+        Ok(Node::with_index(NodeKind::Number, index))
+    }
+
+    // Stack -----------------------------------------------------------------
+
+    #[inline]
+    pub(crate) fn push(&mut self, node: Node) {
+        self.stack.push(node);
+    }
+
+    pub(crate) fn pop_if(
+        &mut self,
+        f: impl Fn(NodeKind) -> bool,
+    ) -> Option<Node> {
+        match self.stack.last() {
+            Some(node) if f(node.kind()) => self.stack.pop(),
+            _ => None,
+        }
+    }
+
+    #[inline]
+    pub(crate) fn pop_kind(&mut self, kind: NodeKind) -> Option<Node> {
+        self.pop_if(|k| k == kind)
+    }
+
+    #[inline]
+    fn pop_any(&mut self) -> Result<Node> {
+        self.stack.pop().ok_or_else(|| self.error())
+    }
+
+    #[inline]
+    fn pop_type(&mut self) -> Result<Node> {
+        self.pop_kind(NodeKind::Type).ok_or_else(|| self.error())
+    }
+
+    fn pop_type_and_get_child(&mut self) -> Result<Node> {
+        let ty = self.pop_type()?;
+        let mut children = ty.into_children();
+        if children.len() != 1 {
+            return self.fail();
+        }
+        Ok(children.remove(0))
+    }
+
+    fn pop_type_and_get_any_generic(&mut self) -> Result<Node> {
+        let child = self.pop_type_and_get_child()?;
+        if child.kind().is_any_generic() {
+            Ok(child)
+        } else {
+            self.fail()
+        }
+    }
+
+    fn pop_module(&mut self) -> Option<Node> {
+        if let Some(mut ident) = self.pop_kind(NodeKind::Identifier) {
+            ident.set_kind(NodeKind::Module);
+            return Some(ident);
+        }
+        self.pop_kind(NodeKind::Module)
+    }
+
+    fn pop_context(&mut self) -> Result<Node> {
+        if let Some(module) = self.pop_module() {
+            return Ok(module);
+        }
+        if self.stack.last().map(Node::kind) == Some(NodeKind::Type) {
+            let child = self.pop_type_and_get_child()?;
+            return if child.kind().is_context() {
+                Ok(child)
+            } else {
+                self.fail()
+            };
+        }
+        self.pop_if(NodeKind::is_context)
+            .ok_or_else(|| self.error())
+    }
+
+    fn pop_decl_name(&mut self) -> Result<Node> {
+        self.pop_if(NodeKind::is_decl_name)
+            .ok_or_else(|| self.error())
+    }
+
+    fn pop_protocol(&mut self) -> Result<Node> {
+        if let Some(ty) = self.pop_kind(NodeKind::Type) {
+            return match ty.unwrap_type().kind() {
+                NodeKind::Protocol => Ok(ty),
+                _ => self.fail(),
+            };
+        }
+        let name = self.pop_decl_name()?;
+        let context = self.pop_context()?;
+        let proto =
+            Node::with_children(NodeKind::Protocol, vec![context, name]);
+        Ok(Node::ty(proto))
+    }
+
+    fn pop_type_list(&mut self) -> Result<Node> {
+        let mut list = Node::new(NodeKind::TypeList);
+        if self.pop_kind(NodeKind::EmptyList).is_none() {
+            loop {
+                let first =
+                    self.pop_kind(NodeKind::FirstElementMarker).is_some();
+                list.push(self.pop_type()?);
+                if first {
+                    break;
+                }
+            }
+            list.children_mut().reverse();
+        }
+        Ok(list)
+    }
+
+    fn pop_protocol_conformance(&mut self) -> Result<Node> {
+        let gen_sig = self.pop_kind(NodeKind::DependentGenericSignature);
+        let module = self.pop_module().ok_or_else(|| self.error())?;
+        let proto = self.pop_protocol()?;
+        let mut ty = self.pop_type()?;
+        if let Some(gen_sig) = gen_sig {
+            ty = Node::ty(Node::with_children(
+                NodeKind::DependentGenericType,
+                vec![gen_sig, ty],
+            ));
+        }
+        Ok(Node::with_children(
+            NodeKind::ProtocolConformance,
+            vec![ty, proto, module],
+        ))
+    }
+
+    // Substitutions ---------------------------------------------------------
+
+    #[inline]
+    fn add_substitution(&mut self, node: &Node) {
+        self.substitutions.push(node.clone());
+    }
+
+    fn substitution(&self, index: u64) -> Result<Node> {
+        usize::try_from(index)
+            .ok()
+            .and_then(|index| self.substitutions.get(index))
+            .cloned()
+            .map_or_else(|| self.invalid_substitution(), Ok)
+    }
+
+    /// Pushes `repeat_count - 1` copies of the substitution at `index` and
+    /// returns the last copy.
+    fn push_multi_substitutions(
+        &mut self,
+        repeat_count: Option<u64>,
+        index: u64,
+    ) -> Result<Node> {
+        let repeat_count = repeat_count.unwrap_or(1);
+        if repeat_count > MAX_REPEAT_COUNT {
+            return self.fail();
+        }
+        let node = self.substitution(index)?;
+        for _ in 1..repeat_count {
+            self.push(node.clone());
+        }
+        Ok(node)
+    }
+
+    fn demangle_multi_substitutions(&mut self) -> Result<Node> {
+        let mut repeat_count = None;
+        loop {
+            let ch = self.next();
+            match ch {
+                0 => return self.fail(),
+                b'a'..=b'z' => {
+                    let index = u64::from(ch - b'a');
+                    let node =
+                        self.push_multi_substitutions(repeat_count, index)?;
+                    self.push(node);
+                    repeat_count = None;
+                }
+                b'A'..=b'Z' => {
+                    let index = u64::from(ch - b'A');
+                    return self.push_multi_substitutions(repeat_count, index);
+                }
+                b'_' => {
+                    // The previous number is a large index, not a repeat count.
+                    let index = repeat_count.map_or(26, |n| n + 27);
+                    return self.substitution(index);
+                }
+                _ => {
+                    self.push_back();
+                    repeat_count = Some(
+                        self.demangle_natural().ok_or_else(|| self.error())?,
+                    );
+                }
+            }
+        }
+    }
+
+    fn demangle_standard_substitution(&mut self) -> Result<Node> {
+        match self.next() {
+            b'o' => Ok(Node::with_text(NodeKind::Module, "__C")),
+            b'C' => Ok(Node::with_text(NodeKind::Module, "__C_Synthesized")),
+            b'g' => {
+                let wrapped = self.pop_type()?;
+                let optional = Node::ty(Node::with_children(
+                    NodeKind::BoundGenericEnum,
+                    vec![
+                        swift_type(NodeKind::Enum, "Optional"),
+                        Node::with_children(NodeKind::TypeList, Some(wrapped)),
+                    ],
+                ));
+                self.add_substitution(&optional);
+                Ok(optional)
+            }
+            0 => self.fail(),
+            _ => {
+                self.push_back();
+                let repeat_count = self.demangle_natural().unwrap_or(0);
+                if repeat_count > MAX_REPEAT_COUNT {
+                    return self.fail();
+                }
+                let node = standard_substitution(self.next())
+                    .ok_or_else(|| self.error())?;
+                for _ in 1..repeat_count {
+                    self.push(node.clone());
+                }
+                Ok(node)
+            }
+        }
+    }
+
+    // Identifiers -----------------------------------------------------------
+
+    fn demangle_identifier(&mut self) -> Result<Node> {
+        let mut has_word_substs = false;
+        let mut is_punycoded = false;
+        if self.next_if(b'0') {
+            if self.next_if(b'0') {
+                is_punycoded = true;
+            } else {
+                has_word_substs = true;
+            }
+        }
+
+        let mut ident = Vec::new();
+        loop {
+            while has_word_substs && self.peek().is_ascii_alphabetic() {
+                let ch = self.next();
+                let index = if ch.is_ascii_lowercase() {
+                    usize::from(ch - b'a')
+                } else {
+                    has_word_substs = false;
+                    usize::from(ch - b'A')
+                };
+                let word = match self.words.get(index) {
+                    Some(&word) => word,
+                    None => return self.invalid_substitution(),
+                };
+                ident.extend_from_slice(word);
+            }
+            if self.next_if(b'0') {
+                break;
+            }
+
+            let len = match self.demangle_natural() {
+                Some(len) if len > 0 => len as usize,
+                _ => return self.fail(),
+            };
+            if is_punycoded {
+                // Separates the length from an encoding that starts with a
+                // digit or `_`.
+                self.next_if(b'_');
+            }
+            let end =
+                self.pos.checked_add(len).ok_or_else(|| self.truncated())?;
+            let slice = self
+                .text
+                .get(self.pos..end)
+                .ok_or_else(|| self.truncated())?;
+            self.pos = end;
+
+            if is_punycoded {
+                let decoded =
+                    punycode::decode(slice).ok_or_else(|| self.error())?;
+                ident.extend_from_slice(decoded.as_bytes());
+                break;
+            }
+            ident.extend_from_slice(slice);
+
+            let words = &mut self.words;
+            for_each_word(slice, |start, len| {
+                if len >= 2 && words.len() < MAX_NUM_WORDS {
+                    words.push(&slice[start..(start + len)]);
+                }
+            });
+
+            if !has_word_substs {
+                break;
+            }
+        }
+
+        if ident.is_empty() {
+            return self.fail();
+        }
+        let ident = String::from_utf8(ident).map_err(|_| self.error())?;
+        let node = Node::with_text(NodeKind::Identifier, ident);
+        self.add_substitution(&node);
+        Ok(node)
+    }
+
+    fn demangle_operator_identifier(&mut self) -> Result<Node> {
+        const OP_CHARS: &[u8; 26] = b"& @/= >    <*!|+?%-~   ^ .";
+
+        let ident = self.pop_kind(NodeKind::Identifier);
+        let ident = ident.as_ref().and_then(Node::text);
+        let ident = ident.ok_or_else(|| self.error())?;
+
+        let mut op = String::with_capacity(ident.len());
+        for ch in ident.chars() {
+            if !ch.is_ascii() {
+                // Pass through Unicode characters.
+                op.push(ch);
+                continue;
+            }
+            let ch = ch as u8;
+            if !ch.is_ascii_lowercase() {
+                return self.fail();
+            }
+            match OP_CHARS[usize::from(ch - b'a')] {
+                b' ' => return self.fail(),
+                op_ch => op.push(char::from(op_ch)),
+            }
+        }
+
+        let kind = match self.next() {
+            b'i' => NodeKind::InfixOperator,
+            b'p' => NodeKind::PrefixOperator,
+            b'P' => NodeKind::PostfixOperator,
+            ch => return self.unknown_operator(ch),
+        };
+        Ok(Node::with_text(kind, op))
+    }
+
+    fn demangle_local_identifier(&mut self) -> Result<Node> {
+        if self.next_if(b'L') {
+            let discriminator = self
+                .pop_kind(NodeKind::Identifier)
+                .ok_or_else(|| self.error())?;
+            let name = self.pop_decl_name()?;
+            return Ok(Node::with_children(
+                NodeKind::PrivateDeclName,
+                vec![discriminator, name],
+            ));
+        }
+        if self.next_if(b'l') {
+            let discriminator = self
+                .pop_kind(NodeKind::Identifier)
+                .ok_or_else(|| self.error())?;
+            return Ok(Node::with_children(
+                NodeKind::PrivateDeclName,
+                Some(discriminator),
+            ));
+        }
+        let discriminator = self.demangle_index_node()?;
+        let name = self.pop_decl_name()?;
+        Ok(Node::with_children(
+            NodeKind::LocalDeclName,
+            vec![discriminator, name],
+        ))
+    }
+}
+
+/// Moves the element names of the parameter tuple `params` into a label list,
+/// or returns `None` if there are none.
+fn take_tuple_labels(params: &mut Node) -> Option<Node> {
+    if params.kind() != NodeKind::Tuple {
+        return None;
+    }
+
+    let mut labels = Node::new(NodeKind::LabelList);
+    let mut has_labels = false;
+    for element in params.children_mut() {
+        let name = element
+            .children()
+            .iter()
+            .position(|child| child.kind() == NodeKind::TupleElementName);
+        match name {
+            Some(name) => {
+                let mut name = element.children_mut().remove(name);
+                name.set_kind(NodeKind::Identifier);
+                labels.push(name);
+                has_labels = true;
+            }
+            None => labels.push(Node::new(NodeKind::FirstElementMarker)),
+        }
+    }
+
+    if has_labels {
+        Some(labels)
+    } else {
+        None
+    }
+}
+
+/// Creates a type in the `Swift` module.
+fn swift_type(kind: NodeKind, name: &str) -> Node {
+    Node::ty(Node::with_children(
+        kind,
+        vec![
+            Node::with_text(NodeKind::Module, "Swift"),
+            Node::with_text(NodeKind::Identifier, name),
+        ],
+    ))
+}
+
+/// Returns the type for the standard substitution `S<ch>`.
+fn standard_substitution(ch: u8) -> Option<Node> {
+    let (kind, name) = super::std_types::lookup(ch)?;
+    Some(swift_type(kind, name))
+}
+
+impl<'a> Demangler<'a> {
+    pub(crate) fn parse_and_push_nodes(&mut self) -> Result<()> {
+        while self.pos < self.text.len() {
+            let start = self.pos;
+            let node = self.demangle_operator()?;
+            if node.exceeds_depth(MAX_DEPTH) {
+                return Err(DemangleError::new(
+                    DemangleErrorKind::TooComplex,
+                    start,
+                ));
+            }
+            self.push(node);
+        }
+        Ok(())
+    }
+
+    fn demangle_operator(&mut self) -> Result<Node> {
+        match self.next() {
+            b'A' => self.demangle_multi_substitutions(),
+            b'B' => self.demangle_builtin_type(),
+            b'C' => self.demangle_any_generic_type(NodeKind::Class),
+            b'D' => {
+                let ty = self.pop_type()?;
+                Ok(Node::with_children(NodeKind::TypeMangling, Some(ty)))
+            }
+            b'E' => self.demangle_extension_context(),
+            b'F' => self.demangle_plain_function(),
+            b'G' => self.demangle_bound_generic_type(),
+            b'I' => self.demangle_impl_function_type(),
+            b'K' => Ok(Node::new(NodeKind::ThrowsAnnotation)),
+            b'L' => self.demangle_local_identifier(),
+            b'M' => self.demangle_metatype(),
+            b'N' => {
+                let ty = self.pop_type()?;
+                Ok(Node::with_children(NodeKind::TypeMetadata, Some(ty)))
+            }
+            b'O' => self.demangle_any_generic_type(NodeKind::Enum),
+            b'P' => self.demangle_any_generic_type(NodeKind::Protocol),
+            b'Q' => self.demangle_archetype(),
+            b'R' => self.demangle_generic_requirement(),
+            b'S' => self.demangle_standard_substitution(),
+            b'T' => self.demangle_thunk_or_specialization(),
+            b'V' => self.demangle_any_generic_type(NodeKind::Structure),
+            b'W' => self.demangle_witness(),
+            b'X' => self.demangle_special_type(),
+            b'Y' => match self.next() {
+                b'a' => Ok(Node::new(NodeKind::AsyncAnnotation)),
+                ch => self.unknown_operator(ch),
+            },
+            b'Z' => {
+                let entity = self
+                    .pop_if(NodeKind::is_entity)
+                    .ok_or_else(|| self.error())?;
+                Ok(Node::with_children(NodeKind::Static, Some(entity)))
+            }
+            b'a' => self.demangle_any_generic_type(NodeKind::TypeAlias),
+            b'c' => self.pop_function_type(NodeKind::FunctionType),
+            b'd' => Ok(Node::new(NodeKind::VariadicMarker)),
+            b'f' => self.demangle_function_entity(),
+            b'h' => self.demangle_wrapper_type(NodeKind::Shared),
+            b'i' => self.demangle_subscript(),
+            b'l' => self.demangle_generic_signature(false),
+            b'm' => {
+                let ty = self.pop_type()?;
+                let metatype =
+                    Node::with_children(NodeKind::Metatype, Some(ty));
+                Ok(Node::ty(metatype))
+            }
+            b'n' => self.demangle_wrapper_type(NodeKind::Owned),
+            b'o' => self.demangle_operator_identifier(),
+            b'p' => Ok(Node::ty(self.demangle_protocol_list()?)),
+            b'q' => Ok(Node::ty(self.demangle_generic_param_index()?)),
+            b'r' => self.demangle_generic_signature(true),
+            b's' => Ok(Node::with_text(NodeKind::Module, "Swift")),
+            b't' => self.pop_tuple(),
+            b'u' => {
+                let sig = self
+                    .pop_kind(NodeKind::DependentGenericSignature)
+                    .ok_or_else(|| self.error())?;
+                let ty = self.pop_type()?;
+                Ok(Node::ty(Node::with_children(
+                    NodeKind::DependentGenericType,
+                    vec![sig, ty],
+                )))
+            }
+            b'v' => {
+                let variable = self.demangle_entity(NodeKind::Variable)?;
+                self.demangle_accessor(variable)
+            }
+            b'w' => self.demangle_value_witness(),
+            b'x' => Ok(Node::ty(dependent_generic_param_type(0, 0))),
+            b'y' => Ok(Node::new(NodeKind::EmptyList)),
+            b'z' => self.demangle_wrapper_type(NodeKind::InOut),
+            b'_' => Ok(Node::new(NodeKind::FirstElementMarker)),
+            b'.' => {
+                // IRGen uses `.<suffix>` to disambiguate clones of functions.
+                self.push_back();
+                let suffix = &self.text[self.pos..];
+                self.pos = self.text.len();
+                let suffix =
+                    std::str::from_utf8(suffix).map_err(|_| self.error())?;
+                Ok(Node::with_text(NodeKind::Suffix, suffix))
+            }
+            b'0'..=b'9' => {
+                self.push_back();
+                self.demangle_identifier()
+            }
+            ch @ 0x01..=0x17 => self.demangle_symbolic_reference(ch),
+            ch => self.unknown_operator(ch),
+        }
+    }
+
+    fn demangle_symbolic_reference(&mut self, control: u8) -> Result<Node> {
+        let start = self.pos - 1;
+        let offset = symbolic::read_offset(&self.text[self.pos..])
+            .ok_or_else(|| self.truncated())?;
+        let reference = SymbolicReference::new(control, offset, self.pos)
+            .ok_or_else(|| {
+                DemangleError::new(DemangleErrorKind::UnknownOperator, start)
+            })?;
+        self.pos += 4;
+
+        let resolved = self
+            .resolver
+            .as_mut()
+            .and_then(|resolve| resolve(&reference))
+            .ok_or_else(|| {
+                DemangleError::new(
+                    DemangleErrorKind::UnresolvedSymbolicReference,
+                    start,
+                )
+            })?;
+        // Types are substitutable even when referenced symbolically.
+        if reference.kind() == SymbolicReferenceKind::Context
+            && resolved.kind() == NodeKind::Type
+        {
+            self.add_substitution(&resolved);
+        }
+        Ok(resolved)
+    }
+
+    fn demangle_wrapper_type(&mut self, kind: NodeKind) -> Result<Node> {
+        let child = self.pop_type_and_get_child()?;
+        let wrapper = Node::with_children(kind, Some(Node::ty(child)));
+        Ok(Node::ty(wrapper))
+    }
+
+    fn demangle_any_generic_type(&mut self, kind: NodeKind) -> Result<Node> {
+        let name = self.pop_decl_name()?;
+        let context = self.pop_context()?;
+        let ty = Node::ty(Node::with_children(kind, vec![context, name]));
+        self.add_substitution(&ty);
+        Ok(ty)
+    }
+
+    fn demangle_extension_context(&mut self) -> Result<Node> {
+        let gen_sig = self.pop_kind(NodeKind::DependentGenericSignature);
+        let module = self.pop_module().ok_or_else(|| self.error())?;
+        let ty = self.pop_type_and_get_any_generic()?;
+        let mut ext =
+            Node::with_children(NodeKind::Extension, vec![module, ty]);
+        if let Some(gen_sig) = gen_sig {
+            ext.push(gen_sig);
+        }
+        Ok(ext)
+    }
+
+    fn demangle_builtin_type(&mut self) -> Result<Node> {
+        let name = match self.next() {
+            b'b' => "Builtin.BridgeObject".into(),
+            b'B' => "Builtin.UnsafeValueBuffer".into(),
+            b'f' => {
+                let bits = self.demangle_index()?.wrapping_sub(1);
+                format!("Builtin.FPIEEE{}", bits)
+            }
+            b'i' => {
+                let bits = self.demangle_index()?.wrapping_sub(1);
+                format!("Builtin.Int{}", bits)
+            }
+            b'I' => "Builtin.IntLiteral".into(),
+            b'v' => {
+                let elements = self.demangle_index()?.wrapping_sub(1);
+                let elt = self.pop_type_and_get_child()?;
+                let elt_name = match elt.text() {
+                    Some(name) if elt.kind() == NodeKind::BuiltinTypeName => {
+                        name.trim_start_matches("Builtin.")
+                    }
+                    _ => return self.fail(),
+                };
+                format!("Builtin.Vec{}x{}", elements, elt_name)
+            }
+            b'O' => "Builtin.UnknownObject".into(),
+            b'o' => "Builtin.NativeObject".into(),
+            b'p' => "Builtin.RawPointer".into(),
+            b't' => "Builtin.SILToken".into(),
+            b'w' => "Builtin.Word".into(),
+            ch => return self.unknown_operator(ch),
+        };
+        let ty = Node::ty(Node::with_text(NodeKind::BuiltinTypeName, name));
+        self.add_substitution(&ty);
+        Ok(ty)
+    }
+
+    // Functions -------------------------------------------------------------
+
+    fn pop_function_params(&mut self, kind: NodeKind) -> Result<Node> {
+        let params = if self.pop_kind(NodeKind::EmptyList).is_some() {
+            Node::ty(Node::new(NodeKind::Tuple))
+        } else {
+            self.pop_type()?
+        };
+        Ok(Node::with_children(kind, Some(params)))
+    }
+
+    fn pop_function_type(&mut self, kind: NodeKind) -> Result<Node> {
+        let mut func = Node::new(kind);
+        if let Some(throws) = self.pop_kind(NodeKind::ThrowsAnnotation) {
+            func.push(throws);
+        }
+        if let Some(async_) = self.pop_kind(NodeKind::AsyncAnnotation) {
+            func.push(async_);
+        }
+        func.push(self.pop_function_params(NodeKind::ArgumentTuple)?);
+        func.push(self.pop_function_params(NodeKind::ReturnType)?);
+        Ok(Node::ty(func))
+    }
+
+    /// Pops the argument labels of the function or subscript of type `ty`.
+    fn pop_function_param_labels(
+        &mut self,
+        ty: &mut Node,
+    ) -> Result<Option<Node>> {
+        if !self.old_function_types
+            && self.pop_kind(NodeKind::EmptyList).is_some()
+        {
+            return Ok(Some(Node::new(NodeKind::LabelList)));
+        }
+
+        let mut func = ty.unwrap_type_mut();
+        if func.kind() == NodeKind::DependentGenericType {
+            func = match func.children_mut().get_mut(1) {
+                Some(ty) => ty.unwrap_type_mut(),
+                None => return self.fail(),
+            };
+        }
+        if !func.kind().is_function_type() {
+            return Ok(None);
+        }
+
+        let args = func
+            .children_mut()
+            .iter_mut()
+            .find(|child| child.kind() == NodeKind::ArgumentTuple);
+        let params = match args.and_then(|args| args.children_mut().first_mut())
+        {
+            Some(params) => params.unwrap_type_mut(),
+            None => return self.fail(),
+        };
+        if self.old_function_types {
+            return Ok(take_tuple_labels(params));
+        }
+
+        let num_params = match params.kind() {
+            NodeKind::Tuple => params.children().len(),
+            _ => 1,
+        };
+        if num_params == 0 {
+            return Ok(None);
+        }
+
+        let mut labels = Node::new(NodeKind::LabelList);
+        for _ in 0..num_params {
+            let label = self.pop_if(|kind| {
+                kind == NodeKind::Identifier
+                    || kind == NodeKind::FirstElementMarker
+            });
+            match label {
+                Some(label) => labels.push(label),
+                None => return self.fail(),
+            }
+        }
+        labels.children_mut().reverse();
+        Ok(Some(labels))
+    }
+
+    fn demangle_plain_function(&mut self) -> Result<Node> {
+        let gen_sig = self.pop_kind(NodeKind::DependentGenericSignature);
+        let mut ty = self.pop_function_type(NodeKind::FunctionType)?;
+        let labels = self.pop_function_param_labels(&mut ty)?;
+        if let Some(gen_sig) = gen_sig {
+            ty = Node::ty(Node::with_children(
+                NodeKind::DependentGenericType,
+                vec![gen_sig, ty],
+            ));
+        }
+        let name = self.pop_decl_name()?;
+        let context = self.pop_context()?;
+
+        let mut func =
+            Node::with_children(NodeKind::Function, vec![context, name]);
+        if let Some(labels) = labels {
+            func.push(labels);
+        }
+        func.push(ty);
+        Ok(func)
+    }
+
+    fn demangle_entity(&mut self, kind: NodeKind) -> Result<Node> {
+        let mut ty = self.pop_type()?;
+        let labels = self.pop_function_param_labels(&mut ty)?;
+        let name = self.pop_decl_name()?;
+        let context = self.pop_context()?;
+
+        let mut entity = Node::with_children(kind, vec![context, name]);
+        if let Some(labels) = labels {
+            entity.push(labels);
+        }
+        entity.push(ty);
+        Ok(entity)
+    }
+
+    fn demangle_subscript(&mut self) -> Result<Node> {
+        let private_name = self.pop_kind(NodeKind::PrivateDeclName);
+        let mut ty = self.pop_type()?;
+        let labels = self.pop_function_param_labels(&mut ty)?;
+        let context = self.pop_context()?;
+
+        let mut subscript =
+            Node::with_children(NodeKind::Subscript, Some(context));
+        if let Some(labels) = labels {
+            subscript.push(labels);
+        }
+        subscript.push(ty);
+        if let Some(private_name) = private_name {
+            subscript.push(private_name);
+        }
+        self.demangle_accessor(subscript)
+    }
+
+    fn demangle_accessor(&mut self, storage: Node) -> Result<Node> {
+        let kind = match self.next() {
+            b'm' => NodeKind::MaterializeForSet,
+            b's' => NodeKind::Setter,
+            b'g' => NodeKind::Getter,
+            b'G' => NodeKind::GlobalGetter,
+            b'w' => NodeKind::WillSet,
+            b'W' => NodeKind::DidSet,
+            b'r' => NodeKind::ReadAccessor,
+            b'M' => NodeKind::ModifyAccessor,
+            b'a' => match self.next() {
+                b'O' => NodeKind::OwningMutableAddressor,
+                b'o' => NodeKind::NativeOwningMutableAddressor,
+                b'p' => NodeKind::NativePinningMutableAddressor,
+                b'u' => NodeKind::UnsafeMutableAddressor,
+                ch => return self.unknown_operator(ch),
+            },
+            b'l' => match self.next() {
+                b'O' => NodeKind::OwningAddressor,
+                b'o' => NodeKind::NativeOwningAddressor,
+                b'p' => NodeKind::NativePinningAddressor,
+                b'u' => NodeKind::UnsafeAddressor,
+                ch => return self.unknown_operator(ch),
+            },
+            // Pseudo-accessor referring to the storage itself.
+            b'p' => return Ok(storage),
+            ch => return self.unknown_operator(ch),
+        };
+        Ok(Node::with_children(kind, Some(storage)))
+    }
+
+    fn demangle_function_entity(&mut self) -> Result<Node> {
+        enum Args {
+            None,
+            TypeAndMaybePrivateName,
+            TypeAndIndex,
+            Index,
+        }
+
+        let (args, kind) = match self.next() {
+            b'D' => (Args::None, NodeKind::Deallocator),
+            b'd' => (Args::None, NodeKind::Destructor),
+            b'E' => (Args::None, NodeKind::IVarDestroyer),
+            b'e' => (Args::None, NodeKind::IVarInitializer),
+            b'i' => (Args::None, NodeKind::Initializer),
+            b'C' => (Args::TypeAndMaybePrivateName, NodeKind::Allocator),
+            b'c' => (Args::TypeAndMaybePrivateName, NodeKind::Constructor),
+            b'U' => (Args::TypeAndIndex, NodeKind::ExplicitClosure),
+            b'u' => (Args::TypeAndIndex, NodeKind::ImplicitClosure),
+            b'A' => (Args::Index, NodeKind::DefaultArgumentInitializer),
+            ch => return self.unknown_operator(ch),
+        };
+
+        let mut trailing = Vec::new();
+        match args {
+            Args::None => {}
+            Args::TypeAndMaybePrivateName => {
+                let private_name = self.pop_kind(NodeKind::PrivateDeclName);
+                let mut ty = self.pop_type()?;
+                if let Some(labels) = self.pop_function_param_labels(&mut ty)? {
+                    trailing.push(labels);
+                }
+                trailing.push(ty);
+                trailing.extend(private_name);
+            }
+            Args::TypeAndIndex => {
+                let index = self.demangle_index_node()?;
+                let ty = self.pop_type()?;
+                trailing.push(index);
+                trailing.push(ty);
+            }
+            Args::Index => trailing.push(self.demangle_index_node()?),
+        }
+
+        let mut entity = Node::with_children(kind, Some(self.pop_context()?));
+        entity.children_mut().extend(trailing);
+        Ok(entity)
+    }
+
+    fn pop_tuple(&mut self) -> Result<Node> {
+        let mut tuple = Node::new(NodeKind::Tuple);
+        if self.pop_kind(NodeKind::EmptyList).is_none() {
+            loop {
+                let first =
+                    self.pop_kind(NodeKind::FirstElementMarker).is_some();
+                let mut element = Node::new(NodeKind::TupleElement);
+                let variadic = self.pop_kind(NodeKind::VariadicMarker);
+                if let Some(mut label) = self.pop_kind(NodeKind::Identifier) {
+                    label.set_kind(NodeKind::TupleElementName);
+                    element.push(label);
+                }
+                element.push(self.pop_type()?);
+                element.children_mut().extend(variadic);
+                tuple.push(element);
+                if first {
+                    break;
+                }
+            }
+            tuple.children_mut().reverse();
+        }
+        Ok(Node::ty(tuple))
+    }
+
+    fn demangle_bound_generic_type(&mut self) -> Result<Node> {
+        let type_lists = self.pop_bound_generic_args()?;
+        let nominal = self.pop_type_and_get_any_generic()?;
+        let bound =
+            self.demangle_bound_generic_args(nominal, &type_lists, 0)?;
+        let ty = Node::ty(bound);
+        self.add_substitution(&ty);
+        Ok(ty)
+    }
+
+    /// Pops the lists of generic arguments of a bound generic type, from the
+    /// innermost type to the outermost.
+    fn pop_bound_generic_args(&mut self) -> Result<Vec<Node>> {
+        let mut type_lists = Vec::new();
+        loop {
+            let mut list = Node::new(NodeKind::TypeList);
+            while let Some(ty) = self.pop_kind(NodeKind::Type) {
+                list.push(ty);
+            }
+            list.children_mut().reverse();
+            type_lists.push(list);
+
+            if self.pop_kind(NodeKind::EmptyList).is_some() {
+                break;
+            }
+            if self.pop_kind(NodeKind::FirstElementMarker).is_none() {
+                return self.fail();
+            }
+        }
+        Ok(type_lists)
+    }
+
+    fn demangle_bound_generic_args(
+        &self,
+        nominal: Node,
+        type_lists: &[Node],
+        index: usize,
+    ) -> Result<Node> {
+        let args = match type_lists.get(index) {
+            Some(args) => args.clone(),
+            None => return self.fail(),
+        };
+
+        let mut nominal = nominal;
+        if index + 1 < type_lists.len() {
+            let children = nominal.children_mut();
+            if children.is_empty() {
+                return self.fail();
+            }
+            let context = children.remove(0);
+            let bound_parent = if context.kind() == NodeKind::Extension {
+                let mut ext = context.into_children();
+                if ext.len() < 2 {
+                    return self.fail();
+                }
+                let ty = ext.remove(1);
+                let ty = self.demangle_bound_generic_args(
+                    ty,
+                    type_lists,
+                    index + 1,
+                )?;
+                ext.insert(1, ty);
+                Node::with_children(NodeKind::Extension, ext)
+            } else {
+                self.demangle_bound_generic_args(
+                    context,
+                    type_lists,
+                    index + 1,
+                )?
+            };
+            children.insert(0, bound_parent);
+        }
+
+        if args.children().is_empty() {
+            return Ok(nominal);
+        }
+
+        let kind = match nominal.kind() {
+            NodeKind::Class => NodeKind::BoundGenericClass,
+            NodeKind::Structure => NodeKind::BoundGenericStructure,
+            NodeKind::Enum => NodeKind::BoundGenericEnum,
+            NodeKind::Protocol => NodeKind::BoundGenericProtocol,
+            NodeKind::TypeAlias => NodeKind::BoundGenericTypeAlias,
+            _ => return self.fail(),
+        };
+        Ok(Node::with_children(kind, vec![Node::ty(nominal), args]))
+    }
+
+    fn demangle_protocol_list(&mut self) -> Result<Node> {
+        let mut list = Node::new(NodeKind::TypeList);
+        if self.pop_kind(NodeKind::EmptyList).is_none() {
+            loop {
+                let first =
+                    self.pop_kind(NodeKind::FirstElementMarker).is_some();
+                list.push(self.pop_protocol()?);
+                if first {
+                    break;
+                }
+            }
+            list.children_mut().reverse();
+        }
+        Ok(Node::with_children(NodeKind::ProtocolList, Some(list)))
+    }
+
+    fn demangle_special_type(&mut self) -> Result<Node> {
+        let kind = match self.next() {
+            b'E' => {
+                return self.pop_function_type(NodeKind::NoEscapeFunctionType)
+            }
+            b'A' => {
+                return self
+                    .pop_function_type(NodeKind::EscapingAutoClosureType)
+            }
+            b'f' => return self.pop_function_type(NodeKind::ThinFunctionType),
+            b'K' => return self.pop_function_type(NodeKind::AutoClosureType),
+            b'B' => return self.pop_function_type(NodeKind::ObjCBlock),
+            b'C' => return self.pop_function_type(NodeKind::CFunctionPointer),
+            b'o' => NodeKind::Unowned,
+            b'u' => NodeKind::Unmanaged,
+            b'w' => NodeKind::Weak,
+            b'D' => NodeKind::DynamicSelf,
+            b'p' => NodeKind::ExistentialMetatype,
+            b'c' => {
+                let superclass = self.pop_type()?;
+                let protocols = self.demangle_protocol_list()?;
+                return Ok(Node::ty(Node::with_children(
+                    NodeKind::ProtocolListWithClass,
+                    vec![protocols, superclass],
+                )));
+            }
+            b'l' => {
+                let protocols = self.demangle_protocol_list()?;
+                return Ok(Node::ty(Node::with_children(
+                    NodeKind::ProtocolListWithAnyObject,
+                    Some(protocols),
+                )));
+            }
+            b'S' => return self.demangle_sugared_type(),
+            ch => return self.unknown_operator(ch),
+        };
+        let ty = self.pop_type()?;
+        Ok(Node::ty(Node::with_children(kind, Some(ty))))
+    }
+
+    fn demangle_sugared_type(&mut self) -> Result<Node> {
+        let sugar = match self.next() {
+            b'q' => {
+                let ty = self.pop_type()?;
+                Node::with_children(NodeKind::SugaredOptional, Some(ty))
+            }
+            b'a' => {
+                let ty = self.pop_type()?;
+                Node::with_children(NodeKind::SugaredArray, Some(ty))
+            }
+            b'D' => {
+                let value = self.pop_type()?;
+                let key = self.pop_type()?;
+                Node::with_children(
+                    NodeKind::SugaredDictionary,
+                    vec![key, value],
+                )
+            }
+            b'p' => {
+                let ty = self.pop_type()?;
+                Node::with_children(NodeKind::SugaredParen, Some(ty))
+            }
+            ch => return self.unknown_operator(ch),
+        };
+        Ok(Node::ty(sugar))
+    }
+}
+
+impl<'a> Demangler<'a> {
+    // Generics --------------------------------------------------------------
+
+    fn demangle_generic_param_index(&mut self) -> Result<Node> {
+        if self.next_if(b'd') {
+            let depth = self.demangle_index()? + 1;
+            let index = self.demangle_index()?;
+            return Ok(dependent_generic_param_type(depth, index));
+        }
+        if self.next_if(b'z') {
+            return Ok(dependent_generic_param_type(0, 0));
+        }
+        let index = self.demangle_index()? + 1;
+        Ok(dependent_generic_param_type(0, index))
+    }
+
+    fn demangle_generic_signature(
+        &mut self,
+        has_param_counts: bool,
+    ) -> Result<Node> {
+        let mut sig = Node::new(NodeKind::DependentGenericSignature);
+        if has_param_counts {
+            while !self.next_if(b'l') {
+                let count = if self.next_if(b'z') {
+                    0
+                } else {
+                    self.demangle_index()? + 1
+                };
+                sig.push(Node::with_index(
+                    NodeKind::DependentGenericParamCount,
+                    count,
+                ));
+            }
+        } else {
+            sig.push(Node::with_index(NodeKind::DependentGenericParamCount, 1));
+        }
+
+        let num_counts = sig.children().len();
+        while let Some(req) = self.pop_if(NodeKind::is_requirement) {
+            sig.push(req);
+        }
+        sig.children_mut()[num_counts..].reverse();
+        Ok(sig)
+    }
+
+    fn demangle_generic_requirement(&mut self) -> Result<Node> {
+        enum TypeKind {
+            Generic,
+            Assoc,
+            CompoundAssoc,
+            Substitution,
+        }
+        enum ConstraintKind {
+            Protocol,
+            BaseClass,
+            SameType,
+            Layout,
+        }
+
+        let (constraint_kind, type_kind) = match self.next() {
+            b'c' => (ConstraintKind::BaseClass, TypeKind::Assoc),
+            b'C' => (ConstraintKind::BaseClass, TypeKind::CompoundAssoc),
+            b'b' => (ConstraintKind::BaseClass, TypeKind::Generic),
+            b'B' => (ConstraintKind::BaseClass, TypeKind::Substitution),
+            b't' => (ConstraintKind::SameType, TypeKind::Assoc),
+            b'T' => (ConstraintKind::SameType, TypeKind::CompoundAssoc),
+            b's' => (ConstraintKind::SameType, TypeKind::Generic),
+            b'S' => (ConstraintKind::SameType, TypeKind::Substitution),
+            b'm' => (ConstraintKind::Layout, TypeKind::Assoc),
+            b'M' => (ConstraintKind::Layout, TypeKind::CompoundAssoc),
+            b'l' => (ConstraintKind::Layout, TypeKind::Generic),
+            b'L' => (ConstraintKind::Layout, TypeKind::Substitution),
+            b'p' => (ConstraintKind::Protocol, TypeKind::Assoc),
+            b'P' => (ConstraintKind::Protocol, TypeKind::CompoundAssoc),
+            b'Q' => (ConstraintKind::Protocol, TypeKind::Substitution),
+            0 => return self.fail(),
+            _ => {
+                self.push_back();
+                (ConstraintKind::Protocol, TypeKind::Generic)
+            }
+        };
+
+        let constrained = match type_kind {
+            TypeKind::Generic => Node::ty(self.demangle_generic_param_index()?),
+            TypeKind::Assoc => {
+                let base = self.demangle_generic_param_index()?;
+                let ty = self.demangle_associated_type_simple(Some(base))?;
+                self.add_substitution(&ty);
+                ty
+            }
+            TypeKind::CompoundAssoc => {
+                let base = self.demangle_generic_param_index()?;
+                let ty = self.demangle_associated_type_compound(Some(base))?;
+                self.add_substitution(&ty);
+                ty
+            }
+            TypeKind::Substitution => self.pop_type()?,
+        };
+
+        let (kind, constraint) = match constraint_kind {
+            ConstraintKind::Protocol => (
+                NodeKind::DependentGenericConformanceRequirement,
+                self.pop_protocol()?,
+            ),
+            ConstraintKind::BaseClass => (
+                NodeKind::DependentGenericConformanceRequirement,
+                self.pop_type()?,
+            ),
+            ConstraintKind::SameType => (
+                NodeKind::DependentGenericSameTypeRequirement,
+                self.pop_type()?,
+            ),
+            ConstraintKind::Layout => {
+                let name = match self.next() {
+                    b'U' => "_UnknownLayout",
+                    b'R' => "_RefCountedObject",
+                    b'N' => "_NativeRefCountedObject",
+                    b'C' => "AnyObject",
+                    b'D' => "_NativeClass",
+                    b'T' => "_Trivial",
+                    ch => return self.unknown_operator(ch),
+                };
+                (
+                    NodeKind::DependentGenericLayoutRequirement,
+                    Node::with_text(NodeKind::Identifier, name),
+                )
+            }
+        };
+        Ok(Node::with_children(kind, vec![constrained, constraint]))
+    }
+
+    fn pop_assoc_type_name(&mut self) -> Result<Node> {
+        let proto = self.pop_kind(NodeKind::Type);
+        if let Some(proto) = &proto {
+            if proto.unwrap_type().kind() != NodeKind::Protocol {
+                return self.fail();
+            }
+        }
+        let mut name = self
+            .pop_kind(NodeKind::Identifier)
+            .ok_or_else(|| self.error())?;
+        name.set_kind(NodeKind::DependentAssociatedTypeRef);
+        name.children_mut().extend(proto);
+        Ok(name)
+    }
+
+    fn pop_assoc_type_path(&mut self) -> Result<Vec<Node>> {
+        let mut path = Vec::new();
+        loop {
+            let first = self.pop_kind(NodeKind::FirstElementMarker).is_some();
+            path.push(self.pop_assoc_type_name()?);
+            if first {
+                break;
+            }
+        }
+        path.reverse();
+        Ok(path)
+    }
+
+    fn demangle_associated_type_simple(
+        &mut self,
+        base: Option<Node>,
+    ) -> Result<Node> {
+        let name = self.pop_assoc_type_name()?;
+        let base = match base {
+            Some(base) => Node::ty(base),
+            None => self.pop_type()?,
+        };
+        Ok(Node::ty(Node::with_children(
+            NodeKind::DependentMemberType,
+            vec![base, name],
+        )))
+    }
+
+    fn demangle_associated_type_compound(
+        &mut self,
+        base: Option<Node>,
+    ) -> Result<Node> {
+        let path = self.pop_assoc_type_path()?;
+        let mut base = match base {
+            Some(base) => Node::ty(base),
+            None => self.pop_type()?,
+        };
+        for name in path {
+            base = Node::ty(Node::with_children(
+                NodeKind::DependentMemberType,
+                vec![base, name],
+            ));
+        }
+        Ok(base)
+    }
+
+    fn demangle_archetype(&mut self) -> Result<Node> {
+        let ty = match self.next() {
+            b'x' => self.demangle_associated_type_simple(None)?,
+            b'X' => self.demangle_associated_type_compound(None)?,
+            b'y' => {
+                let base = self.demangle_generic_param_index()?;
+                self.demangle_associated_type_simple(Some(base))?
+            }
+            b'Y' => {
+                let base = self.demangle_generic_param_index()?;
+                self.demangle_associated_type_compound(Some(base))?
+            }
+            b'z' => {
+                let base = dependent_generic_param_type(0, 0);
+                self.demangle_associated_type_simple(Some(base))?
+            }
+            b'Z' => {
+                let base = dependent_generic_param_type(0, 0);
+                self.demangle_associated_type_compound(Some(base))?
+            }
+            b'r' => return Ok(Node::ty(Node::new(NodeKind::OpaqueReturnType))),
+            b'O' => {
+                let context = self.pop_context()?;
+                return Ok(Node::with_children(
+                    NodeKind::OpaqueReturnTypeOf,
+                    Some(context),
+                ));
+            }
+            b'o' => {
+                let index = self.demangle_index()?;
+                let mut type_lists = self.pop_bound_generic_args()?;
+                let name = self.pop_any()?;
+                type_lists.reverse();
+                Node::ty(Node::with_children(
+                    NodeKind::OpaqueType,
+                    vec![
+                        name,
+                        Node::with_index(NodeKind::Index, index),
+                        Node::with_children(NodeKind::TypeList, type_lists),
+                    ],
+                ))
+            }
+            ch => return self.unknown_operator(ch),
+        };
+        self.add_substitution(&ty);
+        Ok(ty)
+    }
+
+    // SIL function types ----------------------------------------------------
+
+    fn demangle_impl_param_convention(&mut self) -> Option<Node> {
+        let convention = match self.peek() {
+            b'i' => "@in",
+            b'c' => "@in_constant",
+            b'l' => "@inout",
+            b'b' => "@inout_aliasable",
+            b'n' => "@in_guaranteed",
+            b'x' => "@owned",
+            b'g' => "@guaranteed",
+            b'e' => "@deallocating",
+            b'y' => "@unowned",
+            _ => return None,
+        };
+        self.pos += 1;
+        let convention = Node::with_text(NodeKind::ImplConvention, convention);
+        Some(Node::with_children(
+            NodeKind::ImplParameter,
+            Some(convention),
+        ))
+    }
+
+    fn demangle_impl_result_convention(
+        &mut self,
+        kind: NodeKind,
+    ) -> Option<Node> {
+        let convention = match self.peek() {
+            b'r' => "@out",
+            b'o' => "@owned",
+            b'd' => "@unowned",
+            b'u' => "@unowned_inner_pointer",
+            b'a' => "@autoreleased",
+            _ => return None,
+        };
+        self.pos += 1;
+        let convention = Node::with_text(NodeKind::ImplConvention, convention);
+        Some(Node::with_children(kind, Some(convention)))
+    }
+
+    fn demangle_impl_function_type(&mut self) -> Result<Node> {
+        let mut ty = Node::new(NodeKind::ImplFunctionType);
+        let gen_sig = self.pop_kind(NodeKind::DependentGenericSignature);
+
+        if self.next_if(b'e') {
+            ty.push(Node::new(NodeKind::ImplEscaping));
+        }
+
+        let callee = match self.next() {
+            b'y' => "@callee_unowned",
+            b'g' => "@callee_guaranteed",
+            b'x' => "@callee_owned",
+            b't' => "@convention(thin)",
+            ch => return self.unknown_operator(ch),
+        };
+        ty.push(Node::with_text(NodeKind::ImplConvention, callee));
+
+        let attr = match self.peek() {
+            b'B' => Some("@convention(block)"),
+            b'C' => Some("@convention(c)"),
+            b'M' => Some("@convention(method)"),
+            b'O' => Some("@convention(objc_method)"),
+            b'K' => Some("@convention(closure)"),
+            b'W' => Some("@convention(witness_method)"),
+            _ => None,
+        };
+        if let Some(attr) = attr {
+            self.pos += 1;
+            ty.push(Node::with_text(NodeKind::ImplFunctionAttribute, attr));
+        }
+        ty.children_mut().extend(gen_sig);
+
+        let mut num_types = 0;
+        while let Some(param) = self.demangle_impl_param_convention() {
+            ty.push(param);
+            num_types += 1;
+        }
+        while let Some(result) =
+            self.demangle_impl_result_convention(NodeKind::ImplResult)
+        {
+            ty.push(result);
+            num_types += 1;
+        }
+        if self.next_if(b'z') {
+            let error = self
+                .demangle_impl_result_convention(NodeKind::ImplErrorResult)
+                .ok_or_else(|| self.error())?;
+            ty.push(error);
+            num_types += 1;
+        }
+        if !self.next_if(b'_') {
+            return self.fail();
+        }
+
+        let len = ty.children().len();
+        for i in 0..num_types {
+            let conv_ty = self.pop_type()?;
+            ty.children_mut()[len - i - 1].push(conv_ty);
+        }
+        Ok(Node::ty(ty))
+    }
+
+    // Globals ---------------------------------------------------------------
+
+    fn with_popped_type(&mut self, kind: NodeKind) -> Result<Node> {
+        let ty = self.pop_type()?;
+        Ok(Node::with_children(kind, Some(ty)))
+    }
+
+    fn with_popped_entity(&mut self, kind: NodeKind) -> Result<Node> {
+        let entity = self
+            .pop_if(NodeKind::is_entity)
+            .ok_or_else(|| self.error())?;
+        Ok(Node::with_children(kind, Some(entity)))
+    }
+
+    fn with_popped_conformance(&mut self, kind: NodeKind) -> Result<Node> {
+        let conformance = self.pop_protocol_conformance()?;
+        Ok(Node::with_children(kind, Some(conformance)))
+    }
+
+    fn demangle_metatype(&mut self) -> Result<Node> {
+        match self.next() {
+            b'a' => self.with_popped_type(NodeKind::TypeMetadataAccessFunction),
+            b'B' => self.with_popped_type(
+                NodeKind::ReflectionMetadataBuiltinDescriptor,
+            ),
+            b'F' => self
+                .with_popped_type(NodeKind::ReflectionMetadataFieldDescriptor),
+            b'A' => self.with_popped_conformance(
+                NodeKind::ReflectionMetadataAssocTypeDescriptor,
+            ),
+            b'C' => {
+                let ty = self.pop_type_and_get_any_generic()?;
+                Ok(Node::with_children(
+                    NodeKind::ReflectionMetadataSuperclassDescriptor,
+                    Some(ty),
+                ))
+            }
+            b'f' => self.with_popped_type(NodeKind::FullTypeMetadata),
+            b'i' => self
+                .with_popped_type(NodeKind::TypeMetadataInstantiationFunction),
+            b'I' => {
+                self.with_popped_type(NodeKind::TypeMetadataInstantiationCache)
+            }
+            b'l' => self.with_popped_type(
+                NodeKind::TypeMetadataSingletonInitializationCache,
+            ),
+            b'L' => self.with_popped_type(NodeKind::TypeMetadataLazyCache),
+            b'm' => self.with_popped_type(NodeKind::Metaclass),
+            b'n' => self.with_popped_type(NodeKind::NominalTypeDescriptor),
+            b'o' => self.with_popped_type(NodeKind::ClassMetadataBaseOffset),
+            b'p' => {
+                let proto = self.pop_protocol()?;
+                Ok(Node::with_children(
+                    NodeKind::ProtocolDescriptor,
+                    Some(proto),
+                ))
+            }
+            b'P' => self.with_popped_type(NodeKind::GenericTypeMetadataPattern),
+            b'r' => {
+                self.with_popped_type(NodeKind::TypeMetadataCompletionFunction)
+            }
+            b's' => self.with_popped_type(NodeKind::ObjCResilientClassStub),
+            b'u' => self.with_popped_type(NodeKind::MethodLookupFunction),
+            b'V' => self.with_popped_entity(NodeKind::PropertyDescriptor),
+            b'c' => self.with_popped_conformance(
+                NodeKind::ProtocolConformanceDescriptor,
+            ),
+            b'X' => match self.next() {
+                b'E' => {
+                    let context = self.pop_context()?;
+                    Ok(Node::with_children(
+                        NodeKind::ExtensionDescriptor,
+                        Some(context),
+                    ))
+                }
+                b'M' => {
+                    let module =
+                        self.pop_module().ok_or_else(|| self.error())?;
+                    Ok(Node::with_children(
+                        NodeKind::ModuleDescriptor,
+                        Some(module),
+                    ))
+                }
+                ch => self.unknown_operator(ch),
+            },
+            ch => self.unknown_operator(ch),
+        }
+    }
+
+    fn demangle_witness(&mut self) -> Result<Node> {
+        match self.next() {
+            b'V' => self.with_popped_type(NodeKind::ValueWitnessTable),
+            b'v' => {
+                let directness = match self.next() {
+                    b'd' => 0,
+                    b'i' => 1,
+                    ch => return self.unknown_operator(ch),
+                };
+                let entity = self
+                    .pop_if(NodeKind::is_entity)
+                    .ok_or_else(|| self.error())?;
+                Ok(Node::with_children(
+                    NodeKind::FieldOffset,
+                    vec![
+                        Node::with_index(NodeKind::Directness, directness),
+                        entity,
+                    ],
+                ))
+            }
+            b'P' => {
+                self.with_popped_conformance(NodeKind::ProtocolWitnessTable)
+            }
+            b'p' => self
+                .with_popped_conformance(NodeKind::ProtocolWitnessTablePattern),
+            b'G' => self
+                .with_popped_conformance(NodeKind::GenericProtocolWitnessTable),
+            b'I' => self.with_popped_conformance(
+                NodeKind::GenericProtocolWitnessTableInstantiationFunction,
+            ),
+            b'r' => self.with_popped_conformance(
+                NodeKind::ResilientProtocolWitnessTable,
+            ),
+            b'a' => self.with_popped_conformance(
+                NodeKind::ProtocolWitnessTableAccessor,
+            ),
+            b'l' | b'L' => {
+                self.push_back();
+                let kind = match self.next() {
+                    b'l' => NodeKind::LazyProtocolWitnessTableAccessor,
+                    _ => NodeKind::LazyProtocolWitnessTableCacheVariable,
+                };
+                let conformance = self.pop_protocol_conformance()?;
+                let ty = self.pop_type()?;
+                Ok(Node::with_children(kind, vec![ty, conformance]))
+            }
+            b'T' => {
+                let proto = self.pop_type()?;
+                let path = self.pop_assoc_type_path()?;
+                let conformance = self.pop_protocol_conformance()?;
+                Ok(Node::with_children(
+                    NodeKind::AssociatedTypeWitnessTableAccessor,
+                    vec![
+                        conformance,
+                        Node::with_children(NodeKind::AssocTypePath, path),
+                        proto,
+                    ],
+                ))
+            }
+            b'C' => self.with_popped_entity(NodeKind::EnumCase),
+            b't' => {
+                let name = self.pop_decl_name()?;
+                let conformance = self.pop_protocol_conformance()?;
+                Ok(Node::with_children(
+                    NodeKind::AssociatedTypeMetadataAccessor,
+                    vec![conformance, name],
+                ))
+            }
+            ch => self.unknown_operator(ch),
+        }
+    }
+
+    fn demangle_value_witness(&mut self) -> Result<Node> {
+        let code = [self.next(), self.next()];
+        let kind =
+            ValueWitnessKind::from_code(code).ok_or_else(|| self.error())?;
+        let ty = self.pop_type()?;
+        Ok(Node::with_children(
+            NodeKind::ValueWitness,
+            vec![Node::with_index(NodeKind::Index, kind as u64), ty],
+        ))
+    }
+
+    fn demangle_thunk_or_specialization(&mut self) -> Result<Node> {
+        match self.next() {
+            b'c' => self.with_popped_entity(NodeKind::CurryThunk),
+            b'j' => self.with_popped_entity(NodeKind::DispatchThunk),
+            b'q' => self.with_popped_entity(NodeKind::MethodDescriptor),
+            b'L' => {
+                let proto = self.pop_protocol()?;
+                Ok(Node::with_children(
+                    NodeKind::ProtocolRequirementsBaseDescriptor,
+                    Some(proto),
+                ))
+            }
+            b'l' => {
+                let name = self.pop_assoc_type_name()?;
+                Ok(Node::with_children(
+                    NodeKind::AssociatedTypeDescriptor,
+                    Some(name),
+                ))
+            }
+            b'n' => {
+                let requirement = self.pop_protocol()?;
+                let path = self.pop_assoc_type_path()?;
+                let proto = self.pop_type()?;
+                Ok(Node::with_children(
+                    NodeKind::AssociatedConformanceDescriptor,
+                    vec![
+                        proto,
+                        Node::with_children(NodeKind::AssocTypePath, path),
+                        requirement,
+                    ],
+                ))
+            }
+            b'v' => {
+                let index = self.demangle_index()?;
+                Ok(Node::with_index(NodeKind::OutlinedVariable, index))
+            }
+            b'o' => Ok(Node::new(NodeKind::ObjCAttribute)),
+            b'O' => Ok(Node::new(NodeKind::NonObjCAttribute)),
+            b'D' => Ok(Node::new(NodeKind::DynamicAttribute)),
+            b'd' => Ok(Node::new(NodeKind::DirectMethodReferenceAttribute)),
+            b'a' => Ok(Node::new(NodeKind::PartialApplyObjCForwarder)),
+            b'A' => Ok(Node::new(NodeKind::PartialApplyForwarder)),
+            b'm' => Ok(Node::new(NodeKind::MergedFunction)),
+            b'X' => Ok(Node::new(NodeKind::DynamicallyReplaceableFunctionVar)),
+            b'x' => Ok(Node::new(NodeKind::DynamicallyReplaceableFunctionKey)),
+            b'I' => Ok(Node::new(NodeKind::DynamicallyReplaceableFunctionImpl)),
+            b'V' => {
+                let base = self.pop_any()?;
+                let derived = self.pop_any()?;
+                if !base.kind().is_entity() || !derived.kind().is_entity() {
+                    return self.fail();
+                }
+                Ok(Node::with_children(
+                    NodeKind::VTableThunk,
+                    vec![derived, base],
+                ))
+            }
+            b'W' => {
+                let entity = self
+                    .pop_if(NodeKind::is_entity)
+                    .ok_or_else(|| self.error())?;
+                let conformance = self.pop_protocol_conformance()?;
+                Ok(Node::with_children(
+                    NodeKind::ProtocolWitness,
+                    vec![conformance, entity],
+                ))
+            }
+            ch @ b'R' | ch @ b'r' => {
+                let mut thunk = Node::new(if ch == b'R' {
+                    NodeKind::ReabstractionThunkHelper
+                } else {
+                    NodeKind::ReabstractionThunk
+                });
+                if let Some(gen_sig) =
+                    self.pop_kind(NodeKind::DependentGenericSignature)
+                {
+                    thunk.push(gen_sig);
+                }
+                let to = self.pop_type()?;
+                let from = self.pop_type()?;
+                thunk.push(from);
+                thunk.push(to);
+                Ok(thunk)
+            }
+            b'g' => self.demangle_generic_specialization(
+                NodeKind::GenericSpecialization,
+            ),
+            b'G' => self.demangle_generic_specialization(
+                NodeKind::GenericSpecializationNotReAbstracted,
+            ),
+            b'f' => self.demangle_function_specialization(),
+            ch => self.unknown_operator(ch),
+        }
+    }
+
+    fn demangle_spec_attributes(&mut self, kind: NodeKind) -> Result<Node> {
+        let mut spec = Node::new(kind);
+        if self.next_if(b'q') {
+            spec.push(Node::new(NodeKind::IsSerialized));
+        }
+        let pass_id = match self.next() {
+            ch @ b'0'..=b'9' => u64::from(ch - b'0'),
+            ch => return self.unknown_operator(ch),
+        };
+        spec.push(Node::with_index(NodeKind::SpecializationPassID, pass_id));
+        Ok(spec)
+    }
+
+    fn demangle_generic_specialization(
+        &mut self,
+        kind: NodeKind,
+    ) -> Result<Node> {
+        let mut spec = self.demangle_spec_attributes(kind)?;
+        let args = self.pop_type_list()?;
+        for arg in args.into_children() {
+            spec.push(Node::with_children(
+                NodeKind::GenericSpecializationParam,
+                Some(arg),
+            ));
+        }
+        Ok(spec)
+    }
+
+    fn demangle_function_specialization(&mut self) -> Result<Node> {
+        let mut spec = self.demangle_spec_attributes(
+            NodeKind::FunctionSignatureSpecialization,
+        )?;
+        while !self.next_if(b'_') {
+            let param = self.demangle_func_spec_param(
+                NodeKind::FunctionSignatureSpecializationParam,
+            )?;
+            spec.push(param);
+        }
+        if !self.next_if(b'n') {
+            let ret = self.demangle_func_spec_param(
+                NodeKind::FunctionSignatureSpecializationReturn,
+            )?;
+            spec.push(ret);
+        }
+
+        // Propagated constants and closures were mangled before the
+        // specialization, so they're popped in reverse order.
+        for param in spec.children_mut().iter_mut().rev() {
+            if param.kind() != NodeKind::FunctionSignatureSpecializationParam {
+                continue;
+            }
+            let kind = match param.child(0).and_then(Node::index) {
+                Some(kind) => kind,
+                None => continue,
+            };
+            match kind {
+                spec_param::CONSTANT_PROP_FUNCTION
+                | spec_param::CONSTANT_PROP_GLOBAL
+                | spec_param::CONSTANT_PROP_STRING
+                | spec_param::CLOSURE_PROP => {
+                    let mut types = Vec::new();
+                    while let Some(ty) = self.pop_kind(NodeKind::Type) {
+                        if kind != spec_param::CLOSURE_PROP {
+                            return self.fail();
+                        }
+                        types.push(ty);
+                    }
+                    let name = self
+                        .pop_kind(NodeKind::Identifier)
+                        .ok_or_else(|| self.error())?;
+                    let mut text = name.text().unwrap_or("");
+                    if kind == spec_param::CONSTANT_PROP_STRING {
+                        // A '_' escapes a leading digit or '_' of a string.
+                        if text.starts_with('_') {
+                            text = &text[1..];
+                        }
+                    }
+                    param.push(Node::with_text(
+                        NodeKind::FunctionSignatureSpecializationParamPayload,
+                        text,
+                    ));
+                    param.children_mut().extend(types.into_iter().rev());
+                }
+                _ => {}
+            }
+        }
+        Ok(spec)
+    }
+
+    fn demangle_func_spec_param(&mut self, kind: NodeKind) -> Result<Node> {
+        let mut param = Node::new(kind);
+        let param_kind = |kind| {
+            Node::with_index(
+                NodeKind::FunctionSignatureSpecializationParamKind,
+                kind,
+            )
+        };
+
+        let mut options = match self.next() {
+            b'n' => return Ok(param),
+            b'c' => {
+                param.push(param_kind(spec_param::CLOSURE_PROP));
+                return Ok(param);
+            }
+            b'p' => {
+                let kind = match self.next() {
+                    b'f' => spec_param::CONSTANT_PROP_FUNCTION,
+                    b'g' => spec_param::CONSTANT_PROP_GLOBAL,
+                    b'i' => spec_param::CONSTANT_PROP_INTEGER,
+                    b'd' => spec_param::CONSTANT_PROP_FLOAT,
+                    b's' => {
+                        let encoding = match self.next() {
+                            b'b' => "u8",
+                            b'w' => "u16",
+                            b'c' => "objc",
+                            ch => return self.unknown_operator(ch),
+                        };
+                        param
+                            .push(param_kind(spec_param::CONSTANT_PROP_STRING));
+                        param.push(Node::with_text(
+                            NodeKind::FunctionSignatureSpecializationParamPayload,
+                            encoding,
+                        ));
+                        return Ok(param);
+                    }
+                    ch => return self.unknown_operator(ch),
+                };
+                param.push(param_kind(kind));
+                if kind == spec_param::CONSTANT_PROP_INTEGER
+                    || kind == spec_param::CONSTANT_PROP_FLOAT
+                {
+                    let start = self.pos;
+                    while self.peek().is_ascii_digit() {
+                        self.pos += 1;
+                    }
+                    if start == self.pos {
+                        return self.fail();
+                    }
+                    let number =
+                        std::str::from_utf8(&self.text[start..self.pos])
+                            .map_err(|_| self.error())?;
+                    param.push(Node::with_text(
+                        NodeKind::FunctionSignatureSpecializationParamPayload,
+                        number,
+                    ));
+                }
+                return Ok(param);
+            }
+            b'i' => {
+                param.push(param_kind(spec_param::BOX_TO_VALUE));
+                return Ok(param);
+            }
+            b's' => {
+                param.push(param_kind(spec_param::BOX_TO_STACK));
+                return Ok(param);
+            }
+            b'e' => spec_param::EXISTENTIAL_TO_GENERIC,
+            b'd' => spec_param::DEAD,
+            b'g' => spec_param::OWNED_TO_GUARANTEED,
+            b'o' => spec_param::GUARANTEED_TO_OWNED,
+            b'x' => spec_param::SROA,
+            ch => return self.unknown_operator(ch),
+        };
+
+        if options & (spec_param::EXISTENTIAL_TO_GENERIC | spec_param::DEAD)
+            != 0
+        {
+            if self.next_if(b'G') {
+                options |= spec_param::OWNED_TO_GUARANTEED;
+            }
+            if self.next_if(b'O') {
+                options |= spec_param::GUARANTEED_TO_OWNED;
+            }
+        }
+        if options & spec_param::SROA == 0 && self.next_if(b'X') {
+            options |= spec_param::SROA;
+        }
+        param.push(param_kind(options));
+        Ok(param)
+    }
+}
+
+/// The ways in which a function signature specialization changes a parameter.
+///
+/// Based on `FunctionSigSpecializationParamKind` in
+/// `include/swift/Demangling/Demangle.h`.
+pub(crate) mod spec_param {
+    pub(crate) const CONSTANT_PROP_FUNCTION: u64 = 0;
+    pub(crate) const CONSTANT_PROP_GLOBAL: u64 = 1;
+    pub(crate) const CONSTANT_PROP_INTEGER: u64 = 2;
+    pub(crate) const CONSTANT_PROP_FLOAT: u64 = 3;
+    pub(crate) const CONSTANT_PROP_STRING: u64 = 4;
+    pub(crate) const CLOSURE_PROP: u64 = 5;
+    pub(crate) const BOX_TO_VALUE: u64 = 6;
+    pub(crate) const BOX_TO_STACK: u64 = 7;
+
+    // Option set bits, which may be combined.
+    pub(crate) const DEAD: u64 = 1 << 6;
+    pub(crate) const OWNED_TO_GUARANTEED: u64 = 1 << 7;
+    pub(crate) const SROA: u64 = 1 << 8;
+    pub(crate) const GUARANTEED_TO_OWNED: u64 = 1 << 9;
+    pub(crate) const EXISTENTIAL_TO_GENERIC: u64 = 1 << 10;
+}
+
+/// Creates the generic parameter at `depth` and `index`.
+pub(crate) fn dependent_generic_param_type(depth: u64, index: u64) -> Node {
+    Node::with_children(
+        NodeKind::DependentGenericParamType,
+        vec![
+            Node::with_index(NodeKind::Index, depth),
+            Node::with_index(NodeKind::Index, index),
+        ],
+    )
+}
+
+macro_rules! value_witnesses {
+    ($($kind:ident = $code:expr, $name:expr;)+) => {
+        /// The functions within a value witness table.
+        ///
+        /// Based on `include/swift/Demangling/ValueWitnessMangling.def`.
+        #[derive(Clone, Copy, Debug, PartialEq, Eq)]
+        pub(crate) enum ValueWitnessKind {
+            $($kind,)+
+        }
+
+        impl ValueWitnessKind {
+            const ALL: &'static [Self] = &[$(Self::$kind,)+];
+
+            pub(crate) fn from_code(code: [u8; 2]) -> Option<Self> {
+                match &code {
+                    $($code => Some(Self::$kind),)+
+                    _ => None,
+                }
+            }
+
+            pub(crate) fn from_index(index: u64) -> Option<Self> {
+                Self::ALL.get(usize::try_from(index).ok()?).copied()
+            }
+
+            pub(crate) fn name(self) -> &'static str {
+                match self {
+                    $(Self::$kind => $name,)+
+                }
+            }
+        }
+    };
+}
+
+value_witnesses! {
+    AllocateBuffer = b"al", "allocateBuffer";
+    AssignWithCopy = b"ca", "assignWithCopy";
+    AssignWithTake = b"ta", "assignWithTake";
+    DeallocateBuffer = b"de", "deallocateBuffer";
+    Destroy = b"xx", "destroy";
+    DestroyBuffer = b"XX", "destroyBuffer";
+    DestroyArray = b"Xx", "destroyArray";
+    InitializeBufferWithCopyOfBuffer = b"CP", "initializeBufferWithCopyOfBuffer";
+    InitializeBufferWithCopy = b"Cp", "initializeBufferWithCopy";
+    InitializeWithCopy = b"cp", "initializeWithCopy";
+    InitializeBufferWithTake = b"Tk", "initializeBufferWithTake";
+    InitializeWithTake = b"tk", "initializeWithTake";
+    ProjectBuffer = b"pr", "projectBuffer";
+    InitializeBufferWithTakeOfBuffer = b"TK", "initializeBufferWithTakeOfBuffer";
+    InitializeArrayWithCopy = b"Cc", "initializeArrayWithCopy";
+    InitializeArrayWithTakeFrontToBack = b"Tt", "initializeArrayWithTakeFrontToBack";
+    InitializeArrayWithTakeBackToFront = b"tT", "initializeArrayWithTakeBackToFront";
+    StoreExtraInhabitant = b"xs", "storeExtraInhabitant";
+    GetExtraInhabitantIndex = b"xg", "getExtraInhabitantIndex";
+    GetEnumTag = b"ug", "getEnumTag";
+    DestructiveProjectEnumData = b"up", "destructiveProjectEnumData";
+    DestructiveInjectEnumTag = b"ui", "destructiveInjectEnumTag";
+    GetEnumTagSinglePayload = b"et", "getEnumTagSinglePayload";
+    StoreEnumTagSinglePayload = b"st", "storeEnumTagSinglePayload";
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn demangle(symbol: &str) -> Result<String> {
+        let node = demangle_symbol(symbol.as_bytes())?;
+        Ok(DemangleOptions::new().print(&node))
+    }
+
+    #[track_caller]
+    fn check(symbol: &str, expected: &str) {
+        assert_eq!(demangle(symbol).as_deref(), Ok(expected), "{}", symbol);
+    }
+
+    #[test]
+    fn types() {
+        check("$sSiD", "Swift.Int");
+        check("$sSaySSGD", "Swift.Array<Swift.String>");
+        check("$sSDySSSiGD", "Swift.Dictionary<Swift.String, Swift.Int>");
+        check("$sSiSgD", "Swift.Optional<Swift.Int>");
+        check("$sSo8NSObjectCN", "type metadata for __C.NSObject");
+    }
+
+    #[test]
+    fn entities() {
+        check("$s4main3fooyyF", "main.foo() -> ()");
+        check(
+            "$s4main3foo1xSSSi_tF",
+            "main.foo(x: Swift.Int) -> Swift.String",
+        );
+        check("$s4main3fooyySiz_tF", "main.foo(inout Swift.Int) -> ()");
+        check(
+            "$ss5print_9separator10terminatoryypd_S2StF",
+            "Swift.print(_: Any..., separator: Swift.String, terminator: \
+             Swift.String) -> ()",
+        );
+        check("$s4main3FooV1xSivg", "main.Foo.x.getter : Swift.Int");
+        check("$s4main3FooCfD", "main.Foo.__deallocating_deinit");
+        check(
+            "$s4main3FooV1x33_A1B2C3D4E5F60718293A4B5C6D7E8F90LLSivp",
+            "main.Foo.(x in _A1B2C3D4E5F60718293A4B5C6D7E8F90) : Swift.Int",
+        );
+    }
+
+    #[test]
+    fn globals() {
+        check("$s4main3FooVMa", "type metadata accessor for main.Foo");
+        check("$s4main3FooVMn", "nominal type descriptor for main.Foo");
+        check(
+            "$s4main3FooVAA1PAAMc",
+            "protocol conformance descriptor for main.Foo : main.P in main",
+        );
+        check(
+            "$s4main3FooVAA1PAAWP",
+            "protocol witness table for main.Foo : main.P in main",
+        );
+        check(
+            "$s4main3FooV3baryySiFTj",
+            "dispatch thunk of main.Foo.bar(Swift.Int) -> ()",
+        );
+        check(
+            "$s4main3fooyyFTA",
+            "partial apply forwarder for main.foo() -> ()",
+        );
+        check(
+            "$s4main3FooV1xSivpMV",
+            "property descriptor for main.Foo.x : Swift.Int",
+        );
+    }
+
+    #[test]
+    fn descriptors() {
+        check(
+            "$s4main1PPTL",
+            "protocol requirements base descriptor for main.P",
+        );
+        check("$s1A4main1PPTl", "associated type descriptor for main.P.A");
+        check(
+            "$s4main1PP1A_AA1QPTn",
+            "associated conformance descriptor for main.P.A: main.Q",
+        );
+        check(
+            "$sSayxGSlsWI",
+            "instantiation function for generic protocol witness table for \
+             Swift.Array<A> : Swift.Collection in Swift",
+        );
+        check(
+            "$s4main3fooyyFTv0_",
+            "outlined variable #1 of main.foo() -> ()",
+        );
+    }
+
+    #[test]
+    fn opaque_types() {
+        check("$s4main3fooQryF", "main.foo() -> some");
+        check(
+            "$s4main3fooQryFQOyQo_D",
+            "<<opaque return type of main.foo() -> some>>.0",
+        );
+    }
+
+    #[test]
+    fn older_generations() {
+        check(
+            "_T04main3fooSSSi1x_tF",
+            "main.foo(x: Swift.Int) -> Swift.String",
+        );
+        check("_TF4main3fooFT_T_", "main.foo() -> ()");
+        check("_TtGSqSi_", "Swift.Optional<Swift.Int>");
+        check("$S4main3fooyyF", "main.foo() -> ()");
+        check("$SSaySSGD", "Swift.Array<Swift.String>");
+        check("_T04main3FooVMa", "type metadata accessor for main.Foo");
+    }
+
+    #[test]
+    fn mangling_generations() {
+        use ManglingGeneration::*;
+        for &(symbol, generation) in &[
+            ("$s4main3fooyyF", Swift5),
+            ("_$s4main3fooyyF", Swift5),
+            ("$S4main3fooyyF", Swift4_2),
+            ("_T04main3fooyyF", Swift4),
+            ("__T04main3fooyyF", Swift4),
+            ("_TF4main3fooFT_T_", Swift3),
+        ] {
+            assert_eq!(
+                prefix(symbol.as_bytes()).map(|p| p.0),
+                Some(generation)
+            );
+            assert!(demangle(symbol).is_ok(), "{}", symbol);
+        }
+        assert_eq!(prefix(b"main"), None);
+    }
+
+    #[test]
+    fn punycode_identifiers() {
+        check(
+            "$s8mangling0022egbpdajGbuEbxfgehfvwxnyyF",
+            "mangling.ليهمابتكلموشعربي؟() -> ()",
+        );
+        check(
+            "$S8mangling0024ihqwcrbEcvIaIdqgAFGpqjyeyyF",
+            "mangling.他们为什么不说中文() -> ()",
+        );
+        check(
+            "_T08mangling0030Proprostnemluvesky_uybCEdmaEBayyF",
+            "mangling.Pročprostěnemluvíčesky() -> ()",
+        );
+        check(
+            "_TF8manglingX22egbpdajGbuEbxfgehfvwxnFT_T_",
+            "mangling.ليهمابتكلموشعربي؟() -> ()",
+        );
+    }
+
+    #[test]
+    fn generic_initializer() {
+        check("$s4main3FooVyACxclufC", "main.Foo.init<A>(A) -> main.Foo");
+    }
+
+    #[test]
+    fn bound_generic_without_parent() {
+        let error = demangle("$s1D5IndexVy__GN").unwrap_err();
+        assert_eq!(error.kind(), DemangleErrorKind::Malformed);
+    }
+
+    #[test]
+    fn malformed_symbols_fail() {
+        for symbol in &["$s", "$sS", "$sSiR", "$s4main3fo", "$sGGGG", "$sy_G"] {
+            assert!(demangle(symbol).is_err(), "{}", symbol);
+        }
+    }
+
+    #[test]
+    fn deep_nesting_fails() {
+        let mut symbol = String::from("$sSi");
+        symbol.push_str(&"Sg".repeat(10_000));
+        symbol.push('D');
+        let error = demangle(&symbol).unwrap_err();
+        assert_eq!(error.kind(), DemangleErrorKind::TooComplex);
+
+        let mut symbol = String::from("_Tt");
+        symbol.push_str(&"GSq".repeat(10_000));
+        symbol.push_str("Si");
+        symbol.push_str(&"_".repeat(10_000));
+        let error = demangle(&symbol).unwrap_err();
+        assert_eq!(error.kind(), DemangleErrorKind::TooComplex);
+    }
+
+    #[test]
+    fn nesting_below_the_limit_succeeds() {
+        let mut symbol = String::from("$sSi");
+        symbol.push_str(&"Sg".repeat(100));
+        symbol.push('D');
+        let name = demangle(&symbol).unwrap();
+        assert!(name.ends_with(&">".repeat(100)));
+    }
+}
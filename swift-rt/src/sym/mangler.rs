@@ -0,0 +1,1204 @@
+//! Construction of mangled symbols from a structured description.
+
+// Based on:
+// lib/Demangling/Remangler.cpp
+// include/swift/Demangling/ManglingUtils.h
+
+use super::{demangler, node::NodeKind, punycode, std_types};
+use std::{collections::HashMap, error::Error, fmt};
+
+/// An error returned when an entity can't be mangled within a context, such as
+/// the type metadata of a module.
+#[derive(Debug, PartialEq, Eq, Hash)]
+pub struct MangleError(());
+
+impl fmt::Display for MangleError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "Entity cannot be mangled within its context")
+    }
+}
+
+impl Error for MangleError {}
+
+/// The kind of a nominal type.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub enum NominalKind {
+    /// A `struct`.
+    Struct,
+    /// An `enum`.
+    Enum,
+    /// A `class`.
+    Class,
+    /// A `protocol`.
+    Protocol,
+    /// A `typealias`.
+    TypeAlias,
+}
+
+impl NominalKind {
+    #[inline]
+    fn operator(self) -> char {
+        match self {
+            Self::Struct => 'V',
+            Self::Enum => 'O',
+            Self::Class => 'C',
+            Self::Protocol => 'P',
+            Self::TypeAlias => 'a',
+        }
+    }
+
+    #[inline]
+    pub(crate) fn node_kind(self) -> NodeKind {
+        match self {
+            Self::Struct => NodeKind::Structure,
+            Self::Enum => NodeKind::Enum,
+            Self::Class => NodeKind::Class,
+            Self::Protocol => NodeKind::Protocol,
+            Self::TypeAlias => NodeKind::TypeAlias,
+        }
+    }
+}
+
+/// A module or a nominal type declared within one, such as `main.Foo.Bar`.
+///
+/// # Examples
+///
+/// ```
+/// use swift_rt::sym::{Context, NominalKind};
+///
+/// let bar = Context::module("main")
+///     .nested(NominalKind::Struct, "Foo")
+///     .nested(NominalKind::Enum, "Bar");
+/// ```
+#[derive(Clone, Debug, PartialEq, Eq, Hash)]
+pub struct Context {
+    module: String,
+    path: Vec<(NominalKind, String)>,
+    generic_args: Vec<Type>,
+}
+
+impl Context {
+    /// Creates the context for the module named `name`.
+    #[inline]
+    pub fn module(name: impl Into<String>) -> Self {
+        Self {
+            module: name.into(),
+            path: Vec::new(),
+            generic_args: Vec::new(),
+        }
+    }
+
+    /// Creates the context for the type named `name` in the `Swift` module.
+    #[inline]
+    pub fn swift(kind: NominalKind, name: impl Into<String>) -> Self {
+        Self::module("Swift").nested(kind, name)
+    }
+
+    /// Returns the context for the type named `name` declared within `self`.
+    #[inline]
+    pub fn nested(
+        mut self,
+        kind: NominalKind,
+        name: impl Into<String>,
+    ) -> Self {
+        self.path.push((kind, name.into()));
+        self
+    }
+
+    /// Returns `self` bound to the generic arguments `args`, such as the `Int`
+    /// in `Array<Int>`.
+    ///
+    /// Arguments apply to the innermost type. Entities that refer to the
+    /// declaration rather than a type, such as nominal type descriptors,
+    /// ignore them.
+    #[inline]
+    pub fn generic_args(
+        mut self,
+        args: impl IntoIterator<Item = Type>,
+    ) -> Self {
+        self.generic_args = args.into_iter().collect();
+        self
+    }
+
+    /// Returns the name of the module that `self` is declared in.
+    #[inline]
+    pub fn module_name(&self) -> &str {
+        &self.module
+    }
+
+    /// Returns the kind of the innermost type, or `None` if `self` is a
+    /// module.
+    #[inline]
+    pub fn nominal_kind(&self) -> Option<NominalKind> {
+        self.path.last().map(|&(kind, _)| kind)
+    }
+}
+
+/// A type, as referenced by a mangled symbol.
+#[derive(Clone, Debug, PartialEq, Eq, Hash)]
+pub enum Type {
+    /// A nominal type, possibly bound to generic arguments.
+    Nominal(Context),
+    /// The generic parameter at a depth and index, such as `A` at `(0, 0)`.
+    GenericParam(u32, u32),
+    /// A tuple of types, or `()` if empty.
+    Tuple(Vec<Type>),
+    /// An escaping function type taking parameters and returning a result.
+    Function(Vec<Type>, Box<Type>),
+    /// The metatype of a type, such as `Int.Type`.
+    Metatype(Box<Type>),
+    /// A composition of protocols, or `Any` if empty.
+    Existential(Vec<Context>),
+}
+
+impl From<Context> for Type {
+    #[inline]
+    fn from(context: Context) -> Self {
+        Self::Nominal(context)
+    }
+}
+
+impl Type {
+    /// Returns the empty tuple, `()`.
+    #[inline]
+    pub fn unit() -> Self {
+        Self::Tuple(Vec::new())
+    }
+
+    /// Returns the struct named `name` in the `Swift` module, such as `Int`.
+    #[inline]
+    pub fn swift(name: impl Into<String>) -> Self {
+        Self::Nominal(Context::swift(NominalKind::Struct, name))
+    }
+
+    /// Returns `Optional<wrapped>`.
+    #[inline]
+    pub fn optional(wrapped: Type) -> Self {
+        Self::Nominal(
+            Context::swift(NominalKind::Enum, "Optional")
+                .generic_args(Some(wrapped)),
+        )
+    }
+
+    /// Returns `Array<element>`.
+    #[inline]
+    pub fn array(element: Type) -> Self {
+        Self::Nominal(
+            Context::swift(NominalKind::Struct, "Array")
+                .generic_args(Some(element)),
+        )
+    }
+
+    /// Returns `Dictionary<key, value>`.
+    #[inline]
+    pub fn dictionary(key: Type, value: Type) -> Self {
+        Self::Nominal(
+            Context::swift(NominalKind::Struct, "Dictionary")
+                .generic_args(vec![key, value]),
+        )
+    }
+}
+
+#[derive(Clone, Debug, PartialEq, Eq, Hash)]
+struct Param {
+    label: Option<String>,
+    ty: Type,
+    inout: bool,
+}
+
+/// A function, method or initializer signature.
+///
+/// # Examples
+///
+/// ```
+/// use swift_rt::sym::{Function, Type};
+///
+/// // func foo(x: Int, _ y: inout String) throws -> Bool
+/// let foo = Function::new("foo")
+///     .param("x", Type::swift("Int"))
+///     .inout_param("_", Type::swift("String"))
+///     .returns(Type::swift("Bool"))
+///     .throws(true);
+/// ```
+#[derive(Clone, Debug, PartialEq, Eq, Hash)]
+pub struct Function {
+    name: String,
+    params: Vec<Param>,
+    result: Type,
+    throws: bool,
+    is_static: bool,
+    generic_params: u32,
+    requirements: Vec<(u32, Context)>,
+}
+
+impl Function {
+    /// Creates a function named `name` that takes no parameters and returns
+    /// `()`.
+    #[inline]
+    pub fn new(name: impl Into<String>) -> Self {
+        Self {
+            name: name.into(),
+            params: Vec::new(),
+            result: Type::unit(),
+            throws: false,
+            is_static: false,
+            generic_params: 0,
+            requirements: Vec::new(),
+        }
+    }
+
+    /// Appends a parameter with the argument label `label`, or `"_"` for none.
+    #[inline]
+    pub fn param(mut self, label: &str, ty: Type) -> Self {
+        self.push_param(label, ty, false);
+        self
+    }
+
+    /// Appends an `inout` parameter with the argument label `label`, or `"_"`
+    /// for none.
+    #[inline]
+    pub fn inout_param(mut self, label: &str, ty: Type) -> Self {
+        self.push_param(label, ty, true);
+        self
+    }
+
+    fn push_param(&mut self, label: &str, ty: Type, inout: bool) {
+        let label = match label {
+            "" | "_" => None,
+            label => Some(label.to_owned()),
+        };
+        self.params.push(Param { label, ty, inout });
+    }
+
+    /// Sets the result type, which is `()` by default.
+    #[inline]
+    pub fn returns(mut self, ty: Type) -> Self {
+        self.result = ty;
+        self
+    }
+
+    /// Sets whether the function `throws`.
+    #[inline]
+    pub fn throws(mut self, yes: bool) -> Self {
+        self.throws = yes;
+        self
+    }
+
+    /// Sets whether the function is a `static` member of its context.
+    #[inline]
+    pub fn is_static(mut self, yes: bool) -> Self {
+        self.is_static = yes;
+        self
+    }
+
+    /// Sets the number of generic parameters introduced by the function.
+    ///
+    /// These are referred to as `Type::GenericParam(0, index)`, so the
+    /// function's context must not be generic.
+    #[inline]
+    pub fn generic_params(mut self, count: u32) -> Self {
+        self.generic_params = count;
+        self
+    }
+
+    /// Adds the requirement that the generic parameter at `index` conforms to
+    /// `protocol`.
+    ///
+    /// This implies that the function has at least `index + 1` generic
+    /// parameters.
+    #[inline]
+    pub fn requirement(mut self, index: u32, protocol: Context) -> Self {
+        self.requirements.push((index, protocol));
+        self
+    }
+
+    /// Returns the number of generic parameters, including those implied by
+    /// requirements.
+    fn num_generic_params(&self) -> u32 {
+        let required = self.requirements.iter().map(|&(index, _)| index + 1);
+        required.fold(self.generic_params, u32::max)
+    }
+}
+
+/// A stored or computed property.
+#[derive(Clone, Debug, PartialEq, Eq, Hash)]
+pub struct Variable {
+    name: String,
+    ty: Type,
+    is_static: bool,
+}
+
+impl Variable {
+    /// Creates a property named `name` of type `ty`.
+    #[inline]
+    pub fn new(name: impl Into<String>, ty: Type) -> Self {
+        Self {
+            name: name.into(),
+            ty,
+            is_static: false,
+        }
+    }
+
+    /// Sets whether the property is a `static` member of its context.
+    #[inline]
+    pub fn is_static(mut self, yes: bool) -> Self {
+        self.is_static = yes;
+        self
+    }
+}
+
+/// The entity referred to by a mangled symbol, relative to a context.
+#[derive(Clone, Copy, Debug)]
+pub enum Entity<'a> {
+    /// The metadata record of the context type (`N`).
+    TypeMetadata,
+    /// The function that returns the metadata of the context type (`Ma`).
+    TypeMetadataAccessor,
+    /// The metadata of the context type, including its value witness table
+    /// pointer (`Mf`).
+    FullTypeMetadata,
+    /// The context descriptor of the context type (`Mn`).
+    NominalTypeDescriptor,
+    /// The context descriptor of the context protocol (`Mp`).
+    ProtocolDescriptor,
+    /// The value witness table of the context type (`WV`).
+    ValueWitnessTable,
+    /// The witness table for the context type's conformance to a protocol,
+    /// declared in the type's module (`WP`).
+    ProtocolWitnessTable(&'a Context),
+    /// The descriptor of the context type's conformance to a protocol,
+    /// declared in the type's module (`Mc`).
+    ProtocolConformanceDescriptor(&'a Context),
+    /// A function declared within the context (`F`).
+    Function(&'a Function),
+    /// The method descriptor of a function within a class or protocol (`Tq`).
+    MethodDescriptor(&'a Function),
+    /// The dispatch thunk of a function within a class or protocol (`Tj`).
+    DispatchThunk(&'a Function),
+    /// An initializer of the context type, whose name is ignored (`fC`).
+    Allocator(&'a Function),
+    /// The getter of a property (`vg`).
+    Getter(&'a Variable),
+    /// The setter of a property (`vs`).
+    Setter(&'a Variable),
+    /// The `_modify` coroutine of a property (`vM`).
+    ModifyAccessor(&'a Variable),
+    /// The descriptor of a property, used by key paths (`MV`).
+    PropertyDescriptor(&'a Variable),
+}
+
+/// A part of a symbol which may be referred to by a later substitution.
+#[derive(PartialEq, Eq, Hash)]
+enum Key {
+    Identifier(String),
+    Nominal(String, Vec<(NominalKind, String)>),
+    Type(Type),
+}
+
+/// Merges consecutive substitutions, such as `AaB` or `S2i`.
+#[derive(Default)]
+struct SubstitutionMerging {
+    last_position: usize,
+    last_size: usize,
+    last_count: usize,
+    last_is_standard: bool,
+}
+
+impl SubstitutionMerging {
+    /// Attempts to merge `subst` with the substitution that ends `buffer`.
+    ///
+    /// If this fails, the caller must append the substitution itself.
+    fn try_merge(
+        &mut self,
+        buffer: &mut String,
+        subst: u8,
+        is_standard: bool,
+    ) -> bool {
+        if self.last_count > 0
+            && (self.last_count as u64) < demangler::MAX_REPEAT_COUNT
+            && buffer.len() == self.last_position + self.last_size
+            && self.last_is_standard == is_standard
+        {
+            let last = buffer.as_bytes()[buffer.len() - 1];
+            if last != subst && !is_standard {
+                // Merge with a different substitution: `AB` -> `AbC`.
+                buffer.truncate(buffer.len() - 1);
+                buffer.push(char::from(last.to_ascii_lowercase()));
+                buffer.push(char::from(subst));
+                self.last_position = buffer.len() - 1;
+                self.last_size = 1;
+                self.last_count = 1;
+                return true;
+            }
+            if last == subst {
+                // Merge with the same substitution: `AB` -> `A2B`.
+                self.last_count += 1;
+                buffer.truncate(self.last_position);
+                buffer.push_str(&self.last_count.to_string());
+                buffer.push(char::from(subst));
+                self.last_size = buffer.len() - self.last_position;
+                return true;
+            }
+        }
+
+        // Remember the substitution which will be appended by the caller.
+        self.last_position = buffer.len() + 1;
+        self.last_size = 1;
+        self.last_count = 1;
+        self.last_is_standard = is_standard;
+        false
+    }
+}
+
+/// Builds mangled symbols from a structured description.
+///
+/// The resulting symbols are exactly those emitted by the Swift compiler, so
+/// they may be used as the `#[link_name]` of Swift entry points.
+///
+/// # Examples
+///
+/// ```
+/// use swift_rt::sym::{Context, Entity, Function, Mangler, NominalKind, Type};
+///
+/// let mut mangler = Mangler::new();
+///
+/// let foo = Context::module("main").nested(NominalKind::Struct, "Foo");
+/// let accessor = mangler.mangle(&foo, Entity::TypeMetadataAccessor).unwrap();
+/// assert_eq!(accessor, "$s4main3FooVMa");
+///
+/// let bar = Function::new("bar")
+///     .param("x", Type::swift("Int"))
+///     .returns(Type::swift("String"));
+/// let method = mangler.mangle(&foo, Entity::Function(&bar)).unwrap();
+/// assert_eq!(method, "$s4main3FooV3bar1xSSSi_tF");
+/// ```
+#[derive(Default)]
+pub struct Mangler {
+    buffer: String,
+    /// The `(start, len)` within `buffer` of every word that identifiers may
+    /// substitute.
+    words: Vec<(usize, usize)>,
+    substitutions: HashMap<Key, usize>,
+    merging: SubstitutionMerging,
+}
+
+impl fmt::Debug for Mangler {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.debug_struct("Mangler").finish()
+    }
+}
+
+impl Mangler {
+    /// Creates a new mangler.
+    #[inline]
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Returns the symbol for `entity` within `context`.
+    pub fn mangle(
+        &mut self,
+        context: &Context,
+        entity: Entity,
+    ) -> Result<String, MangleError> {
+        self.reset();
+        self.buffer.push_str("$s");
+        self.append_entity(context, entity)?;
+        Ok(std::mem::take(&mut self.buffer))
+    }
+
+    /// Returns the mangled name of `ty` without a symbol prefix, as used by
+    /// runtime type lookups and reflection metadata.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use swift_rt::sym::{Mangler, Type};
+    ///
+    /// let ty = Type::array(Type::swift("String"));
+    /// assert_eq!(Mangler::new().mangle_type(&ty).unwrap(), "SaySSG");
+    /// ```
+    pub fn mangle_type(&mut self, ty: &Type) -> Result<String, MangleError> {
+        self.reset();
+        self.append_type(ty)?;
+        Ok(std::mem::take(&mut self.buffer))
+    }
+
+    fn reset(&mut self) {
+        self.buffer.clear();
+        self.words.clear();
+        self.substitutions.clear();
+        self.merging = SubstitutionMerging::default();
+    }
+
+    fn append_entity(
+        &mut self,
+        context: &Context,
+        entity: Entity,
+    ) -> Result<(), MangleError> {
+        match entity {
+            Entity::TypeMetadata => {
+                self.append_context_type(context)?;
+                self.buffer.push('N');
+            }
+            Entity::TypeMetadataAccessor => {
+                self.append_context_type(context)?;
+                self.buffer.push_str("Ma");
+            }
+            Entity::FullTypeMetadata => {
+                self.append_context_type(context)?;
+                self.buffer.push_str("Mf");
+            }
+            Entity::ValueWitnessTable => {
+                self.append_context_type(context)?;
+                self.buffer.push_str("WV");
+            }
+            Entity::NominalTypeDescriptor => {
+                if context.path.is_empty() {
+                    return Err(MangleError(()));
+                }
+                self.append_context(context);
+                self.buffer.push_str("Mn");
+            }
+            Entity::ProtocolDescriptor => {
+                self.append_protocol(context)?;
+                self.buffer.push_str("Mp");
+            }
+            Entity::ProtocolWitnessTable(protocol) => {
+                self.append_conformance(context, protocol)?;
+                self.buffer.push_str("WP");
+            }
+            Entity::ProtocolConformanceDescriptor(protocol) => {
+                self.append_conformance(context, protocol)?;
+                self.buffer.push_str("Mc");
+            }
+            Entity::Function(function) => {
+                self.append_function(context, function)?;
+            }
+            Entity::MethodDescriptor(function) => {
+                self.append_function(context, function)?;
+                self.buffer.push_str("Tq");
+            }
+            Entity::DispatchThunk(function) => {
+                self.append_function(context, function)?;
+                self.buffer.push_str("Tj");
+            }
+            Entity::Allocator(function) => {
+                if context.path.is_empty() {
+                    return Err(MangleError(()));
+                }
+                self.append_context(context);
+                self.append_labels(function);
+                let result = Type::Nominal(context.clone());
+                self.append_function_signature(&result, function)?;
+                self.buffer.push('c');
+                if function.num_generic_params() > 0 {
+                    self.append_generic_signature(function)?;
+                    self.buffer.push('u');
+                }
+                self.buffer.push_str("fC");
+            }
+            Entity::Getter(variable) => {
+                self.append_storage(context, variable, "g")?;
+            }
+            Entity::Setter(variable) => {
+                self.append_storage(context, variable, "s")?;
+            }
+            Entity::ModifyAccessor(variable) => {
+                self.append_storage(context, variable, "M")?;
+            }
+            Entity::PropertyDescriptor(variable) => {
+                self.append_storage(context, variable, "p")?;
+                self.buffer.push_str("MV");
+            }
+        }
+        Ok(())
+    }
+
+    fn append_function(
+        &mut self,
+        context: &Context,
+        function: &Function,
+    ) -> Result<(), MangleError> {
+        self.append_context(context);
+        self.append_identifier(&function.name);
+        self.append_labels(function);
+        self.append_function_signature(&function.result, function)?;
+        if function.num_generic_params() > 0 {
+            self.append_generic_signature(function)?;
+        }
+        self.buffer.push('F');
+        if function.is_static {
+            self.buffer.push('Z');
+        }
+        Ok(())
+    }
+
+    fn append_storage(
+        &mut self,
+        context: &Context,
+        variable: &Variable,
+        accessor: &str,
+    ) -> Result<(), MangleError> {
+        self.append_context(context);
+        self.append_identifier(&variable.name);
+        self.append_type(&variable.ty)?;
+        self.buffer.push('v');
+        self.buffer.push_str(accessor);
+        if variable.is_static {
+            self.buffer.push('Z');
+        }
+        Ok(())
+    }
+
+    fn append_conformance(
+        &mut self,
+        context: &Context,
+        protocol: &Context,
+    ) -> Result<(), MangleError> {
+        self.append_context_type(context)?;
+        self.append_protocol(protocol)?;
+        self.append_module(&context.module);
+        Ok(())
+    }
+
+    /// Appends argument labels, which are omitted if there are no parameters.
+    fn append_labels(&mut self, function: &Function) {
+        if function.params.is_empty() {
+            return;
+        }
+        if function.params.iter().all(|param| param.label.is_none()) {
+            self.buffer.push('y');
+            return;
+        }
+        for param in &function.params {
+            match &param.label {
+                Some(label) => self.append_identifier(label),
+                None => self.buffer.push('_'),
+            }
+        }
+    }
+
+    /// Appends the result type, parameter types and `throws` of `function`.
+    fn append_function_signature(
+        &mut self,
+        result: &Type,
+        function: &Function,
+    ) -> Result<(), MangleError> {
+        self.append_type(result)?;
+        match function.params.as_slice() {
+            [] => self.buffer.push('y'),
+            // A single unlabeled parameter is mangled without a tuple.
+            [param] if param.label.is_none() && !is_tuple(&param.ty) => {
+                self.append_type(&param.ty)?;
+                if param.inout {
+                    self.buffer.push('z');
+                }
+            }
+            params => {
+                for (i, param) in params.iter().enumerate() {
+                    self.append_type(&param.ty)?;
+                    if param.inout {
+                        self.buffer.push('z');
+                    }
+                    if i == 0 {
+                        self.buffer.push('_');
+                    }
+                }
+                self.buffer.push('t');
+            }
+        }
+        if function.throws {
+            self.buffer.push('K');
+        }
+        Ok(())
+    }
+
+    fn append_generic_signature(
+        &mut self,
+        function: &Function,
+    ) -> Result<(), MangleError> {
+        for (index, protocol) in &function.requirements {
+            self.append_protocol(protocol)?;
+            self.buffer.push('R');
+            match index {
+                0 => self.buffer.push('z'),
+                index => self.append_index(u64::from(*index) - 1),
+            }
+        }
+        match function.num_generic_params() {
+            // A single parameter is implied.
+            1 => self.buffer.push('l'),
+            count => {
+                self.buffer.push('r');
+                self.append_index(u64::from(count) - 1);
+                self.buffer.push('l');
+            }
+        }
+        Ok(())
+    }
+
+    fn append_type(&mut self, ty: &Type) -> Result<(), MangleError> {
+        match ty {
+            Type::Nominal(context) => self.append_context_type(context)?,
+            Type::GenericParam(0, 0) => self.buffer.push('x'),
+            Type::GenericParam(depth, index) => {
+                self.buffer.push('q');
+                if *depth != 0 {
+                    self.buffer.push('d');
+                    self.append_index(u64::from(*depth) - 1);
+                    self.append_index(u64::from(*index));
+                } else {
+                    self.append_index(u64::from(*index) - 1);
+                }
+            }
+            Type::Tuple(elements) => match elements.as_slice() {
+                [] => self.buffer.push('y'),
+                [element] => self.append_type(element)?,
+                elements => {
+                    for (i, element) in elements.iter().enumerate() {
+                        self.append_type(element)?;
+                        if i == 0 {
+                            self.buffer.push('_');
+                        }
+                    }
+                    self.buffer.push('t');
+                }
+            },
+            Type::Function(params, result) => {
+                self.append_type(result)?;
+                match params.as_slice() {
+                    [] => self.buffer.push('y'),
+                    [param] if !is_tuple(param) => self.append_type(param)?,
+                    params => {
+                        for (i, param) in params.iter().enumerate() {
+                            self.append_type(param)?;
+                            if i == 0 {
+                                self.buffer.push('_');
+                            }
+                        }
+                        self.buffer.push('t');
+                    }
+                }
+                self.buffer.push('c');
+            }
+            Type::Metatype(instance) => {
+                self.append_type(instance)?;
+                self.buffer.push('m');
+            }
+            Type::Existential(protocols) => {
+                if protocols.is_empty() {
+                    self.buffer.push('y');
+                }
+                for (i, protocol) in protocols.iter().enumerate() {
+                    self.append_protocol(protocol)?;
+                    if i == 0 {
+                        self.buffer.push('_');
+                    }
+                }
+                self.buffer.push('p');
+            }
+        }
+        Ok(())
+    }
+
+    /// Appends `context` as a type, including its generic arguments.
+    fn append_context_type(
+        &mut self,
+        context: &Context,
+    ) -> Result<(), MangleError> {
+        if context.path.is_empty() {
+            return Err(MangleError(()));
+        }
+        if context.generic_args.is_empty() {
+            self.append_context(context);
+            return Ok(());
+        }
+
+        let key = Key::Type(Type::Nominal(context.clone()));
+        if self.try_substitution(&key) {
+            return Ok(());
+        }
+
+        let is_optional = context.module == "Swift"
+            && context.path.len() == 1
+            && context.path[0] == (NominalKind::Enum, "Optional".to_owned())
+            && context.generic_args.len() == 1;
+        if is_optional {
+            self.append_type(&context.generic_args[0])?;
+            self.buffer.push_str("Sg");
+        } else {
+            self.append_context(context);
+            // Every type in the path has its own list of arguments.
+            self.buffer.push('y');
+            for _ in 1..context.path.len() {
+                self.buffer.push('_');
+            }
+            for arg in &context.generic_args {
+                self.append_type(arg)?;
+            }
+            self.buffer.push('G');
+        }
+        self.add_substitution(key);
+        Ok(())
+    }
+
+    /// Appends `context` as a declaration context, without generic arguments.
+    fn append_context(&mut self, context: &Context) {
+        if context.path.is_empty() {
+            self.append_module(&context.module);
+        } else {
+            self.append_nominal(context, context.path.len());
+        }
+    }
+
+    /// Appends the nominal type at `depth` within the path of `context`.
+    fn append_nominal(&mut self, context: &Context, depth: usize) {
+        let (kind, name) = &context.path[depth - 1];
+        if depth == 1 && context.module == "Swift" {
+            if let Some(subst) = std_types::find(kind.node_kind(), name) {
+                self.append_standard_substitution(subst);
+                return;
+            }
+        }
+
+        let key = Key::Nominal(
+            context.module.clone(),
+            context.path[..depth].to_vec(),
+        );
+        if self.try_substitution(&key) {
+            return;
+        }
+        if depth == 1 {
+            self.append_module(&context.module);
+        } else {
+            self.append_nominal(context, depth - 1);
+        }
+        self.append_identifier(name);
+        self.buffer.push(kind.operator());
+        self.add_substitution(key);
+    }
+
+    /// Appends a protocol as referenced by requirements and conformances,
+    /// which omit the `P` operator.
+    fn append_protocol(
+        &mut self,
+        protocol: &Context,
+    ) -> Result<(), MangleError> {
+        let depth = protocol.path.len();
+        let (kind, name) = match protocol.path.last() {
+            Some(last) if last.0 == NominalKind::Protocol => last,
+            _ => return Err(MangleError(())),
+        };
+        if depth == 1 && protocol.module == "Swift" {
+            if let Some(subst) = std_types::find(kind.node_kind(), name) {
+                self.append_standard_substitution(subst);
+                return Ok(());
+            }
+        }
+        if depth == 1 {
+            self.append_module(&protocol.module);
+        } else {
+            self.append_nominal(protocol, depth - 1);
+        }
+        self.append_identifier(name);
+        Ok(())
+    }
+
+    fn append_module(&mut self, name: &str) {
+        match name {
+            "Swift" => self.buffer.push('s'),
+            "__C" => self.buffer.push_str("So"),
+            "__C_Synthesized" => self.buffer.push_str("SC"),
+            _ => self.append_identifier(name),
+        }
+    }
+
+    /// Appends `ident`, substituting words that occurred earlier.
+    fn append_identifier(&mut self, ident: &str) {
+        let key = Key::Identifier(ident.to_owned());
+        if self.try_substitution(&key) {
+            return;
+        }
+        self.add_substitution(key);
+
+        if !ident.bytes().all(punycode::is_symbol_char) {
+            // Identifiers with other characters are encoded in their
+            // entirety, without word substitutions.
+            let encoded = punycode::encode(ident);
+            self.buffer.push_str("00");
+            self.buffer.push_str(&encoded.len().to_string());
+            if encoded.starts_with(|ch: char| ch.is_ascii_digit() || ch == '_')
+            {
+                self.buffer.push('_');
+            }
+            self.buffer.push_str(&encoded);
+            return;
+        }
+
+        let bytes = ident.as_bytes();
+        let words_in_buffer = self.words.len();
+        // The `(position, word index)` of each substituted word.
+        let mut substs: Vec<(usize, Option<usize>)> = Vec::new();
+
+        let buffer = self.buffer.as_bytes();
+        let words = &mut self.words;
+        demangler::for_each_word(bytes, |start, len| {
+            let word = &bytes[start..start + len];
+            let index = (0..words_in_buffer)
+                .find(|&i| {
+                    let (s, l) = words[i];
+                    &buffer[s..s + l] == word
+                })
+                .or_else(|| {
+                    (words_in_buffer..words.len()).find(|&i| {
+                        let (s, l) = words[i];
+                        &bytes[s..s + l] == word
+                    })
+                });
+            match index {
+                Some(index) => substs.push((start, Some(index))),
+                None if len >= 2 && words.len() < demangler::MAX_NUM_WORDS => {
+                    // Relative to `ident` until it's appended.
+                    words.push((start, len));
+                }
+                None => {}
+            }
+        });
+
+        if substs.is_empty() {
+            self.buffer.push_str(&ident.len().to_string());
+            let start = self.buffer.len();
+            for word in &mut self.words[words_in_buffer..] {
+                word.0 += start;
+            }
+            self.buffer.push_str(ident);
+            return;
+        }
+
+        self.buffer.push('0');
+        let mut pos = 0;
+        let mut new_words = words_in_buffer;
+        // The final entry marks the end of `ident`.
+        substs.push((bytes.len(), None));
+        let count = substs.len();
+        for (i, &(subst_pos, index)) in substs.iter().enumerate() {
+            if pos < subst_pos {
+                self.buffer.push_str(&(subst_pos - pos).to_string());
+                while pos < subst_pos {
+                    if new_words < self.words.len()
+                        && self.words[new_words].0 == pos
+                    {
+                        self.words[new_words].0 = self.buffer.len();
+                        new_words += 1;
+                    }
+                    self.buffer.push(char::from(bytes[pos]));
+                    pos += 1;
+                }
+            }
+            if let Some(index) = index {
+                pos += self.words[index].1;
+                if i + 2 < count {
+                    self.buffer.push(char::from(b'a' + index as u8));
+                } else {
+                    // The last substituted word is uppercase.
+                    self.buffer.push(char::from(b'A' + index as u8));
+                    if pos == bytes.len() {
+                        self.buffer.push('0');
+                    }
+                }
+            }
+        }
+    }
+
+    fn append_index(&mut self, index: u64) {
+        if index != 0 {
+            self.buffer.push_str(&(index - 1).to_string());
+        }
+        self.buffer.push('_');
+    }
+
+    fn append_standard_substitution(&mut self, subst: u8) {
+        if !self.merging.try_merge(&mut self.buffer, subst, true) {
+            self.buffer.push('S');
+            self.buffer.push(char::from(subst));
+        }
+    }
+
+    fn try_substitution(&mut self, key: &Key) -> bool {
+        let index = match self.substitutions.get(key) {
+            Some(&index) => index,
+            None => return false,
+        };
+        if index >= 26 {
+            self.buffer.push('A');
+            self.append_index(index as u64 - 26);
+        } else {
+            let subst = b'A' + index as u8;
+            if !self.merging.try_merge(&mut self.buffer, subst, false) {
+                self.buffer.push('A');
+                self.buffer.push(char::from(subst));
+            }
+        }
+        true
+    }
+
+    #[inline]
+    fn add_substitution(&mut self, key: Key) {
+        let index = self.substitutions.len();
+        self.substitutions.insert(key, index);
+    }
+}
+
+#[inline]
+fn is_tuple(ty: &Type) -> bool {
+    matches!(ty, Type::Tuple(elements) if elements.len() != 1)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::super::demangle;
+    use super::*;
+
+    fn foo() -> Context {
+        Context::module("main").nested(NominalKind::Struct, "Foo")
+    }
+
+    #[track_caller]
+    fn check(context: &Context, entity: Entity, symbol: &str, name: &str) {
+        let mangled = Mangler::new().mangle(context, entity).unwrap();
+        assert_eq!(mangled, symbol);
+        assert_eq!(demangle(mangled.as_bytes()).unwrap(), name);
+    }
+
+    #[test]
+    fn type_entities() {
+        check(
+            &foo(),
+            Entity::TypeMetadata,
+            "$s4main3FooVN",
+            "type metadata for main.Foo",
+        );
+        check(
+            &foo(),
+            Entity::NominalTypeDescriptor,
+            "$s4main3FooVMn",
+            "nominal type descriptor for main.Foo",
+        );
+        let proto = Context::module("main").nested(NominalKind::Protocol, "P");
+        check(
+            &foo(),
+            Entity::ProtocolWitnessTable(&proto),
+            "$s4main3FooVAA1PAAWP",
+            "protocol witness table for main.Foo : main.P in main",
+        );
+        let array = Context::swift(NominalKind::Struct, "Array")
+            .generic_args(Some(Type::swift("Int")));
+        check(
+            &array,
+            Entity::TypeMetadata,
+            "$sSaySiGN",
+            "type metadata for Swift.Array<Swift.Int>",
+        );
+        assert_eq!(
+            Mangler::new()
+                .mangle(&Context::module("main"), Entity::TypeMetadata),
+            Err(MangleError(())),
+        );
+    }
+
+    #[test]
+    fn functions() {
+        let bar = Function::new("bar")
+            .param("x", Type::swift("Int"))
+            .inout_param("_", Type::optional(Type::swift("String")))
+            .returns(Type::array(Type::swift("Bool")))
+            .throws(true);
+        let symbol = Mangler::new()
+            .mangle(&foo(), Entity::Function(&bar))
+            .unwrap();
+        assert_eq!(
+            demangle(symbol.as_bytes()).unwrap(),
+            "main.Foo.bar(x: Swift.Int, _: inout \
+             Swift.Optional<Swift.String>) throws -> Swift.Array<Swift.Bool>",
+        );
+
+        let x = Variable::new("x", Type::swift("Int"));
+        check(
+            &foo(),
+            Entity::Getter(&x),
+            "$s4main3FooV1xSivg",
+            "main.Foo.x.getter : Swift.Int",
+        );
+    }
+
+    #[test]
+    fn generic_functions() {
+        let equatable = Context::swift(NominalKind::Protocol, "Equatable");
+        let baz = Function::new("baz")
+            .param("_", Type::GenericParam(0, 0))
+            .requirement(0, equatable.clone());
+        let with_params = baz.clone().generic_params(1);
+        let symbol = Mangler::new()
+            .mangle(&foo(), Entity::Function(&baz))
+            .unwrap();
+        let expected = Mangler::new()
+            .mangle(&foo(), Entity::Function(&with_params))
+            .unwrap();
+        assert_eq!(symbol, expected);
+        assert_eq!(
+            demangle(symbol.as_bytes()).unwrap(),
+            "main.Foo.baz<A where A: Swift.Equatable>(A) -> ()",
+        );
+
+        let init = Function::new("init")
+            .param("_", Type::GenericParam(0, 0))
+            .generic_params(1);
+        check(
+            &foo(),
+            Entity::Allocator(&init),
+            "$s4main3FooVyACxclufC",
+            "main.Foo.init<A>(A) -> main.Foo",
+        );
+    }
+
+    #[test]
+    fn word_substitutions() {
+        let context = Context::module("main")
+            .nested(NominalKind::Struct, "FooBar")
+            .nested(NominalKind::Struct, "BarFoo");
+        let symbol = Mangler::new()
+            .mangle(&context, Entity::TypeMetadata)
+            .unwrap();
+        assert_eq!(symbol, "$s4main6FooBarV0cB0VN");
+        assert_eq!(
+            demangle(symbol.as_bytes()).unwrap(),
+            "type metadata for main.FooBar.BarFoo",
+        );
+    }
+
+    #[test]
+    fn punycode_identifiers() {
+        let context =
+            Context::module("main").nested(NominalKind::Struct, "bücher");
+        check(
+            &context,
+            Entity::TypeMetadata,
+            "$s4main009bcher_kvaVN",
+            "type metadata for main.bücher",
+        );
+
+        for name in &["ü", "Ünïcödé", "a b", "1π", "日本語", "_ä"] {
+            let context =
+                Context::module("main").nested(NominalKind::Class, *name);
+            let symbol = Mangler::new()
+                .mangle(&context, Entity::TypeMetadata)
+                .unwrap();
+            assert!(symbol.is_ascii(), "{}", symbol);
+            assert_eq!(
+                demangle(symbol.as_bytes()).unwrap(),
+                format!("type metadata for main.{}", name),
+            );
+        }
+    }
+}
@@ -0,0 +1,365 @@
+//! Symbol name operations.
+//!
+//! Symbols are demangled by a native Rust implementation of the Swift mangling
+//! grammar, whose output matches that of `swift_demangle`. Every generation
+//! of the grammar is supported, from the `_T` prefix of Swift 3 to the `$s`
+//! prefix of Swift 5. The [`Mangler`](struct.Mangler.html) performs the
+//! reverse, building symbols from a description of the entity they refer to.
+//!
+//! Mangled type names in reflection metadata may contain symbolic references
+//! to descriptors instead of names, which are resolved by the callback given
+//! to [`parse_type_with`](fn.parse_type_with.html).
+
+mod classify;
+mod demangler;
+mod mangler;
+mod node;
+mod old_demangler;
+mod options;
+mod printer;
+mod punycode;
+mod std_types;
+mod symbolic;
+
+pub use classify::{SymbolInfo, SymbolKind};
+pub use mangler::{
+    Context, Entity, Function, MangleError, Mangler, NominalKind, Type,
+    Variable,
+};
+pub use node::{Node, NodeKind};
+pub use options::DemangleOptions;
+pub use symbolic::{context_node, SymbolicReference, SymbolicReferenceKind};
+
+use std::{error::Error, fmt};
+
+/// An error returned when attempting to demangle an invalid Swift symbol.
+///
+/// # Examples
+///
+/// ```
+/// use swift_rt::sym::{self, DemangleErrorKind};
+///
+/// let error = sym::demangle(b"$s4main3fooyyF!").unwrap_err();
+/// assert_eq!(error.kind(), DemangleErrorKind::UnknownOperator);
+/// assert_eq!(error.offset(), 14);
+/// assert_eq!(error.partial(), "main.foo() -> ()");
+///
+/// let error = sym::demangle(b"$s4main3fo").unwrap_err();
+/// assert_eq!(error.kind(), DemangleErrorKind::UnexpectedEnd);
+///
+/// let error = sym::demangle(b"main").unwrap_err();
+/// assert_eq!(error.kind(), DemangleErrorKind::NotMangled);
+/// ```
+#[derive(Clone, Debug, PartialEq, Eq, Hash)]
+pub struct DemangleError {
+    kind: DemangleErrorKind,
+    offset: usize,
+    partial: String,
+}
+
+impl DemangleError {
+    #[inline]
+    pub(crate) fn new(kind: DemangleErrorKind, offset: usize) -> Self {
+        Self {
+            kind,
+            offset,
+            partial: String::new(),
+        }
+    }
+
+    #[inline]
+    pub(crate) fn with_partial(mut self, partial: String) -> Self {
+        self.partial = partial;
+        self
+    }
+
+    /// Returns the reason for which demangling failed.
+    #[inline]
+    pub fn kind(&self) -> DemangleErrorKind {
+        self.kind
+    }
+
+    /// Returns the byte offset within the symbol at which demangling failed.
+    #[inline]
+    pub fn offset(&self) -> usize {
+        self.offset
+    }
+
+    /// Returns the demangled text of what was parsed before the failure,
+    /// which may be empty.
+    #[inline]
+    pub fn partial(&self) -> &str {
+        &self.partial
+    }
+}
+
+impl fmt::Display for DemangleError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(
+            f,
+            "Failed to demangle symbol: {} at byte {}",
+            self.kind.description(),
+            self.offset,
+        )
+    }
+}
+
+impl Error for DemangleError {}
+
+/// The reason for which demangling a symbol failed.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub enum DemangleErrorKind {
+    /// The symbol doesn't start with a Swift mangling prefix.
+    NotMangled,
+    /// The symbol ended in the middle of an entity, such as a truncated name.
+    UnexpectedEnd,
+    /// An operator character isn't part of the mangling grammar.
+    UnknownOperator,
+    /// A substitution refers to an entity that hasn't been seen yet.
+    InvalidSubstitution,
+    /// The operators are valid but don't form a well-formed symbol.
+    Malformed,
+    /// A symbolic reference wasn't resolved to the entity it refers to.
+    UnresolvedSymbolicReference,
+    /// Entities are nested too deeply to be demangled.
+    TooComplex,
+}
+
+impl DemangleErrorKind {
+    fn description(self) -> &'static str {
+        match self {
+            Self::NotMangled => "not a Swift symbol",
+            Self::UnexpectedEnd => "unexpected end of symbol",
+            Self::UnknownOperator => "unknown operator",
+            Self::InvalidSubstitution => "invalid substitution index",
+            Self::Malformed => "malformed symbol",
+            Self::UnresolvedSymbolicReference => {
+                "unresolved symbolic reference"
+            }
+            Self::TooComplex => "entities nested too deeply",
+        }
+    }
+}
+
+/// A version of the Swift mangling scheme, identified by a symbol's prefix.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub enum ManglingGeneration {
+    /// Swift 3 and earlier, with the `_T` prefix.
+    Swift3,
+    /// Swift 4.0 and 4.1, with the `_T0` prefix.
+    Swift4,
+    /// Swift 4.2, with the `$S` prefix.
+    Swift4_2,
+    /// Swift 5 and later, with the `$s` prefix.
+    Swift5,
+}
+
+/// Returns whether `symbol` starts with a Swift mangling prefix.
+///
+/// This does not check whether the rest of `symbol` is well-formed.
+#[inline]
+pub fn is_mangled(symbol: &[u8]) -> bool {
+    demangler::prefix_len(symbol) != 0
+}
+
+/// Returns the mangling generation of `symbol` based on its prefix, or `None`
+/// if it isn't mangled.
+///
+/// Symbols on Darwin platforms may have an extra leading underscore, such as
+/// `_$s` or `__T0`.
+///
+/// # Examples
+///
+/// ```
+/// use swift_rt::sym::{self, ManglingGeneration};
+///
+/// let generation = sym::mangling_generation(b"_T04main3fooSSSi1x_tF");
+/// assert_eq!(generation, Some(ManglingGeneration::Swift4));
+///
+/// let name = sym::demangle(b"_T04main3fooSSSi1x_tF").unwrap();
+/// assert_eq!(name, "main.foo(x: Swift.Int) -> Swift.String");
+/// ```
+#[inline]
+pub fn mangling_generation(symbol: &[u8]) -> Option<ManglingGeneration> {
+    demangler::prefix(symbol).map(|(generation, _)| generation)
+}
+
+/// Attempts to parse `symbol` into a tree of nodes, returning an error if it
+/// isn't mangled.
+///
+/// The tree's text can be obtained via its `Display` implementation or
+/// rendered in other styles via
+/// [`DemangleOptions::print`](struct.DemangleOptions.html#method.print).
+#[inline]
+pub fn parse(symbol: &[u8]) -> Result<Node, DemangleError> {
+    demangler::demangle_symbol(symbol)
+}
+
+/// Attempts to classify `symbol` by what it refers to, returning an error if
+/// it isn't mangled.
+///
+/// This allows for filtering symbols, such as a module's exports, without
+/// matching against their demangled text.
+///
+/// # Examples
+///
+/// ```
+/// use swift_rt::sym::{self, SymbolKind};
+///
+/// let info = sym::classify(b"$s4main3FooVMa").unwrap();
+/// assert_eq!(info.kind(), SymbolKind::TypeMetadataAccessor);
+/// assert_eq!(info.context(), ["main"]);
+/// assert_eq!(info.name(), Some("Foo"));
+/// ```
+#[inline]
+pub fn classify(symbol: &[u8]) -> Result<SymbolInfo, DemangleError> {
+    parse(symbol).map(|node| SymbolInfo::of(&node))
+}
+
+/// Attempts to parse the mangled type name `mangled` (without a symbol prefix)
+/// into a tree of nodes, such as the `Si` in `$sSiN`.
+///
+/// Types that contain symbolic references, such as those in reflection
+/// metadata, fail to parse with an
+/// [`UnresolvedSymbolicReference`](enum.DemangleErrorKind.html#variant.UnresolvedSymbolicReference)
+/// error. See [`parse_type_with`](fn.parse_type_with.html) for resolving
+/// them.
+#[inline]
+pub fn parse_type(mangled: &[u8]) -> Result<Node, DemangleError> {
+    demangler::demangle_type(mangled, None)
+}
+
+/// Attempts to parse the mangled type name `mangled` into a tree of nodes,
+/// calling `resolver` for the node of the entity that each symbolic
+/// reference refers to.
+///
+/// Symbolic references whose resolver returns `None` fail to parse with an
+/// [`UnresolvedSymbolicReference`](enum.DemangleErrorKind.html#variant.UnresolvedSymbolicReference)
+/// error.
+///
+/// # Examples
+///
+/// Resolving references to descriptors whose names are known ahead of time:
+///
+/// ```
+/// use swift_rt::sym::{self, NominalKind, Node};
+///
+/// // `[Foo]`, where `Foo` is referenced 0x40 bytes after the offset.
+/// let mut mangled = b"Say\x01____G".to_vec();
+/// mangled[4..8].copy_from_slice(&0x40i32.to_ne_bytes());
+///
+/// let node = sym::parse_type_with(&mangled, |reference| {
+///     assert_eq!(reference.position(), 4);
+///     assert_eq!(reference.offset(), 0x40);
+///     let main = Node::module("main");
+///     Some(Node::nominal(NominalKind::Struct, main, "Foo"))
+/// })
+/// .unwrap();
+/// assert_eq!(node.to_string(), "Swift.Array<main.Foo>");
+/// ```
+#[inline]
+pub fn parse_type_with(
+    mangled: &[u8],
+    mut resolver: impl FnMut(&SymbolicReference) -> Option<Node>,
+) -> Result<Node, DemangleError> {
+    demangler::demangle_type(mangled, Some(&mut resolver))
+}
+
+/// Attempts to parse the mangled type name `mangled` into a tree of nodes,
+/// resolving its symbolic references to the context descriptors they refer
+/// to in the current process.
+///
+/// # Safety
+///
+/// `mangled` must be at its original location within an image that's loaded
+/// in the current process, such as the mangled type of a field.
+pub unsafe fn parse_type_in_process(
+    mangled: &[u8],
+) -> Result<Node, DemangleError> {
+    parse_type_with(mangled, |reference| {
+        symbolic::resolve_in_process(reference, mangled)
+    })
+}
+
+/// Attempts to demangle `symbol`, returning an error if it isn't mangled.
+///
+/// # Examples
+///
+/// ```
+/// use swift_rt::sym;
+///
+/// let name = sym::demangle(b"$s4main3foo1xSSSi_tF").unwrap();
+/// assert_eq!(name, "main.foo(x: Swift.Int) -> Swift.String");
+/// ```
+#[inline]
+pub fn demangle(symbol: &[u8]) -> Result<String, DemangleError> {
+    let mut buf = String::new();
+    unsafe {
+        let buf = buf.as_mut_vec();
+        demangle_into(symbol, buf)?;
+    }
+    Ok(buf)
+}
+
+/// Attempts to demangle `symbol` into `buffer`, returning the demangled slice
+/// or an error if `symbol` isn't mangled.
+///
+/// This allows for reusing the same buffer while still having a safe UTF-8
+/// string for the demangled name.
+///
+/// See [`DemangleOptions`](struct.DemangleOptions.html) for other styles.
+pub fn demangle_into<'b>(
+    symbol: &[u8],
+    buffer: &'b mut Vec<u8>,
+) -> Result<&'b mut str, DemangleError> {
+    DemangleOptions::new().demangle_into(symbol, buffer)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[track_caller]
+    fn check_error(
+        symbol: &str,
+        kind: DemangleErrorKind,
+        offset: usize,
+        partial: &str,
+    ) {
+        let error = parse(symbol.as_bytes()).unwrap_err();
+        assert_eq!(error.kind(), kind, "{}", symbol);
+        assert_eq!(error.offset(), offset, "{}", symbol);
+        assert_eq!(error.partial(), partial, "{}", symbol);
+    }
+
+    #[test]
+    fn not_mangled() {
+        check_error("main", DemangleErrorKind::NotMangled, 0, "");
+    }
+
+    #[test]
+    fn truncated() {
+        check_error("$s4main3fo", DemangleErrorKind::UnexpectedEnd, 10, "main");
+    }
+
+    #[test]
+    fn unknown_operator() {
+        check_error(
+            "$sSi&",
+            DemangleErrorKind::UnknownOperator,
+            4,
+            "Swift.Int",
+        );
+    }
+
+    #[test]
+    fn substitution_out_of_range() {
+        // `AG` refers to the seventh substitution, but only three were seen.
+        check_error(
+            "$s4main3FooVAGSgD",
+            DemangleErrorKind::InvalidSubstitution,
+            13,
+            "main.Foo",
+        );
+    }
+}
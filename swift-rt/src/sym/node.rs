@@ -0,0 +1,736 @@
+use super::DemangleOptions;
+use std::fmt;
+
+/// A node in the tree produced by demangling a Swift symbol.
+///
+/// This mirrors `swift::Demangle::Node` in C++. Every node has a
+/// [`kind`](#method.kind), an optional payload (either [`text`] or an
+/// [`index`]), and zero or more children.
+///
+/// [`text`]: #method.text
+/// [`index`]: #method.index
+#[derive(Clone, PartialEq, Eq, Hash)]
+pub struct Node {
+    kind: NodeKind,
+    payload: Payload,
+    children: Vec<Node>,
+}
+
+#[derive(Clone, PartialEq, Eq, Hash)]
+enum Payload {
+    None,
+    Text(Box<str>),
+    Index(u64),
+}
+
+impl fmt::Debug for Node {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        let mut tuple = f.debug_tuple("Node");
+        tuple.field(&self.kind);
+        match &self.payload {
+            Payload::None => {}
+            Payload::Text(text) => {
+                tuple.field(text);
+            }
+            Payload::Index(index) => {
+                tuple.field(index);
+            }
+        }
+        if !self.children.is_empty() {
+            tuple.field(&self.children);
+        }
+        tuple.finish()
+    }
+}
+
+impl fmt::Display for Node {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        let mut buf = Vec::new();
+        f.write_str(DemangleOptions::new().print_into(self, &mut buf))
+    }
+}
+
+impl Node {
+    #[inline]
+    pub(crate) fn new(kind: NodeKind) -> Self {
+        Self {
+            kind,
+            payload: Payload::None,
+            children: Vec::new(),
+        }
+    }
+
+    #[inline]
+    pub(crate) fn with_text<T: Into<Box<str>>>(
+        kind: NodeKind,
+        text: T,
+    ) -> Self {
+        Self {
+            kind,
+            payload: Payload::Text(text.into()),
+            children: Vec::new(),
+        }
+    }
+
+    #[inline]
+    pub(crate) fn with_index(kind: NodeKind, index: u64) -> Self {
+        Self {
+            kind,
+            payload: Payload::Index(index),
+            children: Vec::new(),
+        }
+    }
+
+    #[inline]
+    pub(crate) fn with_children<I>(kind: NodeKind, children: I) -> Self
+    where
+        I: IntoIterator<Item = Node>,
+    {
+        Self {
+            kind,
+            payload: Payload::None,
+            children: children.into_iter().collect(),
+        }
+    }
+
+    /// Wraps `node` in a [`NodeKind::Type`] node.
+    #[inline]
+    pub(crate) fn ty(node: Node) -> Self {
+        Self::with_children(NodeKind::Type, Some(node))
+    }
+
+    /// Returns what `self` represents.
+    #[inline]
+    pub fn kind(&self) -> NodeKind {
+        self.kind
+    }
+
+    /// Returns the text associated with `self`, such as an identifier.
+    #[inline]
+    pub fn text(&self) -> Option<&str> {
+        match &self.payload {
+            Payload::Text(text) => Some(text),
+            _ => None,
+        }
+    }
+
+    /// Returns the number associated with `self`, such as a generic parameter
+    /// depth or a closure discriminator.
+    #[inline]
+    pub fn index(&self) -> Option<u64> {
+        match self.payload {
+            Payload::Index(index) => Some(index),
+            _ => None,
+        }
+    }
+
+    /// Returns the children of `self`.
+    #[inline]
+    pub fn children(&self) -> &[Node] {
+        &self.children
+    }
+
+    /// Returns the child of `self` at `index`.
+    #[inline]
+    pub fn child(&self, index: usize) -> Option<&Node> {
+        self.children.get(index)
+    }
+
+    /// Returns the first child of `self` of the given `kind`.
+    #[inline]
+    pub fn child_of_kind(&self, kind: NodeKind) -> Option<&Node> {
+        self.children.iter().find(|child| child.kind == kind)
+    }
+
+    #[inline]
+    pub(crate) fn push(&mut self, child: Node) {
+        self.children.push(child);
+    }
+
+    #[inline]
+    pub(crate) fn children_mut(&mut self) -> &mut Vec<Node> {
+        &mut self.children
+    }
+
+    #[inline]
+    pub(crate) fn set_kind(&mut self, kind: NodeKind) {
+        self.kind = kind;
+    }
+
+    /// Returns whether the tree rooted at `self` is more than `limit` nodes
+    /// deep.
+    pub(crate) fn exceeds_depth(&self, limit: usize) -> bool {
+        limit == 0
+            || self
+                .children
+                .iter()
+                .any(|child| child.exceeds_depth(limit - 1))
+    }
+
+    #[inline]
+    pub(crate) fn into_children(self) -> Vec<Node> {
+        self.children
+    }
+
+    /// Returns `self` without its [`NodeKind::Type`] wrapper, if any.
+    #[inline]
+    pub(crate) fn unwrap_type(&self) -> &Node {
+        match self.kind {
+            NodeKind::Type if self.children.len() == 1 => &self.children[0],
+            _ => self,
+        }
+    }
+
+    /// Returns `self` without its [`NodeKind::Type`] wrapper, if any.
+    #[inline]
+    pub(crate) fn unwrap_type_mut(&mut self) -> &mut Node {
+        match self.kind {
+            NodeKind::Type if self.children.len() == 1 => &mut self.children[0],
+            _ => self,
+        }
+    }
+}
+
+macro_rules! node_kinds {
+    ($($(#[$meta:meta])+ $kind:ident,)+) => {
+        /// The kind of a demangled [`Node`](struct.Node.html).
+        ///
+        /// Names match `swift::Demangle::Node::Kind` from
+        /// [`DemangleNodes.def`](https://github.com/apple/swift/blob/master/include/swift/Demangling/DemangleNodes.def).
+        #[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+        pub enum NodeKind {
+            $($(#[$meta])+ $kind,)+
+        }
+    };
+}
+
+node_kinds! {
+    /// The root of a demangled symbol.
+    Global,
+    /// A type mangled on its own, e.g. `$sSiD`.
+    TypeMangling,
+    /// Unmangled trailing text, such as `.cold`.
+    Suffix,
+
+    /// A plain identifier.
+    Identifier,
+    /// A module name.
+    Module,
+    /// A declaration local to a function; children are the discriminator
+    /// index and the name.
+    LocalDeclName,
+    /// A `private`/`fileprivate` declaration; children are the discriminator
+    /// and the name.
+    PrivateDeclName,
+    /// The name of an infix operator.
+    InfixOperator,
+    /// The name of a prefix operator.
+    PrefixOperator,
+    /// The name of a postfix operator.
+    PostfixOperator,
+
+    /// A struct.
+    Structure,
+    /// A class.
+    Class,
+    /// An enum.
+    Enum,
+    /// A protocol.
+    Protocol,
+    /// A type alias.
+    TypeAlias,
+    /// An extension; children are the module, extended type and an optional
+    /// generic signature.
+    Extension,
+    /// A struct with generic arguments applied.
+    BoundGenericStructure,
+    /// A class with generic arguments applied.
+    BoundGenericClass,
+    /// An enum with generic arguments applied.
+    BoundGenericEnum,
+    /// A protocol with generic arguments applied.
+    BoundGenericProtocol,
+    /// A type alias with generic arguments applied.
+    BoundGenericTypeAlias,
+    /// A list of types, such as generic arguments.
+    TypeList,
+
+    /// A wrapper around anything usable as a type.
+    Type,
+    /// A tuple type.
+    Tuple,
+    /// An element of a tuple type.
+    TupleElement,
+    /// The label of a tuple element.
+    TupleElementName,
+    /// Marks a tuple element as variadic.
+    VariadicMarker,
+    /// An empty list; only exists while demangling.
+    EmptyList,
+    /// Marks the first element of a list, or an unlabeled parameter within
+    /// a [`LabelList`](#variant.LabelList).
+    FirstElementMarker,
+
+    /// A function type.
+    FunctionType,
+    /// A non-escaping function type.
+    NoEscapeFunctionType,
+    /// A `@convention(thin)` function type.
+    ThinFunctionType,
+    /// A `@convention(c)` function type.
+    CFunctionPointer,
+    /// A `@convention(block)` function type.
+    ObjCBlock,
+    /// An `@autoclosure` function type.
+    AutoClosureType,
+    /// An escaping `@autoclosure` function type.
+    EscapingAutoClosureType,
+    /// The parameters of a function type.
+    ArgumentTuple,
+    /// The result of a function type.
+    ReturnType,
+    /// Marks a function type as `throws`.
+    ThrowsAnnotation,
+    /// Marks a function type as `async`.
+    AsyncAnnotation,
+    /// The argument labels of a function declaration.
+    LabelList,
+
+    /// An `inout` parameter type.
+    InOut,
+    /// A `__shared` parameter type.
+    Shared,
+    /// An `__owned` parameter type.
+    Owned,
+    /// The metatype of a type, e.g. `Int.Type`.
+    Metatype,
+    /// The metatype of an existential, e.g. `P.Type`.
+    ExistentialMetatype,
+    /// A protocol composition.
+    ProtocolList,
+    /// A protocol composition with a superclass constraint.
+    ProtocolListWithClass,
+    /// A protocol composition with an `AnyObject` constraint.
+    ProtocolListWithAnyObject,
+    /// The dynamic `Self` type.
+    DynamicSelf,
+    /// A `weak` reference storage type.
+    Weak,
+    /// An `unowned` reference storage type.
+    Unowned,
+    /// An `unowned(unsafe)` reference storage type.
+    Unmanaged,
+    /// Sugar for `T?`.
+    SugaredOptional,
+    /// Sugar for `[T]`.
+    SugaredArray,
+    /// Sugar for `[K : V]`.
+    SugaredDictionary,
+    /// Sugar for `(T)`.
+    SugaredParen,
+    /// A type from the `Builtin` module.
+    BuiltinTypeName,
+    /// The `some` return type of the function being demangled.
+    OpaqueReturnType,
+    /// The opaque return type of a declaration, used as the context of an
+    /// [`OpaqueType`](#variant.OpaqueType).
+    OpaqueReturnTypeOf,
+    /// An opaque type; children are its declaration, index and generic
+    /// arguments.
+    OpaqueType,
+
+    /// A generic parameter; children are its depth and index.
+    DependentGenericParamType,
+    /// A generic signature.
+    DependentGenericSignature,
+    /// The number of generic parameters at one depth of a generic signature.
+    DependentGenericParamCount,
+    /// A `T: P` or `T: C` requirement.
+    DependentGenericConformanceRequirement,
+    /// A `T == U` requirement.
+    DependentGenericSameTypeRequirement,
+    /// A layout requirement, such as `T: AnyObject`.
+    DependentGenericLayoutRequirement,
+    /// A type with a generic signature applied.
+    DependentGenericType,
+    /// An associated type of a generic parameter, e.g. `T.Element`.
+    DependentMemberType,
+    /// The name of an associated type.
+    DependentAssociatedTypeRef,
+    /// A number.
+    Index,
+    /// A discriminator, such as that of a closure or local declaration.
+    Number,
+
+    /// A SIL function type.
+    ImplFunctionType,
+    /// Marks a SIL function type as `@escaping`.
+    ImplEscaping,
+    /// The callee convention of a SIL function type.
+    ImplConvention,
+    /// A `@convention` attribute of a SIL function type.
+    ImplFunctionAttribute,
+    /// A parameter of a SIL function type.
+    ImplParameter,
+    /// A result of a SIL function type.
+    ImplResult,
+    /// The error result of a SIL function type.
+    ImplErrorResult,
+
+    /// A function.
+    Function,
+    /// An allocating initializer.
+    Allocator,
+    /// A non-allocating initializer.
+    Constructor,
+    /// A deinitializer.
+    Destructor,
+    /// A deallocating deinitializer.
+    Deallocator,
+    /// A class's instance variable initializer.
+    IVarInitializer,
+    /// A class's instance variable destroyer.
+    IVarDestroyer,
+    /// The initial value expression of a variable.
+    Initializer,
+    /// The initializer of a default argument.
+    DefaultArgumentInitializer,
+    /// An explicit closure.
+    ExplicitClosure,
+    /// An implicit closure, e.g. an `@autoclosure` argument.
+    ImplicitClosure,
+    /// A variable or property.
+    Variable,
+    /// A subscript.
+    Subscript,
+    /// Marks an entity as `static`.
+    Static,
+
+    /// A getter.
+    Getter,
+    /// A setter.
+    Setter,
+    /// A `_modify` coroutine.
+    ModifyAccessor,
+    /// A `_read` coroutine.
+    ReadAccessor,
+    /// A `willSet` observer.
+    WillSet,
+    /// A `didSet` observer.
+    DidSet,
+    /// A `materializeForSet` accessor.
+    MaterializeForSet,
+    /// The getter of a global variable.
+    GlobalGetter,
+    /// An unsafe addressor.
+    UnsafeAddressor,
+    /// An unsafe mutable addressor.
+    UnsafeMutableAddressor,
+    /// An owning addressor.
+    OwningAddressor,
+    /// An owning mutable addressor.
+    OwningMutableAddressor,
+    /// A native owning addressor.
+    NativeOwningAddressor,
+    /// A native owning mutable addressor.
+    NativeOwningMutableAddressor,
+    /// A native pinning addressor.
+    NativePinningAddressor,
+    /// A native pinning mutable addressor.
+    NativePinningMutableAddressor,
+
+    /// Type metadata.
+    TypeMetadata,
+    /// A type metadata accessor function.
+    TypeMetadataAccessFunction,
+    /// Full type metadata, including the value witness table pointer prefix.
+    FullTypeMetadata,
+    /// The metaclass of a class.
+    Metaclass,
+    /// A nominal type descriptor.
+    NominalTypeDescriptor,
+    /// A protocol descriptor.
+    ProtocolDescriptor,
+    /// The base of the requirements of a protocol descriptor.
+    ProtocolRequirementsBaseDescriptor,
+    /// A protocol conformance descriptor.
+    ProtocolConformanceDescriptor,
+    /// A module context descriptor.
+    ModuleDescriptor,
+    /// An extension context descriptor.
+    ExtensionDescriptor,
+    /// A generic type metadata pattern.
+    GenericTypeMetadataPattern,
+    /// A type metadata completion function.
+    TypeMetadataCompletionFunction,
+    /// A type metadata instantiation function.
+    TypeMetadataInstantiationFunction,
+    /// A type metadata instantiation cache.
+    TypeMetadataInstantiationCache,
+    /// A type metadata singleton initialization cache.
+    TypeMetadataSingletonInitializationCache,
+    /// A lazy cache variable for type metadata.
+    TypeMetadataLazyCache,
+    /// The base offset of a class's metadata.
+    ClassMetadataBaseOffset,
+    /// A method lookup function.
+    MethodLookupFunction,
+    /// An Objective-C resilient class stub.
+    ObjCResilientClassStub,
+    /// A property descriptor.
+    PropertyDescriptor,
+    /// A reflection field descriptor.
+    ReflectionMetadataFieldDescriptor,
+    /// A reflection builtin type descriptor.
+    ReflectionMetadataBuiltinDescriptor,
+    /// A reflection associated type descriptor.
+    ReflectionMetadataAssocTypeDescriptor,
+    /// A reflection superclass descriptor.
+    ReflectionMetadataSuperclassDescriptor,
+
+    /// A conformance of a type to a protocol; children are the type,
+    /// protocol and module.
+    ProtocolConformance,
+    /// A value witness table.
+    ValueWitnessTable,
+    /// A single value witness function.
+    ValueWitness,
+    /// A protocol witness table.
+    ProtocolWitnessTable,
+    /// A protocol witness table pattern.
+    ProtocolWitnessTablePattern,
+    /// A generic protocol witness table.
+    GenericProtocolWitnessTable,
+    /// The instantiation function of a generic protocol witness table.
+    GenericProtocolWitnessTableInstantiationFunction,
+    /// A resilient protocol witness table.
+    ResilientProtocolWitnessTable,
+    /// A protocol witness table accessor.
+    ProtocolWitnessTableAccessor,
+    /// A lazy protocol witness table accessor.
+    LazyProtocolWitnessTableAccessor,
+    /// A lazy protocol witness table cache variable.
+    LazyProtocolWitnessTableCacheVariable,
+    /// An associated type metadata accessor.
+    AssociatedTypeMetadataAccessor,
+    /// An accessor for the witness table of an associated type conformance.
+    AssociatedTypeWitnessTableAccessor,
+    /// An associated type descriptor.
+    AssociatedTypeDescriptor,
+    /// An associated conformance descriptor; children are the protocol, the
+    /// path of the associated type and the protocol it conforms to.
+    AssociatedConformanceDescriptor,
+    /// The function which constructs an enum case.
+    EnumCase,
+    /// A path of associated types, such as `A.B`.
+    AssocTypePath,
+    /// A field offset.
+    FieldOffset,
+    /// Whether a field offset is direct or indirect.
+    Directness,
+
+    /// A protocol witness thunk; children are the conformance and the
+    /// requirement.
+    ProtocolWitness,
+    /// A method descriptor.
+    MethodDescriptor,
+    /// A dispatch thunk for a resilient method.
+    DispatchThunk,
+    /// A curry thunk.
+    CurryThunk,
+    /// A vtable thunk.
+    VTableThunk,
+    /// A reabstraction thunk.
+    ReabstractionThunk,
+    /// A reabstraction thunk helper.
+    ReabstractionThunkHelper,
+    /// An `@objc` thunk.
+    ObjCAttribute,
+    /// A `@nonobjc` thunk.
+    NonObjCAttribute,
+    /// A `dynamic` thunk.
+    DynamicAttribute,
+    /// A direct method reference thunk.
+    DirectMethodReferenceAttribute,
+    /// A partial application forwarder.
+    PartialApplyForwarder,
+    /// An Objective-C partial application forwarder.
+    PartialApplyObjCForwarder,
+    /// A merged function.
+    MergedFunction,
+    /// A variable outlined from a function.
+    OutlinedVariable,
+    /// The variable of a dynamically replaceable function.
+    DynamicallyReplaceableFunctionVar,
+    /// The key of a dynamically replaceable function.
+    DynamicallyReplaceableFunctionKey,
+    /// The original implementation of a dynamically replaceable function.
+    DynamicallyReplaceableFunctionImpl,
+    /// A generic specialization.
+    GenericSpecialization,
+    /// A generic specialization which is not re-abstracted.
+    GenericSpecializationNotReAbstracted,
+    /// A generic argument of a specialization.
+    GenericSpecializationParam,
+    /// The optimizer pass which produced a specialization.
+    SpecializationPassID,
+    /// Marks a specialization as serialized.
+    IsSerialized,
+    /// A function specialized by changing how its arguments are passed.
+    FunctionSignatureSpecialization,
+    /// How an argument of a function signature specialization changed.
+    FunctionSignatureSpecializationParam,
+    /// How the result of a function signature specialization changed.
+    FunctionSignatureSpecializationReturn,
+    /// The kind of change made to an argument or result.
+    FunctionSignatureSpecializationParamKind,
+    /// The constant or closure propagated into an argument.
+    FunctionSignatureSpecializationParamPayload,
+}
+
+impl NodeKind {
+    /// Returns whether `self` can be the context of another declaration.
+    pub fn is_context(self) -> bool {
+        use NodeKind::*;
+        matches!(
+            self,
+            Allocator
+                | Class
+                | Constructor
+                | Deallocator
+                | DefaultArgumentInitializer
+                | Destructor
+                | DidSet
+                | Enum
+                | ExplicitClosure
+                | Extension
+                | Function
+                | Getter
+                | GlobalGetter
+                | IVarInitializer
+                | IVarDestroyer
+                | ImplicitClosure
+                | Initializer
+                | MaterializeForSet
+                | ModifyAccessor
+                | Module
+                | NativeOwningAddressor
+                | NativeOwningMutableAddressor
+                | NativePinningAddressor
+                | NativePinningMutableAddressor
+                | OpaqueReturnTypeOf
+                | OwningAddressor
+                | OwningMutableAddressor
+                | Protocol
+                | ReadAccessor
+                | Setter
+                | Static
+                | Structure
+                | Subscript
+                | TypeAlias
+                | UnsafeAddressor
+                | UnsafeMutableAddressor
+                | Variable
+                | WillSet
+        )
+    }
+
+    /// Returns whether `self` is a nominal type or type alias.
+    pub fn is_any_generic(self) -> bool {
+        use NodeKind::*;
+        matches!(self, Structure | Class | Enum | Protocol | TypeAlias)
+    }
+
+    /// Returns whether `self` names a declaration.
+    pub fn is_decl_name(self) -> bool {
+        use NodeKind::*;
+        matches!(
+            self,
+            Identifier
+                | LocalDeclName
+                | PrivateDeclName
+                | PrefixOperator
+                | PostfixOperator
+                | InfixOperator
+        )
+    }
+
+    /// Returns whether `self` is an entity that may be wrapped by a global
+    /// symbol such as a thunk or descriptor.
+    pub fn is_entity(self) -> bool {
+        self == NodeKind::Type || self.is_context()
+    }
+
+    /// Returns whether `self` is a generic requirement.
+    pub fn is_requirement(self) -> bool {
+        use NodeKind::*;
+        matches!(
+            self,
+            DependentGenericConformanceRequirement
+                | DependentGenericSameTypeRequirement
+                | DependentGenericLayoutRequirement
+        )
+    }
+
+    /// Returns whether `self` is an accessor of a variable or subscript.
+    pub fn is_accessor(self) -> bool {
+        use NodeKind::*;
+        matches!(
+            self,
+            Getter
+                | Setter
+                | ModifyAccessor
+                | ReadAccessor
+                | WillSet
+                | DidSet
+                | MaterializeForSet
+                | GlobalGetter
+                | UnsafeAddressor
+                | UnsafeMutableAddressor
+                | OwningAddressor
+                | OwningMutableAddressor
+                | NativeOwningAddressor
+                | NativeOwningMutableAddressor
+                | NativePinningAddressor
+                | NativePinningMutableAddressor
+        )
+    }
+
+    /// Returns whether `self` is a function type.
+    pub fn is_function_type(self) -> bool {
+        use NodeKind::*;
+        matches!(
+            self,
+            FunctionType
+                | NoEscapeFunctionType
+                | ThinFunctionType
+                | CFunctionPointer
+                | ObjCBlock
+                | AutoClosureType
+                | EscapingAutoClosureType
+        )
+    }
+
+    /// Returns whether `self` is an attribute that prefixes the entity of a
+    /// global symbol, such as `@objc`.
+    pub(crate) fn is_function_attr(self) -> bool {
+        use NodeKind::*;
+        matches!(
+            self,
+            GenericSpecialization
+                | GenericSpecializationNotReAbstracted
+                | FunctionSignatureSpecialization
+                | ObjCAttribute
+                | NonObjCAttribute
+                | DynamicAttribute
+                | DirectMethodReferenceAttribute
+                | PartialApplyForwarder
+                | PartialApplyObjCForwarder
+                | MergedFunction
+                | OutlinedVariable
+                | DynamicallyReplaceableFunctionVar
+                | DynamicallyReplaceableFunctionKey
+                | DynamicallyReplaceableFunctionImpl
+        )
+    }
+}
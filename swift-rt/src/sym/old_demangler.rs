@@ -0,0 +1,1003 @@
+//! Demangling of the Swift 3 and earlier mangling grammar (`_T`).
+//!
+//! Unlike the stack-based grammar of later versions, this one is parsed by
+//! recursive descent. The resulting nodes have the same shape as those of
+//! later versions, so that they're rendered the same way.
+
+// Based on:
+// lib/Demangling/OldDemangler.cpp
+
+use super::{
+    demangler::{dependent_generic_param_type, ValueWitnessKind, MAX_DEPTH},
+    node::{Node, NodeKind},
+    punycode, DemangleError, DemangleErrorKind,
+};
+
+type Result<T> = std::result::Result<T, DemangleError>;
+
+/// Demangles a whole symbol, such as `_TF4main3fooFT_T_`.
+pub(crate) fn demangle_symbol(
+    symbol: &[u8],
+    prefix_len: usize,
+) -> Result<Node> {
+    let mut dem = OldDemangler {
+        text: symbol,
+        pos: prefix_len,
+        substitutions: Vec::new(),
+        depth: 0,
+    };
+    dem.demangle_top_level()
+}
+
+/// Demangling state.
+struct OldDemangler<'a> {
+    text: &'a [u8],
+    pos: usize,
+    substitutions: Vec<Node>,
+    /// The number of types and contexts being demangled within each other.
+    depth: usize,
+}
+
+impl<'a> OldDemangler<'a> {
+    #[inline]
+    fn error(&self) -> DemangleError {
+        let kind = if self.pos < self.text.len() {
+            DemangleErrorKind::Malformed
+        } else {
+            DemangleErrorKind::UnexpectedEnd
+        };
+        DemangleError::new(kind, self.pos)
+    }
+
+    #[inline]
+    fn fail<T>(&self) -> Result<T> {
+        Err(self.error())
+    }
+
+    /// Returns an error for an entity that extends past the end of the text.
+    fn truncated(&self) -> DemangleError {
+        let offset = self.text.len();
+        DemangleError::new(DemangleErrorKind::UnexpectedEnd, offset)
+    }
+
+    /// Fails on the operator `ch` that was just read, which is 0 at the end of
+    /// the text.
+    fn unknown_operator<T>(&self, ch: u8) -> Result<T> {
+        if ch == 0 {
+            return self.fail();
+        }
+        let offset = self.pos - 1;
+        Err(DemangleError::new(
+            DemangleErrorKind::UnknownOperator,
+            offset,
+        ))
+    }
+
+    /// Enters a nested type or context, failing if they're nested too
+    /// deeply. Each level adds at most a few nodes of depth.
+    fn enter(&mut self) -> Result<()> {
+        if self.depth >= MAX_DEPTH / 4 {
+            return Err(DemangleError::new(
+                DemangleErrorKind::TooComplex,
+                self.pos,
+            ));
+        }
+        self.depth += 1;
+        Ok(())
+    }
+
+    // Cursor ----------------------------------------------------------------
+
+    #[inline]
+    fn peek(&self) -> u8 {
+        self.text.get(self.pos).copied().unwrap_or(0)
+    }
+
+    #[inline]
+    fn next(&mut self) -> u8 {
+        let ch = self.peek();
+        if self.pos < self.text.len() {
+            self.pos += 1;
+        }
+        ch
+    }
+
+    #[inline]
+    fn next_if(&mut self, ch: u8) -> bool {
+        if self.peek() == ch && self.pos < self.text.len() {
+            self.pos += 1;
+            true
+        } else {
+            false
+        }
+    }
+
+    #[inline]
+    fn next_if_str(&mut self, s: &[u8]) -> bool {
+        if self.text[self.pos..].starts_with(s) {
+            self.pos += s.len();
+            true
+        } else {
+            false
+        }
+    }
+
+    #[inline]
+    fn is_empty(&self) -> bool {
+        self.pos >= self.text.len()
+    }
+
+    fn demangle_natural(&mut self) -> Result<u64> {
+        if !self.peek().is_ascii_digit() {
+            return self.fail();
+        }
+        let mut n: u64 = 0;
+        while self.peek().is_ascii_digit() {
+            let digit = u64::from(self.next() - b'0');
+            n = n
+                .checked_mul(10)
+                .and_then(|n| n.checked_add(digit))
+                .ok_or_else(|| self.error())?;
+        }
+        Ok(n)
+    }
+
+    /// Demangles `'_'` as 0 or `NATURAL '_'` as `NATURAL + 1`.
+    fn demangle_index(&mut self) -> Result<u64> {
+        if self.next_if(b'_') {
+            return Ok(0);
+        }
+        let n = self.demangle_natural()?;
+        if self.next_if(b'_') {
+            Ok(n + 1)
+        } else {
+            self.fail()
+        }
+    }
+
+    fn demangle_index_node(&mut self) -> Result<Node> {
+        let index = self.demangle_index()?;
+        Ok(Node::with_index(NodeKind::Number, index))
+    }
+
+    // Globals ---------------------------------------------------------------
+
+    fn demangle_top_level(&mut self) -> Result<Node> {
+        let mut global = Node::new(NodeKind::Global);
+
+        if self.next_if_str(b"To") {
+            global.push(Node::new(NodeKind::ObjCAttribute));
+        } else if self.next_if_str(b"TO") {
+            global.push(Node::new(NodeKind::NonObjCAttribute));
+        } else if self.next_if_str(b"TD") {
+            global.push(Node::new(NodeKind::DynamicAttribute));
+        } else if self.next_if_str(b"Td") {
+            global.push(Node::new(NodeKind::DirectMethodReferenceAttribute));
+        }
+        global.push(self.demangle_global()?);
+
+        if !self.is_empty() {
+            let suffix = String::from_utf8_lossy(&self.text[self.pos..]);
+            global.push(Node::with_text(NodeKind::Suffix, suffix));
+        }
+        Ok(global)
+    }
+
+    fn demangle_global(&mut self) -> Result<Node> {
+        if self.next_if(b'M') {
+            let kind = match self.next() {
+                b'P' => NodeKind::GenericTypeMetadataPattern,
+                b'a' => NodeKind::TypeMetadataAccessFunction,
+                b'L' => NodeKind::TypeMetadataLazyCache,
+                b'm' => NodeKind::Metaclass,
+                b'n' => NodeKind::NominalTypeDescriptor,
+                b'f' => NodeKind::FullTypeMetadata,
+                b'p' => {
+                    let proto = self.demangle_protocol_name()?;
+                    return Ok(Node::with_children(
+                        NodeKind::ProtocolDescriptor,
+                        Some(proto),
+                    ));
+                }
+                _ => {
+                    self.pos -= 1;
+                    NodeKind::TypeMetadata
+                }
+            };
+            return self.with_type(kind);
+        }
+
+        if self.next_if_str(b"PA") {
+            let kind = if self.next_if(b'o') {
+                NodeKind::PartialApplyObjCForwarder
+            } else {
+                NodeKind::PartialApplyForwarder
+            };
+            let mut forwarder = Node::new(kind);
+            if self.next_if_str(b"__T") {
+                forwarder.push(self.demangle_global()?);
+            }
+            return Ok(forwarder);
+        }
+
+        if self.next_if(b't') {
+            return self.with_type(NodeKind::TypeMangling);
+        }
+
+        if self.next_if(b'w') {
+            let code = [self.next(), self.next()];
+            let kind = ValueWitnessKind::from_code(code)
+                .ok_or_else(|| self.error())?;
+            let ty = self.demangle_type()?;
+            return Ok(Node::with_children(
+                NodeKind::ValueWitness,
+                vec![Node::with_index(NodeKind::Index, kind as u64), ty],
+            ));
+        }
+
+        if self.next_if(b'W') {
+            return self.demangle_witness();
+        }
+
+        if self.next_if(b'T') {
+            let kind = match self.next() {
+                b'R' => NodeKind::ReabstractionThunkHelper,
+                b'r' => NodeKind::ReabstractionThunk,
+                b'W' => {
+                    let conformance = self.demangle_protocol_conformance()?;
+                    let entity = self.demangle_entity()?;
+                    return Ok(Node::with_children(
+                        NodeKind::ProtocolWitness,
+                        vec![conformance, entity],
+                    ));
+                }
+                ch => return self.unknown_operator(ch),
+            };
+            let mut thunk = Node::new(kind);
+            if self.next_if(b'G') {
+                thunk.push(self.demangle_generic_signature()?);
+            }
+            thunk.push(self.demangle_type()?);
+            thunk.push(self.demangle_type()?);
+            return Ok(thunk);
+        }
+
+        self.demangle_entity()
+    }
+
+    fn demangle_witness(&mut self) -> Result<Node> {
+        match self.next() {
+            b'V' => self.with_type(NodeKind::ValueWitnessTable),
+            b'v' => {
+                let directness = match self.next() {
+                    b'd' => 0,
+                    b'i' => 1,
+                    ch => return self.unknown_operator(ch),
+                };
+                let entity = self.demangle_entity()?;
+                Ok(Node::with_children(
+                    NodeKind::FieldOffset,
+                    vec![
+                        Node::with_index(NodeKind::Directness, directness),
+                        entity,
+                    ],
+                ))
+            }
+            b'P' => self.with_conformance(NodeKind::ProtocolWitnessTable),
+            b'G' => {
+                self.with_conformance(NodeKind::GenericProtocolWitnessTable)
+            }
+            b'a' => {
+                self.with_conformance(NodeKind::ProtocolWitnessTableAccessor)
+            }
+            kind @ b'l' | kind @ b'L' => {
+                let kind = if kind == b'l' {
+                    NodeKind::LazyProtocolWitnessTableAccessor
+                } else {
+                    NodeKind::LazyProtocolWitnessTableCacheVariable
+                };
+                let ty = self.demangle_type()?;
+                let conformance = self.demangle_protocol_conformance()?;
+                Ok(Node::with_children(kind, vec![ty, conformance]))
+            }
+            b't' => {
+                let conformance = self.demangle_protocol_conformance()?;
+                let name = self.demangle_decl_name()?;
+                Ok(Node::with_children(
+                    NodeKind::AssociatedTypeMetadataAccessor,
+                    vec![conformance, name],
+                ))
+            }
+            b'T' => {
+                let conformance = self.demangle_protocol_conformance()?;
+                let name = self.demangle_decl_name()?;
+                let proto = self.demangle_protocol_name()?;
+                Ok(Node::with_children(
+                    NodeKind::AssociatedTypeWitnessTableAccessor,
+                    vec![
+                        conformance,
+                        Node::with_children(
+                            NodeKind::AssocTypePath,
+                            Some(name),
+                        ),
+                        proto,
+                    ],
+                ))
+            }
+            ch => self.unknown_operator(ch),
+        }
+    }
+
+    #[inline]
+    fn with_type(&mut self, kind: NodeKind) -> Result<Node> {
+        let ty = self.demangle_type()?;
+        Ok(Node::with_children(kind, Some(ty)))
+    }
+
+    #[inline]
+    fn with_conformance(&mut self, kind: NodeKind) -> Result<Node> {
+        let conformance = self.demangle_protocol_conformance()?;
+        Ok(Node::with_children(kind, Some(conformance)))
+    }
+
+    fn demangle_protocol_conformance(&mut self) -> Result<Node> {
+        let mut ty = self.demangle_type()?;
+        if self.next_if(b'u') {
+            let sig = self.demangle_generic_signature()?;
+            let inner = self.demangle_type()?;
+            ty = Node::ty(Node::with_children(
+                NodeKind::DependentGenericType,
+                vec![sig, inner],
+            ));
+        }
+        let proto = self.demangle_protocol_name()?;
+        let context = self.demangle_context()?;
+        Ok(Node::with_children(
+            NodeKind::ProtocolConformance,
+            vec![ty, proto, context],
+        ))
+    }
+
+    // Entities --------------------------------------------------------------
+
+    fn demangle_entity(&mut self) -> Result<Node> {
+        let is_static = self.next_if(b'Z');
+
+        let basic_kind = match self.next() {
+            b'F' => NodeKind::Function,
+            b'v' => NodeKind::Variable,
+            b'I' => NodeKind::Initializer,
+            b'i' => NodeKind::Subscript,
+            _ => {
+                self.pos -= 1;
+                return self.demangle_nominal_type();
+            }
+        };
+
+        let context = self.demangle_context()?;
+
+        let mut kind = basic_kind;
+        let mut accessor = None;
+        let mut name = None;
+        let mut has_type = true;
+        match self.next() {
+            b'D' => {
+                kind = NodeKind::Deallocator;
+                has_type = false;
+            }
+            b'd' => {
+                kind = NodeKind::Destructor;
+                has_type = false;
+            }
+            b'e' => {
+                kind = NodeKind::IVarInitializer;
+                has_type = false;
+            }
+            b'E' => {
+                kind = NodeKind::IVarDestroyer;
+                has_type = false;
+            }
+            b'C' => kind = NodeKind::Allocator,
+            b'c' => kind = NodeKind::Constructor,
+            b'a' => {
+                accessor = Some(match self.next() {
+                    b'O' => NodeKind::OwningMutableAddressor,
+                    b'o' => NodeKind::NativeOwningMutableAddressor,
+                    b'p' => NodeKind::NativePinningMutableAddressor,
+                    b'u' => NodeKind::UnsafeMutableAddressor,
+                    ch => return self.unknown_operator(ch),
+                });
+                name = Some(self.demangle_decl_name()?);
+            }
+            b'l' => {
+                accessor = Some(match self.next() {
+                    b'O' => NodeKind::OwningAddressor,
+                    b'o' => NodeKind::NativeOwningAddressor,
+                    b'p' => NodeKind::NativePinningAddressor,
+                    b'u' => NodeKind::UnsafeAddressor,
+                    ch => return self.unknown_operator(ch),
+                });
+                name = Some(self.demangle_decl_name()?);
+            }
+            ch @ b'g'
+            | ch @ b'G'
+            | ch @ b's'
+            | ch @ b'm'
+            | ch @ b'w'
+            | ch @ b'W' => {
+                accessor = Some(match ch {
+                    b'g' => NodeKind::Getter,
+                    b'G' => NodeKind::GlobalGetter,
+                    b's' => NodeKind::Setter,
+                    b'm' => NodeKind::MaterializeForSet,
+                    b'w' => NodeKind::WillSet,
+                    _ => NodeKind::DidSet,
+                });
+                name = Some(self.demangle_decl_name()?);
+            }
+            b'U' => {
+                kind = NodeKind::ExplicitClosure;
+                name = Some(self.demangle_index_node()?);
+            }
+            b'u' => {
+                kind = NodeKind::ImplicitClosure;
+                name = Some(self.demangle_index_node()?);
+            }
+            b'A' if basic_kind == NodeKind::Initializer => {
+                kind = NodeKind::DefaultArgumentInitializer;
+                name = Some(self.demangle_index_node()?);
+                has_type = false;
+            }
+            b'i' if basic_kind == NodeKind::Initializer => has_type = false,
+            _ if basic_kind == NodeKind::Initializer => return self.fail(),
+            _ => {
+                self.pos -= 1;
+                name = Some(self.demangle_decl_name()?);
+            }
+        }
+
+        // Accessors wrap the storage they access.
+        if accessor.is_some() && kind != NodeKind::Subscript {
+            kind = NodeKind::Variable;
+        }
+        // Subscripts are unnamed in later manglings.
+        if kind == NodeKind::Subscript {
+            name = None;
+        }
+
+        let mut entity = Node::with_children(kind, Some(context));
+        entity.children_mut().extend(name);
+        if has_type {
+            entity.push(self.demangle_type()?);
+        }
+
+        if let Some(accessor) = accessor {
+            entity = Node::with_children(accessor, Some(entity));
+        }
+        if is_static {
+            entity = Node::with_children(NodeKind::Static, Some(entity));
+        }
+        Ok(entity)
+    }
+
+    fn demangle_context(&mut self) -> Result<Node> {
+        self.enter()?;
+        let context = self.demangle_context_impl();
+        self.depth -= 1;
+        context
+    }
+
+    fn demangle_context_impl(&mut self) -> Result<Node> {
+        match self.peek() {
+            b'E' => {
+                self.next();
+                let module = self.demangle_module()?;
+                let ty = self.demangle_context()?;
+                Ok(Node::with_children(NodeKind::Extension, vec![module, ty]))
+            }
+            b'e' => {
+                self.next();
+                let module = self.demangle_module()?;
+                let sig = self.demangle_generic_signature()?;
+                let ty = self.demangle_context()?;
+                Ok(Node::with_children(
+                    NodeKind::Extension,
+                    vec![module, ty, sig],
+                ))
+            }
+            b'S' => {
+                self.next();
+                self.demangle_substitution_index()
+            }
+            b's' => {
+                self.next();
+                Ok(Node::with_text(NodeKind::Module, "Swift"))
+            }
+            b'F' | b'I' | b'v' | b'P' | b'Z' | b'C' | b'V' | b'O' => {
+                self.demangle_entity()
+            }
+            _ => self.demangle_module(),
+        }
+    }
+
+    fn demangle_module(&mut self) -> Result<Node> {
+        if self.next_if(b's') {
+            return Ok(Node::with_text(NodeKind::Module, "Swift"));
+        }
+        if self.next_if(b'S') {
+            let module = self.demangle_substitution_index()?;
+            if module.kind() != NodeKind::Module {
+                return self.fail();
+            }
+            return Ok(module);
+        }
+        let module = self.demangle_identifier(NodeKind::Module)?;
+        self.substitutions.push(module.clone());
+        Ok(module)
+    }
+
+    fn demangle_nominal_type(&mut self) -> Result<Node> {
+        let kind = match self.next() {
+            b'S' => return self.demangle_substitution_index(),
+            b'V' => NodeKind::Structure,
+            b'O' => NodeKind::Enum,
+            b'C' => NodeKind::Class,
+            b'P' => NodeKind::Protocol,
+            ch => return self.unknown_operator(ch),
+        };
+        self.demangle_declaration_name(kind)
+    }
+
+    fn demangle_declaration_name(&mut self, kind: NodeKind) -> Result<Node> {
+        let context = self.demangle_context()?;
+        let name = self.demangle_decl_name()?;
+        let decl = Node::with_children(kind, vec![context, name]);
+        self.substitutions.push(decl.clone());
+        Ok(decl)
+    }
+
+    fn demangle_substitution_index(&mut self) -> Result<Node> {
+        let swift_type = |kind: NodeKind, name: &str| {
+            Node::with_children(
+                kind,
+                vec![
+                    Node::with_text(NodeKind::Module, "Swift"),
+                    Node::with_text(NodeKind::Identifier, name),
+                ],
+            )
+        };
+
+        let (kind, name) = match self.next() {
+            b'o' => return Ok(Node::with_text(NodeKind::Module, "__ObjC")),
+            b'C' => return Ok(Node::with_text(NodeKind::Module, "__C")),
+            b's' => return Ok(Node::with_text(NodeKind::Module, "Swift")),
+            b'a' => (NodeKind::Structure, "Array"),
+            b'b' => (NodeKind::Structure, "Bool"),
+            b'c' => (NodeKind::Structure, "UnicodeScalar"),
+            b'd' => (NodeKind::Structure, "Double"),
+            b'f' => (NodeKind::Structure, "Float"),
+            b'i' => (NodeKind::Structure, "Int"),
+            b'V' => (NodeKind::Structure, "UnsafeRawPointer"),
+            b'v' => (NodeKind::Structure, "UnsafeMutableRawPointer"),
+            b'P' => (NodeKind::Structure, "UnsafePointer"),
+            b'p' => (NodeKind::Structure, "UnsafeMutablePointer"),
+            b'q' => (NodeKind::Enum, "Optional"),
+            b'Q' => (NodeKind::Enum, "ImplicitlyUnwrappedOptional"),
+            b'R' => (NodeKind::Structure, "UnsafeBufferPointer"),
+            b'r' => (NodeKind::Structure, "UnsafeMutableBufferPointer"),
+            b'S' => (NodeKind::Structure, "String"),
+            b'u' => (NodeKind::Structure, "UInt"),
+            _ => {
+                self.pos -= 1;
+                let start = self.pos;
+                let index = self.demangle_index()?;
+                return match self.substitutions.get(index as usize) {
+                    Some(node) => Ok(node.clone()),
+                    None => Err(DemangleError::new(
+                        DemangleErrorKind::InvalidSubstitution,
+                        start,
+                    )),
+                };
+            }
+        };
+        Ok(swift_type(kind, name))
+    }
+
+    // Names -----------------------------------------------------------------
+
+    fn demangle_decl_name(&mut self) -> Result<Node> {
+        if self.next_if(b'L') {
+            let discriminator = self.demangle_index_node()?;
+            let name = self.demangle_identifier(NodeKind::Identifier)?;
+            return Ok(Node::with_children(
+                NodeKind::LocalDeclName,
+                vec![discriminator, name],
+            ));
+        }
+        if self.next_if(b'P') {
+            let discriminator =
+                self.demangle_identifier(NodeKind::Identifier)?;
+            let name = self.demangle_identifier(NodeKind::Identifier)?;
+            return Ok(Node::with_children(
+                NodeKind::PrivateDeclName,
+                vec![discriminator, name],
+            ));
+        }
+        self.demangle_identifier(NodeKind::Identifier)
+    }
+
+    fn demangle_identifier(&mut self, mut kind: NodeKind) -> Result<Node> {
+        const OP_CHARS: &[u8; 26] = b"& @/= >    <*!|+?%-~   ^ .";
+
+        let is_punycoded = self.next_if(b'X');
+
+        let is_operator = self.next_if(b'o');
+        if is_operator {
+            // Operators are only valid as declaration names.
+            if kind != NodeKind::Identifier {
+                return self.fail();
+            }
+            kind = match self.next() {
+                b'p' => NodeKind::PrefixOperator,
+                b'P' => NodeKind::PostfixOperator,
+                b'i' => NodeKind::InfixOperator,
+                ch => return self.unknown_operator(ch),
+            };
+        }
+
+        let len = self.demangle_natural()? as usize;
+        let end = self.pos.checked_add(len).ok_or_else(|| self.truncated())?;
+        let slice = self
+            .text
+            .get(self.pos..end)
+            .ok_or_else(|| self.truncated())?;
+        self.pos = end;
+
+        let mut ident = if is_punycoded {
+            punycode::decode(slice).ok_or_else(|| self.error())?
+        } else {
+            String::from_utf8(slice.to_vec()).map_err(|_| self.error())?
+        };
+        if ident.is_empty() {
+            return self.fail();
+        }
+
+        if is_operator {
+            let mut op = String::with_capacity(ident.len());
+            for ch in ident.chars() {
+                if !ch.is_ascii() {
+                    // Pass through Unicode characters.
+                    op.push(ch);
+                    continue;
+                }
+                let ch = ch as u8;
+                if !ch.is_ascii_lowercase() {
+                    return self.fail();
+                }
+                match OP_CHARS[usize::from(ch - b'a')] {
+                    b' ' => return self.fail(),
+                    op_ch => op.push(char::from(op_ch)),
+                }
+            }
+            ident = op;
+        }
+
+        Ok(Node::with_text(kind, ident))
+    }
+
+    fn demangle_protocol_name(&mut self) -> Result<Node> {
+        let proto = if self.next_if(b'S') {
+            let sub = self.demangle_substitution_index()?;
+            match sub.kind() {
+                NodeKind::Protocol => sub,
+                NodeKind::Module => self.demangle_protocol_in(sub)?,
+                _ => return self.fail(),
+            }
+        } else if self.next_if(b's') {
+            let swift = Node::with_text(NodeKind::Module, "Swift");
+            self.demangle_protocol_in(swift)?
+        } else {
+            self.demangle_declaration_name(NodeKind::Protocol)?
+        };
+        Ok(Node::ty(proto))
+    }
+
+    fn demangle_protocol_in(&mut self, context: Node) -> Result<Node> {
+        let name = self.demangle_decl_name()?;
+        let proto =
+            Node::with_children(NodeKind::Protocol, vec![context, name]);
+        self.substitutions.push(proto.clone());
+        Ok(proto)
+    }
+
+    // Types -----------------------------------------------------------------
+
+    fn demangle_type(&mut self) -> Result<Node> {
+        self.enter()?;
+        let ty = self.demangle_type_impl();
+        self.depth -= 1;
+        let ty = ty?;
+        Ok(match ty.kind() {
+            NodeKind::Type => ty,
+            _ => Node::ty(ty),
+        })
+    }
+
+    fn demangle_type_impl(&mut self) -> Result<Node> {
+        match self.next() {
+            b'B' => self.demangle_builtin_type(),
+            b'a' => self.demangle_declaration_name(NodeKind::TypeAlias),
+            b'b' => self.demangle_function_type(NodeKind::ObjCBlock),
+            b'c' => self.demangle_function_type(NodeKind::CFunctionPointer),
+            b'D' => self.with_type(NodeKind::DynamicSelf),
+            b'F' | b'f' => self.demangle_function_type(NodeKind::FunctionType),
+            b'K' => self.demangle_function_type(NodeKind::AutoClosureType),
+            b'G' => self.demangle_bound_generic_type(),
+            b'M' => self.with_type(NodeKind::Metatype),
+            b'P' => {
+                if self.next_if(b'M') {
+                    return self.with_type(NodeKind::ExistentialMetatype);
+                }
+                let mut list = Node::new(NodeKind::TypeList);
+                while !self.next_if(b'_') {
+                    if self.is_empty() {
+                        return self.fail();
+                    }
+                    list.push(self.demangle_protocol_name()?);
+                }
+                Ok(Node::with_children(NodeKind::ProtocolList, Some(list)))
+            }
+            b'Q' => {
+                // Archetypes are rendered like generic parameters.
+                let (depth, index) = if self.next_if(b'd') {
+                    (self.demangle_index()? + 1, self.demangle_index()?)
+                } else {
+                    (0, self.demangle_index()?)
+                };
+                Ok(dependent_generic_param_type(depth, index))
+            }
+            b'x' => Ok(dependent_generic_param_type(0, 0)),
+            b'q' => self.demangle_generic_param_index(),
+            b'w' => {
+                let base = self.demangle_generic_param_index()?;
+                self.demangle_dependent_member_type(Node::ty(base))
+            }
+            b'W' => {
+                let mut base = Node::ty(self.demangle_generic_param_index()?);
+                while !self.next_if(b'_') {
+                    if self.is_empty() {
+                        return self.fail();
+                    }
+                    base = Node::ty(self.demangle_dependent_member_type(base)?);
+                }
+                Ok(base)
+            }
+            b'R' => self.with_type(NodeKind::InOut),
+            b'S' => self.demangle_substitution_index(),
+            b'T' => self.demangle_tuple(false),
+            b't' => self.demangle_tuple(true),
+            b'u' => {
+                let sig = self.demangle_generic_signature()?;
+                let ty = self.demangle_type()?;
+                Ok(Node::with_children(
+                    NodeKind::DependentGenericType,
+                    vec![sig, ty],
+                ))
+            }
+            b'X' => match self.next() {
+                b'w' => self.with_type(NodeKind::Weak),
+                b'o' => self.with_type(NodeKind::Unowned),
+                b'u' => self.with_type(NodeKind::Unmanaged),
+                ch => self.unknown_operator(ch),
+            },
+            b'C' => self.demangle_declaration_name(NodeKind::Class),
+            b'V' => self.demangle_declaration_name(NodeKind::Structure),
+            b'O' => self.demangle_declaration_name(NodeKind::Enum),
+            ch => self.unknown_operator(ch),
+        }
+    }
+
+    fn demangle_builtin_type(&mut self) -> Result<Node> {
+        let name = match self.next() {
+            b'b' => "Builtin.BridgeObject".into(),
+            b'B' => "Builtin.UnsafeValueBuffer".into(),
+            b'f' => {
+                let bits = self.demangle_natural()?;
+                if !self.next_if(b'_') {
+                    return self.fail();
+                }
+                format!("Builtin.FPIEEE{}", bits)
+            }
+            b'i' => {
+                let bits = self.demangle_natural()?;
+                if !self.next_if(b'_') {
+                    return self.fail();
+                }
+                format!("Builtin.Int{}", bits)
+            }
+            b'O' => "Builtin.UnknownObject".into(),
+            b'o' => "Builtin.NativeObject".into(),
+            b'p' => "Builtin.RawPointer".into(),
+            b'w' => "Builtin.Word".into(),
+            ch => return self.unknown_operator(ch),
+        };
+        Ok(Node::with_text(NodeKind::BuiltinTypeName, name))
+    }
+
+    fn demangle_function_type(&mut self, kind: NodeKind) -> Result<Node> {
+        let throws = self.next_if(b'z');
+        let args = self.demangle_type()?;
+        let result = self.demangle_type()?;
+
+        let mut func = Node::new(kind);
+        if throws {
+            func.push(Node::new(NodeKind::ThrowsAnnotation));
+        }
+        func.push(Node::with_children(NodeKind::ArgumentTuple, Some(args)));
+        func.push(Node::with_children(NodeKind::ReturnType, Some(result)));
+        Ok(func)
+    }
+
+    fn demangle_bound_generic_type(&mut self) -> Result<Node> {
+        let unbound = self.demangle_type()?;
+        let mut args = Node::new(NodeKind::TypeList);
+        while !self.next_if(b'_') {
+            if self.is_empty() {
+                return self.fail();
+            }
+            args.push(self.demangle_type()?);
+        }
+
+        let kind = match unbound.unwrap_type().kind() {
+            NodeKind::Structure => NodeKind::BoundGenericStructure,
+            NodeKind::Class => NodeKind::BoundGenericClass,
+            NodeKind::Enum => NodeKind::BoundGenericEnum,
+            NodeKind::Protocol => NodeKind::BoundGenericProtocol,
+            NodeKind::TypeAlias => NodeKind::BoundGenericTypeAlias,
+            _ => return self.fail(),
+        };
+        Ok(Node::with_children(kind, vec![unbound, args]))
+    }
+
+    fn demangle_tuple(&mut self, is_variadic: bool) -> Result<Node> {
+        let mut tuple = Node::new(NodeKind::Tuple);
+        while !self.next_if(b'_') {
+            if self.is_empty() {
+                return self.fail();
+            }
+            let mut element = Node::new(NodeKind::TupleElement);
+            let ch = self.peek();
+            if ch.is_ascii_digit() || ch == b'X' || ch == b'o' {
+                element.push(
+                    self.demangle_identifier(NodeKind::TupleElementName)?,
+                );
+            }
+            element.push(self.demangle_type()?);
+            tuple.push(element);
+        }
+
+        if is_variadic {
+            match tuple.children_mut().last_mut() {
+                Some(last) => last.push(Node::new(NodeKind::VariadicMarker)),
+                None => return self.fail(),
+            }
+        }
+        Ok(tuple)
+    }
+
+    // Generics --------------------------------------------------------------
+
+    fn demangle_generic_param_index(&mut self) -> Result<Node> {
+        let (depth, index) = if self.next_if(b'd') {
+            (self.demangle_index()? + 1, self.demangle_index()?)
+        } else if self.next_if(b'x') {
+            (0, 0)
+        } else {
+            (0, self.demangle_index()? + 1)
+        };
+        Ok(dependent_generic_param_type(depth, index))
+    }
+
+    fn demangle_dependent_member_type(&mut self, base: Node) -> Result<Node> {
+        let assoc = if self.next_if(b'S') {
+            let sub = self.demangle_substitution_index()?;
+            if sub.kind() != NodeKind::DependentAssociatedTypeRef {
+                return self.fail();
+            }
+            sub
+        } else {
+            let proto = if self.next_if(b'P') {
+                Some(self.demangle_protocol_name()?)
+            } else {
+                None
+            };
+            let mut assoc = self.demangle_identifier(NodeKind::Identifier)?;
+            assoc.set_kind(NodeKind::DependentAssociatedTypeRef);
+            assoc.children_mut().extend(proto);
+            self.substitutions.push(assoc.clone());
+            assoc
+        };
+        Ok(Node::with_children(
+            NodeKind::DependentMemberType,
+            vec![base, assoc],
+        ))
+    }
+
+    fn demangle_generic_signature(&mut self) -> Result<Node> {
+        let mut sig = Node::new(NodeKind::DependentGenericSignature);
+
+        while self.peek() != b'R' && self.peek() != b'r' {
+            let count = if self.next_if(b'z') {
+                0
+            } else {
+                self.demangle_index()? + 1
+            };
+            sig.push(Node::with_index(
+                NodeKind::DependentGenericParamCount,
+                count,
+            ));
+        }
+        // No counts means there's exactly one parameter.
+        if sig.children().is_empty() {
+            sig.push(Node::with_index(NodeKind::DependentGenericParamCount, 1));
+        }
+
+        if self.next_if(b'r') {
+            return Ok(sig);
+        }
+        if !self.next_if(b'R') {
+            return self.fail();
+        }
+        while !self.next_if(b'r') {
+            if self.is_empty() {
+                return self.fail();
+            }
+            sig.push(self.demangle_generic_requirement()?);
+        }
+        Ok(sig)
+    }
+
+    fn demangle_generic_requirement(&mut self) -> Result<Node> {
+        let constrained = match self.next() {
+            b'w' => {
+                let base = self.demangle_generic_param_index()?;
+                self.demangle_dependent_member_type(Node::ty(base))?
+            }
+            _ => {
+                self.pos -= 1;
+                self.demangle_generic_param_index()?
+            }
+        };
+        let constrained = Node::ty(constrained);
+
+        if self.next_if(b'z') {
+            let ty = self.demangle_type()?;
+            return Ok(Node::with_children(
+                NodeKind::DependentGenericSameTypeRequirement,
+                vec![constrained, ty],
+            ));
+        }
+
+        let constraint = match self.peek() {
+            b'C' => self.demangle_type()?,
+            b'S' => {
+                // A substitution may refer to a protocol, a class or the
+                // module of a protocol.
+                self.next();
+                let sub = self.demangle_substitution_index()?;
+                match sub.kind() {
+                    NodeKind::Protocol | NodeKind::Class => Node::ty(sub),
+                    NodeKind::Module => {
+                        Node::ty(self.demangle_protocol_in(sub)?)
+                    }
+                    _ => return self.fail(),
+                }
+            }
+            _ => self.demangle_protocol_name()?,
+        };
+        Ok(Node::with_children(
+            NodeKind::DependentGenericConformanceRequirement,
+            vec![constrained, constraint],
+        ))
+    }
+}
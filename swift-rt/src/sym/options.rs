@@ -0,0 +1,255 @@
+//! Options for how demangled symbols are rendered.
+
+use super::{demangler, printer, DemangleError, Node};
+
+/// Options that control the style of demangled symbol names.
+///
+/// The default options produce the same text as `swift_demangle`. Use
+/// [`simplified`](#method.simplified) for the compact form produced by
+/// `swift demangle --simplified`, which is better suited for display in UIs.
+///
+/// # Examples
+///
+/// ```
+/// use swift_rt::sym::DemangleOptions;
+///
+/// let symbol = b"$s4main3foo1xSSSi_tF";
+///
+/// let full = DemangleOptions::new().demangle(symbol).unwrap();
+/// assert_eq!(full, "main.foo(x: Swift.Int) -> Swift.String");
+///
+/// let simplified = DemangleOptions::simplified().demangle(symbol).unwrap();
+/// assert_eq!(simplified, "foo(x:)");
+///
+/// let no_modules = DemangleOptions::new()
+///     .module_names(false)
+///     .return_types(false)
+///     .demangle(symbol)
+///     .unwrap();
+/// assert_eq!(no_modules, "foo(x: Int)");
+/// ```
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub struct DemangleOptions {
+    pub(crate) sugar: bool,
+    pub(crate) qualify_entities: bool,
+    pub(crate) module_names: bool,
+    pub(crate) extension_contexts: bool,
+    pub(crate) unmangled_suffix: bool,
+    pub(crate) generic_specializations: bool,
+    pub(crate) protocol_conformances: bool,
+    pub(crate) where_clauses: bool,
+    pub(crate) entity_types: bool,
+    pub(crate) argument_types: bool,
+    pub(crate) return_types: bool,
+    pub(crate) private_discriminators: bool,
+    pub(crate) shorten_thunks: bool,
+}
+
+impl Default for DemangleOptions {
+    #[inline]
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl DemangleOptions {
+    /// Returns options that produce the same text as `swift_demangle`.
+    #[inline]
+    pub const fn new() -> Self {
+        Self {
+            sugar: false,
+            qualify_entities: true,
+            module_names: true,
+            extension_contexts: true,
+            unmangled_suffix: true,
+            generic_specializations: true,
+            protocol_conformances: true,
+            where_clauses: true,
+            entity_types: true,
+            argument_types: true,
+            return_types: true,
+            private_discriminators: true,
+            shorten_thunks: false,
+        }
+    }
+
+    /// Returns options that produce the same text as
+    /// `swift demangle --simplified`.
+    ///
+    /// Functions are rendered by name and argument labels only, such as
+    /// `foo(x:)`, and module names, where clauses and type annotations are
+    /// omitted.
+    #[inline]
+    pub const fn simplified() -> Self {
+        Self {
+            sugar: true,
+            qualify_entities: true,
+            module_names: false,
+            extension_contexts: false,
+            unmangled_suffix: false,
+            generic_specializations: false,
+            protocol_conformances: false,
+            where_clauses: false,
+            entity_types: false,
+            argument_types: false,
+            return_types: false,
+            private_discriminators: false,
+            shorten_thunks: true,
+        }
+    }
+
+    /// Sets whether `Optional`, `Array` and `Dictionary` are rendered with
+    /// their sugared syntax, such as `[Int]` instead of `Array<Int>`.
+    #[inline]
+    pub const fn sugar(mut self, yes: bool) -> Self {
+        self.sugar = yes;
+        self
+    }
+
+    /// Sets whether entities are prefixed with the context they're declared
+    /// in, such as `Foo.bar` instead of `bar`.
+    #[inline]
+    pub const fn qualify_entities(mut self, yes: bool) -> Self {
+        self.qualify_entities = yes;
+        self
+    }
+
+    /// Sets whether module names are rendered, such as `Swift.Int` instead of
+    /// `Int`.
+    #[inline]
+    pub const fn module_names(mut self, yes: bool) -> Self {
+        self.module_names = yes;
+        self
+    }
+
+    /// Sets whether declarations within extensions are rendered with the
+    /// extension's module, such as `(extension in Foundation):Swift.String`.
+    #[inline]
+    pub const fn extension_contexts(mut self, yes: bool) -> Self {
+        self.extension_contexts = yes;
+        self
+    }
+
+    /// Sets whether text following the mangled portion of a symbol is
+    /// rendered.
+    #[inline]
+    pub const fn unmangled_suffix(mut self, yes: bool) -> Self {
+        self.unmangled_suffix = yes;
+        self
+    }
+
+    /// Sets whether the details of specializations are rendered, such as
+    /// `generic specialization <Swift.Int> of` instead of `specialized`.
+    #[inline]
+    pub const fn generic_specializations(mut self, yes: bool) -> Self {
+        self.generic_specializations = yes;
+        self
+    }
+
+    /// Sets whether the protocol and module of a protocol conformance are
+    /// rendered after the conforming type.
+    #[inline]
+    pub const fn protocol_conformances(mut self, yes: bool) -> Self {
+        self.protocol_conformances = yes;
+        self
+    }
+
+    /// Sets whether the `where` clauses of generic signatures are rendered.
+    #[inline]
+    pub const fn where_clauses(mut self, yes: bool) -> Self {
+        self.where_clauses = yes;
+        self
+    }
+
+    /// Sets whether the types of variables and accessors are rendered, such
+    /// as the `: Swift.Int` in `main.x : Swift.Int`.
+    #[inline]
+    pub const fn entity_types(mut self, yes: bool) -> Self {
+        self.entity_types = yes;
+        self
+    }
+
+    /// Sets whether the argument types of functions are rendered.
+    ///
+    /// If not, only argument labels are rendered, such as `foo(x:_:)`.
+    /// This also hides the return type.
+    #[inline]
+    pub const fn argument_types(mut self, yes: bool) -> Self {
+        self.argument_types = yes;
+        self
+    }
+
+    /// Sets whether the return types of functions are rendered.
+    #[inline]
+    pub const fn return_types(mut self, yes: bool) -> Self {
+        self.return_types = yes;
+        self
+    }
+
+    /// Sets whether the discriminators of `private` declarations are
+    /// rendered.
+    #[inline]
+    pub const fn private_discriminators(mut self, yes: bool) -> Self {
+        self.private_discriminators = yes;
+        self
+    }
+
+    /// Sets whether thunks, partial applications, value witnesses and closures
+    /// are rendered with short descriptions, such as `thunk for` instead of
+    /// `reabstraction thunk helper from ... to`.
+    #[inline]
+    pub const fn shorten_thunks(mut self, yes: bool) -> Self {
+        self.shorten_thunks = yes;
+        self
+    }
+
+    /// Attempts to demangle `symbol` with these options, returning an error if
+    /// it isn't mangled.
+    #[inline]
+    pub fn demangle(&self, symbol: &[u8]) -> Result<String, DemangleError> {
+        let mut buf = String::new();
+        unsafe {
+            let buf = buf.as_mut_vec();
+            self.demangle_into(symbol, buf)?;
+        }
+        Ok(buf)
+    }
+
+    /// Attempts to demangle `symbol` with these options into `buffer`,
+    /// returning the demangled slice or an error if `symbol` isn't mangled.
+    pub fn demangle_into<'b>(
+        &self,
+        symbol: &[u8],
+        buffer: &'b mut Vec<u8>,
+    ) -> Result<&'b mut str, DemangleError> {
+        let node = demangler::demangle_symbol(symbol)?;
+        Ok(self.print_into(&node, buffer))
+    }
+
+    /// Renders `node` with these options.
+    #[inline]
+    pub fn print(&self, node: &Node) -> String {
+        let mut buf = String::new();
+        unsafe {
+            self.print_into(node, buf.as_mut_vec());
+        }
+        buf
+    }
+
+    /// Renders `node` with these options into `buffer`, returning the
+    /// rendered slice.
+    pub fn print_into<'b>(
+        &self,
+        node: &Node,
+        buffer: &'b mut Vec<u8>,
+    ) -> &'b mut str {
+        let old_len = buffer.len();
+        printer::print(node, self, buffer);
+
+        unsafe {
+            // Only whole UTF-8 strings are ever printed into `buffer`.
+            let printed = buffer.get_unchecked_mut(old_len..);
+            std::str::from_utf8_unchecked_mut(printed)
+        }
+    }
+}
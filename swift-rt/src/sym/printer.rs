@@ -0,0 +1,1598 @@
+//! Renders demangled nodes as text.
+
+// Based on:
+// lib/Demangling/NodePrinter.cpp
+
+use super::{
+    demangler::{spec_param, ValueWitnessKind},
+    node::{Node, NodeKind},
+    DemangleOptions,
+};
+
+/// Nodes nested deeper than this are not printed, which protects against stack
+/// overflow on malicious input.
+const MAX_DEPTH: usize = 768;
+
+/// Appends the text of `node` to `out`, styled according to `options`.
+pub(crate) fn print(node: &Node, options: &DemangleOptions, out: &mut Vec<u8>) {
+    let mut printer = Printer {
+        out,
+        options,
+        depth: 0,
+        specialization_prefix_printed: false,
+    };
+    printer.print(node, false);
+}
+
+/// How an entity's type is printed.
+#[derive(Clone, Copy, PartialEq, Eq)]
+enum TypePrinting {
+    NoType,
+    WithColon,
+    FunctionStyle,
+}
+
+struct Printer<'a> {
+    out: &'a mut Vec<u8>,
+    options: &'a DemangleOptions,
+    depth: usize,
+    specialization_prefix_printed: bool,
+}
+
+impl Printer<'_> {
+    #[inline]
+    fn push(&mut self, s: &str) {
+        self.out.extend_from_slice(s.as_bytes());
+    }
+
+    #[inline]
+    fn len(&self) -> usize {
+        self.out.len()
+    }
+
+    fn print_children(&mut self, node: &Node, separator: &str) {
+        for (i, child) in node.children().iter().enumerate() {
+            if i != 0 {
+                self.push(separator);
+            }
+            self.print(child, false);
+        }
+    }
+
+    fn print_child(&mut self, node: &Node, index: usize) {
+        if let Some(child) = node.child(index) {
+            self.print(child, false);
+        }
+    }
+
+    fn print_text(&mut self, node: &Node) {
+        if let Some(text) = node.text() {
+            self.push(text);
+        }
+    }
+
+    fn print_index(&mut self, index: u64) {
+        self.push(&index.to_string());
+    }
+
+    /// Prints `node`, returning a context that could not be printed as a
+    /// prefix and must instead be printed after it.
+    fn print<'n>(
+        &mut self,
+        node: &'n Node,
+        as_prefix_context: bool,
+    ) -> Option<&'n Node> {
+        if self.depth >= MAX_DEPTH {
+            self.push("<<too complex>>");
+            return None;
+        }
+        self.depth += 1;
+        let postfix = self.print_node(node, as_prefix_context);
+        self.depth -= 1;
+        postfix
+    }
+
+    fn print_node<'n>(
+        &mut self,
+        node: &'n Node,
+        as_prefix_context: bool,
+    ) -> Option<&'n Node> {
+        use NodeKind::*;
+
+        match node.kind() {
+            Global | TypeList => self.print_children(node, ""),
+            Type | TypeMangling => self.print_child(node, 0),
+            Suffix => {
+                if self.options.unmangled_suffix {
+                    self.push(" with unmangled suffix \"");
+                    self.print_text(node);
+                    self.push("\"");
+                }
+            }
+
+            Identifier | BuiltinTypeName | TupleElementName => {
+                self.print_text(node)
+            }
+            Module => {
+                if self.options.module_names {
+                    self.print_text(node);
+                }
+            }
+            InfixOperator => {
+                self.print_text(node);
+                self.push(" infix");
+            }
+            PrefixOperator => {
+                self.print_text(node);
+                self.push(" prefix");
+            }
+            PostfixOperator => {
+                self.print_text(node);
+                self.push(" postfix");
+            }
+            LocalDeclName => {
+                self.print_child(node, 1);
+                self.push(" #");
+                let index = node.child(0).and_then(Node::index).unwrap_or(0);
+                self.print_index(index + 1);
+            }
+            PrivateDeclName => {
+                let show_discriminator = self.options.private_discriminators;
+                if let Some(name) = node.child(1) {
+                    if show_discriminator {
+                        self.push("(");
+                    }
+                    self.print(name, false);
+                    if show_discriminator {
+                        self.push(" in ");
+                        self.print_child(node, 0);
+                        self.push(")");
+                    }
+                } else if show_discriminator {
+                    self.push("(in ");
+                    self.print_child(node, 0);
+                    self.push(")");
+                }
+            }
+            Number | Index => self.print_index(node.index().unwrap_or(0)),
+
+            Structure | Class | Enum | Protocol | TypeAlias => {
+                return self.print_entity(
+                    node,
+                    as_prefix_context,
+                    TypePrinting::NoType,
+                    true,
+                    "",
+                    None,
+                );
+            }
+            Extension => {
+                if self.options.qualify_entities
+                    && self.options.extension_contexts
+                {
+                    self.push("(extension in ");
+                    if let Some(module) = node.child(0) {
+                        self.print(module, true);
+                    }
+                    self.push("):");
+                }
+                self.print_child(node, 1);
+                self.print_child(node, 2);
+            }
+            BoundGenericStructure
+            | BoundGenericClass
+            | BoundGenericEnum
+            | BoundGenericProtocol
+            | BoundGenericTypeAlias => self.print_bound_generic(node),
+
+            Tuple => {
+                self.push("(");
+                self.print_children(node, ", ");
+                self.push(")");
+            }
+            TupleElement => {
+                if let Some(label) = node.child_of_kind(TupleElementName) {
+                    self.print(label, false);
+                    self.push(": ");
+                }
+                if let Some(ty) = node.child_of_kind(Type) {
+                    self.print(ty, false);
+                }
+                if node.child_of_kind(VariadicMarker).is_some() {
+                    self.push("...");
+                }
+            }
+            VariadicMarker => self.push("..."),
+            EmptyList | FirstElementMarker | LabelList => {}
+
+            FunctionType | NoEscapeFunctionType | AutoClosureType => {
+                self.print_function_type(None, node, false)
+            }
+            EscapingAutoClosureType => {
+                self.push("@autoclosure ");
+                self.print_function_type(None, node, false);
+            }
+            ThinFunctionType => {
+                self.push("@convention(thin) ");
+                self.print_function_type(None, node, false);
+            }
+            CFunctionPointer => {
+                self.push("@convention(c) ");
+                self.print_function_type(None, node, false);
+            }
+            ObjCBlock => {
+                self.push("@convention(block) ");
+                self.print_function_type(None, node, false);
+            }
+            ArgumentTuple => {
+                let show_types = self.options.argument_types;
+                self.print_function_parameters(None, node, show_types)
+            }
+            ReturnType => {
+                self.push(" -> ");
+                self.print_children(node, "");
+            }
+            ThrowsAnnotation => self.push(" throws"),
+            AsyncAnnotation => self.push(" async"),
+
+            InOut => {
+                self.push("inout ");
+                self.print_child(node, 0);
+            }
+            Shared => {
+                self.push("__shared ");
+                self.print_child(node, 0);
+            }
+            Owned => {
+                self.push("__owned ");
+                self.print_child(node, 0);
+            }
+            Metatype => {
+                if let Some(ty) = node.child(0) {
+                    self.print_with_parens(ty);
+                    if is_existential_type(ty) {
+                        self.push(".Protocol");
+                    } else {
+                        self.push(".Type");
+                    }
+                }
+            }
+            ExistentialMetatype => {
+                self.print_child(node, 0);
+                self.push(".Type");
+            }
+            ProtocolList => match node.child(0) {
+                Some(list) if !list.children().is_empty() => {
+                    self.print_children(list, " & ")
+                }
+                _ => self.push("Any"),
+            },
+            ProtocolListWithClass => {
+                self.print_child(node, 1);
+                self.push(" & ");
+                if let Some(list) = node.child(0).and_then(|list| list.child(0))
+                {
+                    self.print_children(list, " & ");
+                }
+            }
+            ProtocolListWithAnyObject => {
+                if let Some(list) = node.child(0).and_then(|list| list.child(0))
+                {
+                    if !list.children().is_empty() {
+                        self.print_children(list, " & ");
+                        self.push(" & ");
+                    }
+                }
+                if self.options.qualify_entities && self.options.module_names {
+                    self.push("Swift.");
+                }
+                self.push("AnyObject");
+            }
+            DynamicSelf => self.push("Self"),
+            Weak => {
+                self.push("weak ");
+                self.print_child(node, 0);
+            }
+            Unowned => {
+                self.push("unowned ");
+                self.print_child(node, 0);
+            }
+            Unmanaged => {
+                self.push("unowned(unsafe) ");
+                self.print_child(node, 0);
+            }
+            SugaredOptional => {
+                if let Some(ty) = node.child(0) {
+                    self.print_with_parens(ty);
+                }
+                self.push("?");
+            }
+            SugaredArray => {
+                self.push("[");
+                self.print_child(node, 0);
+                self.push("]");
+            }
+            SugaredDictionary => {
+                self.push("[");
+                self.print_child(node, 0);
+                self.push(" : ");
+                self.print_child(node, 1);
+                self.push("]");
+            }
+            SugaredParen => {
+                self.push("(");
+                self.print_child(node, 0);
+                self.push(")");
+            }
+
+            DependentGenericParamType => {
+                let depth = node.child(0).and_then(Node::index).unwrap_or(0);
+                let index = node.child(1).and_then(Node::index).unwrap_or(0);
+                self.push(&generic_param_name(depth, index));
+            }
+            DependentGenericSignature => self.print_generic_signature(node),
+            DependentGenericParamCount => {}
+            DependentGenericConformanceRequirement
+            | DependentGenericLayoutRequirement => {
+                self.print_child(node, 0);
+                self.push(": ");
+                self.print_child(node, 1);
+            }
+            DependentGenericSameTypeRequirement => {
+                self.print_child(node, 0);
+                self.push(" == ");
+                self.print_child(node, 1);
+            }
+            DependentGenericType => {
+                self.print_child(node, 0);
+                if let Some(ty) = node.child(1) {
+                    if need_space_before_type(ty) {
+                        self.push(" ");
+                    }
+                    self.print(ty, false);
+                }
+            }
+            DependentMemberType => {
+                self.print_child(node, 0);
+                self.push(".");
+                self.print_child(node, 1);
+            }
+            DependentAssociatedTypeRef => self.print_text(node),
+            OpaqueReturnType => self.push("some"),
+            OpaqueReturnTypeOf => {
+                self.push("<<opaque return type of ");
+                self.print_children(node, "");
+                self.push(">>");
+            }
+            OpaqueType => {
+                self.print_child(node, 0);
+                self.push(".");
+                self.print_child(node, 1);
+            }
+
+            ImplFunctionType => self.print_impl_function_type(node),
+            ImplEscaping => self.push("@escaping"),
+            ImplConvention | ImplFunctionAttribute => self.print_text(node),
+            ImplParameter | ImplResult => self.print_children(node, " "),
+            ImplErrorResult => {
+                self.push("@error ");
+                self.print_children(node, " ");
+            }
+
+            Function => {
+                return self.print_entity(
+                    node,
+                    as_prefix_context,
+                    TypePrinting::FunctionStyle,
+                    true,
+                    "",
+                    None,
+                );
+            }
+            Allocator => {
+                let name = if is_class_context(node) {
+                    "__allocating_init"
+                } else {
+                    "init"
+                };
+                return self.print_entity(
+                    node,
+                    as_prefix_context,
+                    TypePrinting::FunctionStyle,
+                    false,
+                    name,
+                    None,
+                );
+            }
+            Constructor => {
+                return self.print_entity(
+                    node,
+                    as_prefix_context,
+                    TypePrinting::FunctionStyle,
+                    false,
+                    "init",
+                    None,
+                );
+            }
+            Destructor => {
+                return self.print_entity(
+                    node,
+                    as_prefix_context,
+                    TypePrinting::NoType,
+                    false,
+                    "deinit",
+                    None,
+                );
+            }
+            Deallocator => {
+                let name = if is_class_context(node) {
+                    "__deallocating_deinit"
+                } else {
+                    "deinit"
+                };
+                return self.print_entity(
+                    node,
+                    as_prefix_context,
+                    TypePrinting::NoType,
+                    false,
+                    name,
+                    None,
+                );
+            }
+            IVarInitializer => {
+                return self.print_entity(
+                    node,
+                    as_prefix_context,
+                    TypePrinting::NoType,
+                    false,
+                    "__ivar_initializer",
+                    None,
+                );
+            }
+            IVarDestroyer => {
+                return self.print_entity(
+                    node,
+                    as_prefix_context,
+                    TypePrinting::NoType,
+                    false,
+                    "__ivar_destroyer",
+                    None,
+                );
+            }
+            Initializer => {
+                return self.print_entity(
+                    node,
+                    as_prefix_context,
+                    TypePrinting::NoType,
+                    false,
+                    "variable initialization expression",
+                    None,
+                );
+            }
+            DefaultArgumentInitializer => {
+                let index = node.child(1).and_then(Node::index).unwrap_or(0);
+                return self.print_entity(
+                    node,
+                    as_prefix_context,
+                    TypePrinting::NoType,
+                    false,
+                    "default argument ",
+                    Some(index),
+                );
+            }
+            ExplicitClosure | ImplicitClosure => {
+                let name = if node.kind() == ExplicitClosure {
+                    "closure #"
+                } else {
+                    "implicit closure #"
+                };
+                let index = node.child(1).and_then(Node::index).unwrap_or(0);
+                let type_printing = if self.options.argument_types
+                    && !self.options.shorten_thunks
+                {
+                    TypePrinting::FunctionStyle
+                } else {
+                    TypePrinting::NoType
+                };
+                return self.print_entity(
+                    node,
+                    as_prefix_context,
+                    type_printing,
+                    false,
+                    name,
+                    Some(index + 1),
+                );
+            }
+            Variable => {
+                return self.print_entity(
+                    node,
+                    as_prefix_context,
+                    TypePrinting::WithColon,
+                    true,
+                    "",
+                    None,
+                );
+            }
+            Subscript => {
+                return self.print_subscript(
+                    node,
+                    as_prefix_context,
+                    TypePrinting::FunctionStyle,
+                    "",
+                );
+            }
+            Static => {
+                self.push("static ");
+                self.print_child(node, 0);
+            }
+
+            Getter
+            | Setter
+            | ModifyAccessor
+            | ReadAccessor
+            | WillSet
+            | DidSet
+            | MaterializeForSet
+            | GlobalGetter
+            | UnsafeAddressor
+            | UnsafeMutableAddressor
+            | OwningAddressor
+            | OwningMutableAddressor
+            | NativeOwningAddressor
+            | NativeOwningMutableAddressor
+            | NativePinningAddressor
+            | NativePinningMutableAddressor => {
+                let storage = node.child(0)?;
+                let name = accessor_name(node.kind());
+                return match storage.kind() {
+                    Variable => self.print_entity(
+                        storage,
+                        as_prefix_context,
+                        TypePrinting::WithColon,
+                        true,
+                        name,
+                        None,
+                    ),
+                    Subscript => self.print_subscript(
+                        storage,
+                        as_prefix_context,
+                        TypePrinting::WithColon,
+                        name,
+                    ),
+                    _ => {
+                        self.print(storage, false);
+                        self.push(".");
+                        self.push(name);
+                        None
+                    }
+                };
+            }
+
+            ProtocolConformance => {
+                self.print_child(node, 0);
+                if self.options.protocol_conformances {
+                    self.push(" : ");
+                    self.print_child(node, 1);
+                    self.push(" in ");
+                    self.print_child(node, 2);
+                }
+            }
+            ValueWitness => {
+                let kind = node
+                    .child(0)
+                    .and_then(Node::index)
+                    .and_then(ValueWitnessKind::from_index);
+                if let Some(kind) = kind {
+                    self.push(kind.name());
+                }
+                if self.options.shorten_thunks {
+                    self.push(" for ");
+                } else {
+                    self.push(" value witness for ");
+                }
+                self.print_child(node, 1);
+            }
+            FieldOffset => {
+                self.print_child(node, 0);
+                self.push("field offset for ");
+                self.print_child(node, 1);
+            }
+            Directness => match node.index() {
+                Some(0) => self.push("direct "),
+                _ => self.push("indirect "),
+            },
+            LazyProtocolWitnessTableAccessor
+            | LazyProtocolWitnessTableCacheVariable => {
+                self.push(if node.kind() == LazyProtocolWitnessTableAccessor {
+                    "lazy protocol witness table accessor for type "
+                } else {
+                    "lazy protocol witness table cache variable for type "
+                });
+                self.print_child(node, 0);
+                self.push(" and conformance ");
+                self.print_child(node, 1);
+            }
+            AssociatedTypeWitnessTableAccessor => {
+                self.push("associated type witness table accessor for ");
+                self.print_child(node, 1);
+                self.push(" : ");
+                self.print_child(node, 2);
+                self.push(" in ");
+                self.print_child(node, 0);
+            }
+            AssocTypePath => self.print_children(node, "."),
+            AssociatedTypeDescriptor => {
+                self.push("associated type descriptor for ");
+                if let Some(name) = node.child(0) {
+                    if let Some(proto) = name.child(0) {
+                        self.print(proto, false);
+                        self.push(".");
+                    }
+                    self.print_text(name);
+                }
+            }
+            AssociatedConformanceDescriptor => {
+                self.push("associated conformance descriptor for ");
+                self.print_child(node, 0);
+                self.push(".");
+                self.print_child(node, 1);
+                self.push(": ");
+                self.print_child(node, 2);
+            }
+            OutlinedVariable => {
+                self.push("outlined variable #");
+                self.print_index(node.index().unwrap_or(0));
+                self.push(" of ");
+            }
+            AssociatedTypeMetadataAccessor => {
+                self.push("associated type metadata accessor for ");
+                self.print_child(node, 1);
+                self.push(" in ");
+                self.print_child(node, 0);
+            }
+            ProtocolWitness => {
+                self.push("protocol witness for ");
+                self.print_child(node, 1);
+                self.push(" in conformance ");
+                self.print_child(node, 0);
+            }
+            VTableThunk => {
+                self.push("vtable thunk for ");
+                self.print_child(node, 1);
+                self.push(" dispatching to ");
+                self.print_child(node, 0);
+            }
+            ReabstractionThunk | ReabstractionThunkHelper => {
+                if self.options.shorten_thunks {
+                    self.push("thunk for ");
+                    if let Some(to) = node.children().last() {
+                        self.print(to, false);
+                    }
+                    return None;
+                }
+                self.push("reabstraction thunk ");
+                if node.kind() == ReabstractionThunkHelper {
+                    self.push("helper ");
+                }
+                let mut index = 0;
+                if node.children().len() == 3 {
+                    self.print_child(node, 0);
+                    self.push(" ");
+                    index = 1;
+                }
+                self.push("from ");
+                self.print_child(node, index + 1);
+                self.push(" to ");
+                self.print_child(node, index);
+            }
+            PartialApplyForwarder | PartialApplyObjCForwarder => {
+                self.push(if self.options.shorten_thunks {
+                    "partial apply"
+                } else if node.kind() == PartialApplyForwarder {
+                    "partial apply forwarder"
+                } else {
+                    "partial apply ObjC forwarder"
+                });
+                if !node.children().is_empty() {
+                    self.push(" for ");
+                    self.print_children(node, "");
+                }
+            }
+            GenericSpecialization => {
+                self.print_specialization_prefix(node, "generic specialization")
+            }
+            GenericSpecializationNotReAbstracted => self
+                .print_specialization_prefix(
+                    node,
+                    "generic not re-abstracted specialization",
+                ),
+            FunctionSignatureSpecialization => self
+                .print_specialization_prefix(
+                    node,
+                    "function signature specialization",
+                ),
+            GenericSpecializationParam => self.print_children(node, ""),
+            FunctionSignatureSpecializationParam
+            | FunctionSignatureSpecializationReturn => {
+                self.print_func_spec_params(node)
+            }
+            FunctionSignatureSpecializationParamKind => {
+                self.print_func_spec_param_kind(node.index().unwrap_or(0))
+            }
+            FunctionSignatureSpecializationParamPayload => {
+                self.print_text(node)
+            }
+            SpecializationPassID => {}
+            IsSerialized => self.push("serialized"),
+
+            _ => {
+                let prefix = global_prefix(node.kind()).unwrap_or("");
+                self.push(prefix);
+                self.print_children(node, "");
+            }
+        }
+        None
+    }
+
+    /// Prints an entity, such as a type, function or variable.
+    ///
+    /// The entity's context is printed either as a prefix (`context.name`) or,
+    /// if the name is made of multiple words, as a suffix (`name in context`).
+    fn print_entity<'n>(
+        &mut self,
+        entity: &'n Node,
+        as_prefix_context: bool,
+        mut type_printing: TypePrinting,
+        has_name: bool,
+        extra_name: &str,
+        extra_index: Option<u64>,
+    ) -> Option<&'n Node> {
+        let mut multi_word_name = extra_name.contains(' ');
+        let local_name = has_name
+            && entity.child(1).map(Node::kind) == Some(NodeKind::LocalDeclName);
+        if local_name {
+            multi_word_name = true;
+        }
+
+        if as_prefix_context
+            && (type_printing != TypePrinting::NoType || multi_word_name)
+        {
+            // The context has a type to print, so it can't be a prefix.
+            return Some(entity);
+        }
+
+        let context = entity.child(0)?;
+        let mut postfix_context = if !self.options.qualify_entities {
+            None
+        } else if multi_word_name {
+            Some(context)
+        } else {
+            let start = self.len();
+            let postfix_context = self.print(context, true);
+            if self.len() != start {
+                self.push(".");
+            }
+            postfix_context
+        };
+
+        let mut extra_name = extra_name;
+        let mut extra_index = extra_index;
+        if has_name {
+            if !extra_name.is_empty() && multi_word_name {
+                self.push(extra_name);
+                if let Some(index) = extra_index.take() {
+                    self.print_index(index);
+                }
+                self.push(" of ");
+                extra_name = "";
+            }
+            let start = self.len();
+            if let Some(name) = entity.child(1) {
+                self.print(name, false);
+            }
+            if self.len() != start && !extra_name.is_empty() {
+                self.push(".");
+            }
+        }
+        if !extra_name.is_empty() {
+            self.push(extra_name);
+            if let Some(index) = extra_index {
+                self.print_index(index);
+            }
+        }
+
+        if type_printing != TypePrinting::NoType {
+            let ty = entity.child_of_kind(NodeKind::Type)?.unwrap_type();
+            if type_printing == TypePrinting::FunctionStyle {
+                let mut t = ty;
+                while t.kind() == NodeKind::DependentGenericType {
+                    t = t.child(1)?.unwrap_type();
+                }
+                if !t.kind().is_function_type() {
+                    type_printing = TypePrinting::WithColon;
+                }
+            }
+            if type_printing == TypePrinting::WithColon {
+                if self.options.entity_types {
+                    self.push(" : ");
+                    self.print_entity_type(entity, ty);
+                }
+            } else {
+                if multi_word_name || need_space_before_type(ty) {
+                    self.push(" ");
+                }
+                self.print_entity_type(entity, ty);
+            }
+        }
+
+        if !as_prefix_context {
+            if let Some(context) = postfix_context.take() {
+                if entity.kind() == NodeKind::DefaultArgumentInitializer
+                    || entity.kind() == NodeKind::Initializer
+                {
+                    self.push(" of ");
+                } else {
+                    self.push(" in ");
+                }
+                self.print(context, false);
+            }
+        }
+        postfix_context
+    }
+
+    fn print_subscript<'n>(
+        &mut self,
+        subscript: &'n Node,
+        as_prefix_context: bool,
+        type_printing: TypePrinting,
+        accessor: &str,
+    ) -> Option<&'n Node> {
+        if as_prefix_context && type_printing != TypePrinting::NoType {
+            return Some(subscript);
+        }
+
+        let context = subscript.child(0)?;
+        let mut postfix_context = None;
+        if self.options.qualify_entities {
+            let start = self.len();
+            postfix_context = self.print(context, true);
+            if self.len() != start {
+                self.push(".");
+            }
+        }
+        self.push("subscript");
+        if let Some(private_name) =
+            subscript.child_of_kind(NodeKind::PrivateDeclName)
+        {
+            self.push(" ");
+            self.print(private_name, false);
+        }
+        if !accessor.is_empty() {
+            self.push(".");
+            self.push(accessor);
+        }
+
+        let ty = subscript.child_of_kind(NodeKind::Type)?.unwrap_type();
+        if type_printing == TypePrinting::WithColon {
+            if self.options.entity_types {
+                self.push(" : ");
+                self.print_entity_type(subscript, ty);
+            }
+        } else {
+            if need_space_before_type(ty) {
+                self.push(" ");
+            }
+            self.print_entity_type(subscript, ty);
+        }
+
+        if let Some(context) = postfix_context {
+            if !as_prefix_context {
+                self.push(" in ");
+                self.print(context, false);
+                return None;
+            }
+        }
+        postfix_context
+    }
+
+    fn print_entity_type(&mut self, entity: &Node, ty: &Node) {
+        let labels = entity.child_of_kind(NodeKind::LabelList);
+        match labels {
+            Some(labels) => {
+                let mut ty = ty;
+                if ty.kind() == NodeKind::DependentGenericType {
+                    self.print_child(ty, 0);
+                    ty = match ty.child(1) {
+                        Some(dependent) => {
+                            if need_space_before_type(dependent) {
+                                self.push(" ");
+                            }
+                            dependent.unwrap_type()
+                        }
+                        None => return,
+                    };
+                }
+                self.print_function_type(Some(labels), ty, true);
+            }
+            None if ty.kind().is_function_type() => {
+                self.print_function_type(None, ty, true)
+            }
+            None => {
+                self.print(ty, false);
+            }
+        }
+    }
+
+    /// Prints the parameters and result of a function type.
+    ///
+    /// The result of an entity's type may be hidden by options.
+    fn print_function_type(
+        &mut self,
+        labels: Option<&Node>,
+        func: &Node,
+        is_entity_type: bool,
+    ) {
+        let children = func.children();
+        if children.len() < 2 {
+            return;
+        }
+        let args = &children[children.len() - 2];
+        let result = &children[children.len() - 1];
+        let attrs = &children[..children.len() - 2];
+
+        let show_types = self.options.argument_types;
+        self.print_function_parameters(labels, args, show_types);
+        if !show_types || (is_entity_type && !self.options.return_types) {
+            return;
+        }
+        if attrs.iter().any(|n| n.kind() == NodeKind::AsyncAnnotation) {
+            self.push(" async");
+        }
+        if attrs.iter().any(|n| n.kind() == NodeKind::ThrowsAnnotation) {
+            self.push(" throws");
+        }
+        self.print(result, false);
+    }
+
+    fn print_function_parameters(
+        &mut self,
+        labels: Option<&Node>,
+        args: &Node,
+        show_types: bool,
+    ) {
+        let params = match args.child(0) {
+            Some(params) => params.unwrap_type(),
+            None => return,
+        };
+
+        if params.kind() != NodeKind::Tuple {
+            // Only a single unnamed parameter.
+            if show_types {
+                self.push("(");
+                self.print(params, false);
+                self.push(")");
+            } else {
+                self.push("(_:)");
+            }
+            return;
+        }
+
+        let labels = labels.filter(|labels| !labels.children().is_empty());
+        self.push("(");
+        for (i, param) in params.children().iter().enumerate() {
+            if i != 0 && show_types {
+                self.push(", ");
+            }
+            if let Some(labels) = labels {
+                match labels.child(i) {
+                    Some(label) if label.kind() == NodeKind::Identifier => {
+                        self.print_text(label)
+                    }
+                    _ => self.push("_"),
+                }
+                self.push(":");
+                if show_types {
+                    self.push(" ");
+                }
+            } else if !show_types {
+                match param.child_of_kind(NodeKind::TupleElementName) {
+                    Some(label) => self.print_text(label),
+                    None => self.push("_"),
+                }
+                self.push(":");
+            }
+            if show_types {
+                self.print(param, false);
+            }
+        }
+        self.push(")");
+    }
+
+    fn print_bound_generic(&mut self, node: &Node) {
+        let (ty, args) = match node.children() {
+            [ty, args] => (ty, args),
+            _ => return,
+        };
+
+        let sugar = if self.options.sugar {
+            find_sugar(node)
+        } else {
+            None
+        };
+        let args = args.children();
+        match sugar {
+            Some(Sugar::Optional) => {
+                self.print_with_parens(&args[0]);
+                self.push("?");
+            }
+            Some(Sugar::ImplicitlyUnwrappedOptional) => {
+                self.print_with_parens(&args[0]);
+                self.push("!");
+            }
+            Some(Sugar::Array) => {
+                self.push("[");
+                self.print(&args[0], false);
+                self.push("]");
+            }
+            Some(Sugar::Dictionary) => {
+                self.push("[");
+                self.print(&args[0], false);
+                self.push(" : ");
+                self.print(&args[1], false);
+                self.push("]");
+            }
+            None => {
+                self.print(ty, false);
+                self.push("<");
+                for (i, arg) in args.iter().enumerate() {
+                    if i != 0 {
+                        self.push(", ");
+                    }
+                    self.print(arg, false);
+                }
+                self.push(">");
+            }
+        }
+    }
+
+    fn print_with_parens(&mut self, ty: &Node) {
+        let needs_parens = !is_simple_type(ty);
+        if needs_parens {
+            self.push("(");
+        }
+        self.print(ty, false);
+        if needs_parens {
+            self.push(")");
+        }
+    }
+
+    fn print_generic_signature(&mut self, sig: &Node) {
+        self.push("<");
+        let children = sig.children();
+        let mut depth = 0;
+        while depth < children.len()
+            && children[depth].kind() == NodeKind::DependentGenericParamCount
+        {
+            if depth != 0 {
+                self.push("><");
+            }
+            let count = children[depth].index().unwrap_or(0);
+            for index in 0..count {
+                if index != 0 {
+                    self.push(", ");
+                }
+                // Malformed symbols may have an absurd number of parameters.
+                if index >= 128 {
+                    self.push("...");
+                    break;
+                }
+                self.push(&generic_param_name(depth as u64, index));
+            }
+            depth += 1;
+        }
+
+        if depth != children.len() && self.options.where_clauses {
+            self.push(" where ");
+            for (i, req) in children[depth..].iter().enumerate() {
+                if i != 0 {
+                    self.push(", ");
+                }
+                self.print(req, false);
+            }
+        }
+        self.push(">");
+    }
+
+    fn print_impl_function_type(&mut self, func: &Node) {
+        #[derive(Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
+        enum State {
+            Attrs,
+            Inputs,
+            Results,
+        }
+
+        fn transition(printer: &mut Printer, state: &mut State, new: State) {
+            while *state < new {
+                match *state {
+                    State::Attrs => {
+                        printer.push("(");
+                        *state = State::Inputs;
+                    }
+                    State::Inputs => {
+                        printer.push(") -> (");
+                        *state = State::Results;
+                    }
+                    State::Results => unreachable!(),
+                }
+            }
+        }
+
+        let mut state = State::Attrs;
+        for child in func.children() {
+            match child.kind() {
+                NodeKind::ImplParameter => {
+                    if state == State::Inputs {
+                        self.push(", ");
+                    }
+                    transition(self, &mut state, State::Inputs);
+                    self.print(child, false);
+                }
+                NodeKind::ImplResult | NodeKind::ImplErrorResult => {
+                    if state == State::Results {
+                        self.push(", ");
+                    }
+                    transition(self, &mut state, State::Results);
+                    self.print(child, false);
+                }
+                _ => {
+                    self.print(child, false);
+                    self.push(" ");
+                }
+            }
+        }
+        transition(self, &mut state, State::Results);
+        self.push(")");
+    }
+
+    fn print_specialization_prefix(&mut self, node: &Node, description: &str) {
+        if !self.options.generic_specializations {
+            if !self.specialization_prefix_printed {
+                self.push("specialized ");
+                self.specialization_prefix_printed = true;
+            }
+            return;
+        }
+        self.push(description);
+        self.push(" <");
+        let mut separator = "";
+        let mut arg_num = 0;
+        for child in node.children() {
+            match child.kind() {
+                // The pass ID isn't useful to users.
+                NodeKind::SpecializationPassID => {}
+                NodeKind::IsSerialized => {
+                    self.push(separator);
+                    separator = ", ";
+                    self.print(child, false);
+                }
+                kind => {
+                    // Ignore empty specializations.
+                    if !child.children().is_empty() {
+                        self.push(separator);
+                        separator = ", ";
+                        match kind {
+                            NodeKind::FunctionSignatureSpecializationParam => {
+                                self.push("Arg[");
+                                self.print_index(arg_num);
+                                self.push("] = ");
+                            }
+                            NodeKind::FunctionSignatureSpecializationReturn => {
+                                self.push("Return = ");
+                            }
+                            _ => {}
+                        }
+                        self.print(child, false);
+                    }
+                    arg_num += 1;
+                }
+            }
+        }
+        self.push("> of ");
+    }
+
+    fn print_func_spec_params(&mut self, param: &Node) {
+        let children = param.children();
+        let mut i = 0;
+        while i < children.len() {
+            let kind = children[i].index().unwrap_or(0);
+            match kind {
+                spec_param::BOX_TO_VALUE | spec_param::BOX_TO_STACK => {
+                    self.print(&children[i], false);
+                    i += 1;
+                }
+                spec_param::CONSTANT_PROP_FUNCTION
+                | spec_param::CONSTANT_PROP_GLOBAL => {
+                    self.push("[");
+                    self.print(&children[i], false);
+                    self.push(" : ");
+                    let text = children.get(i + 1).and_then(Node::text);
+                    let text = text.unwrap_or("");
+                    match super::demangle(text.as_bytes()) {
+                        Ok(demangled) => self.push(&demangled),
+                        Err(_) => self.push(text),
+                    }
+                    self.push("]");
+                    i += 2;
+                }
+                spec_param::CONSTANT_PROP_INTEGER
+                | spec_param::CONSTANT_PROP_FLOAT => {
+                    self.push("[");
+                    self.print(&children[i], false);
+                    self.push(" : ");
+                    if let Some(payload) = children.get(i + 1) {
+                        self.print(payload, false);
+                    }
+                    self.push("]");
+                    i += 2;
+                }
+                spec_param::CONSTANT_PROP_STRING => {
+                    self.push("[");
+                    self.print(&children[i], false);
+                    self.push(" : ");
+                    if let Some(encoding) = children.get(i + 1) {
+                        self.print(encoding, false);
+                    }
+                    self.push("'");
+                    if let Some(string) = children.get(i + 2) {
+                        self.print(string, false);
+                    }
+                    self.push("']");
+                    i += 3;
+                }
+                spec_param::CLOSURE_PROP => {
+                    self.push("[");
+                    self.print(&children[i], false);
+                    self.push(" : ");
+                    if let Some(closure) = children.get(i + 1) {
+                        self.print(closure, false);
+                    }
+                    self.push(", Argument Types : [");
+                    i += 2;
+                    while let Some(ty) = children.get(i) {
+                        if ty.kind() != NodeKind::Type {
+                            break;
+                        }
+                        self.print(ty, false);
+                        i += 1;
+                        if matches!(children.get(i), Some(n) if n.text().is_some())
+                        {
+                            self.push(", ");
+                        }
+                    }
+                    self.push("]");
+                }
+                _ => {
+                    self.print(&children[i], false);
+                    i += 1;
+                }
+            }
+        }
+    }
+
+    fn print_func_spec_param_kind(&mut self, kind: u64) {
+        const OPTIONS: &[(u64, &str)] = &[
+            (
+                spec_param::EXISTENTIAL_TO_GENERIC,
+                "Existential To Protocol Constrained Generic",
+            ),
+            (spec_param::DEAD, "Dead"),
+            (spec_param::OWNED_TO_GUARANTEED, "Owned To Guaranteed"),
+            (spec_param::GUARANTEED_TO_OWNED, "Guaranteed To Owned"),
+            (spec_param::SROA, "Exploded"),
+        ];
+
+        let mut printed_option = false;
+        for &(option, name) in OPTIONS {
+            if kind & option != 0 {
+                if printed_option {
+                    self.push(" and ");
+                }
+                self.push(name);
+                printed_option = true;
+            }
+        }
+        if printed_option {
+            return;
+        }
+
+        self.push(match kind {
+            spec_param::BOX_TO_VALUE => "Value Promoted from Box",
+            spec_param::BOX_TO_STACK => "Stack Promoted from Box",
+            spec_param::CONSTANT_PROP_FUNCTION => {
+                "Constant Propagated Function"
+            }
+            spec_param::CONSTANT_PROP_GLOBAL => "Constant Propagated Global",
+            spec_param::CONSTANT_PROP_INTEGER => "Constant Propagated Integer",
+            spec_param::CONSTANT_PROP_FLOAT => "Constant Propagated Float",
+            spec_param::CONSTANT_PROP_STRING => "Constant Propagated String",
+            spec_param::CLOSURE_PROP => "Closure Propagated",
+            _ => "",
+        });
+    }
+}
+
+/// Returns the text that precedes the child of a global symbol node, such as
+/// `"type metadata for "`.
+fn global_prefix(kind: NodeKind) -> Option<&'static str> {
+    use NodeKind::*;
+
+    let prefix = match kind {
+        TypeMetadata => "type metadata for ",
+        TypeMetadataAccessFunction => "type metadata accessor for ",
+        FullTypeMetadata => "full type metadata for ",
+        Metaclass => "metaclass for ",
+        NominalTypeDescriptor => "nominal type descriptor for ",
+        ProtocolDescriptor => "protocol descriptor for ",
+        ProtocolRequirementsBaseDescriptor => {
+            "protocol requirements base descriptor for "
+        }
+        ProtocolConformanceDescriptor => "protocol conformance descriptor for ",
+        ModuleDescriptor => "module descriptor ",
+        ExtensionDescriptor => "extension descriptor ",
+        GenericTypeMetadataPattern => "generic type metadata pattern for ",
+        TypeMetadataCompletionFunction => {
+            "type metadata completion function for "
+        }
+        TypeMetadataInstantiationFunction => {
+            "type metadata instantiation function for "
+        }
+        TypeMetadataInstantiationCache => {
+            "type metadata instantiation cache for "
+        }
+        TypeMetadataSingletonInitializationCache => {
+            "type metadata singleton initialization cache for "
+        }
+        TypeMetadataLazyCache => "lazy cache variable for type metadata for ",
+        ClassMetadataBaseOffset => "class metadata base offset for ",
+        MethodLookupFunction => "method lookup function for ",
+        ObjCResilientClassStub => "ObjC resilient class stub for ",
+        PropertyDescriptor => "property descriptor for ",
+        ReflectionMetadataFieldDescriptor => {
+            "reflection metadata field descriptor "
+        }
+        ReflectionMetadataBuiltinDescriptor => {
+            "reflection metadata builtin descriptor "
+        }
+        ReflectionMetadataAssocTypeDescriptor => {
+            "reflection metadata associated type descriptor "
+        }
+        ReflectionMetadataSuperclassDescriptor => {
+            "reflection metadata superclass descriptor "
+        }
+        ValueWitnessTable => "value witness table for ",
+        ProtocolWitnessTable => "protocol witness table for ",
+        ProtocolWitnessTablePattern => "protocol witness table pattern for ",
+        GenericProtocolWitnessTable => "generic protocol witness table for ",
+        GenericProtocolWitnessTableInstantiationFunction => {
+            "instantiation function for generic protocol witness table for "
+        }
+        ResilientProtocolWitnessTable => {
+            "resilient protocol witness table for "
+        }
+        ProtocolWitnessTableAccessor => "protocol witness table accessor for ",
+        MethodDescriptor => "method descriptor for ",
+        EnumCase => "enum case for ",
+        DispatchThunk => "dispatch thunk of ",
+        CurryThunk => "curry thunk of ",
+        ObjCAttribute => "@objc ",
+        NonObjCAttribute => "@nonobjc ",
+        DynamicAttribute => "dynamic ",
+        DirectMethodReferenceAttribute => "super ",
+        MergedFunction => "merged ",
+        DynamicallyReplaceableFunctionVar => {
+            "dynamically replaceable variable for "
+        }
+        DynamicallyReplaceableFunctionKey => "dynamically replaceable key for ",
+        DynamicallyReplaceableFunctionImpl => {
+            "dynamically replaceable thunk for "
+        }
+        _ => return None,
+    };
+    Some(prefix)
+}
+
+/// Standard library types with syntactic sugar.
+enum Sugar {
+    Optional,
+    ImplicitlyUnwrappedOptional,
+    Array,
+    Dictionary,
+}
+
+/// Returns the sugar that `node` can be printed with.
+fn find_sugar(node: &Node) -> Option<Sugar> {
+    let (ty, args) = match node.children() {
+        [ty, args] => (ty.unwrap_type(), args.children()),
+        _ => return None,
+    };
+    let (module, name) = match ty.children() {
+        [module, name, ..] => (module, name),
+        _ => return None,
+    };
+    if module.kind() != NodeKind::Module || module.text() != Some("Swift") {
+        return None;
+    }
+
+    match (node.kind(), name.text(), args.len()) {
+        (NodeKind::BoundGenericEnum, Some("Optional"), 1) => {
+            Some(Sugar::Optional)
+        }
+        (
+            NodeKind::BoundGenericEnum,
+            Some("ImplicitlyUnwrappedOptional"),
+            1,
+        ) => Some(Sugar::ImplicitlyUnwrappedOptional),
+        (NodeKind::BoundGenericStructure, Some("Array"), 1) => {
+            Some(Sugar::Array)
+        }
+        (NodeKind::BoundGenericStructure, Some("Dictionary"), 2) => {
+            Some(Sugar::Dictionary)
+        }
+        _ => None,
+    }
+}
+
+fn accessor_name(kind: NodeKind) -> &'static str {
+    use NodeKind::*;
+
+    match kind {
+        Getter => "getter",
+        Setter => "setter",
+        ModifyAccessor => "modify",
+        ReadAccessor => "read",
+        WillSet => "willset",
+        DidSet => "didset",
+        MaterializeForSet => "materializeForSet",
+        GlobalGetter => "getter",
+        UnsafeAddressor => "unsafeAddressor",
+        UnsafeMutableAddressor => "unsafeMutableAddressor",
+        OwningAddressor => "owningAddressor",
+        OwningMutableAddressor => "owningMutableAddressor",
+        NativeOwningAddressor => "nativeOwningAddressor",
+        NativeOwningMutableAddressor => "nativeOwningMutableAddressor",
+        NativePinningAddressor => "nativePinningAddressor",
+        NativePinningMutableAddressor => "nativePinningMutableAddressor",
+        _ => "",
+    }
+}
+
+/// Returns the name of a generic parameter, such as `A`, `B1` or `AB`.
+pub(crate) fn generic_param_name(depth: u64, index: u64) -> String {
+    let mut name = String::new();
+    let mut index = index;
+    loop {
+        name.push(char::from(b'A' + (index % 26) as u8));
+        index /= 26;
+        if index == 0 {
+            break;
+        }
+    }
+    if depth != 0 {
+        name.push_str(&depth.to_string());
+    }
+    name
+}
+
+fn need_space_before_type(ty: &Node) -> bool {
+    match ty.kind() {
+        NodeKind::Type => match ty.child(0) {
+            Some(child) => need_space_before_type(child),
+            None => true,
+        },
+        NodeKind::FunctionType
+        | NodeKind::NoEscapeFunctionType
+        | NodeKind::DependentGenericType => false,
+        _ => true,
+    }
+}
+
+fn is_class_context(entity: &Node) -> bool {
+    let context = match entity.child(0) {
+        Some(context) => context,
+        None => return false,
+    };
+    match context.kind() {
+        NodeKind::Class | NodeKind::BoundGenericClass => true,
+        NodeKind::Extension => matches!(
+            context.child(1),
+            Some(ty) if ty.unwrap_type().kind() == NodeKind::Class
+        ),
+        _ => false,
+    }
+}
+
+fn is_existential_type(ty: &Node) -> bool {
+    matches!(
+        ty.unwrap_type().kind(),
+        NodeKind::ExistentialMetatype
+            | NodeKind::ProtocolList
+            | NodeKind::ProtocolListWithClass
+            | NodeKind::ProtocolListWithAnyObject
+    )
+}
+
+fn is_simple_type(ty: &Node) -> bool {
+    let ty = ty.unwrap_type();
+    match ty.kind() {
+        NodeKind::ProtocolList => {
+            matches!(ty.child(0), Some(list) if list.children().len() <= 1)
+        }
+        NodeKind::ProtocolListWithClass
+        | NodeKind::ProtocolListWithAnyObject => false,
+        kind => !kind.is_function_type(),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::super::parse;
+    use super::*;
+
+    fn print(symbol: &str, options: DemangleOptions) -> String {
+        options.print(&parse(symbol.as_bytes()).unwrap())
+    }
+
+    #[test]
+    fn sugar() {
+        let options = DemangleOptions::new().sugar(true);
+        assert_eq!(print("$sSiSgD", options), "Swift.Int?");
+        assert_eq!(print("$sSaySSGD", options), "[Swift.String]");
+        assert_eq!(print("$sSDySSSiGD", options), "[Swift.String : Swift.Int]",);
+    }
+
+    #[test]
+    fn simplified() {
+        let options = DemangleOptions::simplified();
+        assert_eq!(print("$s4main3foo1xSSSi_tF", options), "foo(x:)");
+        assert_eq!(print("$s4main3FooV1xSivg", options), "Foo.x.getter");
+        assert_eq!(
+            print("$s4main3FooVAA1PAAWP", options),
+            "protocol witness table for Foo",
+        );
+        assert_eq!(
+            print("$s4main3fooyyFTA", options),
+            "partial apply for foo()",
+        );
+    }
+
+    #[test]
+    fn module_names() {
+        let options = DemangleOptions::new().module_names(false);
+        assert_eq!(
+            print("$s4main3foo1xSSSi_tF", options),
+            "foo(x: Int) -> String",
+        );
+    }
+
+    #[test]
+    fn unmangled_suffix() {
+        let symbol = "$s4main3fooyyF.cold";
+        assert_eq!(
+            print(symbol, DemangleOptions::new()),
+            "main.foo() -> () with unmangled suffix \".cold\"",
+        );
+        assert_eq!(
+            print(symbol, DemangleOptions::new().unmangled_suffix(false)),
+            "main.foo() -> ()",
+        );
+    }
+
+    #[test]
+    fn deep_nodes_are_cut_off() {
+        let mut node = Node::with_text(NodeKind::Identifier, "x");
+        for _ in 0..MAX_DEPTH {
+            node = Node::with_children(NodeKind::Type, Some(node));
+        }
+        assert_eq!(DemangleOptions::new().print(&node), "<<too complex>>");
+    }
+}
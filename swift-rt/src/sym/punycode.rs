@@ -0,0 +1,234 @@
+//! Swift's variant of Punycode, used for identifiers with non-ASCII
+//! characters.
+//!
+//! This differs from RFC 3492 in that `_` is the delimiter and digits are
+//! encoded as `a-z` followed by `A-J`. ASCII characters which can't appear in
+//! a symbol are encoded as if they were the code points from `U+D800`.
+
+// Based on:
+// lib/Demangling/Punycode.cpp
+
+const BASE: u32 = 36;
+const T_MIN: u32 = 1;
+const T_MAX: u32 = 26;
+const SKEW: u32 = 38;
+const DAMP: u32 = 700;
+const INITIAL_BIAS: u32 = 72;
+const INITIAL_N: u32 = 128;
+const DELIMITER: u8 = b'_';
+/// The code point that ASCII characters which aren't valid in a symbol are
+/// offset by.
+const NON_SYMBOL_BASE: u32 = 0xD800;
+
+/// Returns whether `ch` may appear in a symbol without being encoded.
+#[inline]
+pub(crate) fn is_symbol_char(ch: u8) -> bool {
+    ch.is_ascii_alphanumeric() || ch == b'_' || ch == b'$'
+}
+
+#[inline]
+fn digit_index(ch: u8) -> Option<u32> {
+    match ch {
+        b'a'..=b'z' => Some(u32::from(ch - b'a')),
+        b'A'..=b'J' => Some(u32::from(ch - b'A') + 26),
+        _ => None,
+    }
+}
+
+#[inline]
+fn digit_value(digit: u64) -> char {
+    match digit {
+        0..=25 => char::from(b'a' + digit as u8),
+        _ => char::from(b'A' + (digit - 26) as u8),
+    }
+}
+
+#[inline]
+fn threshold(k: u32, bias: u32) -> u32 {
+    if k <= bias {
+        T_MIN
+    } else if k >= bias + T_MAX {
+        T_MAX
+    } else {
+        k - bias
+    }
+}
+
+fn adapt(delta: u64, num_points: u64, first_time: bool) -> u32 {
+    let mut delta = delta / if first_time { u64::from(DAMP) } else { 2 };
+    delta += delta / num_points;
+
+    let steps = u64::from(BASE - T_MIN);
+    let mut k = 0;
+    while delta > (steps * u64::from(T_MAX)) / 2 {
+        delta /= steps;
+        k += BASE;
+    }
+    k + (((steps + 1) * delta) / (delta + u64::from(SKEW))) as u32
+}
+
+/// Decodes the Punycode-encoded `input`, returning `None` if it's malformed.
+pub(crate) fn decode(mut input: &[u8]) -> Option<String> {
+    let mut output = Vec::<char>::with_capacity(input.len());
+
+    // Code points before the last delimiter are copied as-is.
+    if let Some(delimiter) = input.iter().rposition(|&ch| ch == DELIMITER) {
+        for &ch in &input[..delimiter] {
+            if !ch.is_ascii() {
+                return None;
+            }
+            output.push(char::from(ch));
+        }
+        input = &input[(delimiter + 1)..];
+    }
+
+    let mut n = INITIAL_N;
+    let mut i: u32 = 0;
+    let mut bias = INITIAL_BIAS;
+
+    while !input.is_empty() {
+        let old_i = i;
+        let mut w: u32 = 1;
+        let mut k = BASE;
+        loop {
+            let (&ch, rest) = input.split_first()?;
+            input = rest;
+
+            let digit = digit_index(ch)?;
+            i = i.checked_add(digit.checked_mul(w)?)?;
+
+            let t = threshold(k, bias);
+            if digit < t {
+                break;
+            }
+            w = w.checked_mul(BASE - t)?;
+            k += BASE;
+        }
+
+        let len = output.len() as u32 + 1;
+        bias = adapt(u64::from(i - old_i), u64::from(len), old_i == 0);
+        n = n.checked_add(i / len)?;
+        i %= len;
+
+        // Basic code points must not be encoded.
+        if n < 0x80 {
+            return None;
+        }
+        let ch = match n.checked_sub(NON_SYMBOL_BASE) {
+            Some(ch) if ch < 0x80 => char::from(ch as u8),
+            _ => std::char::from_u32(n)?,
+        };
+        output.insert(i as usize, ch);
+        i += 1;
+    }
+
+    Some(output.into_iter().collect())
+}
+
+/// Encodes `input` with Punycode.
+pub(crate) fn encode(input: &str) -> String {
+    let input: Vec<u32> = input
+        .chars()
+        .map(|ch| match ch {
+            '\0'..='\x7f' if !is_symbol_char(ch as u8) => {
+                NON_SYMBOL_BASE + u32::from(ch)
+            }
+            _ => u32::from(ch),
+        })
+        .collect();
+    let mut output: String = input
+        .iter()
+        .filter(|&&ch| ch < 0x80)
+        .map(|&ch| char::from(ch as u8))
+        .collect();
+
+    // Arithmetic is done on 64 bits so that it can't overflow.
+    let num_basic = output.len() as u64;
+    if num_basic > 0 {
+        output.push(char::from(DELIMITER));
+    }
+
+    let mut n = INITIAL_N;
+    let mut delta: u64 = 0;
+    let mut bias = INITIAL_BIAS;
+    let mut h = num_basic;
+    while (h as usize) < input.len() {
+        // The smallest code point that hasn't been encoded yet.
+        let m = match input.iter().copied().filter(|&ch| ch >= n).min() {
+            Some(m) => m,
+            None => break,
+        };
+        delta += u64::from(m - n) * (h + 1);
+        n = m;
+        for &ch in &input {
+            if ch < n {
+                delta += 1;
+            }
+            if ch != n {
+                continue;
+            }
+            let mut q = delta;
+            let mut k = BASE;
+            loop {
+                let t = u64::from(threshold(k, bias));
+                if q < t {
+                    break;
+                }
+                let base = u64::from(BASE);
+                output.push(digit_value(t + (q - t) % (base - t)));
+                q = (q - t) / (base - t);
+                k += BASE;
+            }
+            output.push(digit_value(q));
+            bias = adapt(delta, h + 1, h == num_basic);
+            delta = 0;
+            h += 1;
+        }
+        delta += 1;
+        n += 1;
+    }
+    output
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    // RFC 3492 samples, with digits spelled `A-J` and `_` as the delimiter.
+    const SAMPLES: &[(&str, &str)] = &[
+        ("egbpdajGbuEbxfgehfvwxn", "ليهمابتكلموشعربي؟"),
+        ("ihqwcrbEcvIaIdqgAFGpqjye", "他们为什么不说中文"),
+        ("Proprostnemluvesky_uybCEdmaEBa", "Pročprostěnemluvíčesky"),
+    ];
+
+    #[test]
+    fn decodes_samples() {
+        for &(encoded, decoded) in SAMPLES {
+            assert_eq!(decode(encoded.as_bytes()).as_deref(), Some(decoded));
+        }
+    }
+
+    #[test]
+    fn encodes_samples() {
+        for &(encoded, decoded) in SAMPLES {
+            assert_eq!(encode(decoded), encoded);
+        }
+    }
+
+    #[test]
+    fn non_symbol_ascii_round_trips() {
+        let encoded = encode("a-b");
+        assert!(encoded.bytes().all(is_symbol_char), "{}", encoded);
+        assert_eq!(decode(encoded.as_bytes()).as_deref(), Some("a-b"));
+    }
+
+    #[test]
+    fn malformed_input_fails() {
+        // `0-9` aren't digits in Swift's variant.
+        assert_eq!(decode(b"egbpdaj6bu4bxfgehfvwxn"), None);
+        // Non-ASCII before the delimiter.
+        assert_eq!(decode("é_a".as_bytes()), None);
+        // A truncated variable-length integer.
+        assert_eq!(decode(b"Proprostnemluvesky_uybCEdmaEB"), None);
+    }
+}
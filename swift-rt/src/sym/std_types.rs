@@ -0,0 +1,80 @@
+//! Types with a standard substitution (`S<char>`), used by both the demangler
+//! and the mangler.
+
+// Based on:
+// include/swift/Demangling/StandardTypesMangling.def
+
+use super::node::NodeKind;
+
+/// Every standard substitution and the type in the `Swift` module that it
+/// refers to.
+pub(crate) const STANDARD_TYPES: &[(u8, NodeKind, &str)] = &[
+    (
+        b'A',
+        NodeKind::Structure,
+        "AutoreleasingUnsafeMutablePointer",
+    ),
+    (b'a', NodeKind::Structure, "Array"),
+    (b'b', NodeKind::Structure, "Bool"),
+    (b'D', NodeKind::Structure, "Dictionary"),
+    (b'd', NodeKind::Structure, "Double"),
+    (b'f', NodeKind::Structure, "Float"),
+    (b'h', NodeKind::Structure, "Set"),
+    (b'I', NodeKind::Structure, "DefaultIndices"),
+    (b'i', NodeKind::Structure, "Int"),
+    (b'J', NodeKind::Structure, "Character"),
+    (b'N', NodeKind::Structure, "ClosedRange"),
+    (b'n', NodeKind::Structure, "Range"),
+    (b'O', NodeKind::Structure, "ObjectIdentifier"),
+    (b'P', NodeKind::Structure, "UnsafePointer"),
+    (b'p', NodeKind::Structure, "UnsafeMutablePointer"),
+    (b'R', NodeKind::Structure, "UnsafeBufferPointer"),
+    (b'r', NodeKind::Structure, "UnsafeMutableBufferPointer"),
+    (b'S', NodeKind::Structure, "String"),
+    (b's', NodeKind::Structure, "Substring"),
+    (b'u', NodeKind::Structure, "UInt"),
+    (b'V', NodeKind::Structure, "UnsafeRawPointer"),
+    (b'v', NodeKind::Structure, "UnsafeMutableRawPointer"),
+    (b'W', NodeKind::Structure, "UnsafeRawBufferPointer"),
+    (b'w', NodeKind::Structure, "UnsafeMutableRawBufferPointer"),
+    (b'q', NodeKind::Enum, "Optional"),
+    (b'B', NodeKind::Protocol, "BinaryFloatingPoint"),
+    (b'E', NodeKind::Protocol, "Encodable"),
+    (b'e', NodeKind::Protocol, "Decodable"),
+    (b'F', NodeKind::Protocol, "FloatingPoint"),
+    (b'G', NodeKind::Protocol, "RandomNumberGenerator"),
+    (b'H', NodeKind::Protocol, "Hashable"),
+    (b'j', NodeKind::Protocol, "Numeric"),
+    (b'K', NodeKind::Protocol, "BidirectionalCollection"),
+    (b'k', NodeKind::Protocol, "RandomAccessCollection"),
+    (b'L', NodeKind::Protocol, "Comparable"),
+    (b'l', NodeKind::Protocol, "Collection"),
+    (b'M', NodeKind::Protocol, "MutableCollection"),
+    (b'm', NodeKind::Protocol, "RangeReplaceableCollection"),
+    (b'Q', NodeKind::Protocol, "Equatable"),
+    (b'T', NodeKind::Protocol, "Sequence"),
+    (b't', NodeKind::Protocol, "IteratorProtocol"),
+    (b'U', NodeKind::Protocol, "UnsignedInteger"),
+    (b'X', NodeKind::Protocol, "RangeExpression"),
+    (b'x', NodeKind::Protocol, "Strideable"),
+    (b'Y', NodeKind::Protocol, "RawRepresentable"),
+    (b'y', NodeKind::Protocol, "StringProtocol"),
+    (b'Z', NodeKind::Protocol, "SignedInteger"),
+    (b'z', NodeKind::Protocol, "BinaryInteger"),
+];
+
+/// Returns the kind and name of the type with the standard substitution `ch`.
+pub(crate) fn lookup(ch: u8) -> Option<(NodeKind, &'static str)> {
+    STANDARD_TYPES
+        .iter()
+        .find(|&&(c, _, _)| c == ch)
+        .map(|&(_, kind, name)| (kind, name))
+}
+
+/// Returns the standard substitution for the `Swift` type `name` of `kind`.
+pub(crate) fn find(kind: NodeKind, name: &str) -> Option<u8> {
+    STANDARD_TYPES
+        .iter()
+        .find(|&&(_, k, n)| k == kind && n == name)
+        .map(|&(ch, _, _)| ch)
+}
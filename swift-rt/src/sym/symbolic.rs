@@ -0,0 +1,230 @@
+//! Resolution of the symbolic references within mangled names.
+
+// Based on:
+// include/swift/Demangling/Demangler.h
+// lib/Demangling/Demangler.cpp
+// stdlib/public/runtime/Demangle.cpp
+
+use super::{Node, NodeKind, NominalKind};
+use crate::sys::metadata::context::{ContextDescriptor, ContextDescriptorKind};
+use std::mem;
+
+/// What a symbolic reference refers to.
+///
+/// Refers to `SymbolicReferenceKind` in C++.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub enum SymbolicReferenceKind {
+    /// A context descriptor, such as that of a type declared in the same
+    /// image (`\x01` directly or `\x02` through a pointer).
+    Context,
+    /// A function that returns the metadata of the type (`\x09`).
+    AccessorFunction,
+    /// The shape of an extended existential type that is unique within the
+    /// process (`\x0a`).
+    UniqueExtendedExistentialTypeShape,
+    /// The shape of an extended existential type that may be duplicated in
+    /// other images (`\x0b`).
+    NonUniqueExtendedExistentialTypeShape,
+    /// An Objective-C protocol (`\x0c`).
+    ObjCProtocol,
+}
+
+/// A reference to an entity from within a mangled name, encoded as a control
+/// byte followed by a 4-byte relative offset.
+///
+/// Mangled names in reflection metadata use these in place of the names of
+/// entities in the same image. They're passed to the resolver of
+/// [`parse_type_with`](fn.parse_type_with.html) to be turned into nodes.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub struct SymbolicReference {
+    kind: SymbolicReferenceKind,
+    is_indirect: bool,
+    offset: i32,
+    position: usize,
+}
+
+impl SymbolicReference {
+    /// Decodes the reference whose control byte is `control` and whose
+    /// offset is `offset`, at `position` within the mangled name.
+    pub(crate) fn new(
+        control: u8,
+        offset: i32,
+        position: usize,
+    ) -> Option<Self> {
+        let (kind, is_indirect) = match control {
+            0x01 => (SymbolicReferenceKind::Context, false),
+            0x02 => (SymbolicReferenceKind::Context, true),
+            0x09 => (SymbolicReferenceKind::AccessorFunction, false),
+            0x0a => (
+                SymbolicReferenceKind::UniqueExtendedExistentialTypeShape,
+                false,
+            ),
+            0x0b => (
+                SymbolicReferenceKind::NonUniqueExtendedExistentialTypeShape,
+                false,
+            ),
+            0x0c => (SymbolicReferenceKind::ObjCProtocol, false),
+            _ => return None,
+        };
+        Some(Self {
+            kind,
+            is_indirect,
+            offset,
+            position,
+        })
+    }
+
+    /// Returns what the reference refers to.
+    #[inline]
+    pub fn kind(&self) -> SymbolicReferenceKind {
+        self.kind
+    }
+
+    /// Returns whether the offset targets a pointer to the entity rather than
+    /// the entity itself, as for descriptors in other images.
+    #[inline]
+    pub fn is_indirect(&self) -> bool {
+        self.is_indirect
+    }
+
+    /// Returns the offset to the target, relative to the offset's own
+    /// position.
+    #[inline]
+    pub fn offset(&self) -> i32 {
+        self.offset
+    }
+
+    /// Returns the position of the offset within the mangled name, which is
+    /// just after the control byte.
+    #[inline]
+    pub fn position(&self) -> usize {
+        self.position
+    }
+
+    /// Returns the address targeted by the offset, given that the mangled
+    /// name starts at `base`.
+    ///
+    /// For indirect references, this is the address of the pointer to the
+    /// entity.
+    #[inline]
+    pub fn target(&self, base: u64) -> u64 {
+        base.wrapping_add(self.position as u64)
+            .wrapping_add(self.offset as i64 as u64)
+    }
+}
+
+impl Node {
+    /// Creates the node of the module named `name`, for use by symbolic
+    /// reference resolvers.
+    #[inline]
+    pub fn module(name: &str) -> Self {
+        Self::with_text(NodeKind::Module, name)
+    }
+
+    /// Creates the node of the nominal type named `name` declared within
+    /// `context`, for use by symbolic reference resolvers.
+    ///
+    /// `context` is either a module or a type, whose generic arguments are
+    /// ignored.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use swift_rt::sym::{NominalKind, Node};
+    ///
+    /// let foo = Node::nominal(NominalKind::Struct, Node::module("main"), "Foo");
+    /// let bar = Node::nominal(NominalKind::Enum, foo, "Bar");
+    /// assert_eq!(bar.to_string(), "main.Foo.Bar");
+    /// ```
+    pub fn nominal(kind: NominalKind, context: Node, name: &str) -> Self {
+        let mut context = context;
+        while matches!(
+            context.kind(),
+            NodeKind::Type
+                | NodeKind::BoundGenericStructure
+                | NodeKind::BoundGenericEnum
+                | NodeKind::BoundGenericClass
+                | NodeKind::BoundGenericTypeAlias
+        ) && !context.children().is_empty()
+        {
+            context = context.into_children().remove(0);
+        }
+        let name = Self::with_text(NodeKind::Identifier, name);
+        let nominal =
+            Self::with_children(kind.node_kind(), vec![context, name]);
+        Self::ty(nominal)
+    }
+}
+
+/// Returns the node of the type, protocol or module that `descriptor`
+/// describes, or `None` if it has no name, such as an opaque type.
+///
+/// Extensions are replaced by the type they extend, whose mangled name is
+/// parsed by `extended_type`. Anonymous contexts are skipped.
+pub fn context_node(
+    descriptor: &ContextDescriptor,
+    mut extended_type: impl FnMut(&[u8]) -> Option<Node>,
+) -> Option<Node> {
+    context_node_impl(descriptor, &mut extended_type)
+}
+
+fn context_node_impl(
+    descriptor: &ContextDescriptor,
+    extended_type: &mut dyn FnMut(&[u8]) -> Option<Node>,
+) -> Option<Node> {
+    let kind = match descriptor.kind() {
+        ContextDescriptorKind::MODULE => {
+            let name = descriptor.name()?.to_str().ok()?;
+            return Some(Node::module(name));
+        }
+        ContextDescriptorKind::EXTENSION => {
+            let extended = descriptor.as_extension()?.extended_context()?;
+            return extended_type(extended);
+        }
+        ContextDescriptorKind::ANONYMOUS => {
+            return context_node_impl(descriptor.parent()?, extended_type);
+        }
+        ContextDescriptorKind::PROTOCOL => NominalKind::Protocol,
+        ContextDescriptorKind::CLASS => NominalKind::Class,
+        ContextDescriptorKind::STRUCT => NominalKind::Struct,
+        ContextDescriptorKind::ENUM => NominalKind::Enum,
+        _ => return None,
+    };
+    let name = descriptor.name()?.to_str().ok()?;
+    let parent = context_node_impl(descriptor.parent()?, extended_type)?;
+    Some(Node::nominal(kind, parent, name))
+}
+
+/// Resolves `reference` within the mangled name at `mangled`, which is at its
+/// original location in the current process.
+///
+/// # Safety
+///
+/// `mangled` must be a mangled name emitted by the Swift compiler into an
+/// image that's loaded in the current process.
+pub(crate) unsafe fn resolve_in_process(
+    reference: &SymbolicReference,
+    mangled: &[u8],
+) -> Option<Node> {
+    if reference.kind() != SymbolicReferenceKind::Context {
+        return None;
+    }
+    let target = reference.target(mangled.as_ptr() as u64) as usize;
+    let descriptor = if reference.is_indirect() {
+        *(target as *const *const ContextDescriptor)
+    } else {
+        target as *const ContextDescriptor
+    };
+    context_node(descriptor.as_ref()?, |extended| {
+        super::parse_type_in_process(extended).ok()
+    })
+}
+
+/// Reads the offset of a symbolic reference from `bytes`, which are in the
+/// byte order of the current process.
+#[inline]
+pub(crate) fn read_offset(bytes: &[u8]) -> Option<i32> {
+    let mut offset = [0; mem::size_of::<i32>()];
+    offset.copy_from_slice(bytes.get(..mem::size_of::<i32>())?);
+    Some(i32::from_ne_bytes(offset))
+}
@@ -0,0 +1,243 @@
+//! Owned values of types that are only known at runtime.
+
+use crate::{
+    metadata::MemoryLayout,
+    obj::MetaType,
+    sys::metadata::value_witness::{ValueBuffer, ValueWitnessTable},
+};
+use std::{alloc, fmt, mem::ManuallyDrop, os::raw::c_void, ptr::NonNull};
+
+/// An owned value of any Swift type, whose storage and value semantics are
+/// provided by the type's value witness table.
+///
+/// Values that fit in a [`ValueBuffer`] are stored inline, like in an
+/// existential container. Others are stored in a separate allocation.
+///
+/// [`ValueBuffer`]: ../../swift_sys/metadata/value_witness/struct.ValueBuffer.html
+///
+/// # Examples
+///
+/// Copying a value out of memory owned by Swift:
+///
+/// ```no_run
+/// use swift_rt::{obj::MetaType, value::OpaqueSwiftValue};
+/// use std::os::raw::c_void;
+///
+/// # let ptr: *const c_void = std::ptr::null();
+/// let ty = MetaType::from_name("Swift.String").unwrap();
+/// let value = unsafe { OpaqueSwiftValue::copy_from(ty, ptr) };
+/// let copy = value.clone();
+/// assert_eq!(copy.meta_type().name(true), "Swift.String");
+/// ```
+pub struct OpaqueSwiftValue {
+    ty: &'static MetaType,
+    storage: Storage,
+}
+
+enum Storage {
+    /// The value itself, which is bitwise-takable and so may be moved along
+    /// with the buffer.
+    Inline(ValueBuffer),
+    /// A pointer to the value, allocated with the layout of the type.
+    Heap(NonNull<u8>),
+}
+
+impl Drop for OpaqueSwiftValue {
+    #[inline]
+    fn drop(&mut self) {
+        unsafe {
+            (self.witnesses().destroy)(self.as_mut_ptr(), self.self_ptr());
+            self.deallocate();
+        }
+    }
+}
+
+impl Clone for OpaqueSwiftValue {
+    #[inline]
+    fn clone(&self) -> Self {
+        unsafe { Self::copy_from(self.ty, self.as_ptr()) }
+    }
+
+    #[inline]
+    fn clone_from(&mut self, source: &Self) {
+        if self.ty.as_ptr() == source.ty.as_ptr() {
+            self.assign(source);
+        } else {
+            *self = source.clone();
+        }
+    }
+}
+
+impl fmt::Debug for OpaqueSwiftValue {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.debug_struct("OpaqueSwiftValue")
+            .field("type", &self.ty.name(true))
+            .field("ptr", &self.as_ptr())
+            .finish()
+    }
+}
+
+impl OpaqueSwiftValue {
+    /// Returns a value of `ty` with storage for, but not yet initialized to,
+    /// a value.
+    pub(crate) unsafe fn uninit(ty: &'static MetaType) -> Self {
+        let storage = if ty.value_witnesses().flags.is_inline_storage() {
+            Storage::Inline(ValueBuffer::default())
+        } else {
+            let layout = MemoryLayout::of(ty).layout();
+            match NonNull::new(alloc::alloc(layout)) {
+                Some(ptr) => Storage::Heap(ptr),
+                None => alloc::handle_alloc_error(layout),
+            }
+        };
+        Self { ty, storage }
+    }
+
+    /// Frees the storage of `self` without destroying its value.
+    unsafe fn deallocate(&mut self) {
+        if let Storage::Heap(ptr) = self.storage {
+            alloc::dealloc(ptr.as_ptr(), MemoryLayout::of(self.ty).layout());
+        }
+    }
+
+    /// Frees the storage of `self`, whose value was moved out or was never
+    /// initialized.
+    pub(crate) unsafe fn forget(self) {
+        ManuallyDrop::new(self).deallocate();
+    }
+
+    /// Creates a value of `ty` as a copy of the value at `src`, leaving the
+    /// original intact.
+    ///
+    /// # Safety
+    ///
+    /// `src` must point to an initialized value of `ty`.
+    pub unsafe fn copy_from(ty: &'static MetaType, src: *const c_void) -> Self {
+        let mut value = Self::uninit(ty);
+        (value.witnesses().initialize_with_copy)(
+            value.as_mut_ptr(),
+            src as *mut c_void,
+            value.self_ptr(),
+        );
+        value
+    }
+
+    /// Creates a value of `ty` by moving the value at `src`, which is left
+    /// uninitialized.
+    ///
+    /// # Safety
+    ///
+    /// `src` must point to an initialized value of `ty`, which must not be
+    /// used or destroyed afterwards.
+    pub unsafe fn take_from(ty: &'static MetaType, src: *mut c_void) -> Self {
+        let mut value = Self::uninit(ty);
+        (value.witnesses().initialize_with_take)(
+            value.as_mut_ptr(),
+            src,
+            value.self_ptr(),
+        );
+        value
+    }
+
+    /// Moves the value into the uninitialized memory at `dest`.
+    ///
+    /// # Safety
+    ///
+    /// `dest` must be valid for writes of a value of the type, with its
+    /// alignment, and must not hold an initialized value.
+    pub unsafe fn move_into(self, dest: *mut c_void) {
+        let mut this = ManuallyDrop::new(self);
+        (this.witnesses().initialize_with_take)(
+            dest,
+            this.as_mut_ptr(),
+            this.self_ptr(),
+        );
+        this.deallocate();
+    }
+
+    /// Replaces the value of `self` with a copy of `other`.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `other` isn't of the same type as `self`.
+    pub fn assign(&mut self, other: &Self) {
+        self.assert_same_type(other);
+        unsafe {
+            (self.witnesses().assign_with_copy)(
+                self.as_mut_ptr(),
+                other.as_ptr() as *mut c_void,
+                self.self_ptr(),
+            );
+        }
+    }
+
+    /// Replaces the value of `self` with `other` by moving it.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `other` isn't of the same type as `self`.
+    pub fn assign_take(&mut self, other: Self) {
+        self.assert_same_type(&other);
+        let mut other = ManuallyDrop::new(other);
+        unsafe {
+            (self.witnesses().assign_with_take)(
+                self.as_mut_ptr(),
+                other.as_mut_ptr(),
+                self.self_ptr(),
+            );
+            other.deallocate();
+        }
+    }
+
+    /// Returns the type of the value.
+    #[inline]
+    pub fn meta_type(&self) -> &'static MetaType {
+        self.ty
+    }
+
+    /// Returns whether the value is stored inline rather than in a separate
+    /// allocation.
+    #[inline]
+    pub fn is_inline(&self) -> bool {
+        matches!(self.storage, Storage::Inline(_))
+    }
+
+    /// Returns a pointer to the value, suitable for passing to Swift.
+    #[inline]
+    pub fn as_ptr(&self) -> *const c_void {
+        match &self.storage {
+            Storage::Inline(buffer) => buffer as *const ValueBuffer as _,
+            Storage::Heap(ptr) => ptr.as_ptr() as _,
+        }
+    }
+
+    /// Returns a mutable pointer to the value, suitable for passing to Swift.
+    #[inline]
+    pub fn as_mut_ptr(&mut self) -> *mut c_void {
+        match &mut self.storage {
+            Storage::Inline(buffer) => buffer as *mut ValueBuffer as _,
+            Storage::Heap(ptr) => ptr.as_ptr() as _,
+        }
+    }
+
+    #[inline]
+    fn witnesses(&self) -> &'static ValueWitnessTable {
+        self.ty.value_witnesses()
+    }
+
+    /// Returns the metadata pointer passed as `self` to value witnesses.
+    #[inline]
+    fn self_ptr(&self) -> *mut c_void {
+        self.ty.as_ptr() as *mut c_void
+    }
+
+    #[inline]
+    fn assert_same_type(&self, other: &Self) {
+        assert!(
+            self.ty.as_ptr() == other.ty.as_ptr(),
+            "cannot assign a value of type {} to a value of type {}",
+            other.ty.name(true),
+            self.ty.name(true),
+        );
+    }
+}
@@ -8,8 +8,7 @@
 use crate::OpaqueValue;
 use std::os::raw::{c_char, c_int, c_void};
 
-/// A metadata object.
-pub type HeapMetadata = OpaqueValue;
+pub use crate::metadata::{HeapMetadata, Metadata};
 
 /// Stores the reference counts for a [`HeapObject`].
 #[repr(C)]
@@ -59,11 +58,6 @@ pub struct BoxPair {
     pub buffer: *mut OpaqueValue,
 }
 
-/// Type metadata.
-///
-/// Refers to `TargetMetadata<InProcess>` in C++.
-pub type Metadata = OpaqueValue;
-
 /// The name of a type.
 #[repr(C)]
 #[derive(Clone, Copy)]
@@ -11,6 +11,8 @@ extern crate static_assertions;
 
 pub mod heap;
 pub mod metadata;
+pub mod ptr;
+pub mod section;
 pub mod sym;
 pub mod sync;
 
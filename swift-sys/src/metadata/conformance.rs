@@ -0,0 +1,342 @@
+//! Protocol conformance descriptors, which describe how a type conforms to a
+//! protocol.
+//!
+//! These are referred to by the records of the `swift5_protocol_conformances`
+//! section.
+
+// Based on:
+// include/swift/ABI/Metadata.h
+// include/swift/ABI/MetadataValues.h
+
+use super::{
+    c_str,
+    context::{
+        ContextDescriptor, GenericRequirementDescriptor, ProtocolDescriptor,
+        ProtocolRequirement,
+    },
+};
+use crate::{
+    ptr::{RelativeDirectPointer, RelativeIndirectablePointer},
+    OpaqueValue,
+};
+use std::{ffi::CStr, mem, os::raw::c_char, slice};
+
+assert_eq_size!(ProtocolConformanceRecord, [u8; 4]);
+assert_eq_size!(ProtocolConformanceDescriptor, [u8; 16]);
+assert_eq_size!(ResilientWitnessesHeader, [u8; 4]);
+assert_eq_size!(ResilientWitness, [u8; 8]);
+assert_eq_size!(GenericWitnessTable, [u8; 12]);
+
+/// How a conformance or type record refers to a type.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+#[repr(transparent)]
+// enum class TypeReferenceKind : unsigned
+pub struct TypeReferenceKind(pub u32);
+
+impl TypeReferenceKind {
+    /// A relative pointer to a type descriptor.
+    pub const DIRECT_TYPE_DESCRIPTOR: Self = Self(0);
+    /// A relative pointer to a pointer to a type descriptor.
+    pub const INDIRECT_TYPE_DESCRIPTOR: Self = Self(1);
+    /// A relative pointer to the name of an Objective-C class.
+    pub const DIRECT_OBJC_CLASS_NAME: Self = Self(2);
+    /// A relative pointer to a pointer to an Objective-C class object.
+    pub const INDIRECT_OBJC_CLASS: Self = Self(3);
+
+    /// Returns whether the reference is through a pointer.
+    #[inline]
+    pub const fn is_indirect(self) -> bool {
+        self.0 == Self::INDIRECT_TYPE_DESCRIPTOR.0
+            || self.0 == Self::INDIRECT_OBJC_CLASS.0
+    }
+}
+
+/// Flags describing a protocol conformance.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+#[repr(transparent)]
+// class ConformanceFlags
+pub struct ConformanceFlags(pub u32);
+
+impl ConformanceFlags {
+    /// Returns how the conformance refers to the conforming type.
+    #[inline]
+    pub const fn type_reference_kind(&self) -> TypeReferenceKind {
+        TypeReferenceKind((self.0 >> 3) & 0x7)
+    }
+
+    /// Returns whether the conformance is declared outside of the modules of
+    /// both the type and the protocol, in which case the descriptor is
+    /// followed by a reference to the extension's context.
+    #[inline]
+    pub const fn is_retroactive(&self) -> bool {
+        self.0 & (1 << 6) != 0
+    }
+
+    /// Returns whether the conformance was synthesized by the compiler, such
+    /// as for an imported C type, and may be duplicated across images.
+    #[inline]
+    pub const fn is_synthesized_non_unique(&self) -> bool {
+        self.0 & (1 << 7) != 0
+    }
+
+    /// Returns the number of conditional requirements, which follow the
+    /// descriptor.
+    #[inline]
+    pub const fn num_conditional_requirements(&self) -> u32 {
+        (self.0 >> 8) & 0xFF
+    }
+
+    /// Returns whether the descriptor is followed by witnesses for resilient
+    /// protocol requirements.
+    #[inline]
+    pub const fn has_resilient_witnesses(&self) -> bool {
+        self.0 & (1 << 16) != 0
+    }
+
+    /// Returns whether the descriptor is followed by a
+    /// [`GenericWitnessTable`](struct.GenericWitnessTable.html), which
+    /// instantiates the witness table at runtime.
+    #[inline]
+    pub const fn has_generic_witness_table(&self) -> bool {
+        self.0 & (1 << 17) != 0
+    }
+}
+
+/// An entry of `swift5_protocol_conformances`.
+///
+/// Refers to `TargetProtocolConformanceRecord<InProcess>` in C++.
+#[repr(C)]
+#[derive(Debug)]
+pub struct ProtocolConformanceRecord {
+    /// The conformance descriptor.
+    pub descriptor: RelativeDirectPointer<ProtocolConformanceDescriptor>,
+}
+
+impl ProtocolConformanceRecord {
+    /// Returns the conformance descriptor, or `None` if it's null.
+    #[inline]
+    pub fn descriptor(&self) -> Option<&ProtocolConformanceDescriptor> {
+        unsafe { self.descriptor.as_ref() }
+    }
+}
+
+/// Describes the conformance of a type to a protocol.
+///
+/// The descriptor is followed by trailing objects, in order:
+///
+/// 1. A relative pointer to the context of a retroactive conformance.
+///
+/// 2. A `GenericRequirementDescriptor` per conditional requirement.
+///
+/// 3. A `ResilientWitnessesHeader` and a `ResilientWitness` per witness.
+///
+/// 4. A `GenericWitnessTable`.
+///
+/// Refers to `TargetProtocolConformanceDescriptor<InProcess>` in C++.
+#[repr(C)]
+#[derive(Debug)]
+pub struct ProtocolConformanceDescriptor {
+    /// The protocol being conformed to.
+    pub protocol: RelativeIndirectablePointer<ProtocolDescriptor>,
+
+    /// The conforming type, whose meaning depends on
+    /// [`ConformanceFlags::type_reference_kind`](struct.ConformanceFlags.html#method.type_reference_kind).
+    pub type_ref: RelativeDirectPointer<OpaqueValue>,
+
+    /// The witness table, or the pattern used to instantiate it.
+    pub witness_table_pattern: RelativeDirectPointer<OpaqueValue>,
+
+    /// Flags describing the conformance.
+    pub flags: ConformanceFlags,
+}
+
+impl ProtocolConformanceDescriptor {
+    /// The offset of `type_ref` from the start of a descriptor.
+    pub const TYPE_REF_OFFSET: usize =
+        mem::size_of::<RelativeIndirectablePointer<ProtocolDescriptor>>();
+
+    /// The offset of `witness_table_pattern` from the start of a descriptor.
+    pub const WITNESS_TABLE_PATTERN_OFFSET: usize = Self::TYPE_REF_OFFSET
+        + mem::size_of::<RelativeDirectPointer<OpaqueValue>>();
+
+    /// The offset of `flags` from the start of a descriptor.
+    pub const FLAGS_OFFSET: usize = Self::WITNESS_TABLE_PATTERN_OFFSET
+        + mem::size_of::<RelativeDirectPointer<OpaqueValue>>();
+
+    /// Returns the protocol being conformed to, or `None` if it can't be
+    /// resolved, such as when it's imported by a file that isn't loaded.
+    #[inline]
+    pub fn protocol(&self) -> Option<&ProtocolDescriptor> {
+        unsafe { self.protocol.as_ref() }
+    }
+
+    /// Returns how the conformance refers to the conforming type.
+    #[inline]
+    pub fn type_reference_kind(&self) -> TypeReferenceKind {
+        self.flags.type_reference_kind()
+    }
+
+    /// Returns the descriptor of the conforming type, or `None` if it's an
+    /// Objective-C class or can't be resolved.
+    #[inline]
+    pub fn type_descriptor(&self) -> Option<&ContextDescriptor> {
+        let target = self.type_ref.get();
+        unsafe {
+            match self.type_reference_kind() {
+                TypeReferenceKind::DIRECT_TYPE_DESCRIPTOR => {
+                    (target as *const ContextDescriptor).as_ref()
+                }
+                TypeReferenceKind::INDIRECT_TYPE_DESCRIPTOR => {
+                    let slot = target as *const *const ContextDescriptor;
+                    slot.as_ref()?.as_ref()
+                }
+                _ => None,
+            }
+        }
+    }
+
+    /// Returns the name of the conforming Objective-C class, or `None` if it
+    /// isn't referred to by name.
+    #[inline]
+    pub fn objc_class_name(&self) -> Option<&CStr> {
+        match self.type_reference_kind() {
+            TypeReferenceKind::DIRECT_OBJC_CLASS_NAME => unsafe {
+                let name = &*(&self.type_ref as *const _
+                    as *const RelativeDirectPointer<c_char>);
+                Some(c_str(name))
+            },
+            _ => None,
+        }
+    }
+
+    /// Returns the witness table, or the pattern used to instantiate it.
+    #[inline]
+    pub fn witness_table_pattern(&self) -> *const OpaqueValue {
+        self.witness_table_pattern.get()
+    }
+
+    /// Returns the context that declares a retroactive conformance, or `None`
+    /// if the conformance isn't retroactive.
+    #[inline]
+    pub fn retroactive_context(&self) -> Option<&ContextDescriptor> {
+        if !self.flags.is_retroactive() {
+            return None;
+        }
+        unsafe {
+            let context = &*((self as *const Self).add(1)
+                as *const RelativeIndirectablePointer<ContextDescriptor>);
+            context.as_ref()
+        }
+    }
+
+    /// Returns the requirements that the conforming type's generic arguments
+    /// must satisfy for the conformance to apply.
+    #[inline]
+    pub fn conditional_requirements(&self) -> &[GenericRequirementDescriptor] {
+        let mut start = (self as *const Self).wrapping_add(1) as *const u8;
+        if self.flags.is_retroactive() {
+            start = start.wrapping_add(mem::size_of::<i32>());
+        }
+        let len = self.flags.num_conditional_requirements() as usize;
+        unsafe {
+            slice::from_raw_parts(
+                start as *const GenericRequirementDescriptor,
+                len,
+            )
+        }
+    }
+
+    /// Returns the header of the resilient witnesses, or `None` if there are
+    /// none.
+    #[inline]
+    pub fn resilient_witnesses_header(
+        &self,
+    ) -> Option<&ResilientWitnessesHeader> {
+        if self.flags.has_resilient_witnesses() {
+            let start = self.conditional_requirements().as_ptr_range().end;
+            Some(unsafe { &*(start as *const ResilientWitnessesHeader) })
+        } else {
+            None
+        }
+    }
+
+    /// Returns the witnesses for requirements of a resilient protocol.
+    #[inline]
+    pub fn resilient_witnesses(&self) -> &[ResilientWitness] {
+        match self.resilient_witnesses_header() {
+            Some(header) => unsafe {
+                let start = (header as *const ResilientWitnessesHeader).add(1)
+                    as *const ResilientWitness;
+                slice::from_raw_parts(start, header.num_witnesses as usize)
+            },
+            None => &[],
+        }
+    }
+
+    /// Returns the generic witness table, or `None` if the witness table
+    /// doesn't need to be instantiated.
+    #[inline]
+    pub fn generic_witness_table(&self) -> Option<&GenericWitnessTable> {
+        if !self.flags.has_generic_witness_table() {
+            return None;
+        }
+        let start = match self.resilient_witnesses_header() {
+            Some(_) => {
+                self.resilient_witnesses().as_ptr_range().end as *const u8
+            }
+            None => {
+                self.conditional_requirements().as_ptr_range().end as *const u8
+            }
+        };
+        Some(unsafe { &*(start as *const GenericWitnessTable) })
+    }
+}
+
+/// The header of the resilient witnesses of a conformance.
+///
+/// Refers to `TargetResilientWitnessesHeader<InProcess>` in C++.
+#[repr(C)]
+#[derive(Clone, Copy, Debug)]
+pub struct ResilientWitnessesHeader {
+    /// The number of witnesses.
+    pub num_witnesses: u32,
+}
+
+/// The witness for a requirement of a resilient protocol.
+///
+/// Refers to `TargetResilientWitness<InProcess>` in C++.
+#[repr(C)]
+#[derive(Debug)]
+pub struct ResilientWitness {
+    /// The protocol requirement that is witnessed.
+    pub requirement: RelativeIndirectablePointer<ProtocolRequirement>,
+
+    /// The implementation of the requirement.
+    pub witness: RelativeDirectPointer<OpaqueValue>,
+}
+
+impl ResilientWitness {
+    /// The offset of `witness` from the start of a resilient witness.
+    pub const WITNESS_OFFSET: usize =
+        mem::size_of::<RelativeIndirectablePointer<ProtocolRequirement>>();
+}
+
+/// Describes how to instantiate the witness table of a conformance.
+///
+/// Refers to `TargetGenericWitnessTable<InProcess>` in C++.
+#[repr(C)]
+#[derive(Debug)]
+pub struct GenericWitnessTable {
+    /// The size of the witness table in words, excluding private storage.
+    pub witness_table_size_in_words: u16,
+
+    /// The size of private storage in words, where the low bit marks that
+    /// the witness table requires instantiation.
+    pub witness_table_private_size_in_words_and_requires_instantiation: u16,
+
+    /// The function that completes the instantiated witness table.
+    pub instantiator: RelativeDirectPointer<OpaqueValue>,
+
+    /// The private data for the conformance.
+    pub private_data: RelativeDirectPointer<OpaqueValue>,
+}
@@ -0,0 +1,298 @@
+use super::{ContextDescriptor, ProtocolDescriptor};
+use crate::{
+    ptr::{
+        RelativeDirectPointer, RelativeIndirectablePointer,
+        RelativeIndirectablePointerIntPair,
+    },
+    OpaqueValue,
+};
+use std::{mem, os::raw::c_char, slice};
+
+assert_eq_size!(GenericContextDescriptorHeader, [u8; 8]);
+assert_eq_size!(TypeGenericContextDescriptorHeader, [u8; 16]);
+assert_eq_size!(GenericParamDescriptor, u8);
+assert_eq_size!(GenericRequirementDescriptor, [u8; 12]);
+
+/// The counts of generic parameters and requirements that follow a generic
+/// context descriptor.
+///
+/// Refers to `TargetGenericContextDescriptorHeader<InProcess>` in C++.
+#[repr(C)]
+#[derive(Clone, Copy, Debug)]
+pub struct GenericContextDescriptorHeader {
+    /// The number of generic parameters, including those of parent contexts.
+    pub num_params: u16,
+
+    /// The number of generic requirements, including those of parent
+    /// contexts.
+    pub num_requirements: u16,
+
+    /// The number of key arguments that are passed to the metadata accessor,
+    /// which are the key parameters followed by the witness tables of key
+    /// requirements.
+    pub num_key_arguments: u16,
+
+    /// Flags, which are 0 unless the context has type parameter packs.
+    pub flags: u16,
+}
+
+/// The generic context header of a nominal type, which also refers to the
+/// cache of instantiated metadata.
+///
+/// Refers to `TargetTypeGenericContextDescriptorHeader<InProcess>` in C++.
+#[repr(C)]
+#[derive(Debug)]
+pub struct TypeGenericContextDescriptorHeader {
+    /// The cache of instantiated metadata.
+    pub instantiation_cache: RelativeDirectPointer<OpaqueValue>,
+
+    /// The pattern used to instantiate metadata.
+    pub default_instantiation_pattern: RelativeDirectPointer<OpaqueValue>,
+
+    /// The counts of generic parameters and requirements.
+    pub base: GenericContextDescriptorHeader,
+}
+
+/// Describes a generic parameter.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+#[repr(transparent)]
+// class GenericParamDescriptor
+pub struct GenericParamDescriptor(pub u8);
+
+impl GenericParamDescriptor {
+    /// The bit marking a parameter that's passed as a key argument.
+    pub const KEY_ARGUMENT: u8 = 0x80;
+
+    /// Returns the kind of the parameter, where 0 is a type and 1 is a type
+    /// parameter pack.
+    #[inline]
+    pub const fn kind(&self) -> u8 {
+        self.0 & 0x3F
+    }
+
+    /// Returns whether the parameter is passed as a key argument.
+    #[inline]
+    pub const fn has_key_argument(&self) -> bool {
+        self.0 & Self::KEY_ARGUMENT != 0
+    }
+}
+
+/// The kind of a generic requirement.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+#[repr(transparent)]
+// enum class GenericRequirementKind : uint8_t
+pub struct GenericRequirementKind(pub u8);
+
+impl GenericRequirementKind {
+    /// The parameter conforms to a protocol.
+    pub const PROTOCOL: Self = Self(0);
+    /// The parameter is the same type as another type.
+    pub const SAME_TYPE: Self = Self(1);
+    /// The parameter is a subclass of a class.
+    pub const BASE_CLASS: Self = Self(2);
+    /// The parameter's conformance is implied by another conformance.
+    pub const SAME_CONFORMANCE: Self = Self(3);
+    /// The parameter pack has the same shape as another.
+    pub const SAME_SHAPE: Self = Self(4);
+    /// The parameter has a layout constraint, such as `AnyObject`.
+    pub const LAYOUT: Self = Self(0x1F);
+}
+
+/// Flags describing a generic requirement.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+#[repr(transparent)]
+// class GenericRequirementFlags
+pub struct GenericRequirementFlags(pub u32);
+
+impl GenericRequirementFlags {
+    /// Returns the kind of the requirement.
+    #[inline]
+    pub const fn kind(&self) -> GenericRequirementKind {
+        GenericRequirementKind((self.0 & 0x1F) as u8)
+    }
+
+    /// Returns whether the requirement's witness is passed as a key argument.
+    #[inline]
+    pub const fn has_key_argument(&self) -> bool {
+        self.0 & 0x80 != 0
+    }
+}
+
+/// Describes a generic requirement.
+///
+/// Refers to `TargetGenericRequirementDescriptor<InProcess>` in C++.
+#[repr(C)]
+#[derive(Debug)]
+pub struct GenericRequirementDescriptor {
+    /// Flags describing the requirement, including its kind.
+    pub flags: GenericRequirementFlags,
+
+    /// The mangled name of the constrained parameter.
+    pub param: RelativeDirectPointer<c_char>,
+
+    // A union whose member depends on the kind.
+    content: i32,
+}
+
+impl GenericRequirementDescriptor {
+    /// Returns the kind of the requirement.
+    #[inline]
+    pub fn kind(&self) -> GenericRequirementKind {
+        self.flags.kind()
+    }
+
+    /// Returns the mangled name of the other type if this is a same-type or
+    /// base class requirement.
+    #[inline]
+    pub fn type_name(&self) -> Option<&RelativeDirectPointer<c_char>> {
+        match self.kind() {
+            GenericRequirementKind::SAME_TYPE
+            | GenericRequirementKind::BASE_CLASS => Some(self.content()),
+            _ => None,
+        }
+    }
+
+    /// Returns the protocol if this is a protocol requirement.
+    ///
+    /// The integer is 1 if the protocol is an Objective-C protocol, in which
+    /// case the target isn't a `ProtocolDescriptor`.
+    #[inline]
+    pub fn protocol(
+        &self,
+    ) -> Option<&RelativeIndirectablePointerIntPair<ProtocolDescriptor>> {
+        match self.kind() {
+            GenericRequirementKind::PROTOCOL => Some(self.content()),
+            _ => None,
+        }
+    }
+
+    /// Returns the protocol conformance descriptor if this is a
+    /// same-conformance requirement.
+    #[inline]
+    pub fn conformance(
+        &self,
+    ) -> Option<&RelativeIndirectablePointer<OpaqueValue>> {
+        match self.kind() {
+            GenericRequirementKind::SAME_CONFORMANCE => Some(self.content()),
+            _ => None,
+        }
+    }
+
+    /// Returns the kind of layout if this is a layout requirement, where 0
+    /// is a class.
+    #[inline]
+    pub fn layout(&self) -> Option<u32> {
+        match self.kind() {
+            GenericRequirementKind::LAYOUT => Some(self.content as u32),
+            _ => None,
+        }
+    }
+
+    #[inline]
+    fn content<T>(&self) -> &T {
+        // All relative pointers are transparent wrappers around `i32`.
+        unsafe { &*(&self.content as *const i32 as *const T) }
+    }
+}
+
+/// The generic parameters and requirements that follow a generic context
+/// descriptor.
+///
+/// Created by [`ContextDescriptor::generic_context`](struct.ContextDescriptor.html#method.generic_context).
+#[derive(Clone, Copy, Debug)]
+pub struct GenericContext<'a> {
+    type_header: Option<&'a TypeGenericContextDescriptorHeader>,
+    header: &'a GenericContextDescriptorHeader,
+    params: &'a [GenericParamDescriptor],
+    requirements: &'a [GenericRequirementDescriptor],
+}
+
+impl<'a> GenericContext<'a> {
+    /// Reads the generic context that follows the `size`-byte descriptor
+    /// `base`, which starts with a `TypeGenericContextDescriptorHeader` if
+    /// `is_type`.
+    ///
+    /// # Safety
+    ///
+    /// `base` must be a generic descriptor of `size` bytes.
+    pub(crate) unsafe fn at(
+        base: &'a ContextDescriptor,
+        size: usize,
+        is_type: bool,
+    ) -> Self {
+        let start = (base as *const ContextDescriptor as *const u8).add(size);
+
+        let (type_header, header) = if is_type {
+            let type_header =
+                &*(start as *const TypeGenericContextDescriptorHeader);
+            (Some(type_header), &type_header.base)
+        } else {
+            (None, &*(start as *const GenericContextDescriptorHeader))
+        };
+
+        let params_start = (header as *const GenericContextDescriptorHeader)
+            .add(1) as *const GenericParamDescriptor;
+        let params =
+            slice::from_raw_parts(params_start, header.num_params as usize);
+
+        let requirements_start =
+            align4(params_start.add(params.len()) as *const u8)
+                as *const GenericRequirementDescriptor;
+        let requirements = slice::from_raw_parts(
+            requirements_start,
+            header.num_requirements as usize,
+        );
+
+        Self {
+            type_header,
+            header,
+            params,
+            requirements,
+        }
+    }
+
+    /// Returns the header of the generic context.
+    #[inline]
+    pub fn header(&self) -> &'a GenericContextDescriptorHeader {
+        self.header
+    }
+
+    /// Returns the header of the generic context if it's that of a nominal
+    /// type.
+    #[inline]
+    pub fn type_header(
+        &self,
+    ) -> Option<&'a TypeGenericContextDescriptorHeader> {
+        self.type_header
+    }
+
+    /// Returns the generic parameters, including those of parent contexts.
+    #[inline]
+    pub fn params(&self) -> &'a [GenericParamDescriptor] {
+        self.params
+    }
+
+    /// Returns the generic requirements, including those of parent contexts.
+    #[inline]
+    pub fn requirements(&self) -> &'a [GenericRequirementDescriptor] {
+        self.requirements
+    }
+
+    /// Returns the address just past the generic context, where the
+    /// descriptor's remaining trailing objects start.
+    #[inline]
+    pub fn end(&self) -> *const u8 {
+        self.requirements.as_ptr_range().end as *const u8
+    }
+}
+
+/// Returns `ptr` rounded up to a multiple of 4.
+#[inline]
+fn align4(ptr: *const u8) -> *const u8 {
+    let misalignment = ptr as usize % mem::align_of::<u32>();
+    if misalignment == 0 {
+        ptr
+    } else {
+        ptr.wrapping_add(mem::align_of::<u32>() - misalignment)
+    }
+}
@@ -0,0 +1,411 @@
+//! Context descriptors, which describe the declarations that contain other
+//! declarations: modules, extensions, types and more.
+//!
+//! Descriptors are emitted by the compiler and must only be accessed in place,
+//! since they refer to their parents, names and trailing objects by relative
+//! pointers. They may be in the memory of the current process or in a file
+//! that has been mapped into memory with its original layout.
+
+// Based on:
+// include/swift/ABI/Metadata.h
+// include/swift/ABI/MetadataValues.h
+
+use super::{c_str, mangled_name};
+use crate::{
+    heap::Metadata,
+    ptr::{RelativeDirectPointer, RelativeIndirectablePointer},
+};
+use std::{ffi::CStr, ops::Deref, os::raw::c_char};
+
+mod generic;
+mod nominal;
+mod protocol;
+
+pub use generic::*;
+pub use nominal::*;
+pub use protocol::*;
+
+assert_eq_size!(ContextDescriptor, [u8; 8]);
+assert_eq_size!(ModuleContextDescriptor, [u8; 12]);
+assert_eq_size!(ExtensionContextDescriptor, [u8; 12]);
+assert_eq_size!(AnonymousContextDescriptor, [u8; 8]);
+
+/// The kind of a context descriptor.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+#[repr(transparent)]
+// enum class ContextDescriptorKind : uint8_t
+pub struct ContextDescriptorKind(pub u8);
+
+impl ContextDescriptorKind {
+    /// A module.
+    pub const MODULE: Self = Self(0);
+    /// An extension.
+    pub const EXTENSION: Self = Self(1);
+    /// An unnamed context, such as the body of a function or closure.
+    pub const ANONYMOUS: Self = Self(2);
+    /// A protocol.
+    pub const PROTOCOL: Self = Self(3);
+    /// An opaque type alias, such as the return type of a function that
+    /// returns `some P`.
+    pub const OPAQUE_TYPE: Self = Self(4);
+    /// A class.
+    pub const CLASS: Self = Self(16);
+    /// A struct.
+    pub const STRUCT: Self = Self(17);
+    /// An enum.
+    pub const ENUM: Self = Self(18);
+
+    /// The first kind that describes a nominal type.
+    pub const TYPE_FIRST: Self = Self(16);
+    /// The last kind that describes a nominal type.
+    pub const TYPE_LAST: Self = Self(31);
+
+    /// Returns whether `self` describes a nominal type, which has a
+    /// [`TypeContextDescriptor`](struct.TypeContextDescriptor.html).
+    #[inline]
+    pub const fn is_type(&self) -> bool {
+        self.0 >= Self::TYPE_FIRST.0 && self.0 <= Self::TYPE_LAST.0
+    }
+}
+
+/// Flags describing a context descriptor, which are always its first field.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+#[repr(transparent)]
+// struct ContextDescriptorFlags
+pub struct ContextDescriptorFlags(pub u32);
+
+impl ContextDescriptorFlags {
+    /// Returns the kind of the descriptor.
+    #[inline]
+    pub const fn kind(&self) -> ContextDescriptorKind {
+        ContextDescriptorKind((self.0 & 0x1F) as u8)
+    }
+
+    /// Returns whether the context is generic, in which case the descriptor
+    /// is followed by a generic context header.
+    #[inline]
+    pub const fn is_generic(&self) -> bool {
+        self.0 & 0x80 != 0
+    }
+
+    /// Returns whether the descriptor is unique, so that descriptors may be
+    /// compared by address.
+    #[inline]
+    pub const fn is_unique(&self) -> bool {
+        self.0 & 0x40 != 0
+    }
+
+    /// Returns the format version of the descriptor.
+    #[inline]
+    pub const fn version(&self) -> u8 {
+        (self.0 >> 8) as u8
+    }
+
+    /// Returns the 16 bits of flags whose meaning depends on the kind.
+    #[inline]
+    pub const fn kind_specific_flags(&self) -> u16 {
+        (self.0 >> 16) as u16
+    }
+}
+
+/// The base of every context descriptor.
+///
+/// Refers to `TargetContextDescriptor<InProcess>` in C++.
+#[repr(C)]
+#[derive(Debug)]
+pub struct ContextDescriptor {
+    /// Flags describing the context, including its kind.
+    pub flags: ContextDescriptorFlags,
+
+    /// The parent context, or null if this is a top-level context such as a
+    /// module.
+    pub parent: RelativeIndirectablePointer<ContextDescriptor>,
+}
+
+impl ContextDescriptor {
+    /// Returns the kind of the context.
+    #[inline]
+    pub fn kind(&self) -> ContextDescriptorKind {
+        self.flags.kind()
+    }
+
+    /// Returns the parent context, or `None` if this is a top-level context
+    /// such as a module.
+    #[inline]
+    pub fn parent(&self) -> Option<&ContextDescriptor> {
+        unsafe { self.parent.as_ref() }
+    }
+
+    /// Returns an iterator over the parent contexts of `self`, innermost
+    /// first, which usually ends with a module.
+    #[inline]
+    pub fn parents(&self) -> Parents<'_> {
+        Parents {
+            next: self.parent(),
+        }
+    }
+
+    /// Returns the module that `self` is declared in, or `self` if it's a
+    /// module.
+    pub fn module(&self) -> Option<&ModuleContextDescriptor> {
+        std::iter::once(self)
+            .chain(self.parents())
+            .find_map(ContextDescriptor::as_module)
+    }
+
+    /// Returns the name of the context if it's a module, nominal type or
+    /// protocol.
+    pub fn name(&self) -> Option<&CStr> {
+        if let Some(module) = self.as_module() {
+            Some(module.name())
+        } else if let Some(ty) = self.as_type() {
+            Some(ty.name())
+        } else {
+            self.as_protocol().map(ProtocolDescriptor::name)
+        }
+    }
+
+    /// Returns the generic parameters and requirements of the context, or
+    /// `None` if it isn't generic.
+    pub fn generic_context(&self) -> Option<GenericContext<'_>> {
+        if !self.flags.is_generic() {
+            return None;
+        }
+        let kind = self.kind();
+        if kind.is_type() {
+            let (size, is_type) = match kind {
+                ContextDescriptorKind::CLASS => {
+                    (std::mem::size_of::<ClassDescriptor>(), true)
+                }
+                ContextDescriptorKind::STRUCT => {
+                    (std::mem::size_of::<StructDescriptor>(), true)
+                }
+                ContextDescriptorKind::ENUM => {
+                    (std::mem::size_of::<EnumDescriptor>(), true)
+                }
+                _ => return None,
+            };
+            return unsafe { Some(GenericContext::at(self, size, is_type)) };
+        }
+        let size = match kind {
+            ContextDescriptorKind::EXTENSION => {
+                std::mem::size_of::<ExtensionContextDescriptor>()
+            }
+            ContextDescriptorKind::ANONYMOUS => {
+                std::mem::size_of::<AnonymousContextDescriptor>()
+            }
+            ContextDescriptorKind::OPAQUE_TYPE => {
+                std::mem::size_of::<OpaqueTypeDescriptor>()
+            }
+            _ => return None,
+        };
+        unsafe { Some(GenericContext::at(self, size, false)) }
+    }
+
+    /// Returns `self` as a module descriptor if it is one.
+    #[inline]
+    pub fn as_module(&self) -> Option<&ModuleContextDescriptor> {
+        self.cast_if(self.kind() == ContextDescriptorKind::MODULE)
+    }
+
+    /// Returns `self` as an extension descriptor if it is one.
+    #[inline]
+    pub fn as_extension(&self) -> Option<&ExtensionContextDescriptor> {
+        self.cast_if(self.kind() == ContextDescriptorKind::EXTENSION)
+    }
+
+    /// Returns `self` as an anonymous context descriptor if it is one.
+    #[inline]
+    pub fn as_anonymous(&self) -> Option<&AnonymousContextDescriptor> {
+        self.cast_if(self.kind() == ContextDescriptorKind::ANONYMOUS)
+    }
+
+    /// Returns `self` as a protocol descriptor if it is one.
+    #[inline]
+    pub fn as_protocol(&self) -> Option<&ProtocolDescriptor> {
+        self.cast_if(self.kind() == ContextDescriptorKind::PROTOCOL)
+    }
+
+    /// Returns `self` as an opaque type descriptor if it is one.
+    #[inline]
+    pub fn as_opaque_type(&self) -> Option<&OpaqueTypeDescriptor> {
+        self.cast_if(self.kind() == ContextDescriptorKind::OPAQUE_TYPE)
+    }
+
+    /// Returns `self` as a nominal type descriptor if it is one.
+    #[inline]
+    pub fn as_type(&self) -> Option<&TypeContextDescriptor> {
+        self.cast_if(self.kind().is_type())
+    }
+
+    /// Returns `self` as a class descriptor if it is one.
+    #[inline]
+    pub fn as_class(&self) -> Option<&ClassDescriptor> {
+        self.cast_if(self.kind() == ContextDescriptorKind::CLASS)
+    }
+
+    /// Returns `self` as a struct descriptor if it is one.
+    #[inline]
+    pub fn as_struct(&self) -> Option<&StructDescriptor> {
+        self.cast_if(self.kind() == ContextDescriptorKind::STRUCT)
+    }
+
+    /// Returns `self` as an enum descriptor if it is one.
+    #[inline]
+    pub fn as_enum(&self) -> Option<&EnumDescriptor> {
+        self.cast_if(self.kind() == ContextDescriptorKind::ENUM)
+    }
+
+    #[inline]
+    fn cast_if<T>(&self, cond: bool) -> Option<&T> {
+        if cond {
+            Some(unsafe { &*(self as *const Self as *const T) })
+        } else {
+            None
+        }
+    }
+}
+
+/// An iterator over the parents of a context descriptor.
+///
+/// Created by [`ContextDescriptor::parents`](struct.ContextDescriptor.html#method.parents).
+#[derive(Clone, Debug)]
+pub struct Parents<'a> {
+    next: Option<&'a ContextDescriptor>,
+}
+
+impl<'a> Iterator for Parents<'a> {
+    type Item = &'a ContextDescriptor;
+
+    #[inline]
+    fn next(&mut self) -> Option<Self::Item> {
+        let next = self.next?;
+        self.next = next.parent();
+        Some(next)
+    }
+}
+
+/// Describes a module.
+///
+/// Refers to `TargetModuleContextDescriptor<InProcess>` in C++.
+#[repr(C)]
+#[derive(Debug)]
+pub struct ModuleContextDescriptor {
+    /// The base context descriptor.
+    pub base: ContextDescriptor,
+
+    /// The name of the module.
+    pub name: RelativeDirectPointer<c_char>,
+}
+
+impl Deref for ModuleContextDescriptor {
+    type Target = ContextDescriptor;
+
+    #[inline]
+    fn deref(&self) -> &Self::Target {
+        &self.base
+    }
+}
+
+impl ModuleContextDescriptor {
+    /// Returns the name of the module.
+    #[inline]
+    pub fn name(&self) -> &CStr {
+        unsafe { c_str(&self.name) }
+    }
+}
+
+/// Describes an extension, which adds members to a type declared elsewhere.
+///
+/// Generic extensions are followed by a
+/// [`GenericContextDescriptorHeader`](struct.GenericContextDescriptorHeader.html).
+///
+/// Refers to `TargetExtensionContextDescriptor<InProcess>` in C++.
+#[repr(C)]
+#[derive(Debug)]
+pub struct ExtensionContextDescriptor {
+    /// The base context descriptor.
+    pub base: ContextDescriptor,
+
+    /// The mangled name of the extended type, whose generic parameters refer
+    /// to those of the extension.
+    pub extended_context: RelativeDirectPointer<c_char>,
+}
+
+impl Deref for ExtensionContextDescriptor {
+    type Target = ContextDescriptor;
+
+    #[inline]
+    fn deref(&self) -> &Self::Target {
+        &self.base
+    }
+}
+
+impl ExtensionContextDescriptor {
+    /// Returns the mangled name of the extended type, which may contain
+    /// symbolic references.
+    #[inline]
+    pub fn extended_context(&self) -> Option<&[u8]> {
+        unsafe { mangled_name(&self.extended_context) }
+    }
+}
+
+/// Describes an unnamed context, such as the body of a function.
+///
+/// Generic contexts are followed by a
+/// [`GenericContextDescriptorHeader`](struct.GenericContextDescriptorHeader.html),
+/// and contexts with a mangled name are then followed by a relative pointer
+/// to it.
+///
+/// Refers to `TargetAnonymousContextDescriptor<InProcess>` in C++.
+#[repr(C)]
+#[derive(Debug)]
+pub struct AnonymousContextDescriptor {
+    /// The base context descriptor.
+    pub base: ContextDescriptor,
+}
+
+impl Deref for AnonymousContextDescriptor {
+    type Target = ContextDescriptor;
+
+    #[inline]
+    fn deref(&self) -> &Self::Target {
+        &self.base
+    }
+}
+
+impl AnonymousContextDescriptor {
+    /// Returns whether the context has a mangled name that identifies it.
+    #[inline]
+    pub fn has_mangled_name(&self) -> bool {
+        self.base.flags.kind_specific_flags() & 0x1 != 0
+    }
+
+    /// Returns the mangled name of the context, or `None` if it has none.
+    #[inline]
+    pub fn mangled_name(&self) -> Option<&[u8]> {
+        if !self.has_mangled_name() {
+            return None;
+        }
+        let start = match self.base.generic_context() {
+            Some(generic) => generic.end(),
+            None => (self as *const Self).wrapping_add(1) as *const u8,
+        };
+        unsafe {
+            let name = &*(start as *const RelativeDirectPointer<c_char>);
+            mangled_name(name)
+        }
+    }
+}
+
+// TODO: Support the Swift calling convention in rustc
+// See https://github.com/rust-lang/rust/pull/64582
+extern "C" {
+    /// Returns the descriptor of the nominal type described by `type_`, or
+    /// null if it isn't a nominal type.
+    // const TypeContextDescriptor *
+    // swift_getTypeContextDescriptor(const Metadata *type);
+    pub fn swift_getTypeContextDescriptor(
+        type_: *const Metadata,
+    ) -> *const TypeContextDescriptor;
+}
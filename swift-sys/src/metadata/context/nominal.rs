@@ -0,0 +1,621 @@
+use super::ContextDescriptor;
+use crate::metadata::{
+    c_str, conformance::TypeReferenceKind, field::FieldDescriptor, mangled_name,
+};
+use crate::{
+    ptr::{
+        RelativeDirectPointer, RelativeDirectPointerIntPair,
+        RelativeIndirectablePointer,
+    },
+    OpaqueValue,
+};
+use std::{ffi::CStr, mem, ops::Deref, os::raw::c_char, slice};
+
+assert_eq_size!(TypeMetadataRecord, [u8; 4]);
+assert_eq_size!(TypeContextDescriptor, [u8; 20]);
+assert_eq_size!(StructDescriptor, [u8; 28]);
+assert_eq_size!(EnumDescriptor, [u8; 28]);
+assert_eq_size!(ClassDescriptor, [u8; 44]);
+assert_eq_size!(ForeignMetadataInitialization, [u8; 4]);
+assert_eq_size!(SingletonMetadataInitialization, [u8; 12]);
+assert_eq_size!(VTableDescriptorHeader, [u8; 8]);
+assert_eq_size!(MethodDescriptor, [u8; 8]);
+assert_eq_size!(OverrideTableHeader, [u8; 4]);
+assert_eq_size!(MethodOverrideDescriptor, [u8; 12]);
+
+/// An entry of `swift5_type_metadata`.
+///
+/// Refers to `TargetTypeMetadataRecord<InProcess>` in C++.
+#[repr(C)]
+#[derive(Debug)]
+pub struct TypeMetadataRecord {
+    /// The type descriptor or a pointer to it, paired with how it's
+    /// referenced.
+    pub descriptor: RelativeDirectPointerIntPair<OpaqueValue>,
+}
+
+impl TypeMetadataRecord {
+    /// Returns how the record refers to the type.
+    #[inline]
+    pub fn type_reference_kind(&self) -> TypeReferenceKind {
+        TypeReferenceKind(self.descriptor.int())
+    }
+
+    /// Returns the type descriptor, or `None` if it's null or the record
+    /// refers to an Objective-C class.
+    #[inline]
+    pub fn descriptor(&self) -> Option<&TypeContextDescriptor> {
+        let target = self.descriptor.get();
+        let descriptor = unsafe {
+            match self.type_reference_kind() {
+                TypeReferenceKind::DIRECT_TYPE_DESCRIPTOR => {
+                    (target as *const ContextDescriptor).as_ref()
+                }
+                TypeReferenceKind::INDIRECT_TYPE_DESCRIPTOR => {
+                    let slot = target as *const *const ContextDescriptor;
+                    slot.as_ref()?.as_ref()
+                }
+                _ => None,
+            }
+        };
+        descriptor?.as_type()
+    }
+}
+
+/// The kind-specific flags of a nominal type descriptor.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+#[repr(transparent)]
+// class TypeContextDescriptorFlags
+pub struct TypeContextDescriptorFlags(pub u16);
+
+impl TypeContextDescriptorFlags {
+    /// The metadata doesn't need to be initialized at runtime.
+    pub const NO_METADATA_INITIALIZATION: u16 = 0;
+    /// The metadata is initialized once, and the descriptor is followed by a
+    /// [`SingletonMetadataInitialization`](struct.SingletonMetadataInitialization.html).
+    pub const SINGLETON_METADATA_INITIALIZATION: u16 = 1;
+    /// The metadata is foreign and completed at runtime, and the descriptor
+    /// is followed by a
+    /// [`ForeignMetadataInitialization`](struct.ForeignMetadataInitialization.html).
+    pub const FOREIGN_METADATA_INITIALIZATION: u16 = 2;
+
+    /// Returns the kind of metadata initialization that the type needs.
+    #[inline]
+    pub const fn metadata_initialization(&self) -> u16 {
+        self.0 & 0x3
+    }
+
+    /// Returns whether the name of the type is followed by information about
+    /// where it was imported from.
+    #[inline]
+    pub const fn has_import_info(&self) -> bool {
+        self.0 & (1 << 2) != 0
+    }
+
+    /// Returns whether a class is an actor.
+    #[inline]
+    pub const fn class_is_actor(&self) -> bool {
+        self.0 & (1 << 7) != 0
+    }
+
+    /// Returns whether a class is a default actor.
+    #[inline]
+    pub const fn class_is_default_actor(&self) -> bool {
+        self.0 & (1 << 8) != 0
+    }
+
+    /// Returns how a class refers to its resilient superclass.
+    #[inline]
+    pub const fn class_resilient_superclass_reference_kind(&self) -> u16 {
+        (self.0 >> 9) & 0x7
+    }
+
+    /// Returns whether the immediate members of a class are at negative
+    /// offsets from its metadata.
+    #[inline]
+    pub const fn class_are_immediate_members_negative(&self) -> bool {
+        self.0 & (1 << 12) != 0
+    }
+
+    /// Returns whether a class has a resilient superclass, in which case its
+    /// descriptor is followed by a
+    /// [`ResilientSuperclass`](struct.ResilientSuperclass.html).
+    #[inline]
+    pub const fn class_has_resilient_superclass(&self) -> bool {
+        self.0 & (1 << 13) != 0
+    }
+
+    /// Returns whether a class has an override table.
+    #[inline]
+    pub const fn class_has_override_table(&self) -> bool {
+        self.0 & (1 << 14) != 0
+    }
+
+    /// Returns whether a class has a vtable.
+    #[inline]
+    pub const fn class_has_vtable(&self) -> bool {
+        self.0 & (1 << 15) != 0
+    }
+}
+
+/// The base of every nominal type descriptor.
+///
+/// Refers to `TargetTypeContextDescriptor<InProcess>` in C++.
+#[repr(C)]
+#[derive(Debug)]
+pub struct TypeContextDescriptor {
+    /// The base context descriptor.
+    pub base: ContextDescriptor,
+
+    /// The name of the type.
+    pub name: RelativeDirectPointer<c_char>,
+
+    /// The function that returns the metadata of the type, which takes the
+    /// generic arguments of the type.
+    pub access_function: RelativeDirectPointer<OpaqueValue>,
+
+    /// The descriptor of the type's stored properties or enum cases, or null
+    /// if the type has no reflection metadata.
+    pub fields: RelativeDirectPointer<FieldDescriptor>,
+}
+
+impl Deref for TypeContextDescriptor {
+    type Target = ContextDescriptor;
+
+    #[inline]
+    fn deref(&self) -> &Self::Target {
+        &self.base
+    }
+}
+
+impl TypeContextDescriptor {
+    /// Returns the name of the type.
+    #[inline]
+    pub fn name(&self) -> &CStr {
+        unsafe { c_str(&self.name) }
+    }
+
+    /// Returns the kind-specific flags of the type.
+    #[inline]
+    pub fn type_flags(&self) -> TypeContextDescriptorFlags {
+        TypeContextDescriptorFlags(self.base.flags.kind_specific_flags())
+    }
+
+    /// Returns the address of the metadata access function, or null if there
+    /// is none.
+    #[inline]
+    pub fn access_function(&self) -> *const OpaqueValue {
+        self.access_function.get()
+    }
+
+    /// Returns the descriptor of the type's stored properties or enum cases,
+    /// or `None` if the type has no reflection metadata.
+    #[inline]
+    pub fn field_descriptor(&self) -> Option<&FieldDescriptor> {
+        unsafe { self.fields.as_ref() }
+    }
+
+    /// Returns the address just past the `size`-byte descriptor and its
+    /// generic context.
+    #[inline]
+    fn generic_end(&self, size: usize) -> *const u8 {
+        match self.base.generic_context() {
+            Some(generic) => generic.end(),
+            None => (self as *const Self as *const u8).wrapping_add(size),
+        }
+    }
+
+    /// Returns the metadata initialization trailing object, which starts at
+    /// `start`.
+    #[inline]
+    unsafe fn metadata_initialization<'a>(
+        &self,
+        start: *const u8,
+    ) -> MetadataInitialization<'a> {
+        match self.type_flags().metadata_initialization() {
+            TypeContextDescriptorFlags::SINGLETON_METADATA_INITIALIZATION => {
+                MetadataInitialization::Singleton(
+                    &*(start as *const SingletonMetadataInitialization),
+                )
+            }
+            TypeContextDescriptorFlags::FOREIGN_METADATA_INITIALIZATION => {
+                MetadataInitialization::Foreign(
+                    &*(start as *const ForeignMetadataInitialization),
+                )
+            }
+            _ => MetadataInitialization::None,
+        }
+    }
+}
+
+/// How the metadata of a nominal type is initialized at runtime.
+#[derive(Clone, Copy, Debug)]
+pub enum MetadataInitialization<'a> {
+    /// The metadata doesn't need to be initialized.
+    None,
+    /// The metadata is initialized once.
+    Singleton(&'a SingletonMetadataInitialization),
+    /// The metadata is foreign and completed at runtime.
+    Foreign(&'a ForeignMetadataInitialization),
+}
+
+impl MetadataInitialization<'_> {
+    /// Returns the size of the trailing object.
+    #[inline]
+    fn size(&self) -> usize {
+        match self {
+            Self::None => 0,
+            Self::Singleton(_) => {
+                mem::size_of::<SingletonMetadataInitialization>()
+            }
+            Self::Foreign(_) => mem::size_of::<ForeignMetadataInitialization>(),
+        }
+    }
+}
+
+/// The trailing object of a type whose metadata is foreign.
+///
+/// Refers to `TargetForeignMetadataInitialization<InProcess>` in C++.
+#[repr(C)]
+#[derive(Debug)]
+pub struct ForeignMetadataInitialization {
+    /// The function that completes the metadata.
+    pub completion_function: RelativeDirectPointer<OpaqueValue>,
+}
+
+/// The trailing object of a type whose metadata is initialized once.
+///
+/// Refers to `TargetSingletonMetadataInitialization<InProcess>` in C++.
+#[repr(C)]
+#[derive(Debug)]
+pub struct SingletonMetadataInitialization {
+    /// The cache of the initialized metadata.
+    pub initialization_cache: RelativeDirectPointer<OpaqueValue>,
+
+    /// The incomplete metadata, or the resilient class metadata pattern.
+    pub incomplete_metadata: RelativeDirectPointer<OpaqueValue>,
+
+    /// The function that completes the metadata.
+    pub completion_function: RelativeDirectPointer<OpaqueValue>,
+}
+
+/// Describes a struct.
+///
+/// Refers to `TargetStructDescriptor<InProcess>` in C++.
+#[repr(C)]
+#[derive(Debug)]
+pub struct StructDescriptor {
+    /// The base type descriptor.
+    pub base: TypeContextDescriptor,
+
+    /// The number of stored properties.
+    pub num_fields: u32,
+
+    /// The offset, in words, of the field offset vector from the start of
+    /// the struct's metadata, or 0 if there is none.
+    pub field_offset_vector_offset: u32,
+}
+
+impl Deref for StructDescriptor {
+    type Target = TypeContextDescriptor;
+
+    #[inline]
+    fn deref(&self) -> &Self::Target {
+        &self.base
+    }
+}
+
+impl StructDescriptor {
+    /// Returns how the metadata of the struct is initialized.
+    #[inline]
+    pub fn metadata_initialization(&self) -> MetadataInitialization<'_> {
+        let start = self.base.generic_end(mem::size_of::<Self>());
+        unsafe { self.base.metadata_initialization(start) }
+    }
+}
+
+/// Describes an enum.
+///
+/// Refers to `TargetEnumDescriptor<InProcess>` in C++.
+#[repr(C)]
+#[derive(Debug)]
+pub struct EnumDescriptor {
+    /// The base type descriptor.
+    pub base: TypeContextDescriptor,
+
+    /// The number of cases with payloads in the low 24 bits, and the offset
+    /// of the payload size in the metadata in the high 8 bits.
+    pub num_payload_cases_and_payload_size_offset: u32,
+
+    /// The number of cases without payloads.
+    pub num_empty_cases: u32,
+}
+
+impl Deref for EnumDescriptor {
+    type Target = TypeContextDescriptor;
+
+    #[inline]
+    fn deref(&self) -> &Self::Target {
+        &self.base
+    }
+}
+
+impl EnumDescriptor {
+    /// Returns the number of cases with payloads.
+    #[inline]
+    pub fn num_payload_cases(&self) -> u32 {
+        self.num_payload_cases_and_payload_size_offset & 0x00FF_FFFF
+    }
+
+    /// Returns the number of cases.
+    #[inline]
+    pub fn num_cases(&self) -> u32 {
+        self.num_payload_cases() + self.num_empty_cases
+    }
+
+    /// Returns the offset, in words, of the payload size in the enum's
+    /// metadata, or 0 if it isn't stored.
+    #[inline]
+    pub fn payload_size_offset(&self) -> u32 {
+        self.num_payload_cases_and_payload_size_offset >> 24
+    }
+
+    /// Returns how the metadata of the enum is initialized.
+    #[inline]
+    pub fn metadata_initialization(&self) -> MetadataInitialization<'_> {
+        let start = self.base.generic_end(mem::size_of::<Self>());
+        unsafe { self.base.metadata_initialization(start) }
+    }
+}
+
+/// Describes a class.
+///
+/// Refers to `TargetClassDescriptor<InProcess>` in C++.
+#[repr(C)]
+#[derive(Debug)]
+pub struct ClassDescriptor {
+    /// The base type descriptor.
+    pub base: TypeContextDescriptor,
+
+    /// The mangled name of the superclass, or null if there is none.
+    pub superclass_type: RelativeDirectPointer<c_char>,
+
+    /// The negative size of the metadata in words, or a relative pointer to
+    /// the cached metadata bounds if the class has a resilient superclass.
+    pub metadata_negative_size_in_words_or_resilient_metadata_bounds: u32,
+
+    /// The positive size of the metadata in words, or extra class flags if
+    /// the class has a resilient superclass.
+    pub metadata_positive_size_in_words_or_extra_class_flags: u32,
+
+    /// The number of members that this class adds to its metadata.
+    pub num_immediate_members: u32,
+
+    /// The number of stored properties.
+    pub num_fields: u32,
+
+    /// The offset, in words, of the field offset vector from the start of
+    /// the class's metadata.
+    pub field_offset_vector_offset: u32,
+}
+
+impl Deref for ClassDescriptor {
+    type Target = TypeContextDescriptor;
+
+    #[inline]
+    fn deref(&self) -> &Self::Target {
+        &self.base
+    }
+}
+
+impl ClassDescriptor {
+    /// Returns the mangled name of the superclass, which may contain symbolic
+    /// references, or `None` if there is none.
+    #[inline]
+    pub fn superclass_type(&self) -> Option<&[u8]> {
+        unsafe { mangled_name(&self.superclass_type) }
+    }
+
+    /// Returns the resilient superclass, or `None` if the superclass isn't
+    /// resilient.
+    #[inline]
+    pub fn resilient_superclass(&self) -> Option<&ResilientSuperclass> {
+        if self.type_flags().class_has_resilient_superclass() {
+            let start = self.base.generic_end(mem::size_of::<Self>());
+            Some(unsafe { &*(start as *const ResilientSuperclass) })
+        } else {
+            None
+        }
+    }
+
+    /// Returns how the metadata of the class is initialized.
+    #[inline]
+    pub fn metadata_initialization(&self) -> MetadataInitialization<'_> {
+        unsafe { self.base.metadata_initialization(self.after_superclass()) }
+    }
+
+    /// Returns the header of the vtable, or `None` if there is none.
+    #[inline]
+    pub fn vtable_header(&self) -> Option<&VTableDescriptorHeader> {
+        if self.type_flags().class_has_vtable() {
+            let start = self.after_metadata_initialization();
+            Some(unsafe { &*(start as *const VTableDescriptorHeader) })
+        } else {
+            None
+        }
+    }
+
+    /// Returns the descriptors of the methods in the vtable.
+    #[inline]
+    pub fn methods(&self) -> &[MethodDescriptor] {
+        match self.vtable_header() {
+            Some(header) => unsafe {
+                let start = (header as *const VTableDescriptorHeader).add(1)
+                    as *const MethodDescriptor;
+                slice::from_raw_parts(start, header.vtable_size as usize)
+            },
+            None => &[],
+        }
+    }
+
+    /// Returns the header of the override table, or `None` if there is none.
+    #[inline]
+    pub fn override_table_header(&self) -> Option<&OverrideTableHeader> {
+        if self.type_flags().class_has_override_table() {
+            let methods = self.methods();
+            let start = match self.vtable_header() {
+                Some(_) => methods.as_ptr_range().end as *const u8,
+                None => self.after_metadata_initialization(),
+            };
+            Some(unsafe { &*(start as *const OverrideTableHeader) })
+        } else {
+            None
+        }
+    }
+
+    /// Returns the descriptors of the methods that this class overrides.
+    #[inline]
+    pub fn method_overrides(&self) -> &[MethodOverrideDescriptor] {
+        match self.override_table_header() {
+            Some(header) => unsafe {
+                let start = (header as *const OverrideTableHeader).add(1)
+                    as *const MethodOverrideDescriptor;
+                slice::from_raw_parts(start, header.num_entries as usize)
+            },
+            None => &[],
+        }
+    }
+
+    #[inline]
+    fn after_superclass(&self) -> *const u8 {
+        let start = self.base.generic_end(mem::size_of::<Self>());
+        if self.type_flags().class_has_resilient_superclass() {
+            start.wrapping_add(mem::size_of::<ResilientSuperclass>())
+        } else {
+            start
+        }
+    }
+
+    #[inline]
+    fn after_metadata_initialization(&self) -> *const u8 {
+        let size = self.metadata_initialization().size();
+        self.after_superclass().wrapping_add(size)
+    }
+}
+
+/// The trailing object of a class whose superclass is resilient.
+///
+/// Refers to `TargetResilientSuperclass<InProcess>` in C++.
+#[repr(C)]
+#[derive(Debug)]
+pub struct ResilientSuperclass {
+    /// The superclass, whose kind of reference is given by
+    /// [`TypeContextDescriptorFlags::class_resilient_superclass_reference_kind`](struct.TypeContextDescriptorFlags.html#method.class_resilient_superclass_reference_kind).
+    pub superclass: RelativeDirectPointer<OpaqueValue>,
+}
+
+/// The header of a class's vtable.
+///
+/// Refers to `TargetVTableDescriptorHeader<InProcess>` in C++.
+#[repr(C)]
+#[derive(Clone, Copy, Debug)]
+pub struct VTableDescriptorHeader {
+    /// The offset, in words, of the vtable from the start of the class's
+    /// metadata.
+    pub vtable_offset: u32,
+
+    /// The number of methods in the vtable.
+    pub vtable_size: u32,
+}
+
+/// The kind of a method.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+#[repr(transparent)]
+// enum class MethodDescriptorFlags::Kind
+pub struct MethodDescriptorKind(pub u8);
+
+impl MethodDescriptorKind {
+    /// A method.
+    pub const METHOD: Self = Self(0);
+    /// An initializer.
+    pub const INIT: Self = Self(1);
+    /// A property getter.
+    pub const GETTER: Self = Self(2);
+    /// A property setter.
+    pub const SETTER: Self = Self(3);
+    /// A `modify` coroutine.
+    pub const MODIFY_COROUTINE: Self = Self(4);
+    /// A `read` coroutine.
+    pub const READ_COROUTINE: Self = Self(5);
+}
+
+/// Flags describing a method.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+#[repr(transparent)]
+// class MethodDescriptorFlags
+pub struct MethodDescriptorFlags(pub u32);
+
+impl MethodDescriptorFlags {
+    /// Returns the kind of the method.
+    #[inline]
+    pub const fn kind(&self) -> MethodDescriptorKind {
+        MethodDescriptorKind((self.0 & 0x0F) as u8)
+    }
+
+    /// Returns whether the method is an instance member.
+    #[inline]
+    pub const fn is_instance(&self) -> bool {
+        self.0 & 0x10 != 0
+    }
+
+    /// Returns whether the method is `dynamic`.
+    #[inline]
+    pub const fn is_dynamic(&self) -> bool {
+        self.0 & 0x20 != 0
+    }
+
+    /// Returns whether the method is `async`.
+    #[inline]
+    pub const fn is_async(&self) -> bool {
+        self.0 & 0x40 != 0
+    }
+}
+
+/// Describes a method in a class's vtable.
+///
+/// Refers to `TargetMethodDescriptor<InProcess>` in C++.
+#[repr(C)]
+#[derive(Debug)]
+pub struct MethodDescriptor {
+    /// Flags describing the method.
+    pub flags: MethodDescriptorFlags,
+
+    /// The implementation of the method, or null if it's abstract.
+    pub implementation: RelativeDirectPointer<OpaqueValue>,
+}
+
+/// The header of a class's override table.
+///
+/// Refers to `TargetOverrideTableHeader<InProcess>` in C++.
+#[repr(C)]
+#[derive(Clone, Copy, Debug)]
+pub struct OverrideTableHeader {
+    /// The number of overridden methods.
+    pub num_entries: u32,
+}
+
+/// Describes a method that a class overrides.
+///
+/// Refers to `TargetMethodOverrideDescriptor<InProcess>` in C++.
+#[repr(C)]
+#[derive(Debug)]
+pub struct MethodOverrideDescriptor {
+    /// The class that declares the overridden method.
+    pub class: RelativeIndirectablePointer<ContextDescriptor>,
+
+    /// The overridden method.
+    pub method: RelativeIndirectablePointer<MethodDescriptor>,
+
+    /// The implementation of the override.
+    pub implementation: RelativeDirectPointer<OpaqueValue>,
+}
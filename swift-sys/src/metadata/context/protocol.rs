@@ -0,0 +1,235 @@
+use super::{ContextDescriptor, GenericRequirementDescriptor};
+use crate::metadata::c_str;
+use crate::{
+    ptr::{RelativeDirectPointer, RelativeIndirectablePointerIntPair},
+    OpaqueValue,
+};
+use std::{ffi::CStr, ops::Deref, os::raw::c_char, slice};
+
+assert_eq_size!(ProtocolDescriptor, [u8; 24]);
+assert_eq_size!(ProtocolRequirement, [u8; 8]);
+assert_eq_size!(OpaqueTypeDescriptor, [u8; 8]);
+assert_eq_size!(ProtocolRecord, [u8; 4]);
+
+/// An entry of `swift5_protocols`.
+///
+/// Refers to `TargetProtocolRecord<InProcess>` in C++.
+#[repr(C)]
+#[derive(Debug)]
+pub struct ProtocolRecord {
+    /// The protocol descriptor, paired with a reserved bit.
+    pub protocol: RelativeIndirectablePointerIntPair<ProtocolDescriptor>,
+}
+
+impl ProtocolRecord {
+    /// Returns the protocol descriptor, or `None` if it's null.
+    #[inline]
+    pub fn protocol(&self) -> Option<&ProtocolDescriptor> {
+        unsafe { self.protocol.as_ref() }
+    }
+}
+
+/// Describes a protocol.
+///
+/// The descriptor is followed by a `GenericRequirementDescriptor` per
+/// requirement in the protocol's requirement signature, and then a
+/// `ProtocolRequirement` per requirement.
+///
+/// Refers to `TargetProtocolDescriptor<InProcess>` in C++.
+#[repr(C)]
+#[derive(Debug)]
+pub struct ProtocolDescriptor {
+    /// The base context descriptor.
+    pub base: ContextDescriptor,
+
+    /// The name of the protocol.
+    pub name: RelativeDirectPointer<c_char>,
+
+    /// The number of generic requirements in the requirement signature,
+    /// such as inherited protocols.
+    pub num_requirements_in_signature: u32,
+
+    /// The number of requirements, which have a witness in each witness
+    /// table.
+    pub num_requirements: u32,
+
+    /// The names of the associated types, separated by spaces, or null if
+    /// there are none.
+    pub associated_type_names: RelativeDirectPointer<c_char>,
+}
+
+impl Deref for ProtocolDescriptor {
+    type Target = ContextDescriptor;
+
+    #[inline]
+    fn deref(&self) -> &Self::Target {
+        &self.base
+    }
+}
+
+impl ProtocolDescriptor {
+    /// Returns the name of the protocol.
+    #[inline]
+    pub fn name(&self) -> &CStr {
+        unsafe { c_str(&self.name) }
+    }
+
+    /// Returns whether the protocol can only be adopted by classes.
+    #[inline]
+    pub fn is_class_constrained(&self) -> bool {
+        // The flag is 0 for class-constrained protocols.
+        self.base.flags.kind_specific_flags() & 0x1 == 0
+    }
+
+    /// Returns whether the protocol is resilient, so that requirements may be
+    /// added to it.
+    #[inline]
+    pub fn is_resilient(&self) -> bool {
+        self.base.flags.kind_specific_flags() & 0x2 != 0
+    }
+
+    /// Returns the names of the associated types, separated by spaces.
+    #[inline]
+    pub fn associated_type_names(&self) -> &CStr {
+        unsafe { c_str(&self.associated_type_names) }
+    }
+
+    /// Returns the requirements of the requirement signature.
+    #[inline]
+    pub fn requirement_signature(&self) -> &[GenericRequirementDescriptor] {
+        unsafe {
+            let start = (self as *const Self).add(1)
+                as *const GenericRequirementDescriptor;
+            let len = self.num_requirements_in_signature as usize;
+            slice::from_raw_parts(start, len)
+        }
+    }
+
+    /// Returns the requirements, in the order of their witnesses.
+    #[inline]
+    pub fn requirements(&self) -> &[ProtocolRequirement] {
+        let signature = self.requirement_signature();
+        unsafe {
+            let start = signature.as_ptr().add(signature.len())
+                as *const ProtocolRequirement;
+            slice::from_raw_parts(start, self.num_requirements as usize)
+        }
+    }
+}
+
+/// The kind of a protocol requirement.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+#[repr(transparent)]
+// enum class ProtocolRequirementFlags::Kind
+pub struct ProtocolRequirementKind(pub u8);
+
+impl ProtocolRequirementKind {
+    /// An inherited protocol.
+    pub const BASE_PROTOCOL: Self = Self(0);
+    /// A method.
+    pub const METHOD: Self = Self(1);
+    /// An initializer.
+    pub const INIT: Self = Self(2);
+    /// A property getter.
+    pub const GETTER: Self = Self(3);
+    /// A property setter.
+    pub const SETTER: Self = Self(4);
+    /// A `read` coroutine.
+    pub const READ_COROUTINE: Self = Self(5);
+    /// A `modify` coroutine.
+    pub const MODIFY_COROUTINE: Self = Self(6);
+    /// The access function of an associated type.
+    pub const ASSOCIATED_TYPE_ACCESS_FUNCTION: Self = Self(7);
+    /// The access function of an associated conformance.
+    pub const ASSOCIATED_CONFORMANCE_ACCESS_FUNCTION: Self = Self(8);
+}
+
+/// Flags describing a protocol requirement.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+#[repr(transparent)]
+// class ProtocolRequirementFlags
+pub struct ProtocolRequirementFlags(pub u32);
+
+impl ProtocolRequirementFlags {
+    /// Returns the kind of the requirement.
+    #[inline]
+    pub const fn kind(&self) -> ProtocolRequirementKind {
+        ProtocolRequirementKind((self.0 & 0x0F) as u8)
+    }
+
+    /// Returns whether the requirement is an instance member.
+    #[inline]
+    pub const fn is_instance(&self) -> bool {
+        self.0 & 0x10 != 0
+    }
+
+    /// Returns whether the requirement is `async`.
+    #[inline]
+    pub const fn is_async(&self) -> bool {
+        self.0 & 0x20 != 0
+    }
+}
+
+/// Describes a requirement of a protocol.
+///
+/// Refers to `TargetProtocolRequirement<InProcess>` in C++.
+#[repr(C)]
+#[derive(Debug)]
+pub struct ProtocolRequirement {
+    /// Flags describing the requirement.
+    pub flags: ProtocolRequirementFlags,
+
+    /// The default implementation of the requirement, or null if there is
+    /// none.
+    pub default_implementation: RelativeDirectPointer<OpaqueValue>,
+}
+
+/// Describes an opaque type, such as the return type of a function that
+/// returns `some P`.
+///
+/// The descriptor is followed by a generic context, and then a relative
+/// pointer to the mangled name of each underlying type argument.
+///
+/// Refers to `TargetOpaqueTypeDescriptor<InProcess>` in C++.
+#[repr(C)]
+#[derive(Debug)]
+pub struct OpaqueTypeDescriptor {
+    /// The base context descriptor.
+    pub base: ContextDescriptor,
+}
+
+impl Deref for OpaqueTypeDescriptor {
+    type Target = ContextDescriptor;
+
+    #[inline]
+    fn deref(&self) -> &Self::Target {
+        &self.base
+    }
+}
+
+impl OpaqueTypeDescriptor {
+    /// Returns the number of underlying type arguments.
+    #[inline]
+    pub fn num_underlying_type_arguments(&self) -> usize {
+        self.base.flags.kind_specific_flags() as usize
+    }
+
+    /// Returns the mangled names of the underlying type arguments, which are
+    /// expressed in terms of the opaque type's generic parameters.
+    #[inline]
+    pub fn underlying_type_arguments(
+        &self,
+    ) -> &[RelativeDirectPointer<c_char>] {
+        let start = match self.base.generic_context() {
+            Some(generic) => generic.end(),
+            None => (self as *const Self).wrapping_add(1) as *const u8,
+        };
+        let len = self.num_underlying_type_arguments();
+        unsafe {
+            slice::from_raw_parts(
+                start as *const RelativeDirectPointer<c_char>,
+                len,
+            )
+        }
+    }
+}
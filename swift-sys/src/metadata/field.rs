@@ -0,0 +1,167 @@
+//! Field descriptors, which describe the stored properties of structs and
+//! classes and the cases of enums.
+//!
+//! These are emitted into the `swift5_fieldmd` section and referred to by the
+//! [`fields`](../context/struct.TypeContextDescriptor.html#structfield.fields)
+//! of nominal type descriptors.
+
+// Based on:
+// include/swift/RemoteInspection/Records.h
+
+use super::{c_str, mangled_name};
+use crate::ptr::RelativeDirectPointer;
+use std::{ffi::CStr, mem, os::raw::c_char, slice};
+
+assert_eq_size!(FieldDescriptor, [u8; 16]);
+assert_eq_size!(FieldRecord, [u8; 12]);
+
+/// The kind of type that a field descriptor describes.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+#[repr(transparent)]
+// enum class FieldDescriptorKind : uint16_t
+pub struct FieldDescriptorKind(pub u16);
+
+impl FieldDescriptorKind {
+    /// A struct.
+    pub const STRUCT: Self = Self(0);
+    /// A class.
+    pub const CLASS: Self = Self(1);
+    /// An enum with at most one case with a payload.
+    pub const ENUM: Self = Self(2);
+    /// An enum with multiple cases with payloads.
+    pub const MULTI_PAYLOAD_ENUM: Self = Self(3);
+    /// A protocol.
+    pub const PROTOCOL: Self = Self(4);
+    /// A class-constrained protocol.
+    pub const CLASS_PROTOCOL: Self = Self(5);
+    /// An Objective-C protocol.
+    pub const OBJC_PROTOCOL: Self = Self(6);
+    /// An Objective-C class.
+    pub const OBJC_CLASS: Self = Self(7);
+
+    /// Returns whether `self` describes an enum, whose records are cases.
+    #[inline]
+    pub const fn is_enum(&self) -> bool {
+        self.0 == Self::ENUM.0 || self.0 == Self::MULTI_PAYLOAD_ENUM.0
+    }
+}
+
+/// Describes the fields of a type.
+///
+/// The descriptor is followed by a [`FieldRecord`](struct.FieldRecord.html)
+/// per field.
+///
+/// Refers to `FieldDescriptor` in C++.
+#[repr(C)]
+#[derive(Debug)]
+pub struct FieldDescriptor {
+    /// The mangled name of the type, or null if it has none.
+    pub mangled_type_name: RelativeDirectPointer<c_char>,
+
+    /// The mangled name of the superclass, or null if there is none.
+    pub superclass: RelativeDirectPointer<c_char>,
+
+    /// The kind of type.
+    pub kind: FieldDescriptorKind,
+
+    /// The size of each field record in bytes.
+    pub field_record_size: u16,
+
+    /// The number of fields.
+    pub num_fields: u32,
+}
+
+impl FieldDescriptor {
+    /// Returns the mangled name of the type, which may contain symbolic
+    /// references, or `None` if it has none.
+    #[inline]
+    pub fn mangled_type_name(&self) -> Option<&[u8]> {
+        unsafe { mangled_name(&self.mangled_type_name) }
+    }
+
+    /// Returns the mangled name of the superclass, which may contain symbolic
+    /// references, or `None` if there is none.
+    #[inline]
+    pub fn superclass(&self) -> Option<&[u8]> {
+        unsafe { mangled_name(&self.superclass) }
+    }
+
+    /// Returns the records of the fields, in declaration order.
+    ///
+    /// This is empty if the records aren't the size of a `FieldRecord`, as
+    /// would be the case with an unknown format.
+    #[inline]
+    pub fn records(&self) -> &[FieldRecord] {
+        if usize::from(self.field_record_size) != mem::size_of::<FieldRecord>()
+        {
+            return &[];
+        }
+        unsafe {
+            let start = (self as *const Self).add(1) as *const FieldRecord;
+            slice::from_raw_parts(start, self.num_fields as usize)
+        }
+    }
+}
+
+/// Flags describing a field.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+#[repr(transparent)]
+// class FieldRecordFlags
+pub struct FieldRecordFlags(pub u32);
+
+impl FieldRecordFlags {
+    /// Returns whether the field is an `indirect` enum case.
+    #[inline]
+    pub const fn is_indirect_case(&self) -> bool {
+        self.0 & 0x1 != 0
+    }
+
+    /// Returns whether the field is a `var` rather than a `let`.
+    #[inline]
+    pub const fn is_var(&self) -> bool {
+        self.0 & 0x2 != 0
+    }
+
+    /// Returns whether the field was synthesized by the compiler, such as
+    /// storage of a `lazy` property.
+    #[inline]
+    pub const fn is_artificial(&self) -> bool {
+        self.0 & 0x4 != 0
+    }
+}
+
+/// Describes a stored property or enum case.
+///
+/// Refers to `FieldRecord` in C++.
+#[repr(C)]
+#[derive(Debug)]
+pub struct FieldRecord {
+    /// Flags describing the field.
+    pub flags: FieldRecordFlags,
+
+    /// The mangled name of the field's type, or null if it's an enum case
+    /// without a payload.
+    pub mangled_type_name: RelativeDirectPointer<c_char>,
+
+    /// The name of the field.
+    pub field_name: RelativeDirectPointer<c_char>,
+}
+
+impl FieldRecord {
+    /// Returns the name of the field.
+    #[inline]
+    pub fn name(&self) -> &CStr {
+        unsafe { c_str(&self.field_name) }
+    }
+
+    /// Returns the mangled name of the field's type, which may contain
+    /// symbolic references, or `None` if it's an enum case without a
+    /// payload.
+    ///
+    /// Weak and unowned properties have types mangled as reference storage,
+    /// such as `Xw` for `weak`.
+    #[inline]
+    pub fn mangled_type_name(&self) -> Option<&[u8]> {
+        unsafe { mangled_name(&self.mangled_type_name) }
+    }
+}
@@ -9,7 +9,7 @@ pub struct MetadataKind(pub u32);
 
 const NON_TYPE_FLAG: u32 = 0x400;
 const NON_HEAP_FLAG: u32 = 0x200;
-const RUNTIME_PRIVATE_FLAG: u32 = 100;
+const RUNTIME_PRIVATE_FLAG: u32 = 0x100;
 
 // Documentation taken from `docs/ABI/TypeMetadata.rst`
 impl MetadataKind {
@@ -0,0 +1,129 @@
+//! Looking up type metadata by mangled name.
+
+// Based on:
+// include/swift/Runtime/Metadata.h
+// include/swift/ABI/Metadata.h
+// stdlib/public/runtime/MetadataLookup.cpp
+
+use super::context::ContextDescriptor;
+use crate::heap::Metadata;
+use std::os::raw::{c_char, c_void};
+
+/// Flags describing a [`GenericEnvironment`].
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+#[repr(transparent)]
+// class GenericEnvironmentFlags
+pub struct GenericEnvironmentFlags(pub u32);
+
+const NUM_GENERIC_PARAMETER_LEVELS_MASK: u32 = 0xFFF;
+const NUM_GENERIC_REQUIREMENTS_SHIFT: u32 = 12;
+const NUM_GENERIC_REQUIREMENTS_MASK: u32 = 0xFFFF << 12;
+
+impl GenericEnvironmentFlags {
+    /// Creates flags for an environment with `levels` levels of generic
+    /// parameters and `requirements` generic requirements.
+    #[inline]
+    pub const fn new(levels: u16, requirements: u16) -> Self {
+        let levels = levels as u32 & NUM_GENERIC_PARAMETER_LEVELS_MASK;
+        let requirements = (requirements as u32)
+            << NUM_GENERIC_REQUIREMENTS_SHIFT
+            & NUM_GENERIC_REQUIREMENTS_MASK;
+        Self(levels | requirements)
+    }
+
+    /// Returns the number of levels of generic parameters, where each nested
+    /// generic context adds a level.
+    #[inline]
+    pub const fn num_generic_parameter_levels(&self) -> u32 {
+        self.0 & NUM_GENERIC_PARAMETER_LEVELS_MASK
+    }
+
+    /// Returns the number of generic requirements across all levels.
+    #[inline]
+    pub const fn num_generic_requirements(&self) -> u32 {
+        (self.0 & NUM_GENERIC_REQUIREMENTS_MASK)
+            >> NUM_GENERIC_REQUIREMENTS_SHIFT
+    }
+}
+
+/// The generic parameters and requirements of a generic context.
+///
+/// This header is followed by trailing objects:
+///
+/// 1. A `u16` per level with the number of generic parameters up to and
+///    including that level.
+///
+/// 2. A `u8` `GenericParamDescriptor` per generic parameter, where `0x80`
+///    marks a type parameter that's passed as a key argument.
+///
+/// 3. A `GenericRequirementDescriptor` per generic requirement, aligned to 4
+///    bytes.
+///
+/// Refers to `TargetGenericEnvironment<InProcess>` in C++.
+#[repr(C)]
+#[derive(Clone, Copy, Debug)]
+pub struct GenericEnvironment {
+    /// The number of parameter levels and requirements.
+    pub flags: GenericEnvironmentFlags,
+}
+
+// TODO: Support the Swift calling convention in rustc
+// See https://github.com/rust-lang/rust/pull/64582
+extern "C" {
+    /// Retrieves the type metadata described by the mangled type name
+    /// `typeNameStart`, which doesn't include a symbol prefix such as `$s`.
+    /// Returns null if the type can't be found.
+    ///
+    /// # Parameters
+    ///
+    /// - `typeNameStart`: the mangled type name, which may contain symbolic
+    ///   references relative to its own address.
+    ///
+    /// - `typeNameLength`: the length of the mangled type name in bytes.
+    ///
+    /// - `context`: the context in which generic parameters referenced by the
+    ///   name are declared, or null if there are none.
+    ///
+    /// - `genericArgs`: the generic arguments of `context`, in the order of its
+    ///   key arguments, or null if there are none.
+    // const Metadata * _Nullable
+    // swift_getTypeByMangledNameInContext(
+    //                     const char *typeNameStart,
+    //                     size_t typeNameLength,
+    //                     const TargetContextDescriptor<InProcess> *context,
+    //                     const void * const *genericArgs);
+    pub fn swift_getTypeByMangledNameInContext(
+        typeNameStart: *const c_char,
+        typeNameLength: usize,
+        context: *const ContextDescriptor,
+        genericArgs: *const *const c_void,
+    ) -> *const Metadata;
+
+    /// Retrieves the type metadata described by the mangled type name
+    /// `typeNameStart`, whose generic parameters are declared by `environment`.
+    /// Returns null if the type can't be found.
+    ///
+    /// # Parameters
+    ///
+    /// - `typeNameStart`: the mangled type name, which may contain symbolic
+    ///   references relative to its own address.
+    ///
+    /// - `typeNameLength`: the length of the mangled type name in bytes.
+    ///
+    /// - `environment`: the generic environment in which generic parameters
+    ///   referenced by the name are declared.
+    ///
+    /// - `genericArgs`: the key arguments of `environment`, in order.
+    // const Metadata * _Nullable
+    // swift_getTypeByMangledNameInEnvironment(
+    //                     const char *typeNameStart,
+    //                     size_t typeNameLength,
+    //                     const TargetGenericEnvironment<InProcess> *environment,
+    //                     const void * const *genericArgs);
+    pub fn swift_getTypeByMangledNameInEnvironment(
+        typeNameStart: *const c_char,
+        typeNameLength: usize,
+        environment: *const GenericEnvironment,
+        genericArgs: *const *const c_void,
+    ) -> *const Metadata;
+}
@@ -1,6 +1,58 @@
 //! Metadata records.
 
+use crate::ptr::RelativeDirectPointer;
+use std::{ffi::CStr, mem, os::raw::c_char, slice};
+
 mod kind;
+mod types;
 pub use kind::MetadataKind;
+pub use types::*;
 
+pub mod conformance;
+pub mod context;
+pub mod field;
+pub mod lookup;
+pub mod reflection;
 pub mod value_witness;
+
+/// Returns the C string targeted by `ptr`, or an empty string if it's null.
+///
+/// # Safety
+///
+/// `ptr` must be at its original location.
+#[inline]
+pub(crate) unsafe fn c_str(ptr: &RelativeDirectPointer<c_char>) -> &CStr {
+    let ptr = ptr.get();
+    if ptr.is_null() {
+        Default::default()
+    } else {
+        CStr::from_ptr(ptr)
+    }
+}
+
+/// Returns the mangled name targeted by `ptr`, or `None` if it's null.
+///
+/// Unlike a C string, a mangled name may contain symbolic references: a
+/// control character followed by a relative offset or an absolute pointer,
+/// either of which may contain zero bytes.
+///
+/// # Safety
+///
+/// `ptr` must be at its original location.
+pub(crate) unsafe fn mangled_name(
+    ptr: &RelativeDirectPointer<c_char>,
+) -> Option<&[u8]> {
+    let start = ptr.get() as *const u8;
+    if start.is_null() {
+        return None;
+    }
+    let mut len = 0;
+    loop {
+        match *start.add(len) {
+            0 => return Some(slice::from_raw_parts(start, len)),
+            0x01..=0x17 => len += 1 + mem::size_of::<i32>(),
+            0x18..=0x1F => len += 1 + mem::size_of::<usize>(),
+            _ => len += 1,
+        }
+    }
+}
@@ -0,0 +1,266 @@
+//! Reflection records of the `swift5_assocty`, `swift5_builtin` and
+//! `swift5_capture` sections, which describe types to tools such as
+//! debuggers.
+//!
+//! Unlike [field descriptors](../field/index.html), these aren't referred to
+//! by other metadata and are only found by walking their sections.
+
+// Based on:
+// include/swift/RemoteInspection/Records.h
+
+use super::{c_str, mangled_name};
+use crate::ptr::RelativeDirectPointer;
+use std::{ffi::CStr, mem, os::raw::c_char, slice};
+
+assert_eq_size!(AssociatedTypeDescriptor, [u8; 16]);
+assert_eq_size!(AssociatedTypeRecord, [u8; 8]);
+assert_eq_size!(BuiltinTypeDescriptor, [u8; 20]);
+assert_eq_size!(CaptureDescriptor, [u8; 12]);
+assert_eq_size!(CaptureTypeRecord, [u8; 4]);
+assert_eq_size!(MetadataSourceRecord, [u8; 8]);
+
+/// Describes the types that satisfy the associated type requirements of a
+/// conformance.
+///
+/// The descriptor is followed by an
+/// [`AssociatedTypeRecord`](struct.AssociatedTypeRecord.html) per associated
+/// type.
+///
+/// Refers to `AssociatedTypeDescriptor` in C++.
+#[repr(C)]
+#[derive(Debug)]
+pub struct AssociatedTypeDescriptor {
+    /// The mangled name of the conforming type.
+    pub conforming_type_name: RelativeDirectPointer<c_char>,
+
+    /// The mangled name of the protocol.
+    pub protocol_type_name: RelativeDirectPointer<c_char>,
+
+    /// The number of associated types.
+    pub num_associated_types: u32,
+
+    /// The size of each associated type record in bytes.
+    pub associated_type_record_size: u32,
+}
+
+impl AssociatedTypeDescriptor {
+    /// Returns the mangled name of the conforming type, which may contain
+    /// symbolic references.
+    #[inline]
+    pub fn conforming_type_name(&self) -> Option<&[u8]> {
+        unsafe { mangled_name(&self.conforming_type_name) }
+    }
+
+    /// Returns the mangled name of the protocol, which may contain symbolic
+    /// references.
+    #[inline]
+    pub fn protocol_type_name(&self) -> Option<&[u8]> {
+        unsafe { mangled_name(&self.protocol_type_name) }
+    }
+
+    /// Returns the records of the associated types.
+    ///
+    /// This is empty if the records aren't the size of an
+    /// `AssociatedTypeRecord`, as would be the case with an unknown format.
+    #[inline]
+    pub fn records(&self) -> &[AssociatedTypeRecord] {
+        if self.associated_type_record_size as usize
+            != mem::size_of::<AssociatedTypeRecord>()
+        {
+            return &[];
+        }
+        unsafe {
+            let start =
+                (self as *const Self).add(1) as *const AssociatedTypeRecord;
+            slice::from_raw_parts(start, self.num_associated_types as usize)
+        }
+    }
+
+    /// Returns the size of the descriptor and its records in bytes, which is
+    /// the offset of the next descriptor in its section.
+    #[inline]
+    pub fn size(&self) -> usize {
+        mem::size_of::<Self>()
+            + self.num_associated_types as usize
+                * self.associated_type_record_size as usize
+    }
+}
+
+/// Describes the type that satisfies an associated type requirement.
+///
+/// Refers to `AssociatedTypeRecord` in C++.
+#[repr(C)]
+#[derive(Debug)]
+pub struct AssociatedTypeRecord {
+    /// The name of the associated type.
+    pub name: RelativeDirectPointer<c_char>,
+
+    /// The mangled name of the type that satisfies the requirement.
+    pub substituted_type_name: RelativeDirectPointer<c_char>,
+}
+
+impl AssociatedTypeRecord {
+    /// Returns the name of the associated type.
+    #[inline]
+    pub fn name(&self) -> &CStr {
+        unsafe { c_str(&self.name) }
+    }
+
+    /// Returns the mangled name of the type that satisfies the requirement,
+    /// which may contain symbolic references.
+    #[inline]
+    pub fn substituted_type_name(&self) -> Option<&[u8]> {
+        unsafe { mangled_name(&self.substituted_type_name) }
+    }
+}
+
+/// Describes the layout of a builtin type, or of a type whose layout is
+/// opaque to reflection, such as an imported C struct.
+///
+/// Refers to `BuiltinTypeDescriptor` in C++.
+#[repr(C)]
+#[derive(Debug)]
+pub struct BuiltinTypeDescriptor {
+    /// The mangled name of the type.
+    pub type_name: RelativeDirectPointer<c_char>,
+
+    /// The size of the type in bytes.
+    pub size: u32,
+
+    /// The alignment of the type in bytes in the low 16 bits, and whether
+    /// the type is bitwise-takable in bit 16.
+    pub alignment_and_flags: u32,
+
+    /// The distance in bytes between consecutive values of the type.
+    pub stride: u32,
+
+    /// The number of invalid bit patterns that enums may use to store
+    /// their cases.
+    pub num_extra_inhabitants: u32,
+}
+
+impl BuiltinTypeDescriptor {
+    /// Returns the mangled name of the type, which may contain symbolic
+    /// references.
+    #[inline]
+    pub fn type_name(&self) -> Option<&[u8]> {
+        unsafe { mangled_name(&self.type_name) }
+    }
+
+    /// Returns the alignment of the type in bytes.
+    #[inline]
+    pub fn alignment(&self) -> u32 {
+        self.alignment_and_flags & 0xFFFF
+    }
+
+    /// Returns whether values of the type can be moved with a bitwise copy.
+    #[inline]
+    pub fn is_bitwise_takable(&self) -> bool {
+        self.alignment_and_flags & (1 << 16) != 0
+    }
+}
+
+/// Describes the captures of a closure context.
+///
+/// The descriptor is followed by a
+/// [`CaptureTypeRecord`](struct.CaptureTypeRecord.html) per captured value
+/// and then a [`MetadataSourceRecord`](struct.MetadataSourceRecord.html)
+/// per metadata source.
+///
+/// Refers to `CaptureDescriptor` in C++.
+#[repr(C)]
+#[derive(Debug)]
+pub struct CaptureDescriptor {
+    /// The number of captured values.
+    pub num_capture_types: u32,
+
+    /// The number of sources of generic arguments' metadata.
+    pub num_metadata_sources: u32,
+
+    /// The number of generic arguments bound in the context, whose metadata
+    /// is stored before the captured values.
+    pub num_bindings: u32,
+}
+
+impl CaptureDescriptor {
+    /// Returns the records of the captured values, in the order they're laid
+    /// out in the context.
+    #[inline]
+    pub fn capture_types(&self) -> &[CaptureTypeRecord] {
+        unsafe {
+            let start =
+                (self as *const Self).add(1) as *const CaptureTypeRecord;
+            slice::from_raw_parts(start, self.num_capture_types as usize)
+        }
+    }
+
+    /// Returns the records of the sources of generic arguments' metadata.
+    #[inline]
+    pub fn metadata_sources(&self) -> &[MetadataSourceRecord] {
+        let captures = self.capture_types();
+        unsafe {
+            let start = captures.as_ptr().add(captures.len())
+                as *const MetadataSourceRecord;
+            slice::from_raw_parts(start, self.num_metadata_sources as usize)
+        }
+    }
+
+    /// Returns the size of the descriptor and its records in bytes, which is
+    /// the offset of the next descriptor in its section.
+    #[inline]
+    pub fn size(&self) -> usize {
+        mem::size_of::<Self>()
+            + self.num_capture_types as usize
+                * mem::size_of::<CaptureTypeRecord>()
+            + self.num_metadata_sources as usize
+                * mem::size_of::<MetadataSourceRecord>()
+    }
+}
+
+/// Describes a value captured by a closure.
+///
+/// Refers to `CaptureTypeRecord` in C++.
+#[repr(C)]
+#[derive(Debug)]
+pub struct CaptureTypeRecord {
+    /// The mangled name of the captured value's type.
+    pub mangled_type_name: RelativeDirectPointer<c_char>,
+}
+
+impl CaptureTypeRecord {
+    /// Returns the mangled name of the captured value's type, which may
+    /// contain symbolic references.
+    #[inline]
+    pub fn mangled_type_name(&self) -> Option<&[u8]> {
+        unsafe { mangled_name(&self.mangled_type_name) }
+    }
+}
+
+/// Describes where a closure finds the metadata of a generic argument.
+///
+/// Refers to `MetadataSourceRecord` in C++.
+#[repr(C)]
+#[derive(Debug)]
+pub struct MetadataSourceRecord {
+    /// The mangled name of the generic parameter.
+    pub mangled_type_name: RelativeDirectPointer<c_char>,
+
+    /// The mangled source of the metadata, such as a captured value or the
+    /// metadata of another argument.
+    pub mangled_metadata_source: RelativeDirectPointer<c_char>,
+}
+
+impl MetadataSourceRecord {
+    /// Returns the mangled name of the generic parameter, which may contain
+    /// symbolic references.
+    #[inline]
+    pub fn mangled_type_name(&self) -> Option<&[u8]> {
+        unsafe { mangled_name(&self.mangled_type_name) }
+    }
+
+    /// Returns the mangled source of the metadata.
+    #[inline]
+    pub fn mangled_metadata_source(&self) -> Option<&[u8]> {
+        unsafe { mangled_name(&self.mangled_metadata_source) }
+    }
+}
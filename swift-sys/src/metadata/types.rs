@@ -0,0 +1,1101 @@
+// Based on:
+// include/swift/ABI/Metadata.h
+// include/swift/ABI/MetadataValues.h
+
+use super::{
+    context::{
+        ClassDescriptor, EnumDescriptor, ProtocolDescriptor, StructDescriptor,
+        TypeContextDescriptor,
+    },
+    value_witness::{EnumValueWitnessTable, ValueWitnessTable},
+    MetadataKind,
+};
+use crate::heap::HeapObject;
+use std::{ffi::CStr, ops::Deref, os::raw::c_char, slice};
+
+/// The common header of all type metadata.
+///
+/// Metadata is referenced by a pointer to this header, its address point.
+/// It's preceded by a pointer to the type's value witness table, as in
+/// [`FullMetadata`](struct.FullMetadata.html), and followed by fields that
+/// depend on its [`kind`](#method.kind).
+///
+/// Metadata can't be created or copied in Rust, only referenced where the
+/// runtime or compiler emitted it. Its fields are private, since its methods
+/// rely on them to read the memory around it.
+///
+/// Refers to `TargetMetadata<InProcess>` in C++.
+#[repr(C)]
+#[derive(Debug)]
+pub struct Metadata {
+    /// The kind of the metadata, or the isa pointer of an Objective-C
+    /// compatible class.
+    kind: usize,
+}
+
+impl Metadata {
+    /// Returns the kind of the metadata.
+    ///
+    /// Class metadata that starts with an isa pointer is of kind
+    /// [`CLASS`](struct.MetadataKind.html#associatedconstant.CLASS).
+    #[inline]
+    pub fn kind(&self) -> MetadataKind {
+        if self.kind > MetadataKind::LAST_ENUMERATED.0 as usize {
+            MetadataKind::CLASS
+        } else {
+            MetadataKind(self.kind as u32)
+        }
+    }
+
+    /// Returns the value witness table of the type, which is stored just
+    /// before the address point.
+    #[inline]
+    pub fn value_witnesses(&self) -> &ValueWitnessTable {
+        unsafe {
+            let full = self as *const Self as *const TypeMetadataHeader;
+            &*(*full.sub(1)).value_witnesses
+        }
+    }
+
+    /// Returns the value witness table of the type with its enum witnesses,
+    /// or `None` if the type isn't an enum.
+    #[inline]
+    pub fn enum_value_witnesses(&self) -> Option<&EnumValueWitnessTable> {
+        let witnesses = self.value_witnesses();
+        if witnesses.flags.has_enum_witnesses() {
+            let witnesses = witnesses as *const ValueWitnessTable;
+            Some(unsafe { &*(witnesses as *const EnumValueWitnessTable) })
+        } else {
+            None
+        }
+    }
+
+    /// Returns `self` with the header that precedes its address point.
+    #[inline]
+    pub fn full(&self) -> &FullMetadata<Self> {
+        unsafe {
+            let header = (self as *const Self as *const TypeMetadataHeader)
+                .sub(1) as *const FullMetadata<Self>;
+            &*header
+        }
+    }
+
+    /// Returns the descriptor of the type if it's a struct, enum or class.
+    ///
+    /// This is the same as `swift_getTypeContextDescriptor`.
+    #[inline]
+    pub fn type_descriptor(&self) -> Option<&TypeContextDescriptor> {
+        if let Some(class) = self.as_class() {
+            class.description().map(|class| &class.base)
+        } else if let Some(value) = self.as_enum() {
+            value.description().map(|value| &value.base)
+        } else if let Some(value) = self.as_struct() {
+            value.description().map(|value| &value.base)
+        } else if let Some(class) = self.as_foreign_class() {
+            class.description().map(|class| &class.base)
+        } else {
+            None
+        }
+    }
+
+    /// Returns `self` as struct metadata if it is one.
+    #[inline]
+    pub fn as_struct(&self) -> Option<&StructMetadata> {
+        self.cast_if(self.kind() == MetadataKind::STRUCT)
+    }
+
+    /// Returns `self` as enum metadata if it's an enum or `Optional`.
+    #[inline]
+    pub fn as_enum(&self) -> Option<&EnumMetadata> {
+        let kind = self.kind();
+        self.cast_if(
+            kind == MetadataKind::ENUM || kind == MetadataKind::OPTIONAL,
+        )
+    }
+
+    /// Returns `self` as class metadata if it's a Swift class.
+    #[inline]
+    pub fn as_class(&self) -> Option<&ClassMetadata> {
+        let class: &ClassMetadata =
+            self.cast_if(self.kind() == MetadataKind::CLASS)?;
+        if class.is_type_metadata() {
+            Some(class)
+        } else {
+            None
+        }
+    }
+
+    /// Returns `self` as foreign class metadata if it is one.
+    #[inline]
+    pub fn as_foreign_class(&self) -> Option<&ForeignClassMetadata> {
+        self.cast_if(self.kind() == MetadataKind::FOREIGN_CLASS)
+    }
+
+    /// Returns `self` as tuple metadata if it is one.
+    #[inline]
+    pub fn as_tuple(&self) -> Option<&TupleTypeMetadata> {
+        self.cast_if(self.kind() == MetadataKind::TUPLE)
+    }
+
+    /// Returns `self` as function metadata if it is one.
+    #[inline]
+    pub fn as_function(&self) -> Option<&FunctionTypeMetadata> {
+        self.cast_if(self.kind() == MetadataKind::FUNCTION)
+    }
+
+    /// Returns `self` as existential metadata if it is one.
+    #[inline]
+    pub fn as_existential(&self) -> Option<&ExistentialTypeMetadata> {
+        self.cast_if(self.kind() == MetadataKind::EXISTENTIAL)
+    }
+
+    /// Returns `self` as metatype metadata if it is one.
+    #[inline]
+    pub fn as_metatype(&self) -> Option<&MetatypeMetadata> {
+        self.cast_if(self.kind() == MetadataKind::METATYPE)
+    }
+
+    /// Returns `self` as existential metatype metadata if it is one.
+    #[inline]
+    pub fn as_existential_metatype(
+        &self,
+    ) -> Option<&ExistentialMetatypeMetadata> {
+        self.cast_if(self.kind() == MetadataKind::EXISTENTIAL_METATYPE)
+    }
+
+    #[inline]
+    fn cast_if<T>(&self, cond: bool) -> Option<&T> {
+        if cond {
+            Some(unsafe { &*(self as *const Self as *const T) })
+        } else {
+            None
+        }
+    }
+
+    /// Returns the word at `offset` words from the address point.
+    #[inline]
+    unsafe fn word_at<T>(&self, offset: usize) -> *const T {
+        (self as *const Self as *const usize).add(offset) as *const T
+    }
+}
+
+/// The header that precedes the address point of type metadata.
+///
+/// Refers to `TargetTypeMetadataHeader<InProcess>` in C++.
+#[repr(C)]
+#[derive(Debug)]
+pub struct TypeMetadataHeader {
+    /// A pointer to the value witness table of the type.
+    pub value_witnesses: *const ValueWitnessTable,
+}
+
+/// The header that precedes the address point of heap metadata.
+///
+/// Refers to `TargetHeapMetadataHeader<InProcess>` in C++.
+#[repr(C)]
+#[derive(Debug)]
+pub struct HeapMetadataHeader {
+    /// Destroys an object of the type, which is called when its strong
+    /// reference count reaches zero.
+    pub destroy: Option<unsafe extern "C" fn(object: *mut HeapObject)>,
+
+    /// The header shared with other type metadata.
+    pub base: TypeMetadataHeader,
+}
+
+/// Metadata along with the header that precedes its address point.
+///
+/// Refers to `TargetFullMetadata<T>` in C++.
+#[repr(C)]
+#[derive(Debug)]
+pub struct FullMetadata<T> {
+    /// The header, which is before the address point.
+    pub header: TypeMetadataHeader,
+
+    /// The metadata at the address point.
+    pub metadata: T,
+}
+
+/// The metadata of a type whose values are allocated on the heap, such as a
+/// class.
+///
+/// Refers to `TargetHeapMetadata<InProcess>` in C++.
+#[repr(C)]
+#[derive(Debug)]
+pub struct HeapMetadata {
+    /// The base metadata.
+    base: Metadata,
+}
+
+impl Deref for HeapMetadata {
+    type Target = Metadata;
+
+    #[inline]
+    fn deref(&self) -> &Self::Target {
+        &self.base
+    }
+}
+
+impl HeapMetadata {
+    /// Returns the header that precedes the address point, which includes
+    /// the function that destroys objects.
+    #[inline]
+    pub fn header(&self) -> &HeapMetadataHeader {
+        unsafe { &*(self as *const Self as *const HeapMetadataHeader).sub(1) }
+    }
+}
+
+/// The metadata of a struct.
+///
+/// Refers to `TargetStructMetadata<InProcess>` in C++.
+#[repr(C)]
+#[derive(Debug)]
+pub struct StructMetadata {
+    /// The base metadata.
+    base: Metadata,
+
+    /// The descriptor of the struct.
+    description: *const StructDescriptor,
+}
+
+impl Deref for StructMetadata {
+    type Target = Metadata;
+
+    #[inline]
+    fn deref(&self) -> &Self::Target {
+        &self.base
+    }
+}
+
+impl StructMetadata {
+    /// Returns the descriptor of the struct.
+    #[inline]
+    pub fn description(&self) -> Option<&StructDescriptor> {
+        unsafe { self.description.as_ref() }
+    }
+
+    /// Returns the offsets in bytes of the struct's stored properties, in
+    /// declaration order.
+    #[inline]
+    pub fn field_offsets(&self) -> &[u32] {
+        let description = match self.description() {
+            Some(description) => description,
+            None => return &[],
+        };
+        let offset = description.field_offset_vector_offset as usize;
+        if offset == 0 {
+            return &[];
+        }
+        unsafe {
+            slice::from_raw_parts(
+                self.word_at(offset),
+                description.num_fields as usize,
+            )
+        }
+    }
+
+    /// Returns the metadata of the struct's generic arguments.
+    #[inline]
+    pub fn generic_args(&self) -> &[&Metadata] {
+        unsafe { value_generic_args(self, self.description().map(|d| &d.base)) }
+    }
+}
+
+/// The metadata of an enum or `Optional`.
+///
+/// Refers to `TargetEnumMetadata<InProcess>` in C++.
+#[repr(C)]
+#[derive(Debug)]
+pub struct EnumMetadata {
+    /// The base metadata.
+    base: Metadata,
+
+    /// The descriptor of the enum.
+    description: *const EnumDescriptor,
+}
+
+impl Deref for EnumMetadata {
+    type Target = Metadata;
+
+    #[inline]
+    fn deref(&self) -> &Self::Target {
+        &self.base
+    }
+}
+
+impl EnumMetadata {
+    /// Returns the descriptor of the enum.
+    #[inline]
+    pub fn description(&self) -> Option<&EnumDescriptor> {
+        unsafe { self.description.as_ref() }
+    }
+
+    /// Returns the size in bytes of the largest payload, or `None` if it
+    /// isn't stored in the metadata.
+    #[inline]
+    pub fn payload_size(&self) -> Option<usize> {
+        let offset = self.description()?.payload_size_offset() as usize;
+        if offset == 0 {
+            None
+        } else {
+            Some(unsafe { *self.word_at::<usize>(offset) })
+        }
+    }
+
+    /// Returns the metadata of the enum's generic arguments, such as the
+    /// `Wrapped` type of an `Optional`.
+    #[inline]
+    pub fn generic_args(&self) -> &[&Metadata] {
+        unsafe { value_generic_args(self, self.description().map(|d| &d.base)) }
+    }
+}
+
+/// Returns the generic arguments of struct or enum metadata, which follow
+/// the descriptor.
+unsafe fn value_generic_args<'a>(
+    metadata: &'a Metadata,
+    description: Option<&TypeContextDescriptor>,
+) -> &'a [&'a Metadata] {
+    let params = match description.and_then(|d| d.generic_context()) {
+        Some(generic_context) => generic_context.params(),
+        None => return &[],
+    };
+    let count = params.iter().filter(|p| p.has_key_argument()).count();
+    slice::from_raw_parts(metadata.word_at(2), count)
+}
+
+/// Flags describing a Swift class.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+#[repr(transparent)]
+// enum class ClassFlags : uint32_t
+pub struct ClassFlags(pub u32);
+
+impl ClassFlags {
+    /// Whether the class was compiled by the Swift 1 compiler.
+    pub const IS_SWIFT_PRE_STABLE_ABI: u32 = 0x1;
+
+    /// Whether the class uses Swift reference counting.
+    pub const USES_SWIFT_REFCOUNTING: u32 = 0x2;
+
+    /// Whether the class has a custom name set by `@objc`.
+    pub const HAS_CUSTOM_OBJC_NAME: u32 = 0x4;
+
+    /// Whether the class is a generic class whose metadata was specialized
+    /// at compile time.
+    pub const IS_STATICALLY_SPECIALIZED: u32 = 0x8;
+
+    /// Returns whether the class uses Swift reference counting.
+    #[inline]
+    pub const fn uses_swift_refcounting(&self) -> bool {
+        self.0 & Self::USES_SWIFT_REFCOUNTING != 0
+    }
+}
+
+/// The metadata of a Swift class.
+///
+/// On platforms with Objective-C interop, this is laid out as an
+/// Objective-C class.
+///
+/// Refers to `TargetClassMetadata<InProcess>` in C++.
+#[repr(C)]
+#[derive(Debug)]
+pub struct ClassMetadata {
+    /// The base heap metadata.
+    base: HeapMetadata,
+
+    /// The metadata of the superclass, or null for a root class.
+    superclass: *const ClassMetadata,
+
+    /// The Objective-C method cache.
+    #[cfg(target_vendor = "apple")]
+    cache_data: [*mut std::os::raw::c_void; 2],
+
+    /// The Objective-C class data, whose low bits mark Swift classes.
+    #[cfg(target_vendor = "apple")]
+    data: usize,
+
+    /// Flags describing the class.
+    flags: ClassFlags,
+
+    /// The offset of the address point of objects from their allocation.
+    instance_address_point: u32,
+
+    /// The size in bytes of objects, including the object header.
+    instance_size: u32,
+
+    /// The alignment mask of objects.
+    instance_align_mask: u16,
+
+    /// Reserved for the runtime.
+    reserved: u16,
+
+    /// The total size in bytes of the class metadata.
+    class_size: u32,
+
+    /// The offset of the address point within the class metadata.
+    class_address_point: u32,
+
+    /// The descriptor of the class.
+    description: *const ClassDescriptor,
+
+    /// Destroys the stored properties of an instance of a class with an
+    /// ivar destroyer.
+    ivar_destroyer: Option<unsafe extern "C" fn(object: *mut HeapObject)>,
+}
+
+impl Deref for ClassMetadata {
+    type Target = Metadata;
+
+    #[inline]
+    fn deref(&self) -> &Self::Target {
+        &self.base
+    }
+}
+
+impl ClassMetadata {
+    /// Returns whether this is the metadata of a Swift class, rather than a
+    /// pure Objective-C class.
+    #[inline]
+    pub fn is_type_metadata(&self) -> bool {
+        #[cfg(target_vendor = "apple")]
+        {
+            // Either of the low bits may be the Swift bit, depending on the
+            // Objective-C runtime.
+            self.data & 0b11 != 0
+        }
+        #[cfg(not(target_vendor = "apple"))]
+        {
+            true
+        }
+    }
+
+    /// Returns the base heap metadata.
+    #[inline]
+    pub fn heap_metadata(&self) -> &HeapMetadata {
+        &self.base
+    }
+
+    /// Returns the metadata of the superclass, or `None` for a root class.
+    #[inline]
+    pub fn superclass(&self) -> Option<&ClassMetadata> {
+        unsafe { self.superclass.as_ref() }
+    }
+
+    /// Returns the flags describing the class.
+    #[inline]
+    pub fn flags(&self) -> ClassFlags {
+        self.flags
+    }
+
+    /// Returns the offset of the address point of objects from their
+    /// allocation.
+    #[inline]
+    pub fn instance_address_point(&self) -> u32 {
+        self.instance_address_point
+    }
+
+    /// Returns the size in bytes of objects, including the object header.
+    #[inline]
+    pub fn instance_size(&self) -> u32 {
+        self.instance_size
+    }
+
+    /// Returns the alignment mask of objects.
+    #[inline]
+    pub fn instance_align_mask(&self) -> u16 {
+        self.instance_align_mask
+    }
+
+    /// Returns the total size in bytes of the class metadata.
+    #[inline]
+    pub fn class_size(&self) -> u32 {
+        self.class_size
+    }
+
+    /// Returns the offset of the address point within the class metadata.
+    #[inline]
+    pub fn class_address_point(&self) -> u32 {
+        self.class_address_point
+    }
+
+    /// Returns the function that destroys the stored properties of an
+    /// instance, if the class has one.
+    #[inline]
+    pub fn ivar_destroyer(
+        &self,
+    ) -> Option<unsafe extern "C" fn(object: *mut HeapObject)> {
+        self.ivar_destroyer
+    }
+
+    /// Returns the descriptor of the class.
+    #[inline]
+    pub fn description(&self) -> Option<&ClassDescriptor> {
+        unsafe { self.description.as_ref() }
+    }
+
+    /// Returns the offsets in bytes of the stored properties declared by the
+    /// class, excluding those of its superclasses.
+    ///
+    /// Returns `None` if the class has a resilient superclass, since the
+    /// offset of the field offset vector is then only known at runtime.
+    #[inline]
+    pub fn field_offsets(&self) -> Option<&[usize]> {
+        let description = self.description()?;
+        if description.type_flags().class_has_resilient_superclass() {
+            return None;
+        }
+        let offset = description.field_offset_vector_offset as usize;
+        if offset == 0 {
+            return Some(&[]);
+        }
+        Some(unsafe {
+            slice::from_raw_parts(
+                self.word_at(offset),
+                description.num_fields as usize,
+            )
+        })
+    }
+}
+
+/// The metadata of a foreign class, such as a Core Foundation class.
+///
+/// Refers to `TargetForeignClassMetadata<InProcess>` in C++.
+#[repr(C)]
+#[derive(Debug)]
+pub struct ForeignClassMetadata {
+    /// The base metadata.
+    base: Metadata,
+
+    /// The descriptor of the class.
+    description: *const ClassDescriptor,
+
+    /// The metadata of the superclass, or null for a root class.
+    superclass: *const ForeignClassMetadata,
+
+    /// Reserved for the runtime.
+    reserved: usize,
+}
+
+impl Deref for ForeignClassMetadata {
+    type Target = Metadata;
+
+    #[inline]
+    fn deref(&self) -> &Self::Target {
+        &self.base
+    }
+}
+
+impl ForeignClassMetadata {
+    /// Returns the descriptor of the class.
+    #[inline]
+    pub fn description(&self) -> Option<&ClassDescriptor> {
+        unsafe { self.description.as_ref() }
+    }
+
+    /// Returns the metadata of the superclass, or `None` for a root class.
+    #[inline]
+    pub fn superclass(&self) -> Option<&ForeignClassMetadata> {
+        unsafe { self.superclass.as_ref() }
+    }
+}
+
+/// The metadata of a tuple type.
+///
+/// Refers to `TargetTupleTypeMetadata<InProcess>` in C++.
+#[repr(C)]
+#[derive(Debug)]
+pub struct TupleTypeMetadata {
+    /// The base metadata.
+    base: Metadata,
+
+    /// The number of elements.
+    num_elements: usize,
+
+    /// The labels of the elements, each followed by a space, or null if no
+    /// element is labeled.
+    labels: *const c_char,
+}
+
+/// An element of a tuple type.
+///
+/// Refers to `TargetTupleTypeMetadata<InProcess>::Element` in C++.
+#[repr(C)]
+#[derive(Debug)]
+pub struct TupleElement {
+    /// The metadata of the element's type.
+    type_: *const Metadata,
+
+    /// The offset in bytes of the element within the tuple.
+    offset: usize,
+}
+
+impl TupleElement {
+    /// Returns the metadata of the element's type.
+    #[inline]
+    pub fn metadata(&self) -> &Metadata {
+        unsafe { &*self.type_ }
+    }
+
+    /// Returns the offset in bytes of the element within the tuple.
+    #[inline]
+    pub fn offset(&self) -> usize {
+        self.offset
+    }
+}
+
+impl Deref for TupleTypeMetadata {
+    type Target = Metadata;
+
+    #[inline]
+    fn deref(&self) -> &Self::Target {
+        &self.base
+    }
+}
+
+impl TupleTypeMetadata {
+    /// Returns the number of elements.
+    #[inline]
+    pub fn num_elements(&self) -> usize {
+        self.num_elements
+    }
+
+    /// Returns the elements of the tuple, which follow the metadata.
+    #[inline]
+    pub fn elements(&self) -> &[TupleElement] {
+        unsafe {
+            let start = (self as *const Self).add(1) as *const TupleElement;
+            slice::from_raw_parts(start, self.num_elements)
+        }
+    }
+
+    /// Returns the labels of the elements, or `None` if no element is
+    /// labeled.
+    ///
+    /// Each label is followed by a space, and unlabeled elements have an
+    /// empty label, such as `"x  z "` for `(x: Int, Int, z: Int)`.
+    #[inline]
+    pub fn labels(&self) -> Option<&CStr> {
+        if self.labels.is_null() {
+            None
+        } else {
+            Some(unsafe { CStr::from_ptr(self.labels) })
+        }
+    }
+}
+
+/// The calling convention of a function type.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+#[repr(transparent)]
+// enum class FunctionMetadataConvention: uint8_t
+pub struct FunctionMetadataConvention(pub u8);
+
+impl FunctionMetadataConvention {
+    /// A Swift closure.
+    pub const SWIFT: Self = Self(0);
+
+    /// An Objective-C block.
+    pub const BLOCK: Self = Self(1);
+
+    /// A function without a context.
+    pub const THIN: Self = Self(2);
+
+    /// A C function pointer.
+    pub const C_FUNCTION_POINTER: Self = Self(3);
+}
+
+/// Flags describing a function type.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+#[repr(transparent)]
+// class TargetFunctionTypeFlags
+pub struct FunctionTypeFlags(pub usize);
+
+impl FunctionTypeFlags {
+    const NUM_PARAMETERS_MASK: usize = 0x0000_FFFF;
+    const CONVENTION_MASK: usize = 0x00FF_0000;
+    const CONVENTION_SHIFT: usize = 16;
+    const THROWS_MASK: usize = 0x0100_0000;
+    const PARAM_FLAGS_MASK: usize = 0x0200_0000;
+    const ESCAPING_MASK: usize = 0x0400_0000;
+    const DIFFERENTIABLE_MASK: usize = 0x0800_0000;
+    const GLOBAL_ACTOR_MASK: usize = 0x1000_0000;
+    const ASYNC_MASK: usize = 0x2000_0000;
+    const SENDABLE_MASK: usize = 0x4000_0000;
+
+    /// Returns the number of parameters.
+    #[inline]
+    pub const fn num_params(&self) -> usize {
+        self.0 & Self::NUM_PARAMETERS_MASK
+    }
+
+    /// Returns the calling convention.
+    #[inline]
+    pub const fn convention(&self) -> FunctionMetadataConvention {
+        let convention =
+            (self.0 & Self::CONVENTION_MASK) >> Self::CONVENTION_SHIFT;
+        FunctionMetadataConvention(convention as u8)
+    }
+
+    /// Returns whether the function throws.
+    #[inline]
+    pub const fn throws(&self) -> bool {
+        self.0 & Self::THROWS_MASK != 0
+    }
+
+    /// Returns whether the parameters are followed by their flags.
+    #[inline]
+    pub const fn has_param_flags(&self) -> bool {
+        self.0 & Self::PARAM_FLAGS_MASK != 0
+    }
+
+    /// Returns whether the function is `@escaping`.
+    #[inline]
+    pub const fn is_escaping(&self) -> bool {
+        self.0 & Self::ESCAPING_MASK != 0
+    }
+
+    /// Returns whether the function is `@differentiable`.
+    #[inline]
+    pub const fn is_differentiable(&self) -> bool {
+        self.0 & Self::DIFFERENTIABLE_MASK != 0
+    }
+
+    /// Returns whether the function is isolated to a global actor.
+    #[inline]
+    pub const fn has_global_actor(&self) -> bool {
+        self.0 & Self::GLOBAL_ACTOR_MASK != 0
+    }
+
+    /// Returns whether the function is `async`.
+    #[inline]
+    pub const fn is_async(&self) -> bool {
+        self.0 & Self::ASYNC_MASK != 0
+    }
+
+    /// Returns whether the function is `@Sendable`.
+    #[inline]
+    pub const fn is_sendable(&self) -> bool {
+        self.0 & Self::SENDABLE_MASK != 0
+    }
+}
+
+/// Flags describing a parameter of a function type.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+#[repr(transparent)]
+// class TargetParameterTypeFlags
+pub struct ParameterFlags(pub u32);
+
+impl ParameterFlags {
+    /// Returns the ownership of the parameter, where 0 is the default, 1 is
+    /// `inout`, 2 is `__shared` and 3 is `__owned`.
+    #[inline]
+    pub const fn value_ownership(&self) -> u8 {
+        (self.0 & 0x7F) as u8
+    }
+
+    /// Returns whether the parameter is variadic.
+    #[inline]
+    pub const fn is_variadic(&self) -> bool {
+        self.0 & 0x80 != 0
+    }
+
+    /// Returns whether the parameter is `@autoclosure`.
+    #[inline]
+    pub const fn is_autoclosure(&self) -> bool {
+        self.0 & 0x100 != 0
+    }
+
+    /// Returns whether the parameter is `@noDerivative`.
+    #[inline]
+    pub const fn is_no_derivative(&self) -> bool {
+        self.0 & 0x200 != 0
+    }
+
+    /// Returns whether the parameter is `isolated`.
+    #[inline]
+    pub const fn is_isolated(&self) -> bool {
+        self.0 & 0x400 != 0
+    }
+}
+
+/// The metadata of a function type.
+///
+/// Refers to `TargetFunctionTypeMetadata<InProcess>` in C++.
+#[repr(C)]
+#[derive(Debug)]
+pub struct FunctionTypeMetadata {
+    /// The base metadata.
+    base: Metadata,
+
+    /// Flags describing the function type.
+    flags: FunctionTypeFlags,
+
+    /// The metadata of the result type.
+    result_type: *const Metadata,
+}
+
+impl Deref for FunctionTypeMetadata {
+    type Target = Metadata;
+
+    #[inline]
+    fn deref(&self) -> &Self::Target {
+        &self.base
+    }
+}
+
+impl FunctionTypeMetadata {
+    /// Returns the flags describing the function type.
+    #[inline]
+    pub fn flags(&self) -> FunctionTypeFlags {
+        self.flags
+    }
+
+    /// Returns the metadata of the result type.
+    #[inline]
+    pub fn result_type(&self) -> &Metadata {
+        unsafe { &*self.result_type }
+    }
+
+    /// Returns the metadata of the parameter types, which follow the
+    /// metadata.
+    #[inline]
+    pub fn params(&self) -> &[&Metadata] {
+        unsafe {
+            let start = (self as *const Self).add(1) as *const &Metadata;
+            slice::from_raw_parts(start, self.flags.num_params())
+        }
+    }
+
+    /// Returns the flags of the parameters, or `None` if they all have the
+    /// default flags.
+    #[inline]
+    pub fn param_flags(&self) -> Option<&[ParameterFlags]> {
+        if !self.flags.has_param_flags() {
+            return None;
+        }
+        let params = self.params();
+        unsafe {
+            let start = params.as_ptr().add(params.len()) as *const _;
+            Some(slice::from_raw_parts(start, params.len()))
+        }
+    }
+}
+
+/// Flags describing an existential type.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+#[repr(transparent)]
+// class ExistentialTypeFlags
+pub struct ExistentialTypeFlags(pub u32);
+
+impl ExistentialTypeFlags {
+    const NUM_WITNESS_TABLES_MASK: u32 = 0x00FF_FFFF;
+    const CLASS_CONSTRAINT_MASK: u32 = 0x8000_0000;
+    const HAS_SUPERCLASS_MASK: u32 = 0x4000_0000;
+    const SPECIAL_PROTOCOL_MASK: u32 = 0x3F00_0000;
+    const SPECIAL_PROTOCOL_SHIFT: u32 = 24;
+
+    /// Returns the number of witness tables stored in values of the type.
+    #[inline]
+    pub const fn num_witness_tables(&self) -> u32 {
+        self.0 & Self::NUM_WITNESS_TABLES_MASK
+    }
+
+    /// Returns whether values of the type must be class instances, and are
+    /// stored as a single reference.
+    #[inline]
+    pub const fn is_class_constrained(&self) -> bool {
+        self.0 & Self::CLASS_CONSTRAINT_MASK == 0
+    }
+
+    /// Returns whether the type has a superclass constraint.
+    #[inline]
+    pub const fn has_superclass_constraint(&self) -> bool {
+        self.0 & Self::HAS_SUPERCLASS_MASK != 0
+    }
+
+    /// Returns whether the type is `Error`, which has a special
+    /// representation.
+    #[inline]
+    pub const fn is_error(&self) -> bool {
+        (self.0 & Self::SPECIAL_PROTOCOL_MASK) >> Self::SPECIAL_PROTOCOL_SHIFT
+            == 1
+    }
+}
+
+/// A reference to a Swift or Objective-C protocol.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+#[repr(transparent)]
+// class TargetProtocolDescriptorRef
+pub struct ProtocolDescriptorRef(usize);
+
+impl ProtocolDescriptorRef {
+    /// Returns the address of the protocol, whose low bit marks an
+    /// Objective-C protocol on Apple platforms.
+    #[inline]
+    pub const fn bits(&self) -> usize {
+        self.0
+    }
+
+    /// Returns whether the reference is to an Objective-C protocol.
+    #[inline]
+    pub const fn is_objc(&self) -> bool {
+        cfg!(target_vendor = "apple") && self.0 & 1 != 0
+    }
+
+    /// Returns the descriptor of the Swift protocol, or `None` if it's an
+    /// Objective-C protocol.
+    #[inline]
+    pub fn swift_protocol(&self) -> Option<&ProtocolDescriptor> {
+        if self.is_objc() {
+            None
+        } else {
+            unsafe { (self.0 as *const ProtocolDescriptor).as_ref() }
+        }
+    }
+}
+
+/// The metadata of an existential type, such as `Any` or `Error & Sendable`.
+///
+/// Refers to `TargetExistentialTypeMetadata<InProcess>` in C++.
+#[repr(C)]
+#[derive(Debug)]
+pub struct ExistentialTypeMetadata {
+    /// The base metadata.
+    base: Metadata,
+
+    /// Flags describing the existential type.
+    flags: ExistentialTypeFlags,
+
+    /// The number of protocols in the composition.
+    num_protocols: u32,
+}
+
+impl Deref for ExistentialTypeMetadata {
+    type Target = Metadata;
+
+    #[inline]
+    fn deref(&self) -> &Self::Target {
+        &self.base
+    }
+}
+
+impl ExistentialTypeMetadata {
+    /// Returns the flags describing the existential type.
+    #[inline]
+    pub fn flags(&self) -> ExistentialTypeFlags {
+        self.flags
+    }
+
+    /// Returns the number of protocols in the composition.
+    #[inline]
+    pub fn num_protocols(&self) -> u32 {
+        self.num_protocols
+    }
+
+    /// Returns the metadata of the superclass constraint, if there is one.
+    #[inline]
+    pub fn superclass_constraint(&self) -> Option<&Metadata> {
+        if !self.flags.has_superclass_constraint() {
+            return None;
+        }
+        unsafe {
+            let start = (self as *const Self).add(1) as *const *const Metadata;
+            (*start).as_ref()
+        }
+    }
+
+    /// Returns the protocols of the composition, which follow the
+    /// superclass constraint.
+    #[inline]
+    pub fn protocols(&self) -> &[ProtocolDescriptorRef] {
+        let skip = self.flags.has_superclass_constraint() as usize;
+        unsafe {
+            let start = (self as *const Self).add(1) as *const usize;
+            slice::from_raw_parts(
+                start.add(skip) as *const ProtocolDescriptorRef,
+                self.num_protocols as usize,
+            )
+        }
+    }
+}
+
+/// The metadata of a metatype, such as `Int.Type`.
+///
+/// Refers to `TargetMetatypeMetadata<InProcess>` in C++.
+#[repr(C)]
+#[derive(Debug)]
+pub struct MetatypeMetadata {
+    /// The base metadata.
+    base: Metadata,
+
+    /// The metadata of the instance type.
+    instance_type: *const Metadata,
+}
+
+impl Deref for MetatypeMetadata {
+    type Target = Metadata;
+
+    #[inline]
+    fn deref(&self) -> &Self::Target {
+        &self.base
+    }
+}
+
+impl MetatypeMetadata {
+    /// Returns the metadata of the instance type, such as `Int` for
+    /// `Int.Type`.
+    #[inline]
+    pub fn instance_type(&self) -> &Metadata {
+        unsafe { &*self.instance_type }
+    }
+}
+
+/// The metadata of an existential metatype, such as `Any.Type`.
+///
+/// Refers to `TargetExistentialMetatypeMetadata<InProcess>` in C++.
+#[repr(C)]
+#[derive(Debug)]
+pub struct ExistentialMetatypeMetadata {
+    /// The base metadata.
+    base: Metadata,
+
+    /// The metadata of the instance type, which is an existential type or
+    /// another existential metatype.
+    instance_type: *const Metadata,
+
+    /// Flags describing the instance type.
+    flags: ExistentialTypeFlags,
+}
+
+impl Deref for ExistentialMetatypeMetadata {
+    type Target = Metadata;
+
+    #[inline]
+    fn deref(&self) -> &Self::Target {
+        &self.base
+    }
+}
+
+impl ExistentialMetatypeMetadata {
+    /// Returns the metadata of the instance type, such as `Any` for
+    /// `Any.Type`.
+    #[inline]
+    pub fn instance_type(&self) -> &Metadata {
+        unsafe { &*self.instance_type }
+    }
+
+    /// Returns the flags describing the instance type.
+    #[inline]
+    pub fn flags(&self) -> ExistentialTypeFlags {
+        self.flags
+    }
+}
+
+// Metadata is referenced by its address point, one word after the header.
+assert_eq_size!(TypeMetadataHeader, usize);
+assert_eq_size!(HeapMetadataHeader, [usize; 2]);
+assert_eq_size!(StructMetadata, [usize; 2]);
+assert_eq_size!(TupleTypeMetadata, [usize; 3]);
+assert_eq_size!(TupleElement, [usize; 2]);
+assert_eq_size!(FunctionTypeMetadata, [usize; 3]);
+assert_eq_size!(MetatypeMetadata, [usize; 2]);
+assert_eq_size!(ForeignClassMetadata, [usize; 4]);
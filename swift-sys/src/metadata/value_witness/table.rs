@@ -3,6 +3,31 @@ use std::os::raw::{c_uint, c_void};
 // TODO: Verify that these sizes are correct.
 assert_eq_size!(ValueWitnessTable, [u8; 88]);
 assert_eq_size!(EnumValueWitnessTable, [u8; 112]);
+assert_eq_size!(ValueBuffer, [*mut c_void; 3]);
+
+/// Storage for a value of an arbitrary type, such as that of an existential.
+///
+/// Values that fit, per
+/// [`ValueWitnessFlags::is_inline_storage`](struct.ValueWitnessFlags.html#method.is_inline_storage),
+/// are stored inline. Others are stored in a heap-allocated box that the
+/// buffer points to.
+///
+/// Refers to `ValueBuffer` in C++.
+#[repr(C)]
+#[derive(Clone, Copy, Debug)]
+pub struct ValueBuffer {
+    /// The inline value or a pointer to its box.
+    pub private_data: [*mut c_void; 3],
+}
+
+impl Default for ValueBuffer {
+    #[inline]
+    fn default() -> Self {
+        Self {
+            private_data: [std::ptr::null_mut(); 3],
+        }
+    }
+}
 
 /// A vtable of functions that implement value semantics of a type.
 ///
@@ -104,34 +129,42 @@ pub struct ValueWitnessTable {
     /// witness table's type (e.g `Optional<ThisType>`), get the tag of the
     /// enum.
     ///
+    /// Returns 0 for the payload case and `1 + n` for the `n`th empty case.
+    ///
     /// Seen as:
     ///
     /// ```c
-    /// unsigned (*getEnumTagSinglePayload)(const T* enum, UINT_TYPE emptyCases);
+    /// unsigned (*getEnumTagSinglePayload)(const T* enum,
+    ///                                     UINT_TYPE emptyCases,
+    ///                                     M *self);
     /// ```
     pub get_enum_tag_single_payload: unsafe extern "C" fn(
         enum_: *const c_void,
         empty_cases: c_uint,
         self_: *mut c_void,
-    ) -> *mut c_uint,
+    ) -> c_uint,
 
     /// Given uninitialized memory for an instance of a single payload enum with
     /// a payload of this witness table's type (e.g `Optional<ThisType>`), store
     /// the tag.
     ///
+    /// `whichCase` is 0 for the payload case, which must already be
+    /// initialized, and `1 + n` for the `n`th empty case.
+    ///
     /// Seen as:
     ///
     /// ```c
     /// void (*storeEnumTagSinglePayload)(T* enum,
     ///                                   UINT_TYPE whichCase,
-    ///                                   UINT_TYPE emptyCases);
+    ///                                   UINT_TYPE emptyCases,
+    ///                                   M *self);
     /// ```
     pub store_enum_tag_single_payload: unsafe extern "C" fn(
         enum_: *mut c_void,
         which_case: c_uint,
         empty_cases: c_uint,
         self_: *mut c_void,
-    ) -> *mut c_uint,
+    ),
 
     /// The required storage size of a single object of this type.
     pub size: usize,
@@ -140,33 +173,105 @@ pub struct ValueWitnessTable {
     /// one, even for zero-sized types, like the empty tuple.
     pub stride: usize,
 
-    /// The ValueWitnessAlignmentMask bits represent the required alignment of
-    /// the first byte of an object of this type, expressed as a mask of the low
-    /// bits that must not be set in the pointer. This representation can be
-    /// easily converted to the 'alignof' result by merely adding 1, but it is
-    /// more directly useful for performing dynamic structure layouts, and it
-    /// grants an additional bit of precision in a compact field without needing
-    /// to switch to an exponent representation.
-    ///
-    /// The ValueWitnessIsNonPOD bit is set if the type is not POD.
-    ///
-    /// The ValueWitnessIsNonInline bit is set if the type cannot be represented
-    /// in a fixed-size buffer or if it is not bitwise takable.
-    ///
-    /// The ExtraInhabitantsMask bits represent the number of "extra
-    /// inhabitants" of the bit representation of the value that do not form
-    /// valid values of the type.
-    ///
-    /// The Enum_HasSpareBits bit is set if the type's binary representation has
-    /// unused bits.
-    ///
-    /// The HasEnumWitnesses bit is set if the type is an enum type.
-    pub flags: c_uint,
+    /// The alignment of the type and whether it's POD, inline, bitwise
+    /// takable or an enum.
+    pub flags: ValueWitnessFlags,
 
     /// The number of extra inhabitants in the type.
     pub extra_inhabitant_count: c_uint,
 }
 
+/// Flags describing the layout of a type.
+///
+/// The `ValueWitnessAlignmentMask` bits represent the required alignment of
+/// the first byte of an object of the type, expressed as a mask of the low
+/// bits that must not be set in the pointer. This representation can be
+/// easily converted to the `alignof` result by merely adding 1, but it is
+/// more directly useful for performing dynamic structure layouts.
+///
+/// Refers to `TargetValueWitnessFlags` in C++.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+#[repr(transparent)]
+pub struct ValueWitnessFlags(pub c_uint);
+
+impl ValueWitnessFlags {
+    /// The bits of the alignment mask.
+    pub const ALIGNMENT_MASK: c_uint = 0x0000_00FF;
+
+    /// Set if the type isn't POD.
+    pub const IS_NON_POD: c_uint = 0x0001_0000;
+
+    /// Set if the type can't be stored inline in a fixed-size buffer or isn't
+    /// bitwise takable.
+    pub const IS_NON_INLINE: c_uint = 0x0002_0000;
+
+    /// Set if the type's binary representation has unused bits.
+    pub const HAS_SPARE_BITS: c_uint = 0x0008_0000;
+
+    /// Set if the type isn't bitwise takable.
+    pub const IS_NON_BITWISE_TAKABLE: c_uint = 0x0010_0000;
+
+    /// Set if the type is an enum, whose value witness table is an
+    /// [`EnumValueWitnessTable`](struct.EnumValueWitnessTable.html).
+    pub const HAS_ENUM_WITNESSES: c_uint = 0x0020_0000;
+
+    /// Set if the type's layout hasn't been completed yet.
+    pub const INCOMPLETE: c_uint = 0x0040_0000;
+
+    /// Returns the mask of the low bits that must not be set in the address
+    /// of a value of the type.
+    #[inline]
+    pub const fn alignment_mask(&self) -> usize {
+        (self.0 & Self::ALIGNMENT_MASK) as usize
+    }
+
+    /// Returns the required alignment of values of the type in bytes.
+    #[inline]
+    pub const fn alignment(&self) -> usize {
+        self.alignment_mask() + 1
+    }
+
+    /// Returns whether the type is POD, and can therefore be copied with
+    /// `memcpy` and destroyed without doing anything.
+    #[inline]
+    pub const fn is_pod(&self) -> bool {
+        self.0 & Self::IS_NON_POD == 0
+    }
+
+    /// Returns whether values of the type are stored inline in a fixed-size
+    /// buffer of three words, such as that of an existential, rather than
+    /// boxed on the heap.
+    #[inline]
+    pub const fn is_inline_storage(&self) -> bool {
+        self.0 & Self::IS_NON_INLINE == 0
+    }
+
+    /// Returns whether values of the type can be moved with `memcpy`.
+    #[inline]
+    pub const fn is_bitwise_takable(&self) -> bool {
+        self.0 & Self::IS_NON_BITWISE_TAKABLE == 0
+    }
+
+    /// Returns whether the type is an enum, whose value witness table is an
+    /// [`EnumValueWitnessTable`](struct.EnumValueWitnessTable.html).
+    #[inline]
+    pub const fn has_enum_witnesses(&self) -> bool {
+        self.0 & Self::HAS_ENUM_WITNESSES != 0
+    }
+
+    /// Returns whether the type's binary representation has unused bits.
+    #[inline]
+    pub const fn has_spare_bits(&self) -> bool {
+        self.0 & Self::HAS_SPARE_BITS != 0
+    }
+
+    /// Returns whether the type's layout hasn't been completed yet.
+    #[inline]
+    pub const fn is_incomplete(&self) -> bool {
+        self.0 & Self::INCOMPLETE != 0
+    }
+}
+
 /// A value-witness table with enum entry points.
 ///
 /// Equivalent to `EnumValueWitnessTable` in
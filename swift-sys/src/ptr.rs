@@ -0,0 +1,429 @@
+//! Relative pointers, which are used throughout Swift's ABI.
+//!
+//! A relative pointer stores the signed 32-bit offset of its target from its
+//! own address, which keeps metadata position-independent and small. Because
+//! of this, relative pointers are only meaningful at their original location
+//! and must never be copied or moved; they're only ever used by reference.
+
+// Based on:
+// include/swift/Basic/RelativePointer.h
+
+use std::{fmt, marker::PhantomData};
+
+/// Returns the address `offset` bytes from `base`.
+#[inline]
+fn apply_offset<T, U>(base: &T, offset: i32) -> *const U {
+    let base = base as *const T as *const u8;
+    base.wrapping_offset(offset as isize) as *const U
+}
+
+/// A relative pointer to a `T`, where an offset of 0 represents null.
+///
+/// Refers to `RelativeDirectPointer<T, /*Nullable*/ true>` in C++.
+#[repr(transparent)]
+pub struct RelativeDirectPointer<T> {
+    offset: i32,
+    marker: PhantomData<*const T>,
+}
+
+impl<T> fmt::Debug for RelativeDirectPointer<T> {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.debug_tuple("RelativeDirectPointer")
+            .field(&self.get())
+            .finish()
+    }
+}
+
+impl<T> RelativeDirectPointer<T> {
+    /// Returns the offset of the target from `self`.
+    #[inline]
+    pub const fn offset(&self) -> i32 {
+        self.offset
+    }
+
+    /// Returns whether the pointer is null.
+    #[inline]
+    pub const fn is_null(&self) -> bool {
+        self.offset == 0
+    }
+
+    /// Returns the address of the target, or null if the offset is 0.
+    #[inline]
+    pub fn get(&self) -> *const T {
+        if self.is_null() {
+            std::ptr::null()
+        } else {
+            apply_offset(self, self.offset)
+        }
+    }
+
+    /// Returns a reference to the target, or `None` if the offset is 0.
+    ///
+    /// # Safety
+    ///
+    /// `self` must be at its original location and the target must be a
+    /// valid `T` that lives for `'a`.
+    #[inline]
+    pub unsafe fn as_ref<'a>(&self) -> Option<&'a T> {
+        self.get().as_ref()
+    }
+}
+
+/// A relative pointer to a `T` paired with an integer in the low bits of its
+/// offset, where an offset of 0 represents null.
+///
+/// The integer occupies the 2 low bits, which are free because the target is
+/// aligned to at least 4 bytes.
+///
+/// Refers to `RelativeDirectPointerIntPair<T, IntTy, /*Nullable*/ true>` in
+/// C++.
+#[repr(transparent)]
+pub struct RelativeDirectPointerIntPair<T> {
+    offset_and_int: i32,
+    marker: PhantomData<*const T>,
+}
+
+impl<T> fmt::Debug for RelativeDirectPointerIntPair<T> {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.debug_tuple("RelativeDirectPointerIntPair")
+            .field(&self.get())
+            .field(&self.int())
+            .finish()
+    }
+}
+
+impl<T> RelativeDirectPointerIntPair<T> {
+    const INT_MASK: i32 = 0x3;
+
+    /// Returns the offset of the target from `self`, without the integer.
+    #[inline]
+    pub const fn offset(&self) -> i32 {
+        self.offset_and_int & !Self::INT_MASK
+    }
+
+    /// Returns the integer stored in the low bits of the offset.
+    #[inline]
+    pub const fn int(&self) -> u32 {
+        (self.offset_and_int & Self::INT_MASK) as u32
+    }
+
+    /// Returns whether the pointer is null.
+    #[inline]
+    pub const fn is_null(&self) -> bool {
+        self.offset() == 0
+    }
+
+    /// Returns the address of the target, or null if the offset is 0.
+    #[inline]
+    pub fn get(&self) -> *const T {
+        if self.is_null() {
+            std::ptr::null()
+        } else {
+            apply_offset(self, self.offset())
+        }
+    }
+
+    /// Returns a reference to the target, or `None` if the offset is 0.
+    ///
+    /// # Safety
+    ///
+    /// `self` must be at its original location and the target must be a
+    /// valid `T` that lives for `'a`.
+    #[inline]
+    pub unsafe fn as_ref<'a>(&self) -> Option<&'a T> {
+        self.get().as_ref()
+    }
+}
+
+/// A relative pointer to either a `T` or a pointer to a `T`, where an offset
+/// of 0 represents null.
+///
+/// The low bit of the offset is set when the target is reached through a
+/// pointer, such as a GOT entry for a `T` defined in another image.
+///
+/// Refers to `RelativeIndirectablePointer<T, /*Nullable*/ true>` in C++.
+#[repr(transparent)]
+pub struct RelativeIndirectablePointer<T> {
+    offset_and_indirect: i32,
+    marker: PhantomData<*const T>,
+}
+
+impl<T> fmt::Debug for RelativeIndirectablePointer<T> {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.debug_struct("RelativeIndirectablePointer")
+            .field("offset", &self.offset())
+            .field("is_indirect", &self.is_indirect())
+            .finish()
+    }
+}
+
+impl<T> RelativeIndirectablePointer<T> {
+    const INDIRECT_MASK: i32 = 0x1;
+
+    /// Returns the offset of the target, or of the pointer to it, from
+    /// `self`.
+    #[inline]
+    pub const fn offset(&self) -> i32 {
+        self.offset_and_indirect & !Self::INDIRECT_MASK
+    }
+
+    /// Returns whether the offset refers to a pointer to the target rather
+    /// than the target itself.
+    #[inline]
+    pub const fn is_indirect(&self) -> bool {
+        self.offset_and_indirect & Self::INDIRECT_MASK != 0
+    }
+
+    /// Returns whether the pointer is null.
+    #[inline]
+    pub const fn is_null(&self) -> bool {
+        self.offset_and_indirect == 0
+    }
+
+    /// Returns the address of the target, or null if the offset is 0.
+    ///
+    /// # Safety
+    ///
+    /// If the pointer is indirect, `self` must be at its original location so
+    /// that the pointer to the target can be read.
+    #[inline]
+    pub unsafe fn get(&self) -> *const T {
+        resolve_indirectable(self, self.offset_and_indirect)
+    }
+
+    /// Returns a reference to the target, or `None` if the offset is 0.
+    ///
+    /// # Safety
+    ///
+    /// `self` must be at its original location and the target must be a
+    /// valid `T` that lives for `'a`.
+    #[inline]
+    pub unsafe fn as_ref<'a>(&self) -> Option<&'a T> {
+        self.get().as_ref()
+    }
+}
+
+/// A relative pointer to either a `T` or a pointer to a `T`, paired with an
+/// integer in the low bits of its offset, where an offset of 0 represents
+/// null.
+///
+/// The low bit of the offset marks an indirect target, as with
+/// [`RelativeIndirectablePointer`], and the next bit holds the integer.
+///
+/// Refers to `RelativeIndirectablePointerIntPair<T, IntTy, /*Nullable*/
+/// true>` in C++.
+#[repr(transparent)]
+pub struct RelativeIndirectablePointerIntPair<T> {
+    offset_and_indirect_and_int: i32,
+    marker: PhantomData<*const T>,
+}
+
+impl<T> fmt::Debug for RelativeIndirectablePointerIntPair<T> {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.debug_struct("RelativeIndirectablePointerIntPair")
+            .field("offset", &self.offset())
+            .field("is_indirect", &self.is_indirect())
+            .field("int", &self.int())
+            .finish()
+    }
+}
+
+impl<T> RelativeIndirectablePointerIntPair<T> {
+    const INDIRECT_MASK: i32 = 0x1;
+    const INT_MASK: i32 = 0x2;
+
+    /// Returns the offset of the target, or of the pointer to it, from
+    /// `self`.
+    #[inline]
+    pub const fn offset(&self) -> i32 {
+        self.offset_and_indirect_and_int
+            & !(Self::INDIRECT_MASK | Self::INT_MASK)
+    }
+
+    /// Returns whether the offset refers to a pointer to the target rather
+    /// than the target itself.
+    #[inline]
+    pub const fn is_indirect(&self) -> bool {
+        self.offset_and_indirect_and_int & Self::INDIRECT_MASK != 0
+    }
+
+    /// Returns the integer stored in the low bits of the offset.
+    #[inline]
+    pub const fn int(&self) -> u32 {
+        ((self.offset_and_indirect_and_int & Self::INT_MASK) >> 1) as u32
+    }
+
+    /// Returns whether the pointer is null.
+    #[inline]
+    pub const fn is_null(&self) -> bool {
+        self.offset_and_indirect_and_int & !Self::INT_MASK == 0
+    }
+
+    /// Returns the address of the target, or null if the offset is 0.
+    ///
+    /// # Safety
+    ///
+    /// If the pointer is indirect, `self` must be at its original location so
+    /// that the pointer to the target can be read.
+    #[inline]
+    pub unsafe fn get(&self) -> *const T {
+        let bits = self.offset_and_indirect_and_int & !Self::INT_MASK;
+        resolve_indirectable(self, bits)
+    }
+
+    /// Returns a reference to the target, or `None` if the offset is 0.
+    ///
+    /// # Safety
+    ///
+    /// `self` must be at its original location and the target must be a
+    /// valid `T` that lives for `'a`.
+    #[inline]
+    pub unsafe fn as_ref<'a>(&self) -> Option<&'a T> {
+        self.get().as_ref()
+    }
+}
+
+/// Resolves the offset `bits` from `base`, whose low bit marks an indirect
+/// target.
+#[inline]
+unsafe fn resolve_indirectable<B, T>(base: &B, bits: i32) -> *const T {
+    if bits == 0 {
+        return std::ptr::null();
+    }
+    let address: *const u8 = apply_offset(base, bits & !1);
+    if bits & 1 != 0 {
+        *(address as *const *const T)
+    } else {
+        address as *const T
+    }
+}
+
+assert_eq_size!(RelativeDirectPointer<u8>, i32);
+assert_eq_size!(RelativeDirectPointerIntPair<u8>, i32);
+assert_eq_size!(RelativeIndirectablePointer<u8>, i32);
+assert_eq_size!(RelativeIndirectablePointerIntPair<u8>, i32);
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Places a pointer with raw bits between targets at known offsets:
+    /// `before` is at -8, `after` is at +8 and `slot` is at +16.
+    #[repr(C)]
+    struct Fixture {
+        before: u64,
+        bits: i32,
+        padding: i32,
+        after: u64,
+        slot: *const u64,
+    }
+
+    impl Fixture {
+        fn new(bits: i32, slot: &u64) -> Self {
+            Self {
+                before: 1,
+                bits,
+                padding: 0,
+                after: 2,
+                slot,
+            }
+        }
+
+        fn pointer<P>(&self) -> &P {
+            // Every relative pointer is a transparent wrapper around an i32.
+            unsafe { &*(&self.bits as *const i32 as *const P) }
+        }
+    }
+
+    #[test]
+    fn direct_pointer() {
+        let target = 3;
+
+        let null = Fixture::new(0, &target);
+        let null = null.pointer::<RelativeDirectPointer<u64>>();
+        assert!(null.is_null());
+        assert!(null.get().is_null());
+
+        let fixture = Fixture::new(8, &target);
+        let forward = fixture.pointer::<RelativeDirectPointer<u64>>();
+        assert_eq!(forward.offset(), 8);
+        assert_eq!(forward.get(), &fixture.after as *const u64);
+
+        let fixture = Fixture::new(-8, &target);
+        let backward = fixture.pointer::<RelativeDirectPointer<u64>>();
+        assert_eq!(backward.offset(), -8);
+        assert_eq!(unsafe { backward.as_ref() }, Some(&1));
+    }
+
+    #[test]
+    fn direct_pointer_int_pair() {
+        let target = 3;
+
+        let null = Fixture::new(1, &target);
+        let null = null.pointer::<RelativeDirectPointerIntPair<u64>>();
+        assert!(null.is_null());
+        assert!(null.get().is_null());
+        assert_eq!(null.int(), 1);
+
+        let fixture = Fixture::new(8 | 2, &target);
+        let forward = fixture.pointer::<RelativeDirectPointerIntPair<u64>>();
+        assert_eq!(forward.offset(), 8);
+        assert_eq!(forward.int(), 2);
+        assert_eq!(forward.get(), &fixture.after as *const u64);
+
+        let fixture = Fixture::new(-8 | 3, &target);
+        let backward = fixture.pointer::<RelativeDirectPointerIntPair<u64>>();
+        assert_eq!(backward.offset(), -8);
+        assert_eq!(backward.int(), 3);
+        assert_eq!(unsafe { backward.as_ref() }, Some(&1));
+    }
+
+    #[test]
+    fn indirectable_pointer() {
+        let target = 3;
+
+        let null = Fixture::new(0, &target);
+        let null = null.pointer::<RelativeIndirectablePointer<u64>>();
+        assert!(null.is_null());
+        assert!(unsafe { null.get() }.is_null());
+
+        let fixture = Fixture::new(-8, &target);
+        let direct = fixture.pointer::<RelativeIndirectablePointer<u64>>();
+        assert!(!direct.is_indirect());
+        assert_eq!(direct.offset(), -8);
+        assert_eq!(unsafe { direct.get() }, &fixture.before as *const u64);
+
+        let fixture = Fixture::new(16 | 1, &target);
+        let indirect = fixture.pointer::<RelativeIndirectablePointer<u64>>();
+        assert!(indirect.is_indirect());
+        assert_eq!(indirect.offset(), 16);
+        assert_eq!(unsafe { indirect.get() }, &target as *const u64);
+        assert_eq!(unsafe { indirect.as_ref() }, Some(&3));
+    }
+
+    #[test]
+    fn indirectable_pointer_int_pair() {
+        type Pointer = RelativeIndirectablePointerIntPair<u64>;
+        let target = 3;
+
+        let null = Fixture::new(2, &target);
+        let null = null.pointer::<Pointer>();
+        assert!(null.is_null());
+        assert!(unsafe { null.get() }.is_null());
+        assert_eq!(null.int(), 1);
+
+        let fixture = Fixture::new(-8 | 2, &target);
+        let direct = fixture.pointer::<Pointer>();
+        assert!(!direct.is_indirect());
+        assert_eq!(direct.offset(), -8);
+        assert_eq!(direct.int(), 1);
+        assert_eq!(unsafe { direct.get() }, &fixture.before as *const u64);
+
+        let fixture = Fixture::new(16 | 1, &target);
+        let indirect = fixture.pointer::<Pointer>();
+        assert!(indirect.is_indirect());
+        assert_eq!(indirect.offset(), 16);
+        assert_eq!(indirect.int(), 0);
+        assert_eq!(unsafe { indirect.as_ref() }, Some(&3));
+    }
+}
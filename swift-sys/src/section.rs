@@ -0,0 +1,116 @@
+//! The metadata sections of images that have been registered with the
+//! runtime.
+//!
+//! On ELF platforms, each image that contains Swift code registers its
+//! sections with the runtime when it's loaded.
+
+// Based on:
+// include/swift/Runtime/ImageInspectionCommon.h
+// stdlib/public/runtime/ImageInspectionCommon.cpp
+
+use std::{ffi::c_void, mem, slice};
+
+/// The range of addresses occupied by a section.
+///
+/// Refers to `MetadataSectionRange` in C++.
+#[repr(C)]
+#[derive(Clone, Copy, Debug)]
+pub struct MetadataSectionRange {
+    /// The address of the start of the section.
+    pub start: usize,
+
+    /// The size of the section in bytes.
+    pub length: usize,
+}
+
+impl MetadataSectionRange {
+    /// Returns the section as a slice of `T`.
+    ///
+    /// # Safety
+    ///
+    /// The section must contain an array of `T` that lives for `'a`.
+    #[inline]
+    pub unsafe fn as_slice<'a, T>(&self) -> &'a [T] {
+        if self.start == 0 || mem::size_of::<T>() == 0 {
+            return &[];
+        }
+        let len = self.length / mem::size_of::<T>();
+        slice::from_raw_parts(self.start as *const T, len)
+    }
+}
+
+/// The metadata sections of an image.
+///
+/// Refers to `MetadataSections` in C++.
+#[repr(C)]
+#[derive(Debug)]
+pub struct MetadataSections {
+    /// The version of this structure, which determines the sections that
+    /// follow `swift5_mpenum`.
+    pub version: usize,
+
+    /// An address within the image, such as its base address.
+    pub base_address: *const c_void,
+
+    /// Reserved.
+    pub unused0: *const c_void,
+
+    /// Reserved.
+    pub unused1: *const c_void,
+
+    /// Protocol descriptor records.
+    pub swift5_protocols: MetadataSectionRange,
+
+    /// Protocol conformance descriptor records.
+    pub swift5_protocol_conformances: MetadataSectionRange,
+
+    /// Type descriptor records.
+    pub swift5_type_metadata: MetadataSectionRange,
+
+    /// Mangled type names.
+    pub swift5_typeref: MetadataSectionRange,
+
+    /// Field names.
+    pub swift5_reflstr: MetadataSectionRange,
+
+    /// Field descriptors.
+    pub swift5_fieldmd: MetadataSectionRange,
+
+    /// Associated type descriptors.
+    pub swift5_assocty: MetadataSectionRange,
+
+    /// Dynamic replacements.
+    pub swift5_replace: MetadataSectionRange,
+
+    /// Dynamic replacements of opaque types.
+    pub swift5_replac2: MetadataSectionRange,
+
+    /// Builtin type descriptors.
+    pub swift5_builtin: MetadataSectionRange,
+
+    /// Closure capture descriptors.
+    pub swift5_capture: MetadataSectionRange,
+
+    /// Multi-payload enum descriptors.
+    pub swift5_mpenum: MetadataSectionRange,
+}
+
+// TODO: Support the Swift calling convention in rustc
+// See https://github.com/rust-lang/rust/pull/64582
+extern "C" {
+    /// Calls `body` with the sections of each registered image and `context`,
+    /// until `body` returns `false`.
+    ///
+    /// Only available on platforms where images register their sections, such
+    /// as Linux.
+    // void swift_enumerateAllMetadataSections(
+    //     bool (* body)(const MetadataSections *sections, void *context),
+    //     void *context);
+    pub fn swift_enumerateAllMetadataSections(
+        body: unsafe extern "C" fn(
+            sections: *const MetadataSections,
+            context: *mut c_void,
+        ) -> bool,
+        context: *mut c_void,
+    );
+}